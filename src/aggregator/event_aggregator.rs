@@ -1,11 +1,19 @@
 //! Event aggregator with rolling buffer implementation
 //!
 //! This module provides the EventAggregator which stores recent log and metrics events
-//! in a time-windowed rolling buffer with capacity limits.
-
-use crate::events::{DiskEvent, LogEvent, MetricsEvent};
+//! in a time-windowed rolling buffer with capacity limits. Events are kept in
+//! one-second buckets (see [`time_buckets`](super::time_buckets)) and read back
+//! through borrowed [`EventSnapshot`]s, so evaluating rules does not copy them.
+//! Metrics and disk samples are also folded into long-lived [`Rollups`], and
+//! log messages are mined into [`LogTemplates`].
+
+use super::rollups::Rollups;
+use super::snapshot::EventSnapshot;
+use super::templates::LogTemplates;
+use super::time_buckets::TimeBuckets;
+use crate::events::{CrashReport, DiskEvent, LogEvent, MetricsEvent, Timestamp};
 use chrono::{Duration, Utc};
-use std::collections::VecDeque;
+use serde::{Deserialize, Serialize};
 
 /// Event aggregator with rolling buffer storage
 ///
@@ -14,15 +22,23 @@ use std::collections::VecDeque;
 /// pruned, and when capacity is reached, the oldest events are removed.
 pub struct EventAggregator {
     /// Buffer for log events
-    log_buffer: VecDeque<LogEvent>,
+    log_buffer: TimeBuckets<LogEvent>,
     /// Buffer for metrics events
-    metrics_buffer: VecDeque<MetricsEvent>,
+    metrics_buffer: TimeBuckets<MetricsEvent>,
     /// Buffer for disk events
-    disk_buffer: VecDeque<DiskEvent>,
+    disk_buffer: TimeBuckets<DiskEvent>,
+    /// Buffer for crash and hang reports
+    crash_buffer: TimeBuckets<CrashReport>,
+    /// Downsampled metrics and disk history, retained beyond `max_age`
+    rollups: Rollups,
+    /// Templates mined from every log message, retained beyond `max_age`
+    templates: LogTemplates,
     /// Maximum age for events before expiration
     max_age: Duration,
     /// Maximum number of events per buffer
     max_size: usize,
+    /// Time that ages are measured from; the current time when unset
+    clock: Option<Timestamp>,
 }
 
 impl EventAggregator {
@@ -43,17 +59,36 @@ impl EventAggregator {
     /// ```
     pub fn new(max_age: Duration, max_size: usize) -> Self {
         Self {
-            log_buffer: VecDeque::with_capacity(max_size),
-            metrics_buffer: VecDeque::with_capacity(max_size),
-            disk_buffer: VecDeque::with_capacity(max_size),
+            log_buffer: TimeBuckets::new(),
+            metrics_buffer: TimeBuckets::new(),
+            disk_buffer: TimeBuckets::new(),
+            crash_buffer: TimeBuckets::new(),
+            rollups: Rollups::new(),
+            templates: LogTemplates::new(),
             max_age,
             max_size,
+            clock: None,
         }
     }
 
+    /// Measure event ages from `now` instead of the current time
+    ///
+    /// Replay sets this as it steps through a capture, so recorded events are
+    /// windowed and pruned as of when they were captured.
+    pub fn set_clock(&mut self, now: Timestamp) {
+        self.clock = Some(now);
+    }
+
+    /// The time event ages are measured from
+    pub fn now(&self) -> Timestamp {
+        self.clock.unwrap_or_else(Utc::now)
+    }
+
     /// Add a log event to the buffer
     ///
-    /// Automatically prunes old entries and enforces capacity limits.
+    /// Automatically prunes old entries and enforces capacity limits. The
+    /// message is also mined into the log templates, and the template it
+    /// joined is stored with the event.
     ///
     /// # Arguments
     ///
@@ -68,9 +103,10 @@ impl EventAggregator {
             event.message.len()
         );
 
+        let template_id = self.templates.add(&event);
         let old_size = self.log_buffer.len();
-        self.log_buffer.push_back(event);
-        self.enforce_capacity_logs();
+        self.log_buffer.push_with_template(event, Some(template_id));
+        enforce_capacity(&mut self.log_buffer, self.max_size);
         self.prune_old_entries();
 
         let new_size = self.log_buffer.len();
@@ -84,7 +120,8 @@ impl EventAggregator {
 
     /// Add a metrics event to the buffer
     ///
-    /// Automatically prunes old entries and enforces capacity limits.
+    /// Automatically prunes old entries and enforces capacity limits. The
+    /// event's measured fields are also folded into the rollups.
     ///
     /// # Arguments
     ///
@@ -97,9 +134,10 @@ impl EventAggregator {
             event.timestamp, event.cpu_power_mw, event.memory_pressure
         );
 
+        self.rollups.add_metrics(&event);
         let old_size = self.metrics_buffer.len();
-        self.metrics_buffer.push_back(event);
-        self.enforce_capacity_metrics();
+        self.metrics_buffer.push(event);
+        enforce_capacity(&mut self.metrics_buffer, self.max_size);
         self.prune_old_entries();
 
         let new_size = self.metrics_buffer.len();
@@ -111,7 +149,8 @@ impl EventAggregator {
 
     /// Add a disk event to the buffer
     ///
-    /// Automatically prunes old entries and enforces capacity limits.
+    /// Automatically prunes old entries and enforces capacity limits. The
+    /// event's rates are also folded into the rollups.
     ///
     /// # Arguments
     ///
@@ -124,9 +163,10 @@ impl EventAggregator {
             event.timestamp, event.disk_name, event.read_kb_per_sec, event.write_kb_per_sec
         );
 
+        self.rollups.add_disk(&event);
         let old_size = self.disk_buffer.len();
-        self.disk_buffer.push_back(event);
-        self.enforce_capacity_disk();
+        self.disk_buffer.push(event);
+        enforce_capacity(&mut self.disk_buffer, self.max_size);
         self.prune_old_entries();
 
         let new_size = self.disk_buffer.len();
@@ -138,6 +178,132 @@ impl EventAggregator {
         }
     }
 
+    /// Add a crash report to the buffer
+    ///
+    /// Automatically prunes old entries and enforces capacity limits.
+    ///
+    /// # Arguments
+    ///
+    /// * `report` - The crash report to add
+    pub fn add_crash(&mut self, report: CrashReport) {
+        use log::debug;
+
+        debug!(
+            "Adding crash report: {} - {} - {:?}",
+            report.timestamp, report.process, report.kind
+        );
+
+        self.crash_buffer.push(report);
+        enforce_capacity(&mut self.crash_buffer, self.max_size);
+        self.prune_old_entries();
+    }
+
+    /// Borrow every buffer's events within the specified duration
+    ///
+    /// The snapshot holds references into the aggregator, so trigger rules can
+    /// evaluate recent events without copying them. Each window starts at the
+    /// bucket for its cutoff instead of scanning the whole buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - Time window to query (e.g., last 5 minutes)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use eyes::aggregator::EventAggregator;
+    /// use chrono::Duration;
+    ///
+    /// let aggregator = EventAggregator::new(Duration::seconds(60), 1000);
+    /// let snapshot = aggregator.snapshot(Duration::minutes(5));
+    /// assert!(snapshot.logs().is_empty());
+    /// ```
+    pub fn snapshot(&self, duration: Duration) -> EventSnapshot<'_> {
+        let now = self.now();
+        let cutoff = now - duration;
+        EventSnapshot::new(
+            self.log_buffer.window(cutoff),
+            self.metrics_buffer.window(cutoff),
+            self.disk_buffer.window(cutoff),
+            self.crash_buffer.window(cutoff),
+            Some(&self.rollups),
+            Some(&self.templates),
+        )
+        .at(now)
+    }
+
+    /// Downsampled metrics and disk history
+    ///
+    /// Rollups are not pruned with the raw buffers; each tier keeps its own
+    /// retention (see [`RollupTier::retention`](super::RollupTier::retention)).
+    pub fn rollups(&self) -> &Rollups {
+        &self.rollups
+    }
+
+    /// Templates mined from every log message seen so far
+    pub fn templates(&self) -> &LogTemplates {
+        &self.templates
+    }
+
+    /// Start a new template learning period now
+    ///
+    /// For restored state that may be missing the templates mined since it
+    /// was saved; see [`LogTemplates::learning_since`].
+    pub fn restart_template_learning(&mut self) {
+        let now = self.now();
+        self.templates.resume_at(now);
+    }
+
+    /// Copy every buffered event, the rollups and the log templates into an
+    /// owned state
+    pub fn export_state(&self) -> AggregatorState {
+        AggregatorState {
+            logs: buffered(&self.log_buffer),
+            metrics: buffered(&self.metrics_buffer),
+            disk: buffered(&self.disk_buffer),
+            crashes: buffered(&self.crash_buffer),
+            rollups: self.rollups.clone(),
+            templates: self.templates.clone(),
+        }
+    }
+
+    /// Load events, rollups and templates saved by [`export_state`](Self::export_state)
+    ///
+    /// Restored events go through the same capacity limit and `max_age`
+    /// expiry as new ones, so anything that aged out while the state was on
+    /// disk is discarded. The rollups and templates are replaced rather than
+    /// merged, and restored log events are not mined again.
+    ///
+    /// # Returns
+    ///
+    /// Number of events kept in the buffers
+    pub fn restore_state(&mut self, state: AggregatorState) -> usize {
+        for event in state.logs {
+            self.log_buffer.push(event);
+        }
+        for event in state.metrics {
+            self.metrics_buffer.push(event);
+        }
+        for event in state.disk {
+            self.disk_buffer.push(event);
+        }
+        for report in state.crashes {
+            self.crash_buffer.push(report);
+        }
+        enforce_capacity(&mut self.log_buffer, self.max_size);
+        enforce_capacity(&mut self.metrics_buffer, self.max_size);
+        enforce_capacity(&mut self.disk_buffer, self.max_size);
+        enforce_capacity(&mut self.crash_buffer, self.max_size);
+        self.rollups = state.rollups;
+        self.templates = state.templates;
+        self.prune_old_entries();
+
+        self.log_buffer.len()
+            + self.metrics_buffer.len()
+            + self.disk_buffer.len()
+            + self.crash_buffer.len()
+    }
+
     /// Get recent log events within the specified duration
     ///
     /// Returns references to all log events that occurred within the
@@ -151,10 +317,9 @@ impl EventAggregator {
     ///
     /// Vector of references to log events within the time window
     pub fn get_recent_logs(&self, duration: Duration) -> Vec<&LogEvent> {
-        let cutoff = Utc::now() - duration;
         self.log_buffer
+            .window(self.now() - duration)
             .iter()
-            .filter(|event| event.timestamp >= cutoff)
             .collect()
     }
 
@@ -171,10 +336,9 @@ impl EventAggregator {
     ///
     /// Vector of references to metrics events within the time window
     pub fn get_recent_metrics(&self, duration: Duration) -> Vec<&MetricsEvent> {
-        let cutoff = Utc::now() - duration;
         self.metrics_buffer
+            .window(self.now() - duration)
             .iter()
-            .filter(|event| event.timestamp >= cutoff)
             .collect()
     }
 
@@ -191,56 +355,50 @@ impl EventAggregator {
     ///
     /// Vector of references to disk events within the time window
     pub fn get_recent_disk(&self, duration: Duration) -> Vec<&DiskEvent> {
-        let cutoff = Utc::now() - duration;
         self.disk_buffer
+            .window(self.now() - duration)
             .iter()
-            .filter(|event| event.timestamp >= cutoff)
             .collect()
     }
 
-    /// Prune old entries from both buffers
+    /// Get recent crash reports within the specified duration
     ///
-    /// Removes all events older than `max_age` from both log and metrics buffers.
+    /// Returns references to all crash reports captured within the
+    /// specified duration from now.
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - Time window to query (e.g., last 60 seconds)
+    ///
+    /// # Returns
+    ///
+    /// Vector of references to crash reports within the time window
+    pub fn get_recent_crashes(&self, duration: Duration) -> Vec<&CrashReport> {
+        self.crash_buffer
+            .window(self.now() - duration)
+            .iter()
+            .collect()
+    }
+
+    /// Prune old entries from all buffers
+    ///
+    /// Removes all events older than `max_age`. Whole expired buckets are
+    /// dropped, so the cost depends on the number of expired seconds rather
+    /// than the number of buffered events.
     pub fn prune_old_entries(&mut self) {
         use log::debug;
 
-        let cutoff = Utc::now() - self.max_age;
+        let cutoff = self.now() - self.max_age;
         let initial_log_count = self.log_buffer.len();
         let initial_metrics_count = self.metrics_buffer.len();
         let initial_disk_count = self.disk_buffer.len();
 
-        // Remove old log events from the front
-        let mut pruned_logs = 0;
-        while let Some(event) = self.log_buffer.front() {
-            if event.timestamp < cutoff {
-                self.log_buffer.pop_front();
-                pruned_logs += 1;
-            } else {
-                break;
-            }
-        }
-
-        // Remove old metrics events from the front
-        let mut pruned_metrics = 0;
-        while let Some(event) = self.metrics_buffer.front() {
-            if event.timestamp < cutoff {
-                self.metrics_buffer.pop_front();
-                pruned_metrics += 1;
-            } else {
-                break;
-            }
-        }
-
-        // Remove old disk events from the front
-        let mut pruned_disk = 0;
-        while let Some(event) = self.disk_buffer.front() {
-            if event.timestamp < cutoff {
-                self.disk_buffer.pop_front();
-                pruned_disk += 1;
-            } else {
-                break;
-            }
-        }
+        let pruned_logs = self.log_buffer.prune_before(cutoff);
+        let pruned_metrics = self.metrics_buffer.prune_before(cutoff);
+        let pruned_disk = self.disk_buffer.prune_before(cutoff);
+        // Crash reports are written after the fact, but buckets are ordered by
+        // event time, so late reports still expire on schedule
+        self.crash_buffer.prune_before(cutoff);
 
         if pruned_logs > 0 || pruned_metrics > 0 || pruned_disk > 0 {
             debug!(
@@ -258,32 +416,35 @@ impl EventAggregator {
             );
         }
     }
+}
 
-    /// Enforce capacity limit for log buffer
-    ///
-    /// Removes oldest entries if buffer exceeds max_size
-    fn enforce_capacity_logs(&mut self) {
-        while self.log_buffer.len() > self.max_size {
-            self.log_buffer.pop_front();
-        }
-    }
+/// Owned copy of an aggregator's buffers and rollups
+///
+/// Used to carry the rolling buffer across restarts; see
+/// [`EventAggregator::export_state`] and [`EventAggregator::restore_state`].
+#[derive(Default, Serialize, Deserialize)]
+pub struct AggregatorState {
+    pub logs: Vec<LogEvent>,
+    pub metrics: Vec<MetricsEvent>,
+    pub disk: Vec<DiskEvent>,
+    pub crashes: Vec<CrashReport>,
+    #[serde(default)]
+    pub rollups: Rollups,
+    #[serde(default)]
+    pub templates: LogTemplates,
+}
 
-    /// Enforce capacity limit for metrics buffer
-    ///
-    /// Removes oldest entries if buffer exceeds max_size
-    fn enforce_capacity_metrics(&mut self) {
-        while self.metrics_buffer.len() > self.max_size {
-            self.metrics_buffer.pop_front();
-        }
-    }
+/// Clone every event in a buffer, oldest bucket first
+fn buffered<T: Clone + super::BufferedEvent>(buffer: &TimeBuckets<T>) -> Vec<T> {
+    buffer.window(Timestamp::MIN_UTC).iter().cloned().collect()
+}
 
-    /// Enforce capacity limit for disk buffer
-    ///
-    /// Removes oldest entries if buffer exceeds max_size
-    fn enforce_capacity_disk(&mut self) {
-        while self.disk_buffer.len() > self.max_size {
-            self.disk_buffer.pop_front();
-        }
+/// Enforce a buffer's capacity limit
+///
+/// Removes oldest entries if buffer exceeds max_size
+fn enforce_capacity<T: super::BufferedEvent>(buffer: &mut TimeBuckets<T>, max_size: usize) {
+    while buffer.len() > max_size {
+        buffer.evict_oldest();
     }
 }
 
@@ -352,6 +513,32 @@ mod tests {
         assert_eq!(recent[0].cpu_power_mw, 1234.5);
     }
 
+    #[test]
+    fn test_crash_reports_expire_with_buffer_age() {
+        let mut aggregator = EventAggregator::new(Duration::seconds(60), 100);
+        let now = Utc::now();
+        let report = |timestamp| CrashReport {
+            timestamp,
+            kind: crate::events::CrashReportKind::Crash,
+            process: "testd".to_string(),
+            process_id: 1234,
+            bundle_id: None,
+            exception_type: None,
+            exception_signal: None,
+            termination_reason: None,
+            frames: Vec::new(),
+            report_path: "/tmp/testd.ips".to_string(),
+        };
+
+        // Reports can arrive after newer ones, so expiry must not stop at the front
+        aggregator.add_crash(report(now - Duration::seconds(10)));
+        aggregator.add_crash(report(now - Duration::seconds(90)));
+
+        let recent = aggregator.get_recent_crashes(Duration::seconds(120));
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].timestamp, now - Duration::seconds(10));
+    }
+
     #[test]
     fn test_time_based_filtering() {
         let mut aggregator = EventAggregator::new(Duration::seconds(60), 100);
@@ -409,6 +596,129 @@ mod tests {
         let all_logs = aggregator.get_recent_logs(Duration::seconds(100));
         assert_eq!(all_logs.len(), 5);
     }
+
+    #[test]
+    fn test_clock_measures_ages_from_a_past_time() {
+        let mut aggregator = EventAggregator::new(Duration::seconds(60), 100);
+        let captured = Utc::now() - Duration::days(2);
+        aggregator.set_clock(captured);
+
+        aggregator.add_log(create_test_log_event(captured - Duration::seconds(90)));
+        aggregator.add_log(create_test_log_event(captured - Duration::seconds(30)));
+        aggregator.add_log(create_test_log_event(captured));
+
+        let snapshot = aggregator.snapshot(Duration::seconds(45));
+        assert_eq!(snapshot.now(), captured);
+        assert_eq!(snapshot.logs().len(), 2);
+        assert_eq!(aggregator.get_recent_logs(Duration::seconds(100)).len(), 2);
+    }
+
+    #[test]
+    fn test_snapshot_borrows_windows_and_indexes_sources() {
+        let mut aggregator = EventAggregator::new(Duration::seconds(300), 100);
+        let now = Utc::now();
+
+        for offset in [200, 40, 20] {
+            aggregator.add_log(create_test_log_event(now - Duration::seconds(offset)));
+        }
+        let mut other = create_test_log_event(now - Duration::seconds(10));
+        other.process = "otherd".to_string();
+        other.subsystem = "com.apple.other".to_string();
+        aggregator.add_log(other);
+        aggregator.add_metric(create_test_metrics_event(now - Duration::seconds(90)));
+        aggregator.add_metric(create_test_metrics_event(now - Duration::seconds(5)));
+
+        let snapshot = aggregator.snapshot(Duration::seconds(60));
+        assert_eq!(snapshot.logs().len(), 3);
+        assert_eq!(snapshot.metrics().len(), 1);
+        assert!(snapshot.disk().is_empty());
+        assert_eq!(snapshot.logs().for_process("testd").count(), 2);
+        assert_eq!(snapshot.logs().for_subsystem("com.apple.other").count(), 1);
+
+        let timestamps = snapshot
+            .logs()
+            .iter()
+            .map(|event| event.timestamp)
+            .collect::<Vec<_>>();
+        assert!(timestamps.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn test_rollups_outlive_raw_buffer() {
+        use crate::aggregator::{MetricField, RollupKey, RollupTier};
+        use crate::events::MeasurementKind;
+
+        let mut aggregator = EventAggregator::new(Duration::seconds(60), 100);
+        let now = Utc::now();
+        let mut old = create_test_metrics_event(now - Duration::minutes(30));
+        old.provenance.cpu_power = MeasurementKind::Measured;
+        let mut recent = create_test_metrics_event(now);
+        recent.provenance.cpu_power = MeasurementKind::Measured;
+        aggregator.add_metric(old);
+        aggregator.add_metric(recent);
+
+        assert_eq!(aggregator.get_recent_metrics(Duration::hours(1)).len(), 1);
+        let points = aggregator
+            .snapshot(Duration::seconds(60))
+            .rollups()
+            .map(|rollups| {
+                rollups.points(
+                    &RollupKey::Metric(MetricField::CpuPowerMw),
+                    RollupTier::OneMinute,
+                    now - Duration::hours(1),
+                )
+            });
+        assert_eq!(points.map(|points| points.len()), Some(2));
+    }
+
+    #[test]
+    fn test_state_round_trip_discards_expired_events() {
+        let mut aggregator = EventAggregator::new(Duration::seconds(60), 100);
+        let now = Utc::now();
+        aggregator.add_log(create_test_log_event(now - Duration::seconds(30)));
+        aggregator.add_metric(create_test_metrics_event(now - Duration::seconds(10)));
+        let mut state = aggregator.export_state();
+        state
+            .logs
+            .insert(0, create_test_log_event(now - Duration::seconds(120)));
+
+        let json = serde_json::to_string(&state).unwrap();
+        let mut restored = EventAggregator::new(Duration::seconds(60), 100);
+        let kept = restored.restore_state(serde_json::from_str(&json).unwrap());
+
+        assert_eq!(kept, 2);
+        assert_eq!(restored.get_recent_logs(Duration::minutes(5)).len(), 1);
+        assert_eq!(restored.get_recent_metrics(Duration::minutes(5)).len(), 1);
+        assert_eq!(restored.templates().len(), 1);
+        assert_eq!(
+            restored.templates().learning_since(),
+            aggregator.templates().learning_since()
+        );
+        restored.restart_template_learning();
+        assert!(restored.templates().learning_since() > aggregator.templates().learning_since());
+    }
+
+    #[test]
+    fn test_logs_keep_the_template_they_were_mined_into() {
+        let mut aggregator = EventAggregator::new(Duration::seconds(60), 100);
+        let now = Utc::now();
+        let mut first = create_test_log_event(now - Duration::seconds(2));
+        first.message = "Volume 7 not mounted".to_string();
+        let mut second = create_test_log_event(now - Duration::seconds(1));
+        second.message = "Volume 9 not mounted".to_string();
+        aggregator.add_log(first);
+        aggregator.add_log(second);
+        let id = aggregator.templates().iter().next().unwrap().id;
+
+        let snapshot = aggregator.snapshot(Duration::seconds(60));
+        for event in snapshot.logs() {
+            assert_eq!(snapshot.logs().template_id(event), Some(id));
+            assert_eq!(snapshot.template_of(event).map(|t| t.id), Some(id));
+        }
+        let summaries = snapshot.summarize_templates(snapshot.logs());
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].matched, 2);
+    }
 }
 
 // Property-based tests
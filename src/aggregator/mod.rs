@@ -1,4 +1,16 @@
 /// Event aggregator with rolling buffer implementation
 pub mod event_aggregator;
+/// Downsampled metrics and disk rollups
+pub mod rollups;
+/// Borrowed snapshots of the aggregator buffers
+pub mod snapshot;
+/// Online log template mining
+pub mod templates;
+/// Time-bucketed, indexed event storage
+pub mod time_buckets;
 
-pub use event_aggregator::EventAggregator;
+pub use event_aggregator::{AggregatorState, EventAggregator};
+pub use rollups::{DiskField, MetricField, RollupKey, RollupPoint, RollupTier, Rollups};
+pub use snapshot::EventSnapshot;
+pub use templates::{LogTemplate, LogTemplateSummary, LogTemplates};
+pub use time_buckets::{BufferedEvent, EventWindow, EventWindowIter};
@@ -0,0 +1,458 @@
+//! Downsampled metrics and disk rollups
+//!
+//! The raw buffers only cover `buffer.max_age_seconds`, which is too short to
+//! tell whether "now" is unusual. Rollups fold every numeric field of
+//! `MetricsEvent` and `DiskEvent` into 10-second, 1-minute and 15-minute
+//! min/avg/max/p95 points as samples arrive, and keep each tier far longer than
+//! the raw events.
+
+use crate::events::{DiskEvent, MeasurementKind, MetricsEvent, Timestamp};
+use chrono::{DateTime, Duration};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Resolution of a rollup series
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RollupTier {
+    /// 10-second points kept for one hour
+    TenSeconds,
+    /// 1-minute points kept for one day
+    OneMinute,
+    /// 15-minute points kept for one week
+    FifteenMinutes,
+}
+
+impl RollupTier {
+    /// Every tier, finest first
+    pub const ALL: [RollupTier; 3] = [Self::TenSeconds, Self::OneMinute, Self::FifteenMinutes];
+
+    /// Time covered by one point
+    pub fn span(self) -> Duration {
+        match self {
+            Self::TenSeconds => Duration::seconds(10),
+            Self::OneMinute => Duration::minutes(1),
+            Self::FifteenMinutes => Duration::minutes(15),
+        }
+    }
+
+    /// How long points are kept after their bucket starts
+    pub fn retention(self) -> Duration {
+        match self {
+            Self::TenSeconds => Duration::hours(1),
+            Self::OneMinute => Duration::days(1),
+            Self::FifteenMinutes => Duration::weeks(1),
+        }
+    }
+}
+
+/// Numeric `MetricsEvent` field tracked by the rollups
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricField {
+    CpuPowerMw,
+    CpuUsagePercent,
+    GpuPowerMw,
+    GpuUsagePercent,
+    MemoryUsedMb,
+    EnergyImpact,
+}
+
+impl MetricField {
+    /// Every tracked metrics field
+    pub const ALL: [MetricField; 6] = [
+        Self::CpuPowerMw,
+        Self::CpuUsagePercent,
+        Self::GpuPowerMw,
+        Self::GpuUsagePercent,
+        Self::MemoryUsedMb,
+        Self::EnergyImpact,
+    ];
+
+    /// The field's value, or `None` if the sample did not measure it
+    ///
+    /// Aggregate values whose provenance is unavailable are placeholders and
+    /// are skipped, as they are in the analysis prompt.
+    pub fn value(self, event: &MetricsEvent) -> Option<f64> {
+        let available = |kind: MeasurementKind| kind != MeasurementKind::Unavailable;
+        match self {
+            Self::CpuPowerMw => available(event.provenance.cpu_power).then_some(event.cpu_power_mw),
+            Self::CpuUsagePercent => {
+                available(event.provenance.cpu_usage).then_some(event.cpu_usage_percent)
+            }
+            Self::GpuPowerMw => event.gpu_power_mw,
+            Self::GpuUsagePercent => event.gpu_usage_percent,
+            Self::MemoryUsedMb => {
+                available(event.provenance.memory_used).then_some(event.memory_used_mb)
+            }
+            Self::EnergyImpact => {
+                available(event.provenance.energy_impact).then_some(event.energy_impact)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for MetricField {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CpuPowerMw => write!(formatter, "cpu_power_mw"),
+            Self::CpuUsagePercent => write!(formatter, "cpu_usage_percent"),
+            Self::GpuPowerMw => write!(formatter, "gpu_power_mw"),
+            Self::GpuUsagePercent => write!(formatter, "gpu_usage_percent"),
+            Self::MemoryUsedMb => write!(formatter, "memory_used_mb"),
+            Self::EnergyImpact => write!(formatter, "energy_impact"),
+        }
+    }
+}
+
+/// Numeric `DiskEvent` field tracked by the rollups
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiskField {
+    ReadKbPerSec,
+    WriteKbPerSec,
+    ReadOpsPerSec,
+    WriteOpsPerSec,
+}
+
+impl DiskField {
+    /// Every tracked disk field
+    pub const ALL: [DiskField; 4] = [
+        Self::ReadKbPerSec,
+        Self::WriteKbPerSec,
+        Self::ReadOpsPerSec,
+        Self::WriteOpsPerSec,
+    ];
+
+    /// The field's value in `event`
+    pub fn value(self, event: &DiskEvent) -> f64 {
+        match self {
+            Self::ReadKbPerSec => event.read_kb_per_sec,
+            Self::WriteKbPerSec => event.write_kb_per_sec,
+            Self::ReadOpsPerSec => event.read_ops_per_sec,
+            Self::WriteOpsPerSec => event.write_ops_per_sec,
+        }
+    }
+}
+
+impl std::fmt::Display for DiskField {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ReadKbPerSec => write!(formatter, "read_kb_per_sec"),
+            Self::WriteKbPerSec => write!(formatter, "write_kb_per_sec"),
+            Self::ReadOpsPerSec => write!(formatter, "read_ops_per_sec"),
+            Self::WriteOpsPerSec => write!(formatter, "write_ops_per_sec"),
+        }
+    }
+}
+
+/// Identifies one rolled-up series
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RollupKey {
+    /// A system-wide metrics field
+    Metric(MetricField),
+    /// A field of one disk device
+    Disk { disk_name: String, field: DiskField },
+}
+
+impl std::fmt::Display for RollupKey {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Metric(field) => write!(formatter, "{}", field),
+            Self::Disk { disk_name, field } => write!(formatter, "{}.{}", disk_name, field),
+        }
+    }
+}
+
+/// Summary of the samples that fell into one rollup bucket
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RollupPoint {
+    /// Start of the bucket, aligned to the tier's span
+    pub start: Timestamp,
+    /// Number of samples in the bucket
+    pub count: usize,
+    pub min: f64,
+    pub avg: f64,
+    pub max: f64,
+    /// 95th percentile (nearest rank)
+    pub p95: f64,
+}
+
+/// The bucket currently receiving samples
+#[derive(Clone, Serialize, Deserialize)]
+struct OpenBucket {
+    /// Unix second the bucket starts at
+    start: i64,
+    samples: Vec<f64>,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl OpenBucket {
+    fn new(start: i64) -> Self {
+        Self {
+            start,
+            samples: Vec::new(),
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    fn push(&mut self, value: f64) {
+        self.samples.push(value);
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    fn point(&self) -> RollupPoint {
+        let mut sorted = self.samples.clone();
+        sorted.sort_by(f64::total_cmp);
+        let rank = (sorted.len() * 95).div_ceil(100).max(1);
+        RollupPoint {
+            start: DateTime::from_timestamp(self.start, 0).unwrap_or_default(),
+            count: sorted.len(),
+            min: self.min,
+            avg: self.sum / sorted.len() as f64,
+            max: self.max,
+            p95: sorted[rank - 1],
+        }
+    }
+}
+
+/// One tier of one series
+#[derive(Clone, Serialize, Deserialize)]
+struct TierSeries {
+    tier: RollupTier,
+    open: Option<OpenBucket>,
+    closed: VecDeque<RollupPoint>,
+}
+
+impl TierSeries {
+    fn new(tier: RollupTier) -> Self {
+        Self {
+            tier,
+            open: None,
+            closed: VecDeque::new(),
+        }
+    }
+
+    fn add(&mut self, timestamp: Timestamp, value: f64) {
+        let span = self.tier.span().num_seconds();
+        let start = timestamp.timestamp().div_euclid(span) * span;
+        match &mut self.open {
+            Some(open) if open.start == start => open.push(value),
+            // Samples for an already closed bucket are dropped
+            Some(open) if open.start > start => return,
+            open => {
+                if let Some(finished) = open.take() {
+                    self.closed.push_back(finished.point());
+                }
+                let mut bucket = OpenBucket::new(start);
+                bucket.push(value);
+                *open = Some(bucket);
+            }
+        }
+
+        let horizon = start - self.tier.retention().num_seconds();
+        while self
+            .closed
+            .front()
+            .is_some_and(|point| point.start.timestamp() < horizon)
+        {
+            self.closed.pop_front();
+        }
+    }
+
+    fn points_since(&self, since: Timestamp) -> Vec<RollupPoint> {
+        let span = self.tier.span();
+        self.closed
+            .iter()
+            .copied()
+            .chain(self.open.as_ref().map(OpenBucket::point))
+            .filter(|point| point.start + span > since)
+            .collect()
+    }
+}
+
+/// Incrementally maintained rollups for every metrics and disk series
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Rollups {
+    #[serde(with = "series_entries")]
+    series: HashMap<RollupKey, Vec<TierSeries>>,
+}
+
+/// Serializes the series map as a list of entries, since JSON object keys
+/// cannot hold a `RollupKey`
+mod series_entries {
+    use super::{RollupKey, TierSeries};
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::collections::HashMap;
+
+    pub fn serialize<S: Serializer>(
+        series: &HashMap<RollupKey, Vec<TierSeries>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(series.iter())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<RollupKey, Vec<TierSeries>>, D::Error> {
+        Vec::<(RollupKey, Vec<TierSeries>)>::deserialize(deserializer)
+            .map(|entries| entries.into_iter().collect())
+    }
+}
+
+impl Rollups {
+    /// Create empty rollups
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fold every measured field of a metrics sample into its series
+    pub fn add_metrics(&mut self, event: &MetricsEvent) {
+        for field in MetricField::ALL {
+            if let Some(value) = field.value(event) {
+                self.record(RollupKey::Metric(field), event.timestamp, value);
+            }
+        }
+    }
+
+    /// Fold every field of a disk sample into its device's series
+    pub fn add_disk(&mut self, event: &DiskEvent) {
+        for field in DiskField::ALL {
+            let key = RollupKey::Disk {
+                disk_name: event.disk_name.clone(),
+                field,
+            };
+            self.record(key, event.timestamp, field.value(event));
+        }
+    }
+
+    fn record(&mut self, key: RollupKey, timestamp: Timestamp, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        let tiers = self
+            .series
+            .entry(key)
+            .or_insert_with(|| RollupTier::ALL.into_iter().map(TierSeries::new).collect());
+        for tier in tiers {
+            tier.add(timestamp, value);
+        }
+    }
+
+    /// Keys of every series that has received a sample
+    pub fn keys(&self) -> impl Iterator<Item = &RollupKey> {
+        self.series.keys()
+    }
+
+    /// Points of one series and tier whose buckets end after `since`, oldest first
+    ///
+    /// The last point is the bucket still receiving samples, so its statistics
+    /// may change on the next sample.
+    pub fn points(&self, key: &RollupKey, tier: RollupTier, since: Timestamp) -> Vec<RollupPoint> {
+        self.series
+            .get(key)
+            .and_then(|tiers| tiers.iter().find(|series| series.tier == tier))
+            .map(|series| series.points_since(since))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{MemoryPressure, MetricsProvenance};
+    use chrono::{TimeZone, Utc};
+
+    fn metrics_at(timestamp: Timestamp, cpu_usage_percent: f64) -> MetricsEvent {
+        MetricsEvent {
+            timestamp,
+            cpu_power_mw: 0.0,
+            cpu_usage_percent,
+            gpu_power_mw: None,
+            gpu_usage_percent: None,
+            memory_pressure: MemoryPressure::Normal,
+            memory_used_mb: 2048.0,
+            energy_impact: 0.0,
+            provenance: MetricsProvenance {
+                cpu_usage: MeasurementKind::Measured,
+                memory_used: MeasurementKind::Measured,
+                ..Default::default()
+            },
+            process_metrics: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_rollups_summarize_each_tier() {
+        let base = Utc.timestamp_opt(1_700_000_400, 0).unwrap();
+        let mut rollups = Rollups::new();
+        for second in 0..20 {
+            rollups.add_metrics(&metrics_at(base + Duration::seconds(second), second as f64));
+        }
+
+        let key = RollupKey::Metric(MetricField::CpuUsagePercent);
+        let points = rollups.points(&key, RollupTier::TenSeconds, base);
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].start, base);
+        assert_eq!(points[0].count, 10);
+        assert_eq!(points[0].min, 0.0);
+        assert_eq!(points[0].avg, 4.5);
+        assert_eq!(points[0].max, 9.0);
+        assert_eq!(points[0].p95, 9.0);
+        assert_eq!(points[1].min, 10.0);
+
+        let minute = rollups.points(&key, RollupTier::OneMinute, base);
+        assert_eq!(minute.len(), 1);
+        assert_eq!(minute[0].count, 20);
+        assert_eq!(minute[0].p95, 18.0);
+
+        // Unmeasured aggregate values are not rolled up
+        assert!(rollups
+            .points(
+                &RollupKey::Metric(MetricField::CpuPowerMw),
+                RollupTier::OneMinute,
+                base
+            )
+            .is_empty());
+    }
+
+    #[test]
+    fn test_rollups_expire_by_tier_retention_and_split_disks() {
+        let base = Utc.timestamp_opt(1_700_000_400, 0).unwrap();
+        let mut rollups = Rollups::new();
+        rollups.add_metrics(&metrics_at(base, 10.0));
+        rollups.add_metrics(&metrics_at(base + Duration::minutes(90), 20.0));
+
+        let key = RollupKey::Metric(MetricField::MemoryUsedMb);
+        let since = base - Duration::days(1);
+        assert_eq!(rollups.points(&key, RollupTier::TenSeconds, since).len(), 1);
+        assert_eq!(rollups.points(&key, RollupTier::OneMinute, since).len(), 2);
+
+        for (disk_name, read_kb_per_sec) in [("disk0", 100.0), ("disk1", 300.0)] {
+            rollups.add_disk(&DiskEvent {
+                timestamp: base,
+                read_kb_per_sec,
+                write_kb_per_sec: 0.0,
+                read_ops_per_sec: 0.0,
+                write_ops_per_sec: 0.0,
+                disk_name: disk_name.to_string(),
+                filesystem_path: None,
+            });
+        }
+        let disk1 = RollupKey::Disk {
+            disk_name: "disk1".to_string(),
+            field: DiskField::ReadKbPerSec,
+        };
+        let points = rollups.points(&disk1, RollupTier::FifteenMinutes, since);
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].max, 300.0);
+        assert_eq!(disk1.to_string(), "disk1.read_kb_per_sec");
+    }
+}
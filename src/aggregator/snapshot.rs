@@ -0,0 +1,164 @@
+//! Borrowed snapshots of the aggregator buffers
+//!
+//! An `EventSnapshot` bundles one [`EventWindow`] per event kind so trigger
+//! rules can read recent events straight out of the aggregator without cloning
+//! them, along with the aggregator's longer-lived rollups and log templates.
+//! Rules measure their windows against the snapshot's evaluation time rather
+//! than the wall clock, so past events can be evaluated as of when they
+//! happened.
+
+use super::rollups::Rollups;
+use super::templates::{LogTemplate, LogTemplateSummary, LogTemplates};
+use super::time_buckets::EventWindow;
+use crate::events::{CrashReport, DiskEvent, LogEvent, MetricsEvent, Timestamp};
+use chrono::Utc;
+
+/// Recent logs, metrics, disk events and crash reports borrowed for evaluation
+#[derive(Clone, Copy)]
+pub struct EventSnapshot<'a> {
+    logs: EventWindow<'a, LogEvent>,
+    metrics: EventWindow<'a, MetricsEvent>,
+    disk: EventWindow<'a, DiskEvent>,
+    crashes: EventWindow<'a, CrashReport>,
+    rollups: Option<&'a Rollups>,
+    templates: Option<&'a LogTemplates>,
+    now: Timestamp,
+}
+
+impl<'a> EventSnapshot<'a> {
+    pub(crate) fn new(
+        logs: EventWindow<'a, LogEvent>,
+        metrics: EventWindow<'a, MetricsEvent>,
+        disk: EventWindow<'a, DiskEvent>,
+        crashes: EventWindow<'a, CrashReport>,
+        rollups: Option<&'a Rollups>,
+        templates: Option<&'a LogTemplates>,
+    ) -> Self {
+        Self {
+            logs,
+            metrics,
+            disk,
+            crashes,
+            rollups,
+            templates,
+            now: Utc::now(),
+        }
+    }
+
+    /// Build a snapshot over events that are already in memory
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use eyes::aggregator::EventSnapshot;
+    ///
+    /// let snapshot = EventSnapshot::from_slices(&[], &[], &[]);
+    /// assert!(snapshot.logs().is_empty());
+    /// ```
+    pub fn from_slices(
+        logs: &'a [LogEvent],
+        metrics: &'a [MetricsEvent],
+        disk: &'a [DiskEvent],
+    ) -> Self {
+        Self::new(
+            EventWindow::from_slice(logs),
+            EventWindow::from_slice(metrics),
+            EventWindow::from_slice(disk),
+            EventWindow::from_slice(&[]),
+            None,
+            None,
+        )
+    }
+
+    /// Evaluate the snapshot as of `now` instead of the moment it was taken
+    ///
+    /// Callers should only include events that had been observed by `now`.
+    pub fn at(mut self, now: Timestamp) -> Self {
+        self.now = now;
+        self
+    }
+
+    /// The time rules evaluate the snapshot at
+    pub fn now(&self) -> Timestamp {
+        self.now
+    }
+
+    /// Attach crash reports to a snapshot built from slices
+    pub fn with_crashes(mut self, crashes: &'a [CrashReport]) -> Self {
+        self.crashes = EventWindow::from_slice(crashes);
+        self
+    }
+
+    /// Log events in the snapshot
+    pub fn logs(&self) -> EventWindow<'a, LogEvent> {
+        self.logs
+    }
+
+    /// Metrics events in the snapshot
+    pub fn metrics(&self) -> EventWindow<'a, MetricsEvent> {
+        self.metrics
+    }
+
+    /// Disk events in the snapshot
+    pub fn disk(&self) -> EventWindow<'a, DiskEvent> {
+        self.disk
+    }
+
+    /// Crash and hang reports in the snapshot
+    pub fn crashes(&self) -> EventWindow<'a, CrashReport> {
+        self.crashes
+    }
+
+    /// Attach rollups to a snapshot built from slices
+    pub fn with_rollups(mut self, rollups: &'a Rollups) -> Self {
+        self.rollups = Some(rollups);
+        self
+    }
+
+    /// Downsampled metrics and disk history, when the snapshot came from an
+    /// aggregator or had rollups attached
+    pub fn rollups(&self) -> Option<&'a Rollups> {
+        self.rollups
+    }
+
+    /// Attach log templates to a snapshot built from slices
+    pub fn with_templates(mut self, templates: &'a LogTemplates) -> Self {
+        self.templates = Some(templates);
+        self
+    }
+
+    /// Log templates mined from every message the aggregator has seen, when
+    /// the snapshot came from an aggregator or had templates attached
+    pub fn templates(&self) -> Option<&'a LogTemplates> {
+        self.templates
+    }
+
+    /// The template a log event belongs to
+    ///
+    /// Events of an aggregator snapshot use the template they were mined into
+    /// when they arrived; other events are matched against the templates.
+    pub fn template_of(&self, event: &LogEvent) -> Option<&'a LogTemplate> {
+        let templates = self.templates?;
+        match self.logs.template_id(event) {
+            Some(id) => templates.get(id),
+            None => templates.find(event),
+        }
+    }
+
+    /// Summarize the templates of `events`, most matched first; empty when
+    /// the snapshot has no templates
+    pub fn summarize_templates<'e>(
+        &self,
+        events: impl IntoIterator<Item = &'e LogEvent>,
+    ) -> Vec<LogTemplateSummary> {
+        let Some(templates) = self.templates else {
+            return Vec::new();
+        };
+        templates.summarize_ids(
+            events
+                .into_iter()
+                .filter_map(|event| self.template_of(event))
+                .map(|template| template.id),
+        )
+    }
+}
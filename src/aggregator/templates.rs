@@ -0,0 +1,522 @@
+//! Online log template mining
+//!
+//! Every log message that reaches the aggregator is reduced to a template in
+//! the style of Drain: the message is split on whitespace, variable-looking
+//! tokens (numbers, hex values, UUIDs, IP addresses and paths) are masked, and
+//! the result is clustered with the most similar existing template of the same
+//! process and token count. Positions where clustered messages disagree become
+//! `<*>` wildcards, so `disk0: I/O error at block 1234` and
+//! `disk2: I/O error at block 99` end up as `<*> I/O error at block <NUM>`.
+//!
+//! Templates keep a lifetime count and the times they were first seen, first
+//! seen as an error and last seen. They are saved with the aggregator state so
+//! "new" means new since the templates were first mined, not since the last
+//! restart. Templates mined after the last checkpoint are lost in a crash, so
+//! restoring a checkpoint that was not written on shutdown starts a fresh
+//! learning period.
+
+use crate::events::{LogEvent, MessageType, Timestamp};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Fraction of positions that must agree for a message to join a template
+const SIMILARITY_THRESHOLD: f64 = 0.5;
+
+/// Templates kept before the least recently seen one is evicted
+const MAX_TEMPLATES: usize = 10_000;
+
+/// Template token standing for any value
+pub const WILDCARD: &str = "<*>";
+
+/// One mined log template
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogTemplate {
+    /// Stable identifier, unique for the lifetime of the template store
+    pub id: u64,
+    /// Process whose messages form the template
+    pub process: String,
+    /// Masked tokens, with `<*>` where clustered messages differ
+    pub tokens: Vec<String>,
+    /// Messages matched since the template was created
+    pub count: u64,
+    /// Of those, messages of type error or fault
+    pub error_count: u64,
+    pub first_seen: Timestamp,
+    /// First error or fault message matched, if any
+    pub first_error_seen: Option<Timestamp>,
+    pub last_seen: Timestamp,
+}
+
+impl LogTemplate {
+    /// The template as text
+    pub fn text(&self) -> String {
+        self.tokens.join(" ")
+    }
+
+    /// Fraction of positions where `tokens` agrees with the template
+    fn similarity(&self, tokens: &[String]) -> f64 {
+        if tokens.is_empty() {
+            return 1.0;
+        }
+        let matching = self
+            .tokens
+            .iter()
+            .zip(tokens)
+            .filter(|(template, token)| *template == WILDCARD || template == token)
+            .count();
+        matching as f64 / tokens.len() as f64
+    }
+}
+
+/// What a trigger context records about the templates of its log events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogTemplateSummary {
+    pub id: u64,
+    pub process: String,
+    pub template: String,
+    /// Events in the context that match the template
+    pub matched: usize,
+    /// Lifetime count of the template
+    pub count: u64,
+    pub first_seen: Timestamp,
+    pub first_error_seen: Option<Timestamp>,
+}
+
+/// Online template miner over the log stream
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "SavedTemplates", into = "SavedTemplates")]
+pub struct LogTemplates {
+    /// When the first message was mined
+    started_at: Option<Timestamp>,
+    /// When the templates were last restored from saved state; not saved
+    resumed_at: Option<Timestamp>,
+    next_id: u64,
+    templates: BTreeMap<u64, LogTemplate>,
+    /// Template ids by process and token count
+    index: HashMap<(String, usize), Vec<u64>>,
+}
+
+/// Serialized form of [`LogTemplates`]; the index is rebuilt on load
+#[derive(Serialize, Deserialize)]
+struct SavedTemplates {
+    started_at: Option<Timestamp>,
+    next_id: u64,
+    templates: Vec<LogTemplate>,
+}
+
+impl From<SavedTemplates> for LogTemplates {
+    fn from(saved: SavedTemplates) -> Self {
+        let mut templates = Self {
+            started_at: saved.started_at,
+            next_id: saved.next_id,
+            ..Self::default()
+        };
+        for template in saved.templates {
+            templates.next_id = templates.next_id.max(template.id + 1);
+            templates
+                .index
+                .entry((template.process.clone(), template.tokens.len()))
+                .or_default()
+                .push(template.id);
+            templates.templates.insert(template.id, template);
+        }
+        templates
+    }
+}
+
+impl From<LogTemplates> for SavedTemplates {
+    fn from(templates: LogTemplates) -> Self {
+        Self {
+            started_at: templates.started_at,
+            next_id: templates.next_id,
+            templates: templates.templates.into_values().collect(),
+        }
+    }
+}
+
+impl LogTemplates {
+    /// Create an empty template store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// When the first message was mined, if any
+    pub fn started_at(&self) -> Option<Timestamp> {
+        self.started_at
+    }
+
+    /// Record that the templates were restored from saved state at `at`
+    pub fn resume_at(&mut self, at: Timestamp) {
+        self.resumed_at = Some(at);
+    }
+
+    /// When the current learning period began: the first mined message, or
+    /// the last restore if that came later
+    ///
+    /// Saved state may be missing the templates mined just before a crash, so
+    /// templates are not new until the learning period after a restore has
+    /// passed.
+    pub fn learning_since(&self) -> Option<Timestamp> {
+        let started_at = self.started_at?;
+        Some(
+            self.resumed_at
+                .map_or(started_at, |resumed| resumed.max(started_at)),
+        )
+    }
+
+    /// Number of templates
+    pub fn len(&self) -> usize {
+        self.templates.len()
+    }
+
+    /// Whether no template has been mined yet
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+
+    /// Template by id
+    pub fn get(&self, id: u64) -> Option<&LogTemplate> {
+        self.templates.get(&id)
+    }
+
+    /// Every template, by id
+    pub fn iter(&self) -> impl Iterator<Item = &LogTemplate> {
+        self.templates.values()
+    }
+
+    /// Mine one log event, returning the id of the template it joined
+    pub fn add(&mut self, event: &LogEvent) -> u64 {
+        let tokens = tokenize(&event.message);
+        let is_error = matches!(event.message_type, MessageType::Error | MessageType::Fault);
+        self.started_at = Some(
+            self.started_at
+                .map_or(event.timestamp, |started| started.min(event.timestamp)),
+        );
+
+        if let Some(id) = self.best_match(&event.process, &tokens) {
+            let template = self
+                .templates
+                .get_mut(&id)
+                .expect("indexed template exists");
+            for (template_token, token) in template.tokens.iter_mut().zip(&tokens) {
+                if template_token != token {
+                    *template_token = WILDCARD.to_string();
+                }
+            }
+            template.count += 1;
+            template.first_seen = template.first_seen.min(event.timestamp);
+            template.last_seen = template.last_seen.max(event.timestamp);
+            if is_error {
+                template.error_count += 1;
+                template.first_error_seen = Some(
+                    template
+                        .first_error_seen
+                        .map_or(event.timestamp, |first| first.min(event.timestamp)),
+                );
+            }
+            return id;
+        }
+
+        if self.templates.len() >= MAX_TEMPLATES {
+            self.evict_least_recently_seen();
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.index
+            .entry((event.process.clone(), tokens.len()))
+            .or_default()
+            .push(id);
+        self.templates.insert(
+            id,
+            LogTemplate {
+                id,
+                process: event.process.clone(),
+                tokens,
+                count: 1,
+                error_count: u64::from(is_error),
+                first_seen: event.timestamp,
+                first_error_seen: is_error.then_some(event.timestamp),
+                last_seen: event.timestamp,
+            },
+        );
+        id
+    }
+
+    /// The template an event belongs to, without mining it
+    pub fn find(&self, event: &LogEvent) -> Option<&LogTemplate> {
+        let tokens = tokenize(&event.message);
+        self.best_match(&event.process, &tokens)
+            .and_then(|id| self.templates.get(&id))
+    }
+
+    /// Summarize the templates of a trigger context's log events, most matched first
+    pub fn summarize<'a>(
+        &self,
+        events: impl IntoIterator<Item = &'a LogEvent>,
+    ) -> Vec<LogTemplateSummary> {
+        self.summarize_ids(
+            events
+                .into_iter()
+                .filter_map(|event| self.find(event))
+                .map(|template| template.id),
+        )
+    }
+
+    /// Summarize the templates with the given ids, one id per matched event
+    pub(crate) fn summarize_ids(
+        &self,
+        ids: impl IntoIterator<Item = u64>,
+    ) -> Vec<LogTemplateSummary> {
+        let mut matched = BTreeMap::<u64, usize>::new();
+        for id in ids {
+            *matched.entry(id).or_default() += 1;
+        }
+        let mut summaries = matched
+            .into_iter()
+            .filter_map(|(id, matched)| {
+                let template = self.templates.get(&id)?;
+                Some(LogTemplateSummary {
+                    id,
+                    process: template.process.clone(),
+                    template: template.text(),
+                    matched,
+                    count: template.count,
+                    first_seen: template.first_seen,
+                    first_error_seen: template.first_error_seen,
+                })
+            })
+            .collect::<Vec<_>>();
+        summaries.sort_by_key(|summary| std::cmp::Reverse(summary.matched));
+        summaries
+    }
+
+    fn best_match(&self, process: &str, tokens: &[String]) -> Option<u64> {
+        let candidates = self.index.get(&(process.to_string(), tokens.len()))?;
+        candidates
+            .iter()
+            .filter_map(|id| {
+                let similarity = self.templates.get(id)?.similarity(tokens);
+                (similarity >= SIMILARITY_THRESHOLD).then_some((*id, similarity))
+            })
+            .max_by(|(_, left), (_, right)| left.total_cmp(right))
+            .map(|(id, _)| id)
+    }
+
+    fn evict_least_recently_seen(&mut self) {
+        let Some(template) = self
+            .templates
+            .values()
+            .min_by_key(|template| template.last_seen)
+            .cloned()
+        else {
+            return;
+        };
+        self.templates.remove(&template.id);
+        let key = (template.process, template.tokens.len());
+        if let Some(ids) = self.index.get_mut(&key) {
+            ids.retain(|id| *id != template.id);
+            if ids.is_empty() {
+                self.index.remove(&key);
+            }
+        }
+    }
+}
+
+/// Split a message into masked tokens
+fn tokenize(message: &str) -> Vec<String> {
+    message.split_whitespace().map(mask_token).collect()
+}
+
+/// Replace a variable-looking token, keeping surrounding punctuation
+///
+/// `key=value` tokens keep the key and mask the value.
+fn mask_token(token: &str) -> String {
+    let is_punctuation = |c: char| "()[]{}<>,;:'\"".contains(c);
+    let core = token.trim_matches(is_punctuation);
+    if core.is_empty() {
+        return token.to_string();
+    }
+    let start = token.len() - token.trim_start_matches(is_punctuation).len();
+    let (prefix, suffix) = (&token[..start], &token[start + core.len()..]);
+
+    let masked = match core.split_once('=') {
+        Some((key, value)) if !key.is_empty() && !value.is_empty() => {
+            mask_value(value).map(|mask| format!("{}={}", key, mask))
+        }
+        _ => mask_value(core).map(str::to_string),
+    };
+    match masked {
+        Some(masked) => format!("{}{}{}", prefix, masked, suffix),
+        None => token.to_string(),
+    }
+}
+
+/// The mask for a value, or `None` if it looks like literal text
+fn mask_value(value: &str) -> Option<&'static str> {
+    if is_uuid(value) {
+        Some("<UUID>")
+    } else if is_path(value) {
+        Some("<PATH>")
+    } else if is_ip(value) {
+        Some("<IP>")
+    } else if is_number(value) {
+        Some("<NUM>")
+    } else if is_hex(value) {
+        Some("<HEX>")
+    } else {
+        None
+    }
+}
+
+fn is_uuid(value: &str) -> bool {
+    value.len() == 36
+        && value.char_indices().all(|(index, c)| match index {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+fn is_path(value: &str) -> bool {
+    (value.starts_with('/') || value.starts_with("~/")) && value.len() > 1
+}
+
+fn is_ip(value: &str) -> bool {
+    let address = value
+        .rsplit_once(':')
+        .filter(|(_, port)| !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()))
+        .map_or(value, |(address, _)| address);
+    let octets = address.split('.').collect::<Vec<_>>();
+    octets.len() == 4
+        && octets
+            .iter()
+            .all(|octet| !octet.is_empty() && octet.len() <= 3 && octet.parse::<u8>().is_ok())
+}
+
+fn is_hex(value: &str) -> bool {
+    if let Some(digits) = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        return !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_hexdigit());
+    }
+    value.len() >= 8
+        && value.bytes().all(|b| b.is_ascii_hexdigit())
+        && value.bytes().any(|b| b.is_ascii_digit())
+}
+
+/// Numbers with an optional sign, separators and a short unit such as `ms` or `%`
+fn is_number(value: &str) -> bool {
+    let value = value.strip_prefix(['-', '+']).unwrap_or(value);
+    let unit_start = value
+        .find(|c: char| !(c.is_ascii_digit() || ".,:".contains(c)))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(unit_start);
+    number.starts_with(|c: char| c.is_ascii_digit())
+        && unit.len() <= 3
+        && (unit.chars().all(|c| c.is_ascii_alphabetic()) || unit == "%")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    fn log_event(process: &str, message_type: MessageType, message: &str) -> LogEvent {
+        LogEvent {
+            timestamp: Utc::now(),
+            message_type,
+            subsystem: "com.apple.kernel".to_string(),
+            category: "default".to_string(),
+            process: process.to_string(),
+            process_id: 0,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_masks_variable_tokens() {
+        assert_eq!(
+            tokenize(
+                "task 4242 (pid=17) read /var/db/x.db from 10.0.0.1:8080 in 12ms, id 0x1f \
+                 uuid 123e4567-e89b-12d3-a456-426614174000 load 95%"
+            )
+            .join(" "),
+            "task <NUM> (pid=<NUM>) read <PATH> from <IP> in <NUM>, id <HEX> \
+             uuid <UUID> load <NUM>"
+        );
+        assert_eq!(tokenize("disk0s1 failed").join(" "), "disk0s1 failed");
+    }
+
+    #[test]
+    fn test_clusters_messages_into_templates() {
+        let mut templates = LogTemplates::new();
+        let first = templates.add(&log_event(
+            "kernel",
+            MessageType::Info,
+            "disk0: I/O error at block 1234",
+        ));
+        let second = templates.add(&log_event(
+            "kernel",
+            MessageType::Error,
+            "disk2: I/O error at block 99",
+        ));
+        let other_process = templates.add(&log_event(
+            "mds",
+            MessageType::Error,
+            "disk0: I/O error at block 1234",
+        ));
+        let unrelated = templates.add(&log_event(
+            "kernel",
+            MessageType::Info,
+            "wake reason: power button pressed",
+        ));
+
+        assert_eq!(first, second);
+        assert_ne!(first, other_process);
+        assert_ne!(first, unrelated);
+        let template = templates.get(first).unwrap();
+        assert_eq!(template.text(), "<*> I/O error at block <NUM>");
+        assert_eq!((template.count, template.error_count), (2, 1));
+        assert!(template.first_error_seen.is_some());
+        assert_eq!(
+            templates
+                .find(&log_event(
+                    "kernel",
+                    MessageType::Info,
+                    "disk7: I/O error at block 5"
+                ))
+                .map(|template| template.id),
+            Some(first)
+        );
+        assert!(templates
+            .find(&log_event(
+                "kernel",
+                MessageType::Info,
+                "completely different words here"
+            ))
+            .is_none());
+    }
+
+    #[test]
+    fn test_templates_survive_serialization() {
+        let mut templates = LogTemplates::new();
+        let mut old = log_event("kernel", MessageType::Error, "panic in thread 12");
+        old.timestamp -= Duration::days(3);
+        let id = templates.add(&old);
+
+        let json = serde_json::to_string(&templates).unwrap();
+        let mut restored: LogTemplates = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.started_at(), Some(old.timestamp));
+        assert_eq!(
+            restored.add(&log_event(
+                "kernel",
+                MessageType::Error,
+                "panic in thread 40"
+            )),
+            id
+        );
+        let new_id = restored.add(&log_event("kernel", MessageType::Info, "booted"));
+        assert!(new_id > id);
+        assert_eq!(restored.get(id).unwrap().first_seen, old.timestamp);
+    }
+}
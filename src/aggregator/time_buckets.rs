@@ -0,0 +1,571 @@
+//! Time-bucketed event storage
+//!
+//! Each aggregator buffer keeps its events in one-second buckets ordered by
+//! time. Expiry drops whole buckets, a time window starts at a bucket found by
+//! binary search instead of a scan over the buffer, and every bucket carries a
+//! process and subsystem index so rules can look up one source without walking
+//! unrelated events. Log buckets also keep the template each message was mined
+//! into, so rules do not tokenize the message again on every pass.
+
+use crate::events::{CrashReport, DiskEvent, LogEvent, MetricsEvent, Timestamp};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{vec_deque, HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+
+/// An event that can be stored in the aggregator's time buckets
+pub trait BufferedEvent {
+    /// When the event occurred
+    fn timestamp(&self) -> Timestamp;
+
+    /// Process the event belongs to, if it is indexed by process
+    fn process(&self) -> Option<&str> {
+        None
+    }
+
+    /// Subsystem the event belongs to, if it is indexed by subsystem
+    fn subsystem(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl BufferedEvent for LogEvent {
+    fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    fn process(&self) -> Option<&str> {
+        Some(&self.process)
+    }
+
+    fn subsystem(&self) -> Option<&str> {
+        Some(&self.subsystem)
+    }
+}
+
+impl BufferedEvent for MetricsEvent {
+    fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+}
+
+impl BufferedEvent for DiskEvent {
+    fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+}
+
+impl BufferedEvent for CrashReport {
+    fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    fn process(&self) -> Option<&str> {
+        Some(&self.process)
+    }
+}
+
+/// Events whose timestamps fall within one whole second
+pub(crate) struct Bucket<T> {
+    /// Unix second covered by this bucket
+    second: i64,
+    /// Events in arrival order
+    events: Vec<T>,
+    /// Number of leading events evicted by capacity enforcement
+    evicted: usize,
+    /// Positions in `events` by hash of their process
+    by_process: HashMap<u64, Vec<usize>>,
+    /// Positions in `events` by hash of their subsystem
+    by_subsystem: HashMap<u64, Vec<usize>>,
+    /// Template id of each event in `events`, for log events mined at ingest
+    template_ids: Vec<Option<u64>>,
+}
+
+impl<T: BufferedEvent> Bucket<T> {
+    fn new(second: i64) -> Self {
+        Self {
+            second,
+            events: Vec::new(),
+            evicted: 0,
+            by_process: HashMap::new(),
+            by_subsystem: HashMap::new(),
+            template_ids: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, event: T, template_id: Option<u64>) {
+        let position = self.events.len();
+        if let Some(process) = event.process() {
+            self.by_process
+                .entry(name_key(process))
+                .or_default()
+                .push(position);
+        }
+        if let Some(subsystem) = event.subsystem() {
+            self.by_subsystem
+                .entry(name_key(subsystem))
+                .or_default()
+                .push(position);
+        }
+        self.events.push(event);
+        self.template_ids.push(template_id);
+    }
+
+    fn live(&self) -> &[T] {
+        &self.events[self.evicted..]
+    }
+
+    /// Template id stored with `event`, if it is one of this bucket's live events
+    fn template_id(&self, event: &T) -> Option<u64> {
+        let position = self
+            .live()
+            .iter()
+            .position(|stored| std::ptr::eq(stored, event))?;
+        self.template_ids[self.evicted + position]
+    }
+
+    /// Live events whose `field` is `key`, looked up through `index`
+    ///
+    /// The index is keyed by a hash of the name so pushing an event never
+    /// copies it; the stored event is compared to rule out hash collisions.
+    fn indexed<'a, 'k>(
+        &'a self,
+        index: &'a HashMap<u64, Vec<usize>>,
+        key: &'k str,
+        field: fn(&T) -> Option<&str>,
+    ) -> impl Iterator<Item = &'a T> + 'k
+    where
+        'a: 'k,
+    {
+        index
+            .get(&name_key(key))
+            .into_iter()
+            .flatten()
+            .filter(move |position| **position >= self.evicted)
+            .map(move |position| &self.events[*position])
+            .filter(move |event| field(event) == Some(key))
+    }
+}
+
+/// Index key for a process or subsystem name
+fn name_key(name: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    name.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Buffer of events grouped into ordered one-second buckets
+pub(crate) struct TimeBuckets<T> {
+    buckets: VecDeque<Bucket<T>>,
+    len: usize,
+}
+
+impl<T: BufferedEvent> TimeBuckets<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            buckets: VecDeque::new(),
+            len: 0,
+        }
+    }
+
+    /// Number of events currently stored
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Store an event in the bucket for its timestamp
+    ///
+    /// Events normally land in the newest bucket; late arrivals such as crash
+    /// reports are placed by searching backwards from it.
+    pub(crate) fn push(&mut self, event: T) {
+        self.push_with_template(event, None);
+    }
+
+    /// Store a log event together with the id of the template it was mined into
+    pub(crate) fn push_with_template(&mut self, event: T, template_id: Option<u64>) {
+        let second = event.timestamp().timestamp();
+        let index = match self
+            .buckets
+            .iter()
+            .rposition(|bucket| bucket.second <= second)
+        {
+            Some(index) if self.buckets[index].second == second => index,
+            Some(index) => {
+                self.buckets.insert(index + 1, Bucket::new(second));
+                index + 1
+            }
+            None => {
+                self.buckets.push_front(Bucket::new(second));
+                0
+            }
+        };
+        self.buckets[index].push(event, template_id);
+        self.len += 1;
+    }
+
+    /// Evict the earliest-arrived event of the oldest bucket
+    pub(crate) fn evict_oldest(&mut self) {
+        let Some(bucket) = self.buckets.front_mut() else {
+            return;
+        };
+        bucket.evicted += 1;
+        if bucket.evicted == bucket.events.len() {
+            self.buckets.pop_front();
+        }
+        self.len -= 1;
+    }
+
+    /// Drop every bucket that ends before `cutoff`, returning how many events
+    /// were removed
+    ///
+    /// Expiry works on whole seconds, so an event can outlive `cutoff` by less
+    /// than a second; windows still filter at the exact cutoff.
+    pub(crate) fn prune_before(&mut self, cutoff: Timestamp) -> usize {
+        let mut pruned = 0;
+        while let Some(bucket) = self.buckets.front() {
+            if bucket.second >= cutoff.timestamp() {
+                break;
+            }
+            pruned += bucket.live().len();
+            self.buckets.pop_front();
+        }
+        self.len -= pruned;
+        pruned
+    }
+
+    /// Borrow the events at or after `cutoff`
+    pub(crate) fn window(&self, cutoff: Timestamp) -> EventWindow<'_, T> {
+        let first = self
+            .buckets
+            .partition_point(|bucket| bucket.second < cutoff.timestamp());
+        let len = self
+            .buckets
+            .range(first..)
+            .enumerate()
+            .map(|(offset, bucket)| {
+                if offset == 0 {
+                    bucket
+                        .live()
+                        .iter()
+                        .filter(|event| event.timestamp() >= cutoff)
+                        .count()
+                } else {
+                    bucket.live().len()
+                }
+            })
+            .sum();
+        EventWindow {
+            source: WindowSource::Buckets {
+                buckets: &self.buckets,
+                first,
+                cutoff,
+            },
+            len,
+        }
+    }
+}
+
+/// Borrowed view over the events of one kind inside a time window
+///
+/// Windows are cheap to copy and never clone the events they cover. They are
+/// produced by [`EventAggregator::snapshot`](super::EventAggregator::snapshot)
+/// or wrap a plain slice with [`EventWindow::from_slice`].
+pub struct EventWindow<'a, T> {
+    source: WindowSource<'a, T>,
+    len: usize,
+}
+
+enum WindowSource<'a, T> {
+    Slice(&'a [T]),
+    Buckets {
+        buckets: &'a VecDeque<Bucket<T>>,
+        first: usize,
+        cutoff: Timestamp,
+    },
+}
+
+impl<T> Clone for WindowSource<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for WindowSource<'_, T> {}
+
+impl<T> Clone for EventWindow<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for EventWindow<'_, T> {}
+
+impl<'a, T: BufferedEvent> EventWindow<'a, T> {
+    /// View every event in `events`
+    pub fn from_slice(events: &'a [T]) -> Self {
+        Self {
+            source: WindowSource::Slice(events),
+            len: events.len(),
+        }
+    }
+
+    /// Number of events in the window
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the window holds no events
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterate over the events in storage order
+    ///
+    /// Bucketed windows yield events second by second; within a second they
+    /// come in arrival order.
+    pub fn iter(&self) -> EventWindowIter<'a, T> {
+        match self.source {
+            WindowSource::Slice(events) => EventWindowIter {
+                current: events.iter(),
+                cutoff: None,
+                pending_cutoff: None,
+                buckets: None,
+            },
+            WindowSource::Buckets {
+                buckets,
+                first,
+                cutoff,
+            } => EventWindowIter {
+                current: [].iter(),
+                cutoff: None,
+                pending_cutoff: Some(cutoff),
+                buckets: Some(buckets.range(first..)),
+            },
+        }
+    }
+
+    /// Template id stored with `event` when it was added to the aggregator
+    ///
+    /// `None` for events that are not in this window's buckets, such as
+    /// events of a slice window or copies, and for events stored without
+    /// being mined.
+    pub fn template_id(&self, event: &T) -> Option<u64> {
+        let WindowSource::Buckets { buckets, first, .. } = self.source else {
+            return None;
+        };
+        let second = event.timestamp().timestamp();
+        let index = buckets
+            .partition_point(|bucket| bucket.second < second)
+            .max(first);
+        buckets
+            .get(index)
+            .filter(|bucket| bucket.second == second)?
+            .template_id(event)
+    }
+
+    /// Events from one process, looked up through the bucket index
+    pub fn for_process<'k>(&self, process: &'k str) -> Box<dyn Iterator<Item = &'a T> + 'k>
+    where
+        'a: 'k,
+    {
+        self.indexed(
+            process,
+            |bucket| &bucket.by_process,
+            |event| event.process(),
+        )
+    }
+
+    /// Events from one subsystem, looked up through the bucket index
+    pub fn for_subsystem<'k>(&self, subsystem: &'k str) -> Box<dyn Iterator<Item = &'a T> + 'k>
+    where
+        'a: 'k,
+    {
+        self.indexed(
+            subsystem,
+            |bucket| &bucket.by_subsystem,
+            |event| event.subsystem(),
+        )
+    }
+
+    fn indexed<'k>(
+        &self,
+        key: &'k str,
+        index: fn(&Bucket<T>) -> &HashMap<u64, Vec<usize>>,
+        field: fn(&T) -> Option<&str>,
+    ) -> Box<dyn Iterator<Item = &'a T> + 'k>
+    where
+        'a: 'k,
+    {
+        match self.source {
+            WindowSource::Slice(events) => {
+                Box::new(events.iter().filter(move |event| field(event) == Some(key)))
+            }
+            WindowSource::Buckets {
+                buckets,
+                first,
+                cutoff,
+            } => Box::new(
+                buckets
+                    .range(first..)
+                    .flat_map(move |bucket| bucket.indexed(index(bucket), key, field))
+                    .filter(move |event| event.timestamp() >= cutoff),
+            ),
+        }
+    }
+}
+
+impl<'a, T: BufferedEvent> IntoIterator for EventWindow<'a, T> {
+    type Item = &'a T;
+    type IntoIter = EventWindowIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T: BufferedEvent> IntoIterator for &EventWindow<'a, T> {
+    type Item = &'a T;
+    type IntoIter = EventWindowIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the events of an [`EventWindow`]
+pub struct EventWindowIter<'a, T> {
+    current: std::slice::Iter<'a, T>,
+    /// Cutoff applied to `current`; only the first bucket can straddle it
+    cutoff: Option<Timestamp>,
+    pending_cutoff: Option<Timestamp>,
+    buckets: Option<vec_deque::Iter<'a, Bucket<T>>>,
+}
+
+impl<'a, T: BufferedEvent> Iterator for EventWindowIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.current.next() {
+                if self.cutoff.is_some_and(|cutoff| event.timestamp() < cutoff) {
+                    continue;
+                }
+                return Some(event);
+            }
+            let bucket = self.buckets.as_mut()?.next()?;
+            self.cutoff = self.pending_cutoff.take();
+            self.current = bucket.live().iter();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::MessageType;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn log_at(timestamp: Timestamp, process: &str) -> LogEvent {
+        LogEvent {
+            timestamp,
+            message_type: MessageType::Error,
+            subsystem: format!("com.example.{process}"),
+            category: "test".to_string(),
+            process: process.to_string(),
+            process_id: 1,
+            message: "Test message".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_window_cuts_inside_a_bucket_and_keeps_late_events_in_order() {
+        let base = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let mut buffer = TimeBuckets::new();
+        buffer.push(log_at(base + Duration::milliseconds(2_100), "a"));
+        buffer.push(log_at(base + Duration::milliseconds(1_900), "b"));
+        buffer.push(log_at(base + Duration::milliseconds(1_200), "c"));
+        buffer.push(log_at(base, "d"));
+
+        let window = buffer.window(base + Duration::milliseconds(1_500));
+        let processes = window
+            .iter()
+            .map(|event| event.process.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(window.len(), 2);
+        assert_eq!(processes, ["b", "a"]);
+    }
+
+    #[test]
+    fn test_template_ids_are_kept_per_stored_event() {
+        let base = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let mut buffer = TimeBuckets::new();
+        buffer.push_with_template(log_at(base, "a"), Some(7));
+        buffer.push_with_template(log_at(base, "b"), Some(9));
+        buffer.push(log_at(base + Duration::seconds(1), "c"));
+        buffer.evict_oldest();
+
+        let window = buffer.window(base);
+        let ids = window
+            .iter()
+            .map(|event| window.template_id(event))
+            .collect::<Vec<_>>();
+        assert_eq!(ids, [Some(9), None]);
+
+        // Equal events that are not stored in the window have no cached id
+        let copy = log_at(base, "b");
+        assert_eq!(window.template_id(&copy), None);
+        let slice = [copy];
+        let slice_window = EventWindow::from_slice(&slice);
+        assert_eq!(slice_window.template_id(&slice[0]), None);
+    }
+
+    #[test]
+    fn test_indexes_skip_evicted_and_expired_events() {
+        let base = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let mut buffer = TimeBuckets::new();
+        for offset in 0..4 {
+            buffer.push(log_at(base + Duration::milliseconds(offset * 400), "a"));
+            buffer.push(log_at(base + Duration::milliseconds(offset * 400), "b"));
+        }
+        buffer.evict_oldest();
+        let window = buffer.window(base);
+        assert_eq!(window.for_process("a").count(), 3);
+        assert_eq!(window.for_subsystem("com.example.b").count(), 4);
+
+        assert_eq!(buffer.prune_before(base + Duration::milliseconds(400)), 0);
+        assert_eq!(buffer.prune_before(base + Duration::milliseconds(1_100)), 5);
+        assert_eq!(buffer.len(), 2);
+        let window = buffer.window(base);
+        assert_eq!(window.for_process("a").count(), 1);
+        assert_eq!(window.for_subsystem("com.example.b").count(), 1);
+        assert_eq!(
+            EventWindow::from_slice(&[log_at(base, "a")])
+                .for_process("a")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_index_lookups_check_the_stored_name() {
+        let base = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let mut buffer = TimeBuckets::new();
+        buffer.push(log_at(base, "a"));
+        buffer.push(log_at(base, "b"));
+
+        // Point the key for "a" at "b" too, as a hash collision would
+        let bucket = &mut buffer.buckets[0];
+        let b_positions = bucket.by_process[&name_key("b")].clone();
+        bucket
+            .by_process
+            .get_mut(&name_key("a"))
+            .unwrap()
+            .extend(b_positions);
+
+        let window = buffer.window(base);
+        let processes = window
+            .for_process("a")
+            .map(|event| event.process.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(processes, ["a"]);
+    }
+}
@@ -158,6 +158,11 @@ impl AIAnalyzer {
         }
     }
 
+    /// Replace the LLM backend used for new analyses and retries
+    pub fn set_backend(&mut self, backend: Arc<dyn LLMBackend>) {
+        self.backend = backend;
+    }
+
     /// Set the self-monitoring collector for tracking analysis latency
     pub fn set_monitoring(&mut self, monitoring: Arc<SelfMonitoringCollector>) {
         self.monitoring = Some(monitoring);
@@ -763,6 +768,72 @@ impl AIAnalyzer {
             .collect::<Vec<_>>()
             .join("\n");
 
+        let crash_reports = context
+            .crash_reports
+            .iter()
+            .take(3)
+            .map(|report| {
+                let mut lines = vec![format!(
+                    "[{}] {} (PID {}{}) {:?} report",
+                    report.timestamp.format("%H:%M:%S"),
+                    report.process,
+                    report.process_id,
+                    report
+                        .bundle_id
+                        .as_deref()
+                        .map(|bundle_id| format!(", {}", bundle_id))
+                        .unwrap_or_default(),
+                    report.kind
+                )];
+                if let Some(exception) = &report.exception_type {
+                    lines.push(format!(
+                        "  Exception: {}{}",
+                        exception,
+                        report
+                            .exception_signal
+                            .as_deref()
+                            .map(|signal| format!(" ({})", signal))
+                            .unwrap_or_default()
+                    ));
+                }
+                if let Some(reason) = &report.termination_reason {
+                    lines.push(format!("  Termination: {}", reason));
+                }
+                if !report.frames.is_empty() {
+                    lines.push("  Crashing thread:".to_string());
+                    lines.extend(
+                        report
+                            .frames
+                            .iter()
+                            .enumerate()
+                            .map(|(index, frame)| format!("    {} {}", index, frame)),
+                    );
+                }
+                lines.join("\n")
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let window_start = context.log_events.iter().map(|event| event.timestamp).min();
+        let log_templates = context
+            .log_templates
+            .iter()
+            .take(10)
+            .map(|template| {
+                let is_new = window_start.is_some_and(|start| template.first_seen >= start);
+                format!(
+                    "{}{}: \"{}\" matched {} here, {} since first seen at {}",
+                    if is_new { "[NEW] " } else { "" },
+                    template.process,
+                    template.template,
+                    template.matched,
+                    template.count,
+                    template.first_seen.format("%Y-%m-%d %H:%M:%S UTC")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
         // Build the complete prompt
         format!(
             r#"You are a macOS system diagnostics expert. Analyze the following system data and provide:
@@ -781,6 +852,8 @@ Diagnostic constraints:
 - Do not infer daemon startup failure, restart, corruption, entitlement damage, or filesystem damage without direct evidence.
 - Observed PIDs cover only this time window. One PID does not prove lifetime, and multiple PIDs do not alone prove a crash.
 - Crash reports, process start times, SIP state, and filesystem health are not supplied unless explicitly listed below.
+- A listed crash report is direct evidence of how that process terminated; its exception and crashing-thread frames locate the fault but do not by themselves establish why it happened.
+- Log templates group messages that differ only in numbers, paths, and identifiers. A [NEW] template was first seen in this window; a high lifetime count means the message is routine for this machine.
 - Do not recommend rebooting, deleting data, disabling security controls, running repair tools, or killing system daemons without direct evidence that the action addresses the observed failure.
 - Evidence-gathering steps are valid recommendations. Use an empty recommendations array when neither remediation nor a useful evidence-gathering step is supported.
 - Do not assign severity above the maximum allowed by the trigger.
@@ -825,6 +898,12 @@ Recent Process Metrics (system-wide snapshot, sorted by RSS):
 Recent Disk I/O:
 {}
 
+Crash Reports:
+{}
+
+Log Templates:
+{}
+
 Respond in JSON format with fields: 
 - summary (string): Brief description of the main issue
 - root_cause (string or null): Most likely underlying cause
@@ -898,6 +977,16 @@ Example response:
                 "No recent disk I/O"
             } else {
                 &recent_disk_events
+            },
+            if crash_reports.is_empty() {
+                "No crash reports"
+            } else {
+                &crash_reports
+            },
+            if log_templates.is_empty() {
+                "No log templates"
+            } else {
+                &log_templates
             }
         )
     }
@@ -997,7 +1086,10 @@ impl LLMBackend for PlaceholderBackend {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::events::{DiskEvent, LogEvent, MemoryPressure, MessageType, MetricsEvent};
+    use crate::events::{
+        CrashReport, CrashReportKind, DiskEvent, LogEvent, MemoryPressure, MessageType,
+        MetricsEvent, StackFrame,
+    };
     use chrono::Utc;
 
     fn test_provenance() -> MetricsProvenance {
@@ -1343,6 +1435,72 @@ mod tests {
 
         assert!(prompt.contains("Time Window: 250 milliseconds"));
     }
+
+    #[test]
+    fn test_format_prompt_includes_crash_report_frames() {
+        let analyzer = AIAnalyzer::new();
+        let log_events = vec![create_test_log_event(MessageType::Fault, "testd crashed")];
+        let mut context = TriggerContext::for_summary(&log_events, &[], &[]);
+        assert!(analyzer
+            .format_prompt(&context)
+            .contains("Crash Reports:\nNo crash reports"));
+
+        context.crash_reports.push(CrashReport {
+            timestamp: Utc::now(),
+            kind: CrashReportKind::Crash,
+            process: "testd".to_string(),
+            process_id: 1234,
+            bundle_id: Some("com.apple.testd".to_string()),
+            exception_type: Some("EXC_BAD_ACCESS".to_string()),
+            exception_signal: Some("SIGSEGV".to_string()),
+            termination_reason: Some(
+                "Namespace SIGNAL, Code 11, Segmentation fault: 11".to_string(),
+            ),
+            frames: vec![
+                StackFrame {
+                    image: "libobjc.A.dylib".to_string(),
+                    symbol: Some("objc_msgSend".to_string()),
+                    image_offset: 16400,
+                },
+                StackFrame {
+                    image: "testd".to_string(),
+                    symbol: None,
+                    image_offset: 0x11e28,
+                },
+            ],
+            report_path: "/tmp/testd.ips".to_string(),
+        });
+        let prompt = analyzer.format_prompt(&context);
+
+        assert!(prompt.contains("testd (PID 1234, com.apple.testd) Crash report"));
+        assert!(prompt.contains("  Exception: EXC_BAD_ACCESS (SIGSEGV)"));
+        assert!(prompt.contains("  Termination: Namespace SIGNAL, Code 11, Segmentation fault: 11"));
+        assert!(prompt.contains("    0 libobjc.A.dylib objc_msgSend\n    1 testd +0x11e28"));
+    }
+
+    #[test]
+    fn test_format_prompt_marks_new_log_templates() {
+        let analyzer = AIAnalyzer::new();
+        let log_events = vec![
+            create_test_log_event(MessageType::Error, "disk0 timeout after 30 ms"),
+            create_test_log_event(MessageType::Error, "disk1 timeout after 12 ms"),
+        ];
+        let mut templates = crate::aggregator::LogTemplates::new();
+        for event in &log_events {
+            templates.add(event);
+        }
+        let mut context = TriggerContext::for_summary(&log_events, &[], &[]);
+        assert!(analyzer
+            .format_prompt(&context)
+            .contains("Log Templates:\nNo log templates"));
+
+        context.log_templates = templates.summarize(log_events.iter());
+        let prompt = analyzer.format_prompt(&context);
+
+        assert!(prompt.contains(
+            "[NEW] testd: \"<*> timeout after <NUM> ms\" matched 2 here, 2 since first seen"
+        ));
+    }
 }
 
 // Property-based tests
@@ -1447,6 +1605,8 @@ mod property_tests {
                 log_events: self.log_events.clone(),
                 metrics_events: self.metrics_events.clone(),
                 disk_events: vec![],
+                crash_reports: vec![],
+                log_templates: vec![],
                 triggered_by: self.triggered_by.clone(),
                 trigger_source: None,
                 expected_severity: Severity::Warning,
@@ -540,6 +540,8 @@ mod tests {
             log_events,
             metrics_events,
             disk_events: vec![],
+            crash_reports: vec![],
+            log_templates: vec![],
             triggered_by: "TestRule".to_string(),
             trigger_source: None,
             expected_severity: Severity::Warning,
@@ -1422,6 +1424,8 @@ mod mock_backend_tests {
             log_events,
             metrics_events,
             disk_events: vec![],
+            crash_reports: vec![],
+            log_templates: vec![],
             triggered_by: "MockRule".to_string(),
             trigger_source: None,
             expected_severity: Severity::Info,
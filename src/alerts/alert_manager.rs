@@ -155,6 +155,19 @@ impl AlertManager {
         self.desktop_notifications_enabled = enabled;
     }
 
+    /// Change the notification rate limit, keeping the current window's count
+    pub fn set_rate_limit(&mut self, max_per_minute: usize) {
+        self.rate_limiter.set_max_per_minute(max_per_minute);
+    }
+
+    /// Change the lowest severity delivered as a notification
+    ///
+    /// Queued alerts below the new threshold are marked suppressed when the
+    /// queue is next processed.
+    pub fn set_minimum_severity(&mut self, minimum_severity: Severity) {
+        self.minimum_severity = minimum_severity;
+    }
+
     /// Send an alert based on an AI insight
     ///
     /// This method formats the insight into a macOS notification and delivers it
@@ -32,6 +32,13 @@ impl RateLimiter {
         }
     }
 
+    /// Change the maximum number of notifications allowed per minute
+    ///
+    /// Notifications already sent in the current window still count.
+    pub fn set_max_per_minute(&mut self, max_per_minute: usize) {
+        self.max_per_minute = max_per_minute;
+    }
+
     /// Check if a notification can be sent now without exceeding the rate limit
     ///
     /// This method cleans up old notifications and checks if sending a new one
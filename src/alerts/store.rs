@@ -1,6 +1,7 @@
+use crate::aggregator::LogTemplateSummary;
 use crate::ai::AIInsight;
 use crate::error::AlertError;
-use crate::events::{DiskEvent, LogEvent, MetricsEvent, Severity};
+use crate::events::{CrashReport, DiskEvent, LogEvent, MetricsEvent, Severity};
 use crate::triggers::TriggerContext;
 use chrono::{SecondsFormat, Utc};
 use regex::Regex;
@@ -11,7 +12,7 @@ use std::collections::HashSet;
 use std::path::Path;
 use std::time::Duration;
 
-const SCHEMA_VERSION: i32 = 6;
+const SCHEMA_VERSION: i32 = 8;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AlertStatus {
@@ -86,6 +87,10 @@ pub struct AlertRecord {
     pub metrics_events: Vec<MetricsEvent>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub disk_events: Vec<DiskEvent>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub crash_reports: Vec<CrashReport>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub log_templates: Vec<LogTemplateSummary>,
     pub group_parent_id: Option<i64>,
     pub resolution_status: String,
     pub resolved_at: Option<String>,
@@ -214,6 +219,8 @@ fn alert_record_from_row(row: &Row<'_>) -> rusqlite::Result<AlertRecord> {
         log_events: Vec::new(),
         metrics_events: Vec::new(),
         disk_events: Vec::new(),
+        crash_reports: Vec::new(),
+        log_templates: Vec::new(),
         group_parent_id: row.get(23)?,
         resolution_status: row.get(24)?,
         resolved_at: row.get(25)?,
@@ -399,6 +406,13 @@ impl AlertStore {
             &context.metrics_events,
         )?;
         insert_context_events(&transaction, candidate_id, "disk", &context.disk_events)?;
+        insert_context_events(&transaction, candidate_id, "crash", &context.crash_reports)?;
+        insert_context_events(
+            &transaction,
+            candidate_id,
+            "template",
+            &context.log_templates,
+        )?;
         if let Some(target_alert_id) = matching_auto_group_target(&transaction, context)? {
             transaction
                 .execute(
@@ -462,6 +476,8 @@ impl AlertStore {
             log_events: self.context_events(candidate_id, "log")?,
             metrics_events: self.context_events(candidate_id, "metrics")?,
             disk_events: self.context_events(candidate_id, "disk")?,
+            crash_reports: self.context_events(candidate_id, "crash")?,
+            log_templates: self.context_events(candidate_id, "template")?,
             triggered_by,
             trigger_source,
             expected_severity: parse_severity(&severity, candidate_id)?,
@@ -1307,6 +1323,70 @@ impl AlertStore {
                 )
                 .map_err(persistence_error)?;
             transaction.commit().map_err(persistence_error)?;
+            version = 6;
+        }
+
+        if version == 6 {
+            let transaction = self
+                .connection
+                .transaction_with_behavior(TransactionBehavior::Immediate)
+                .map_err(persistence_error)?;
+            transaction
+                .execute_batch(
+                    "CREATE TABLE alert_candidate_context_events_v7 (
+                         candidate_id INTEGER NOT NULL
+                             REFERENCES alert_candidates(id) ON DELETE CASCADE,
+                         event_kind TEXT NOT NULL CHECK (
+                             event_kind IN ('log', 'metrics', 'disk', 'crash')
+                         ),
+                         position INTEGER NOT NULL CHECK (position >= 0),
+                         payload TEXT NOT NULL,
+                         PRIMARY KEY (candidate_id, event_kind, position)
+                     );
+                     INSERT INTO alert_candidate_context_events_v7 (
+                         candidate_id, event_kind, position, payload
+                     )
+                     SELECT candidate_id, event_kind, position, payload
+                     FROM alert_candidate_context_events;
+                     DROP TABLE alert_candidate_context_events;
+                     ALTER TABLE alert_candidate_context_events_v7
+                         RENAME TO alert_candidate_context_events;
+                     PRAGMA user_version = 7;",
+                )
+                .map_err(persistence_error)?;
+            transaction.commit().map_err(persistence_error)?;
+            version = 7;
+        }
+
+        if version == 7 {
+            let transaction = self
+                .connection
+                .transaction_with_behavior(TransactionBehavior::Immediate)
+                .map_err(persistence_error)?;
+            transaction
+                .execute_batch(
+                    "CREATE TABLE alert_candidate_context_events_v8 (
+                         candidate_id INTEGER NOT NULL
+                             REFERENCES alert_candidates(id) ON DELETE CASCADE,
+                         event_kind TEXT NOT NULL CHECK (
+                             event_kind IN ('log', 'metrics', 'disk', 'crash', 'template')
+                         ),
+                         position INTEGER NOT NULL CHECK (position >= 0),
+                         payload TEXT NOT NULL,
+                         PRIMARY KEY (candidate_id, event_kind, position)
+                     );
+                     INSERT INTO alert_candidate_context_events_v8 (
+                         candidate_id, event_kind, position, payload
+                     )
+                     SELECT candidate_id, event_kind, position, payload
+                     FROM alert_candidate_context_events;
+                     DROP TABLE alert_candidate_context_events;
+                     ALTER TABLE alert_candidate_context_events_v8
+                         RENAME TO alert_candidate_context_events;
+                     PRAGMA user_version = 8;",
+                )
+                .map_err(persistence_error)?;
+            transaction.commit().map_err(persistence_error)?;
         }
 
         Ok(())
@@ -1370,6 +1450,8 @@ impl AlertStore {
         alert.log_events = self.context_events(alert.id, "log")?;
         alert.metrics_events = self.context_events(alert.id, "metrics")?;
         alert.disk_events = self.context_events(alert.id, "disk")?;
+        alert.crash_reports = self.context_events(alert.id, "crash")?;
+        alert.log_templates = self.context_events(alert.id, "template")?;
         alert.agent_reviews = self.agent_reviews(alert.id)?;
         Ok(())
     }
@@ -1708,7 +1790,7 @@ fn persistence_error(error: rusqlite::Error) -> AlertError {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::events::{MessageType, Severity};
+    use crate::events::{CrashReportKind, MessageType, Severity, StackFrame};
     use tempfile::tempdir;
 
     fn test_insight() -> AIInsight {
@@ -1728,6 +1810,25 @@ mod tests {
         }
     }
 
+    fn crash_report(process: &str) -> CrashReport {
+        CrashReport {
+            timestamp: Utc::now(),
+            kind: CrashReportKind::Crash,
+            process: process.to_string(),
+            process_id: 42,
+            bundle_id: Some("com.example.editor".to_string()),
+            exception_type: Some("EXC_BAD_ACCESS".to_string()),
+            exception_signal: Some("SIGSEGV".to_string()),
+            termination_reason: Some("Namespace SIGNAL, Code 11".to_string()),
+            frames: vec![StackFrame {
+                image: "libobjc.A.dylib".to_string(),
+                symbol: Some("objc_msgSend".to_string()),
+                image_offset: 16400,
+            }],
+            report_path: format!("/tmp/{process}.ips"),
+        }
+    }
+
     fn log_context(process: &str, subsystem: &str, message: &str) -> TriggerContext {
         let mut context = TriggerContext::for_summary(&[], &[], &[]);
         context.triggered_by = "ErrorFrequencyRule".to_string();
@@ -2028,6 +2129,7 @@ mod tests {
             process_id: 42,
             message: "Application terminated unexpectedly".to_string(),
         });
+        context.crash_reports.push(crash_report("ExampleEditor"));
         let candidate_id = store.record_candidate(&context).unwrap();
         store
             .mark_candidate_failed(candidate_id, "backend unavailable")
@@ -2039,6 +2141,11 @@ mod tests {
         assert_eq!(retried.trigger_source, context.trigger_source);
         assert_eq!(retried.expected_severity, Severity::Critical);
         assert_eq!(retried.log_events, context.log_events);
+        assert_eq!(retried.crash_reports, context.crash_reports);
+        assert_eq!(
+            store.get_alert(candidate_id).unwrap().crash_reports,
+            context.crash_reports
+        );
         let page = store
             .list_alerts(1, 10, AlertSort::UpdatedAt, true, true)
             .unwrap();
@@ -2371,6 +2478,90 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn migrates_v6_context_events_to_accept_crash_reports() {
+        let directory = tempdir().unwrap();
+        let database_path = directory.path().join("alerts.db");
+        let mut store = AlertStore::open(&database_path).unwrap();
+        let candidate_id = store
+            .record_candidate(&log_context("ExampleEditor", "com.example", "crashed"))
+            .unwrap();
+        store
+            .connection
+            .execute_batch(
+                "DROP TABLE alert_candidate_context_events;
+                 CREATE TABLE alert_candidate_context_events (
+                     candidate_id INTEGER NOT NULL
+                         REFERENCES alert_candidates(id) ON DELETE CASCADE,
+                     event_kind TEXT NOT NULL CHECK (
+                         event_kind IN ('log', 'metrics', 'disk')
+                     ),
+                     position INTEGER NOT NULL CHECK (position >= 0),
+                     payload TEXT NOT NULL,
+                     PRIMARY KEY (candidate_id, event_kind, position)
+                 );
+                 PRAGMA user_version = 6;",
+            )
+            .unwrap();
+        let context = log_context("ExampleEditor", "com.example", "crashed");
+        insert_context_events(&store.connection, candidate_id, "log", &context.log_events).unwrap();
+        drop(store);
+
+        let mut migrated = AlertStore::open(&database_path).unwrap();
+        assert_eq!(
+            migrated.get_alert(candidate_id).unwrap().log_events,
+            context.log_events
+        );
+        let mut crash_context = log_context("ExampleEditor", "com.example", "crashed");
+        crash_context
+            .crash_reports
+            .push(crash_report("ExampleEditor"));
+        let crash_id = migrated.record_candidate(&crash_context).unwrap();
+        assert_eq!(
+            migrated.get_alert(crash_id).unwrap().crash_reports,
+            crash_context.crash_reports
+        );
+    }
+
+    #[test]
+    fn migrates_v7_context_events_to_accept_log_templates() {
+        let directory = tempdir().unwrap();
+        let database_path = directory.path().join("alerts.db");
+        let store = AlertStore::open(&database_path).unwrap();
+        store
+            .connection
+            .execute_batch(
+                "DROP TABLE alert_candidate_context_events;
+                 CREATE TABLE alert_candidate_context_events (
+                     candidate_id INTEGER NOT NULL
+                         REFERENCES alert_candidates(id) ON DELETE CASCADE,
+                     event_kind TEXT NOT NULL CHECK (
+                         event_kind IN ('log', 'metrics', 'disk', 'crash')
+                     ),
+                     position INTEGER NOT NULL CHECK (position >= 0),
+                     payload TEXT NOT NULL,
+                     PRIMARY KEY (candidate_id, event_kind, position)
+                 );
+                 PRAGMA user_version = 7;",
+            )
+            .unwrap();
+        drop(store);
+
+        let mut migrated = AlertStore::open(&database_path).unwrap();
+        let mut context = log_context("ExampleEditor", "com.example", "crashed");
+        let mut templates = crate::aggregator::LogTemplates::new();
+        for event in &context.log_events {
+            templates.add(event);
+        }
+        context.log_templates = templates.summarize(context.log_events.iter());
+        let candidate_id = migrated.record_candidate(&context).unwrap();
+
+        assert_eq!(
+            migrated.get_alert(candidate_id).unwrap().log_templates,
+            context.log_templates
+        );
+    }
+
     #[test]
     fn migrates_v1_alerts_to_analyzed_candidates() {
         let directory = tempdir().unwrap();
@@ -0,0 +1,481 @@
+use super::disk_collector::{FsUsageAccumulator, IostatParser, FS_USAGE_AGGREGATION_INTERVAL};
+use super::{DiskCollector, LogCollector, MetricsCollector};
+use crate::error::CollectorError;
+use crate::events::{DiskEvent, LogEvent, MetricsEvent, ProcessMetric};
+use chrono::{DateTime, Utc};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+
+/// Raw collector output stream recorded in a capture file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureStream {
+    /// `log stream --style json` stdout
+    LogStream,
+    /// `powermetrics` plist (or JSON lines) stdout
+    Powermetrics,
+    /// `top` output used when powermetrics is unavailable
+    TopFallback,
+    /// One-shot `vm_stat` output used to enrich fallback metrics
+    VmStat,
+    /// Resolved per-process snapshot attached to metrics events, as JSON
+    ProcessSnapshot,
+    /// `iostat -d -w N` stdout
+    Iostat,
+    /// `fs_usage -w -f filesys` stdout
+    FsUsage,
+}
+
+/// Payload of a capture record
+///
+/// Chunks that are valid UTF-8 are stored as text so capture files stay
+/// readable; anything else is hex-encoded so replay sees the exact bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureData {
+    Text(String),
+    Hex(String),
+}
+
+impl CaptureData {
+    fn from_bytes(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => Self::Text(text.to_string()),
+            Err(_) => Self::Hex(bytes.iter().map(|byte| format!("{byte:02x}")).collect()),
+        }
+    }
+
+    /// Decode the payload back into the bytes the collector read
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        match self {
+            Self::Text(text) => Ok(text.as_bytes().to_vec()),
+            Self::Hex(hex) => {
+                if hex.len() % 2 != 0 {
+                    return Err("hex payload has an odd number of digits".to_string());
+                }
+                (0..hex.len())
+                    .step_by(2)
+                    .map(|index| {
+                        u8::from_str_radix(&hex[index..index + 2], 16)
+                            .map_err(|error| format!("invalid hex payload: {error}"))
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// A single chunk of collector input with the time it was read
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaptureRecord {
+    pub captured_at: DateTime<Utc>,
+    pub stream: CaptureStream,
+    #[serde(flatten)]
+    pub data: CaptureData,
+}
+
+/// Shared writer that appends collector input to a JSONL capture file
+///
+/// Cloned into every collector thread; each record is flushed immediately so a
+/// capture stays usable when Eyes is killed.
+#[derive(Clone)]
+pub struct CaptureWriter {
+    writer: Arc<Mutex<BufWriter<File>>>,
+}
+
+impl CaptureWriter {
+    /// Create (or truncate) a capture file
+    pub fn create(path: &Path) -> Result<Self, CollectorError> {
+        let file = File::create(path)?;
+        Ok(Self {
+            writer: Arc::new(Mutex::new(BufWriter::new(file))),
+        })
+    }
+
+    /// Append one chunk of raw collector input
+    ///
+    /// Capture is a debugging aid, so write failures are logged rather than
+    /// interrupting collection.
+    pub fn record(&self, stream: CaptureStream, bytes: &[u8]) {
+        let record = CaptureRecord {
+            captured_at: Utc::now(),
+            stream,
+            data: CaptureData::from_bytes(bytes),
+        };
+        let line = match serde_json::to_string(&record) {
+            Ok(line) => line,
+            Err(error) => {
+                warn!("Failed to serialize capture record: {}", error);
+                return;
+            }
+        };
+
+        let Ok(mut writer) = self.writer.lock() else {
+            warn!("Capture writer lock poisoned; dropping record");
+            return;
+        };
+        if let Err(error) = writeln!(writer, "{line}").and_then(|_| writer.flush()) {
+            warn!("Failed to write capture record: {}", error);
+        }
+    }
+}
+
+/// Read every record from a capture file, in file order
+///
+/// # Errors
+///
+/// Returns `CollectorError::ParseError` with the offending line number when a
+/// record cannot be decoded.
+pub fn read_capture(path: &Path) -> Result<Vec<CaptureRecord>, CollectorError> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut records = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = serde_json::from_str::<CaptureRecord>(&line).map_err(|error| {
+            CollectorError::ParseError(format!(
+                "{}:{}: invalid capture record: {}",
+                path.display(),
+                index + 1,
+                error
+            ))
+        })?;
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+/// Events produced while decoding capture records
+#[derive(Debug, Default)]
+pub struct DecodedEvents {
+    pub log_events: Vec<LogEvent>,
+    pub metrics_events: Vec<MetricsEvent>,
+    pub disk_events: Vec<DiskEvent>,
+}
+
+impl DecodedEvents {
+    pub fn is_empty(&self) -> bool {
+        self.log_events.is_empty() && self.metrics_events.is_empty() && self.disk_events.is_empty()
+    }
+}
+
+/// Feeds capture records through the same buffers and parsers the live
+/// collectors use, preserving their per-stream state between chunks
+#[derive(Default)]
+pub struct CaptureDecoder {
+    log_buffer: String,
+    log_in_json_array: bool,
+    metrics_buffer: Vec<u8>,
+    fallback_buffer: Vec<u8>,
+    pending_metrics: Vec<MetricsEvent>,
+    iostat_buffer: Vec<u8>,
+    iostat_parser: IostatParser,
+    fs_usage_buffer: Vec<u8>,
+    fs_usage_accumulator: FsUsageAccumulator,
+    fs_usage_last_flush: Option<DateTime<Utc>>,
+}
+
+impl CaptureDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode one record into the events the live pipeline would have emitted
+    ///
+    /// Metrics events are held back until their process snapshot arrives or a
+    /// newer sample replaces them, since live collection enriches them with
+    /// `vm_stat` and process snapshots taken right after the chunk was parsed.
+    /// Records of other streams interleaved in between do not release them.
+    /// Metrics and disk events are stamped with the record's capture time,
+    /// as live collection stamps them when the chunk is parsed.
+    pub fn decode(&mut self, record: &CaptureRecord) -> Result<DecodedEvents, CollectorError> {
+        let bytes = record
+            .data
+            .to_bytes()
+            .map_err(|error| CollectorError::ParseError(format!("capture record: {error}")))?;
+        let mut decoded = DecodedEvents::default();
+
+        match record.stream {
+            CaptureStream::VmStat => {
+                let snapshot = MetricsCollector::parse_vm_stat_output(&bytes);
+                MetricsCollector::apply_memory_snapshot(&mut self.pending_metrics, snapshot);
+            }
+            CaptureStream::ProcessSnapshot => {
+                let process_metrics = serde_json::from_slice::<Vec<ProcessMetric>>(&bytes)
+                    .map_err(|error| {
+                        CollectorError::ParseError(format!("process snapshot: {error}"))
+                    })?;
+                for event in &mut self.pending_metrics {
+                    event.process_metrics = process_metrics.clone();
+                }
+                decoded.metrics_events = std::mem::take(&mut self.pending_metrics);
+            }
+            CaptureStream::LogStream => {
+                let (sender, receiver) = mpsc::channel();
+                LogCollector::handle_log_chunk(
+                    &bytes,
+                    &mut self.log_buffer,
+                    &mut self.log_in_json_array,
+                    &sender,
+                )?;
+                drop(sender);
+                decoded.log_events.extend(receiver.try_iter());
+            }
+            CaptureStream::Powermetrics => {
+                self.metrics_buffer.extend_from_slice(&bytes);
+                if let Some(events) = MetricsCollector::try_parse_buffer(&mut self.metrics_buffer) {
+                    decoded.metrics_events = self.hold_metrics(events, record.captured_at);
+                }
+            }
+            CaptureStream::TopFallback => {
+                self.fallback_buffer.extend_from_slice(&bytes);
+                if let Some(events) =
+                    MetricsCollector::try_parse_fallback_buffer(&mut self.fallback_buffer)
+                {
+                    decoded.metrics_events = self.hold_metrics(events, record.captured_at);
+                }
+            }
+            CaptureStream::Iostat => {
+                self.iostat_buffer.extend_from_slice(&bytes);
+                if let Some(events) = DiskCollector::try_parse_iostat_buffer(
+                    &mut self.iostat_buffer,
+                    &mut self.iostat_parser,
+                ) {
+                    decoded.disk_events.extend(events);
+                }
+            }
+            CaptureStream::FsUsage => {
+                self.fs_usage_buffer.extend_from_slice(&bytes);
+                if let Some(events) =
+                    DiskCollector::try_parse_fs_usage_buffer(&mut self.fs_usage_buffer)
+                {
+                    for event in events {
+                        self.fs_usage_accumulator.record(event);
+                    }
+                }
+
+                let last_flush = *self.fs_usage_last_flush.get_or_insert(record.captured_at);
+                let elapsed = (record.captured_at - last_flush)
+                    .to_std()
+                    .unwrap_or_default();
+                if elapsed >= FS_USAGE_AGGREGATION_INTERVAL {
+                    decoded
+                        .disk_events
+                        .extend(self.fs_usage_accumulator.take_sample(elapsed));
+                    self.fs_usage_last_flush = Some(record.captured_at);
+                }
+            }
+        }
+
+        for event in &mut decoded.disk_events {
+            event.timestamp = record.captured_at;
+        }
+        debug!(
+            "Decoded {:?} capture record: {} log, {} metrics, {} disk events",
+            record.stream,
+            decoded.log_events.len(),
+            decoded.metrics_events.len(),
+            decoded.disk_events.len()
+        );
+        Ok(decoded)
+    }
+
+    /// Hold a new metrics sample for enrichment, releasing the one it replaces
+    fn hold_metrics(
+        &mut self,
+        mut events: Vec<MetricsEvent>,
+        captured_at: DateTime<Utc>,
+    ) -> Vec<MetricsEvent> {
+        for event in &mut events {
+            event.timestamp = captured_at;
+        }
+        std::mem::replace(&mut self.pending_metrics, events)
+    }
+
+    /// Release metrics events still waiting for enrichment at end of capture
+    pub fn finish(&mut self) -> DecodedEvents {
+        DecodedEvents {
+            metrics_events: std::mem::take(&mut self.pending_metrics),
+            ..DecodedEvents::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::MessageType;
+    use tempfile::TempDir;
+
+    fn record(stream: CaptureStream, text: &str, offset_ms: i64) -> CaptureRecord {
+        CaptureRecord {
+            captured_at: DateTime::parse_from_rfc3339("2024-01-15T10:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc)
+                + chrono::Duration::milliseconds(offset_ms),
+            stream,
+            data: CaptureData::Text(text.to_string()),
+        }
+    }
+
+    #[test]
+    fn capture_round_trips_text_and_binary_chunks() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("capture.jsonl");
+        let writer = CaptureWriter::create(&path).unwrap();
+
+        writer.record(CaptureStream::LogStream, b"[{\"a\": 1}");
+        writer.record(CaptureStream::Iostat, &[0xff, 0x00, b'\n']);
+
+        let records = read_capture(&path).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].stream, CaptureStream::LogStream);
+        assert_eq!(records[0].data.to_bytes().unwrap(), b"[{\"a\": 1}");
+        assert_eq!(records[1].data, CaptureData::Hex("ff000a".to_string()));
+        assert_eq!(records[1].data.to_bytes().unwrap(), vec![0xff, 0x00, b'\n']);
+    }
+
+    #[test]
+    fn read_capture_reports_line_of_invalid_record() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("capture.jsonl");
+        std::fs::write(
+            &path,
+            "{\"captured_at\":\"2024-01-15T10:00:00Z\",\"stream\":\"iostat\",\"text\":\"\"}\nnot json\n",
+        )
+        .unwrap();
+
+        let error = read_capture(&path).unwrap_err().to_string();
+        assert!(error.contains(":2:"), "{error}");
+    }
+
+    #[test]
+    fn decoder_reassembles_log_entries_split_across_chunks() {
+        let mut decoder = CaptureDecoder::new();
+        let entry = r#"{"timestamp":"2024-01-15 10:30:45.123456-0800","messageType":"Error","subsystem":"com.apple.test","category":"general","process":"kernel","processID":0,"eventMessage":"disk I/O error"}"#;
+        let (first, second) = entry.split_at(40);
+
+        let banner = format!("Filtering the log data using \"x\"\n[{first}");
+        let decoded = decoder
+            .decode(&record(CaptureStream::LogStream, &banner, 0))
+            .unwrap();
+        assert!(decoded.log_events.is_empty());
+
+        let decoded = decoder
+            .decode(&record(CaptureStream::LogStream, second, 10))
+            .unwrap();
+        assert_eq!(decoded.log_events.len(), 1);
+        assert_eq!(decoded.log_events[0].message_type, MessageType::Error);
+        assert_eq!(decoded.log_events[0].message, "disk I/O error");
+    }
+
+    #[test]
+    fn decoder_attaches_process_snapshot_to_preceding_metrics() {
+        let mut decoder = CaptureDecoder::new();
+        let metrics = r#"{"cpu_power_mw":1500.0,"gpu_power_mw":null,"memory_pressure":"Normal"}"#;
+        let decoded = decoder
+            .decode(&record(
+                CaptureStream::Powermetrics,
+                &format!("{metrics}\n"),
+                0,
+            ))
+            .unwrap();
+        assert!(decoded.metrics_events.is_empty());
+
+        let snapshot = r#"[{"process_id":42,"process":"Xcode","cpu_usage_percent":80.0,"resident_memory_mb":2048.0}]"#;
+        let decoded = decoder
+            .decode(&record(CaptureStream::ProcessSnapshot, snapshot, 5))
+            .unwrap();
+        assert_eq!(decoded.metrics_events.len(), 1);
+        assert_eq!(decoded.metrics_events[0].cpu_power_mw, 1500.0);
+        assert_eq!(
+            decoded.metrics_events[0].process_metrics[0].process,
+            "Xcode"
+        );
+        assert!(decoder.finish().is_empty());
+    }
+
+    #[test]
+    fn decoder_keeps_metrics_pending_across_interleaved_records() {
+        let mut decoder = CaptureDecoder::new();
+        let metrics = "{\"cpu_power_mw\":900.0,\"memory_pressure\":\"Warning\"}\n";
+        decoder
+            .decode(&record(CaptureStream::Powermetrics, metrics, 0))
+            .unwrap();
+        // Other streams land between the sample and its process snapshot
+        let decoded = decoder
+            .decode(&record(CaptureStream::Iostat, "", 5))
+            .unwrap();
+        assert!(decoded.metrics_events.is_empty());
+        let decoded = decoder
+            .decode(&record(CaptureStream::LogStream, "", 8))
+            .unwrap();
+        assert!(decoded.metrics_events.is_empty());
+
+        let snapshot = r#"[{"process_id":42,"process":"Xcode","cpu_usage_percent":80.0,"resident_memory_mb":2048.0}]"#;
+        let decoded = decoder
+            .decode(&record(CaptureStream::ProcessSnapshot, snapshot, 10))
+            .unwrap();
+        assert_eq!(decoded.metrics_events.len(), 1);
+        assert_eq!(decoded.metrics_events[0].process_metrics.len(), 1);
+
+        // Without a snapshot, a sample is released by the next one or by finish
+        decoder
+            .decode(&record(CaptureStream::Powermetrics, metrics, 20))
+            .unwrap();
+        let decoded = decoder
+            .decode(&record(CaptureStream::Powermetrics, metrics, 30))
+            .unwrap();
+        assert_eq!(decoded.metrics_events.len(), 1);
+        assert!(decoded.metrics_events[0].process_metrics.is_empty());
+        assert_eq!(decoder.finish().metrics_events.len(), 1);
+    }
+
+    #[test]
+    fn decoder_keeps_iostat_header_across_chunks() {
+        let mut decoder = CaptureDecoder::new();
+        decoder
+            .decode(&record(
+                CaptureStream::Iostat,
+                "              disk0 \n    KB/t  tps  MB/s \n",
+                0,
+            ))
+            .unwrap();
+        let decoded = decoder
+            .decode(&record(
+                CaptureStream::Iostat,
+                "   16.00  100  1.56\n",
+                1000,
+            ))
+            .unwrap();
+
+        assert_eq!(decoded.disk_events.len(), 1);
+        assert_eq!(decoded.disk_events[0].disk_name, "disk0");
+    }
+
+    #[test]
+    fn decoder_aggregates_fs_usage_by_capture_time() {
+        let mut decoder = CaptureDecoder::new();
+        let line = "12:00:00.000  read /Users/test/file.txt 1024 bytes\n";
+        let decoded = decoder
+            .decode(&record(CaptureStream::FsUsage, line, 0))
+            .unwrap();
+        assert!(decoded.disk_events.is_empty());
+
+        let decoded = decoder
+            .decode(&record(CaptureStream::FsUsage, line, 2000))
+            .unwrap();
+        assert_eq!(decoded.disk_events.len(), 1);
+        assert_eq!(decoded.disk_events[0].disk_name, "fs_usage");
+        assert!((decoded.disk_events[0].read_ops_per_sec - 1.0).abs() < f64::EPSILON);
+    }
+}
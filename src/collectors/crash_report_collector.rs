@@ -0,0 +1,564 @@
+use super::source::{EventSource, SourceHealth};
+use crate::error::CollectorError;
+use crate::events::{CrashReport, CrashReportKind, StackFrame, Timestamp};
+use chrono::{DateTime, Utc};
+use log::{debug, error, info, warn};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Number of crashing-thread frames kept per report
+const MAX_CRASH_FRAMES: usize = 8;
+
+/// `bug_type` values of spin and hang reports; everything else is a crash
+const HANG_BUG_TYPES: &[&str] = &["288", "298"];
+
+/// Timestamp format used by `.ips` headers and `captureTime`
+const IPS_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f %z";
+
+/// Crash report collector for DiagnosticReports directories
+///
+/// Polls a directory such as `~/Library/Logs/DiagnosticReports` for new
+/// `.ips` files and sends one `CrashReport` per report. Reports already
+/// present when the collector starts are skipped, so only crashes that
+/// happen while Eyes is running are reported. Files that fail to parse are
+/// retried once they change size, which covers reports still being written.
+pub struct CrashReportCollector {
+    /// Directory scanned for `.ips` files
+    directory: PathBuf,
+    /// Interval between directory scans
+    poll_interval: Duration,
+    /// Channel to send crash reports
+    output_channel: Sender<CrashReport>,
+    /// Handle to the background thread
+    thread_handle: Option<JoinHandle<()>>,
+    /// Shared state for controlling the collector
+    running: Arc<Mutex<bool>>,
+}
+
+impl CrashReportCollector {
+    /// Create a collector scanning `directory` every `poll_interval`
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::sync::mpsc;
+    /// use std::time::Duration;
+    /// use eyes::collectors::CrashReportCollector;
+    ///
+    /// let (tx, rx) = mpsc::channel();
+    /// let collector = CrashReportCollector::new(
+    ///     "/Users/me/Library/Logs/DiagnosticReports".into(),
+    ///     Duration::from_secs(5),
+    ///     tx,
+    /// );
+    /// ```
+    pub fn new(directory: PathBuf, poll_interval: Duration, channel: Sender<CrashReport>) -> Self {
+        Self {
+            directory,
+            poll_interval,
+            output_channel: channel,
+            thread_handle: None,
+            running: Arc::new(Mutex::new(false)),
+        }
+    }
+
+    /// Start scanning the report directory on a background thread
+    ///
+    /// # Errors
+    ///
+    /// Returns `CollectorError::IoError` if the report directory cannot be read.
+    pub fn start(&mut self) -> Result<(), CollectorError> {
+        info!(
+            "Starting CrashReportCollector on {} with interval: {:?}",
+            self.directory.display(),
+            self.poll_interval
+        );
+
+        {
+            let mut running = self.running.lock().unwrap();
+            if *running {
+                info!("CrashReportCollector already running, skipping start");
+                return Ok(());
+            }
+
+            let mut scanner = ReportScanner::new(self.directory.clone());
+            scanner.prime()?;
+            *running = true;
+
+            let interval = self.poll_interval;
+            let channel = self.output_channel.clone();
+            let running = Arc::clone(&self.running);
+            self.thread_handle = Some(thread::spawn(move || {
+                Self::collector_thread(scanner, interval, channel, running);
+            }));
+        }
+
+        info!("CrashReportCollector started successfully");
+        Ok(())
+    }
+
+    /// Stop the collector and wait for its thread to finish
+    ///
+    /// # Errors
+    ///
+    /// Returns `CollectorError::SubprocessTerminated` if the thread panicked.
+    pub fn stop(&mut self) -> Result<(), CollectorError> {
+        info!("Stopping CrashReportCollector");
+        *self.running.lock().unwrap() = false;
+
+        if let Some(handle) = self.thread_handle.take() {
+            handle.join().map_err(|_| {
+                error!("Failed to join CrashReportCollector thread");
+                CollectorError::SubprocessTerminated("Failed to join collector thread".to_string())
+            })?;
+        }
+
+        info!("CrashReportCollector stopped successfully");
+        Ok(())
+    }
+
+    /// Check if the collector is currently running
+    pub fn is_running(&self) -> bool {
+        *self.running.lock().unwrap()
+    }
+
+    fn collector_thread(
+        mut scanner: ReportScanner,
+        interval: Duration,
+        channel: Sender<CrashReport>,
+        running: Arc<Mutex<bool>>,
+    ) {
+        while *running.lock().unwrap() {
+            super::wait_for_retry(interval, &running);
+            if !*running.lock().unwrap() {
+                break;
+            }
+
+            match scanner.scan() {
+                Ok(reports) => {
+                    for report in reports {
+                        info!("New {:?} report for {}", report.kind, report.process);
+                        if channel.send(report).is_err() {
+                            info!("Crash report channel closed, stopping CrashReportCollector");
+                            *running.lock().unwrap() = false;
+                            break;
+                        }
+                    }
+                }
+                Err(e) => warn!(
+                    "Failed to scan crash report directory {}: {}",
+                    scanner.directory.display(),
+                    e
+                ),
+            }
+        }
+
+        debug!("CrashReportCollector thread exiting");
+    }
+}
+
+impl EventSource for CrashReportCollector {
+    fn name(&self) -> &str {
+        "crash_reports"
+    }
+
+    fn start(&mut self) -> Result<(), CollectorError> {
+        CrashReportCollector::start(self)
+    }
+
+    fn stop(&mut self) -> Result<(), CollectorError> {
+        CrashReportCollector::stop(self)
+    }
+
+    fn health(&self) -> SourceHealth {
+        if self.is_running() {
+            SourceHealth::Running
+        } else {
+            SourceHealth::Stopped
+        }
+    }
+}
+
+impl Drop for CrashReportCollector {
+    fn drop(&mut self) {
+        if self.is_running() || self.thread_handle.is_some() {
+            let _ = self.stop();
+        }
+    }
+}
+
+/// Tracks which `.ips` files in a directory have been reported
+struct ReportScanner {
+    directory: PathBuf,
+    /// Reports already sent or present at startup
+    seen: HashSet<PathBuf>,
+    /// Reports that failed to parse, with the size at the failed attempt
+    failed: HashMap<PathBuf, u64>,
+}
+
+impl ReportScanner {
+    fn new(directory: PathBuf) -> Self {
+        Self {
+            directory,
+            seen: HashSet::new(),
+            failed: HashMap::new(),
+        }
+    }
+
+    /// Mark every report currently in the directory as seen
+    fn prime(&mut self) -> std::io::Result<()> {
+        self.seen = self.report_paths()?.into_iter().collect();
+        debug!(
+            "Skipping {} existing reports in {}",
+            self.seen.len(),
+            self.directory.display()
+        );
+        Ok(())
+    }
+
+    /// Parse reports that appeared since the last scan, oldest name first
+    fn scan(&mut self) -> std::io::Result<Vec<CrashReport>> {
+        let paths = self.report_paths()?;
+        // Forget reports that were deleted or moved to Retired/
+        self.seen.retain(|path| paths.contains(path));
+        self.failed.retain(|path, _| paths.contains(path));
+
+        let mut reports = Vec::new();
+        for path in paths {
+            if self.seen.contains(&path) {
+                continue;
+            }
+            let size = match std::fs::metadata(&path) {
+                Ok(metadata) => metadata.len(),
+                Err(e) => {
+                    debug!("Crash report {} disappeared: {}", path.display(), e);
+                    continue;
+                }
+            };
+            if self.failed.get(&path) == Some(&size) {
+                continue;
+            }
+
+            let parsed = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|contents| parse_ips_report(&contents, &path));
+            match parsed {
+                Ok(report) => {
+                    self.failed.remove(&path);
+                    self.seen.insert(path);
+                    reports.push(report);
+                }
+                Err(e) => {
+                    debug!("Failed to parse crash report {}: {}", path.display(), e);
+                    self.failed.insert(path, size);
+                }
+            }
+        }
+        Ok(reports)
+    }
+
+    fn report_paths(&self) -> std::io::Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            if path.is_file() && path.extension().is_some_and(|extension| extension == "ips") {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        Ok(paths)
+    }
+}
+
+/// Parse an `.ips` report: a JSON header line followed by a JSON body
+///
+/// Handles the crash (`bug_type` 309) and hang layouts written by macOS 12
+/// and later. The crashing thread is `faultingThread`, falling back to the
+/// thread marked `triggered` and then the first thread.
+///
+/// # Errors
+///
+/// Returns an error if either JSON document is missing or malformed, or if
+/// no process name is present.
+pub(crate) fn parse_ips_report(contents: &str, path: &Path) -> Result<CrashReport, String> {
+    let (header, body) = contents
+        .split_once('\n')
+        .ok_or_else(|| "missing report body".to_string())?;
+    let header: Value =
+        serde_json::from_str(header).map_err(|e| format!("invalid report header: {}", e))?;
+    let body: Value =
+        serde_json::from_str(body).map_err(|e| format!("invalid report body: {}", e))?;
+
+    let kind = match header.get("bug_type").and_then(Value::as_str) {
+        Some(bug_type) if HANG_BUG_TYPES.contains(&bug_type) => CrashReportKind::Hang,
+        _ => CrashReportKind::Crash,
+    };
+    let process = string_field(&body, &["procName"])
+        .or_else(|| string_field(&header, &["app_name"]))
+        .or_else(|| string_field(&header, &["name"]))
+        .ok_or_else(|| "report has no process name".to_string())?;
+    let timestamp = string_field(&body, &["captureTime"])
+        .or_else(|| string_field(&header, &["timestamp"]))
+        .and_then(|value| parse_ips_timestamp(&value))
+        .unwrap_or_else(Utc::now);
+
+    Ok(CrashReport {
+        timestamp,
+        kind,
+        process,
+        process_id: body.get("pid").and_then(Value::as_u64).unwrap_or(0) as u32,
+        bundle_id: string_field(&body, &["bundleInfo", "CFBundleIdentifier"])
+            .or_else(|| string_field(&header, &["bundleID"])),
+        exception_type: string_field(&body, &["exception", "type"]),
+        exception_signal: string_field(&body, &["exception", "signal"]),
+        termination_reason: termination_reason(&body),
+        frames: crashing_frames(&body),
+        report_path: path.display().to_string(),
+    })
+}
+
+fn string_field(value: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .try_fold(value, |value, key| value.get(key))
+        .and_then(Value::as_str)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+fn parse_ips_timestamp(value: &str) -> Option<Timestamp> {
+    DateTime::parse_from_str(value, IPS_TIMESTAMP_FORMAT)
+        .ok()
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+}
+
+/// Format `termination` the way Crash Reporter prints "Termination Reason"
+fn termination_reason(body: &Value) -> Option<String> {
+    let termination = body.get("termination")?;
+    let namespace = termination.get("namespace").and_then(Value::as_str)?;
+    let mut reason = format!("Namespace {}", namespace);
+    if let Some(code) = termination.get("code").and_then(Value::as_u64) {
+        reason.push_str(&format!(", Code {}", code));
+    }
+    if let Some(indicator) = termination.get("indicator").and_then(Value::as_str) {
+        reason.push_str(&format!(", {}", indicator));
+    }
+    Some(reason)
+}
+
+fn crashing_frames(body: &Value) -> Vec<StackFrame> {
+    let Some(threads) = body.get("threads").and_then(Value::as_array) else {
+        return Vec::new();
+    };
+    let thread = body
+        .get("faultingThread")
+        .and_then(Value::as_u64)
+        .and_then(|index| threads.get(index as usize))
+        .or_else(|| {
+            threads.iter().find(|thread| {
+                thread
+                    .get("triggered")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false)
+            })
+        })
+        .or_else(|| threads.first());
+    let images = body.get("usedImages").and_then(Value::as_array);
+
+    thread
+        .and_then(|thread| thread.get("frames"))
+        .and_then(Value::as_array)
+        .map(|frames| {
+            frames
+                .iter()
+                .take(MAX_CRASH_FRAMES)
+                .map(|frame| StackFrame {
+                    image: frame
+                        .get("imageIndex")
+                        .and_then(Value::as_u64)
+                        .and_then(|index| images?.get(index as usize))
+                        .and_then(image_name)
+                        .unwrap_or_else(|| "???".to_string()),
+                    symbol: string_field(frame, &["symbol"]),
+                    image_offset: frame
+                        .get("imageOffset")
+                        .and_then(Value::as_u64)
+                        .unwrap_or(0),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn image_name(image: &Value) -> Option<String> {
+    string_field(image, &["name"]).or_else(|| {
+        string_field(image, &["path"]).and_then(|path| {
+            Path::new(&path)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use tempfile::TempDir;
+
+    const CRASH_IPS: &str = concat!(
+        r#"{"app_name":"ExampleEditor","timestamp":"2024-05-01 10:15:30.00 -0700","bug_type":"309","bundleID":"com.example.editor","os_version":"macOS 14.4 (23E214)"}"#,
+        "\n",
+        r#"{
+  "procName" : "ExampleEditor",
+  "pid" : 4242,
+  "captureTime" : "2024-05-01 10:15:29.5120 -0700",
+  "bundleInfo" : {"CFBundleIdentifier" : "com.example.editor", "CFBundleShortVersionString" : "2.1"},
+  "exception" : {"type" : "EXC_BAD_ACCESS", "signal" : "SIGSEGV", "subtype" : "KERN_INVALID_ADDRESS at 0x0"},
+  "termination" : {"namespace" : "SIGNAL", "code" : 11, "indicator" : "Segmentation fault: 11"},
+  "faultingThread" : 1,
+  "threads" : [
+    {"id" : 1, "frames" : [{"imageOffset" : 100, "imageIndex" : 0}]},
+    {"id" : 2, "triggered" : true, "frames" : [
+      {"imageOffset" : 16400, "symbol" : "objc_msgSend", "symbolLocation" : 16, "imageIndex" : 1},
+      {"imageOffset" : 73256, "imageIndex" : 0},
+      {"imageOffset" : 5120, "symbol" : "-[Document save:]", "imageIndex" : 0}
+    ]}
+  ],
+  "usedImages" : [
+    {"name" : "ExampleEditor", "path" : "/Applications/ExampleEditor.app/Contents/MacOS/ExampleEditor"},
+    {"path" : "/usr/lib/libobjc.A.dylib"}
+  ]
+}"#
+    );
+
+    const HANG_IPS: &str = concat!(
+        r#"{"name":"Finder","timestamp":"2024-05-01 11:00:00.00 +0000","bug_type":"288"}"#,
+        "\n",
+        r#"{"pid":311,"threads":[{"frames":[]},{"triggered":true,"frames":[{"imageOffset":42,"symbol":"mach_msg2_trap","imageIndex":0}]}],"usedImages":[{"name":"libsystem_kernel.dylib"}]}"#
+    );
+
+    #[test]
+    fn parses_crash_report_fields_and_faulting_frames() {
+        let report = parse_ips_report(CRASH_IPS, Path::new("/tmp/ExampleEditor.ips")).unwrap();
+
+        assert_eq!(report.kind, CrashReportKind::Crash);
+        assert_eq!(report.process, "ExampleEditor");
+        assert_eq!(report.process_id, 4242);
+        assert_eq!(report.bundle_id.as_deref(), Some("com.example.editor"));
+        assert_eq!(report.exception_type.as_deref(), Some("EXC_BAD_ACCESS"));
+        assert_eq!(report.exception_signal.as_deref(), Some("SIGSEGV"));
+        assert_eq!(
+            report.termination_reason.as_deref(),
+            Some("Namespace SIGNAL, Code 11, Segmentation fault: 11")
+        );
+        assert_eq!(
+            report.timestamp.to_rfc3339(),
+            "2024-05-01T17:15:29.512+00:00"
+        );
+        assert_eq!(
+            report.frames,
+            vec![
+                StackFrame {
+                    image: "libobjc.A.dylib".to_string(),
+                    symbol: Some("objc_msgSend".to_string()),
+                    image_offset: 16400,
+                },
+                StackFrame {
+                    image: "ExampleEditor".to_string(),
+                    symbol: None,
+                    image_offset: 73256,
+                },
+                StackFrame {
+                    image: "ExampleEditor".to_string(),
+                    symbol: Some("-[Document save:]".to_string()),
+                    image_offset: 5120,
+                },
+            ]
+        );
+        assert_eq!(report.report_path, "/tmp/ExampleEditor.ips");
+
+        let log_event = report.to_log_event();
+        assert_eq!(log_event.process, "ExampleEditor");
+        assert_eq!(
+            log_event.message,
+            "ExampleEditor crashed: EXC_BAD_ACCESS (SIGSEGV) at libobjc.A.dylib objc_msgSend"
+        );
+    }
+
+    #[test]
+    fn parses_hang_report_from_triggered_thread() {
+        let report = parse_ips_report(HANG_IPS, Path::new("Finder.ips")).unwrap();
+
+        assert_eq!(report.kind, CrashReportKind::Hang);
+        assert_eq!(report.process, "Finder");
+        assert_eq!(report.process_id, 311);
+        assert_eq!(report.bundle_id, None);
+        assert_eq!(report.termination_reason, None);
+        assert_eq!(report.frames.len(), 1);
+        assert_eq!(report.frames[0].image, "libsystem_kernel.dylib");
+        assert_eq!(
+            report.to_log_event().message,
+            "Finder stopped responding at libsystem_kernel.dylib mach_msg2_trap"
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_reports() {
+        let header_only = CRASH_IPS.lines().next().unwrap();
+        assert!(parse_ips_report(header_only, Path::new("a.ips")).is_err());
+
+        let truncated = &CRASH_IPS[..CRASH_IPS.len() / 2];
+        assert!(parse_ips_report(truncated, Path::new("a.ips")).is_err());
+    }
+
+    #[test]
+    fn collector_reports_only_new_and_completed_files() {
+        let directory = TempDir::new().unwrap();
+        std::fs::write(directory.path().join("Old-2024-04-30.ips"), CRASH_IPS).unwrap();
+        std::fs::write(directory.path().join("notes.txt"), "not a report").unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        let mut collector = CrashReportCollector::new(
+            directory.path().to_path_buf(),
+            Duration::from_millis(20),
+            sender,
+        );
+        collector.start().unwrap();
+
+        // A report still being written is retried once it changes size
+        let partial = directory.path().join("ExampleEditor-2024-05-01.ips");
+        std::fs::write(&partial, &CRASH_IPS[..40]).unwrap();
+        assert!(receiver.recv_timeout(Duration::from_millis(200)).is_err());
+        std::fs::write(&partial, CRASH_IPS).unwrap();
+        std::fs::write(directory.path().join("Finder-2024-05-01.ips"), HANG_IPS).unwrap();
+
+        let first = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        let second = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        let mut processes = vec![first.process, second.process];
+        processes.sort();
+        assert_eq!(processes, ["ExampleEditor", "Finder"]);
+        assert!(receiver.recv_timeout(Duration::from_millis(200)).is_err());
+
+        collector.stop().unwrap();
+        assert!(!collector.is_running());
+    }
+
+    #[test]
+    fn start_fails_for_missing_directory() {
+        let directory = TempDir::new().unwrap();
+        let (sender, _receiver) = mpsc::channel();
+        let mut collector = CrashReportCollector::new(
+            directory.path().join("missing"),
+            Duration::from_secs(1),
+            sender,
+        );
+
+        assert!(matches!(collector.start(), Err(CollectorError::IoError(_))));
+        assert!(!collector.is_running());
+    }
+}
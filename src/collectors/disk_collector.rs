@@ -1,3 +1,5 @@
+use super::capture::{CaptureStream, CaptureWriter};
+use super::source::{EventSource, SourceHealth};
 use crate::error::CollectorError;
 use crate::events::DiskEvent;
 use crate::monitoring::SelfMonitoringCollector;
@@ -10,15 +12,15 @@ use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
 
-const FS_USAGE_AGGREGATION_INTERVAL: Duration = Duration::from_secs(1);
+pub(crate) const FS_USAGE_AGGREGATION_INTERVAL: Duration = Duration::from_secs(1);
 
 #[derive(Default)]
-struct IostatParser {
+pub(crate) struct IostatParser {
     disk_names: Vec<String>,
 }
 
 #[derive(Default)]
-struct FsUsageAccumulator {
+pub(crate) struct FsUsageAccumulator {
     read_kb: f64,
     write_kb: f64,
     read_ops: f64,
@@ -29,7 +31,7 @@ struct FsUsageAccumulator {
 }
 
 impl FsUsageAccumulator {
-    fn record(&mut self, event: DiskEvent) {
+    pub(crate) fn record(&mut self, event: DiskEvent) {
         self.read_kb += event.read_kb_per_sec;
         self.write_kb += event.write_kb_per_sec;
         self.read_ops += event.read_ops_per_sec;
@@ -44,7 +46,7 @@ impl FsUsageAccumulator {
         self.event_count += 1;
     }
 
-    fn take_sample(&mut self, elapsed: Duration) -> Option<DiskEvent> {
+    pub(crate) fn take_sample(&mut self, elapsed: Duration) -> Option<DiskEvent> {
         if self.event_count == 0 {
             return None;
         }
@@ -119,6 +121,8 @@ pub struct DiskCollector {
     running: Arc<Mutex<bool>>,
     /// Self-monitoring collector for resource pressure detection
     monitoring: Option<Arc<SelfMonitoringCollector>>,
+    /// Optional capture of raw iostat/fs_usage output for replay
+    capture: Option<CaptureWriter>,
 }
 
 impl DiskCollector {
@@ -148,6 +152,7 @@ impl DiskCollector {
             fs_thread_handle: None,
             running: Arc::new(Mutex::new(false)),
             monitoring: None,
+            capture: None,
         }
     }
 
@@ -156,6 +161,11 @@ impl DiskCollector {
         self.monitoring = Some(monitoring);
     }
 
+    /// Record raw iostat and fs_usage output to a capture file
+    pub fn set_capture(&mut self, capture: CaptureWriter) {
+        self.capture = Some(capture);
+    }
+
     /// Start the disk collector
     ///
     /// Spawns a background thread that manages disk monitoring subprocesses.
@@ -207,18 +217,20 @@ impl DiskCollector {
         let channel = self.output_channel.clone();
         let running = Arc::clone(&self.running);
         let monitoring = self.monitoring.clone();
+        let capture = self.capture.clone();
 
         // Spawn background thread
         debug!("Spawning DiskCollector background thread");
         let handle = thread::spawn(move || {
-            Self::collector_thread(current_interval, channel, running, monitoring);
+            Self::collector_thread(current_interval, channel, running, monitoring, capture);
         });
 
         // Spawn fs_usage thread (filesystem-level events) best-effort
         let fs_channel = self.output_channel.clone();
         let fs_running = Arc::clone(&self.running);
+        let fs_capture = self.capture.clone();
         let fs_handle = thread::spawn(move || {
-            if let Err(e) = Self::fs_usage_thread(fs_channel, fs_running.clone()) {
+            if let Err(e) = Self::fs_usage_thread(fs_channel, fs_running.clone(), fs_capture) {
                 warn!("fs_usage monitoring disabled: {}", e);
             }
         });
@@ -304,6 +316,7 @@ impl DiskCollector {
         channel: Sender<DiskEvent>,
         running: Arc<Mutex<bool>>,
         monitoring: Option<Arc<SelfMonitoringCollector>>,
+        capture: Option<CaptureWriter>,
     ) {
         let initial_interval = *current_interval.lock().unwrap();
         info!(
@@ -352,7 +365,12 @@ impl DiskCollector {
                     info!("Disk monitoring subprocess started successfully");
 
                     let mut had_healthy_run = false;
-                    match Self::process_disk_output(&mut child, &channel, &running) {
+                    match Self::process_disk_output(
+                        &mut child,
+                        &channel,
+                        &running,
+                        capture.as_ref(),
+                    ) {
                         Ok(_) => match child.try_wait() {
                             Ok(Some(exit_status)) => {
                                 warn!("Disk subprocess exited with status: {:?}", exit_status);
@@ -455,6 +473,7 @@ impl DiskCollector {
     fn fs_usage_thread(
         channel: Sender<DiskEvent>,
         running: Arc<Mutex<bool>>,
+        capture: Option<CaptureWriter>,
     ) -> Result<(), CollectorError> {
         // fs_usage requires sudo on many systems; run non-interactively and bail if unavailable
         let mut child = Command::new("sudo")
@@ -493,6 +512,9 @@ impl DiskCollector {
                 Ok(0) => break,
                 Ok(n) => {
                     buffer.extend_from_slice(&temp_buf[..n]);
+                    if let Some(ref capture) = capture {
+                        capture.record(CaptureStream::FsUsage, &temp_buf[..n]);
+                    }
 
                     if let Some(events) = Self::try_parse_fs_usage_buffer(&mut buffer) {
                         for event in events {
@@ -534,6 +556,7 @@ impl DiskCollector {
         child: &mut Child,
         channel: &Sender<DiskEvent>,
         running: &Arc<Mutex<bool>>,
+        capture: Option<&CaptureWriter>,
     ) -> Result<(), CollectorError> {
         use std::io::Read;
 
@@ -560,6 +583,9 @@ impl DiskCollector {
                 }
                 Ok(n) => {
                     buffer.extend_from_slice(&temp_buf[..n]);
+                    if let Some(capture) = capture {
+                        capture.record(CaptureStream::Iostat, &temp_buf[..n]);
+                    }
 
                     if let Some(parsed_events) =
                         Self::try_parse_iostat_buffer(&mut buffer, &mut parser)
@@ -596,7 +622,7 @@ impl DiskCollector {
     }
 
     /// Try to parse iostat output from the buffer
-    fn try_parse_iostat_buffer(
+    pub(crate) fn try_parse_iostat_buffer(
         buffer: &mut Vec<u8>,
         parser: &mut IostatParser,
     ) -> Option<Vec<DiskEvent>> {
@@ -697,7 +723,7 @@ impl DiskCollector {
     }
 
     /// Parse fs_usage output buffer into DiskEvents
-    fn try_parse_fs_usage_buffer(buffer: &mut Vec<u8>) -> Option<Vec<DiskEvent>> {
+    pub(crate) fn try_parse_fs_usage_buffer(buffer: &mut Vec<u8>) -> Option<Vec<DiskEvent>> {
         let buffer_str = String::from_utf8_lossy(buffer);
         let lines: Vec<&str> = buffer_str.lines().collect();
         let mut events = Vec::new();
@@ -749,6 +775,32 @@ impl DiskCollector {
     }
 }
 
+impl EventSource for DiskCollector {
+    fn name(&self) -> &str {
+        "disk"
+    }
+
+    fn start(&mut self) -> Result<(), CollectorError> {
+        DiskCollector::start(self)
+    }
+
+    fn stop(&mut self) -> Result<(), CollectorError> {
+        DiskCollector::stop(self)
+    }
+
+    fn health(&self) -> SourceHealth {
+        if self.is_running() {
+            SourceHealth::Running
+        } else {
+            SourceHealth::Stopped
+        }
+    }
+
+    fn set_capture(&mut self, capture: CaptureWriter) {
+        DiskCollector::set_capture(self, capture)
+    }
+}
+
 impl Drop for DiskCollector {
     fn drop(&mut self) {
         if self.is_running() || self.thread_handle.is_some() || self.fs_thread_handle.is_some() {
@@ -783,7 +835,7 @@ mod tests {
             .spawn()
             .unwrap();
 
-        DiskCollector::process_disk_output(&mut child, &tx, &running).unwrap();
+        DiskCollector::process_disk_output(&mut child, &tx, &running, None).unwrap();
         let _ = child.wait();
 
         assert!(!*running.lock().unwrap());
@@ -806,7 +858,7 @@ mod tests {
         });
         let started = Instant::now();
 
-        DiskCollector::process_disk_output(&mut child, &tx, &running).unwrap();
+        DiskCollector::process_disk_output(&mut child, &tx, &running, None).unwrap();
         let _ = child.kill();
         let _ = child.wait();
         shutdown_thread.join().unwrap();
@@ -0,0 +1,142 @@
+use crate::error::CollectorError;
+use crate::events::{DiskEvent, LogEvent, MetricsEvent};
+use serde::Deserialize;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// One line of an event file, told apart by its fields
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RecordedEvent {
+    Log(LogEvent),
+    Metrics(MetricsEvent),
+    Disk(DiskEvent),
+}
+
+/// Parsed events read from a JSONL event file
+#[derive(Debug, Default)]
+pub struct RecordedEvents {
+    /// Log events sorted by timestamp
+    pub log_events: Vec<LogEvent>,
+    /// Metrics events sorted by timestamp
+    pub metrics_events: Vec<MetricsEvent>,
+    /// Disk events sorted by timestamp
+    pub disk_events: Vec<DiskEvent>,
+}
+
+impl RecordedEvents {
+    /// Total number of events of every kind
+    pub fn len(&self) -> usize {
+        self.log_events.len() + self.metrics_events.len() + self.disk_events.len()
+    }
+
+    /// Whether the file held no events
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Read a JSONL file of serialized `LogEvent`, `MetricsEvent` and `DiskEvent`
+/// records
+///
+/// Each non-empty line holds one event in its serde form, as stored with the
+/// alerts and returned by the web API. Lines may be in any order; each kind is
+/// sorted by timestamp.
+///
+/// # Errors
+///
+/// Returns `CollectorError::IoError` if the file cannot be read, or
+/// `CollectorError::ParseError` with the offending line number when a line is
+/// not one of the three event kinds.
+pub fn read_event_file(path: &Path) -> Result<RecordedEvents, CollectorError> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut events = RecordedEvents::default();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event = serde_json::from_str::<RecordedEvent>(&line).map_err(|_| {
+            CollectorError::ParseError(format!(
+                "{}:{}: expected a LogEvent, MetricsEvent or DiskEvent",
+                path.display(),
+                index + 1
+            ))
+        })?;
+        match event {
+            RecordedEvent::Log(event) => events.log_events.push(event),
+            RecordedEvent::Metrics(event) => events.metrics_events.push(event),
+            RecordedEvent::Disk(event) => events.disk_events.push(event),
+        }
+    }
+    events.log_events.sort_by_key(|event| event.timestamp);
+    events.metrics_events.sort_by_key(|event| event.timestamp);
+    events.disk_events.sort_by_key(|event| event.timestamp);
+
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{MemoryPressure, MessageType};
+    use chrono::{Duration, Utc};
+    use tempfile::TempDir;
+
+    #[test]
+    fn reads_each_event_kind_in_time_order() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("events.jsonl");
+        let start = Utc::now() - Duration::days(7);
+        let log = |seconds: i64| LogEvent {
+            timestamp: start + Duration::seconds(seconds),
+            message_type: MessageType::Error,
+            subsystem: "com.example.backup".to_string(),
+            category: "default".to_string(),
+            process: "backupd".to_string(),
+            process_id: 42,
+            message: "Backup failed".to_string(),
+        };
+        let metrics = MetricsEvent {
+            timestamp: start,
+            cpu_power_mw: 1200.0,
+            cpu_usage_percent: 35.0,
+            gpu_power_mw: None,
+            gpu_usage_percent: None,
+            memory_pressure: MemoryPressure::Warning,
+            memory_used_mb: 8192.0,
+            energy_impact: 1200.0,
+            provenance: Default::default(),
+            process_metrics: Vec::new(),
+        };
+        let disk = DiskEvent {
+            timestamp: start,
+            read_kb_per_sec: 2048.0,
+            write_kb_per_sec: 0.0,
+            read_ops_per_sec: 40.0,
+            write_ops_per_sec: 0.0,
+            disk_name: "disk0".to_string(),
+            filesystem_path: None,
+        };
+        let lines = [
+            serde_json::to_string(&log(30)).unwrap(),
+            serde_json::to_string(&metrics).unwrap(),
+            String::new(),
+            serde_json::to_string(&disk).unwrap(),
+            serde_json::to_string(&log(10)).unwrap(),
+        ];
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let events = read_event_file(&path).unwrap();
+        assert_eq!(events.len(), 4);
+        assert_eq!(events.log_events, vec![log(10), log(30)]);
+        assert_eq!(events.metrics_events, vec![metrics]);
+        assert_eq!(events.disk_events, vec![disk]);
+
+        std::fs::write(&path, format!("{}\n{{\"cpu\": 1}}\n", lines[0])).unwrap();
+        let error = read_event_file(&path).unwrap_err().to_string();
+        assert!(error.contains("events.jsonl:2"), "{error}");
+    }
+}
@@ -0,0 +1,431 @@
+use super::file_tail_collector::{message_type_for_level, parse_timestamp};
+use super::source::{EventSinks, EventSource, SourceHealth};
+use crate::config::ExecCollectorConfig;
+use crate::error::CollectorError;
+use crate::events::{DiskEvent, LogEvent, MessageType, MetricsEvent};
+use chrono::Utc;
+use log::{debug, error, info, warn};
+use serde_json::Value;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// Event parsed from one line of plugin output
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum PluginEvent {
+    Log(LogEvent),
+    Metrics(MetricsEvent),
+    Disk(DiskEvent),
+}
+
+/// Collector that runs an external command as an event source
+///
+/// The command writes one JSON object per line on stdout, tagged with
+/// `"kind": "log"`, `"metrics"` or `"disk"`. Events are published on the
+/// matching `EventSinks` channel. The command is restarted with the same
+/// backoff as `LogCollector` whenever it exits, so it should keep running
+/// and print events as they happen. stderr is passed through to Eyes' own
+/// stderr for debugging.
+pub struct ExecCollector {
+    /// Command and source name
+    config: ExecCollectorConfig,
+    /// Channels to publish events on
+    sinks: EventSinks,
+    /// Handle to the background thread
+    thread_handle: Option<JoinHandle<()>>,
+    /// Shared state for controlling the collector
+    running: Arc<Mutex<bool>>,
+}
+
+impl ExecCollector {
+    /// Create a collector for one `[[collectors.exec]]` entry
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::sync::mpsc;
+    /// use eyes::collectors::{EventSinks, ExecCollector};
+    /// use eyes::config::ExecCollectorConfig;
+    ///
+    /// let sinks = EventSinks {
+    ///     logs: mpsc::channel().0,
+    ///     metrics: mpsc::channel().0,
+    ///     disk: mpsc::channel().0,
+    ///     crashes: mpsc::channel().0,
+    /// };
+    /// let config = ExecCollectorConfig {
+    ///     name: "vpn".to_string(),
+    ///     command: "/usr/local/bin/vpn-probe".to_string(),
+    ///     args: vec!["--follow".to_string()],
+    /// };
+    /// let collector = ExecCollector::new(config, sinks);
+    /// ```
+    pub fn new(config: ExecCollectorConfig, sinks: EventSinks) -> Self {
+        Self {
+            config,
+            sinks,
+            thread_handle: None,
+            running: Arc::new(Mutex::new(false)),
+        }
+    }
+
+    /// Start the command and read its output on a background thread
+    ///
+    /// # Errors
+    ///
+    /// Returns `CollectorError::SubprocessSpawn` if the command cannot be started.
+    pub fn start(&mut self) -> Result<(), CollectorError> {
+        info!(
+            "Starting ExecCollector '{}': {} {:?}",
+            self.config.name, self.config.command, self.config.args
+        );
+
+        {
+            let mut running = self.running.lock().unwrap();
+            if *running {
+                info!(
+                    "ExecCollector '{}' already running, skipping start",
+                    self.config.name
+                );
+                return Ok(());
+            }
+            *running = true;
+        }
+
+        // The first child is handed to the supervisor rather than being a
+        // throwaway test spawn, so a plugin with side effects only runs once
+        let first_child = match Self::spawn_command(&self.config) {
+            Ok(child) => child,
+            Err(e) => {
+                error!(
+                    "Failed to start exec collector '{}': {}",
+                    self.config.name, e
+                );
+                *self.running.lock().unwrap() = false;
+                return Err(e);
+            }
+        };
+
+        let config = self.config.clone();
+        let sinks = self.sinks.clone();
+        let running = Arc::clone(&self.running);
+        self.thread_handle = Some(thread::spawn(move || {
+            let mut first_child = Some(first_child);
+            super::supervise_subprocess(
+                &config.name,
+                &running,
+                || match first_child.take() {
+                    Some(child) => Ok(child),
+                    None => Self::spawn_command(&config),
+                },
+                |child| Self::process_output(child, &config.name, &sinks, &running),
+            );
+            info!("Exec collector '{}' thread finished", config.name);
+        }));
+
+        info!("ExecCollector '{}' started successfully", self.config.name);
+        Ok(())
+    }
+
+    /// Stop the collector, killing the command and joining the thread
+    ///
+    /// # Errors
+    ///
+    /// Returns `CollectorError::SubprocessTerminated` if the thread panicked.
+    pub fn stop(&mut self) -> Result<(), CollectorError> {
+        info!("Stopping ExecCollector '{}'", self.config.name);
+        *self.running.lock().unwrap() = false;
+
+        if let Some(handle) = self.thread_handle.take() {
+            handle.join().map_err(|_| {
+                error!("Failed to join ExecCollector '{}' thread", self.config.name);
+                CollectorError::SubprocessTerminated("Failed to join collector thread".to_string())
+            })?;
+        }
+
+        info!("ExecCollector '{}' stopped successfully", self.config.name);
+        Ok(())
+    }
+
+    /// Check if the collector is currently running
+    pub fn is_running(&self) -> bool {
+        *self.running.lock().unwrap()
+    }
+
+    fn spawn_command(config: &ExecCollectorConfig) -> Result<Child, CollectorError> {
+        debug!("Spawning {} {:?}", config.command, config.args);
+
+        let mut child = Command::new(&config.command)
+            .args(&config.args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| CollectorError::SubprocessSpawn(format!("{}: {}", config.command, e)))?;
+
+        if let Some(stdout) = child.stdout.as_ref() {
+            if let Err(error) = super::set_nonblocking(stdout) {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(CollectorError::IoError(error));
+            }
+        }
+
+        Ok(child)
+    }
+
+    fn process_output(
+        child: &mut Child,
+        name: &str,
+        sinks: &EventSinks,
+        running: &Arc<Mutex<bool>>,
+    ) -> Result<(), CollectorError> {
+        let mut buffer = String::new();
+        let mut in_json_array = false;
+
+        super::read_subprocess_output(child, running, |chunk| {
+            Self::handle_chunk(chunk, &mut buffer, &mut in_json_array, name, sinks)
+        })
+    }
+
+    /// Append a chunk of plugin output and publish complete events
+    pub(crate) fn handle_chunk(
+        chunk: &[u8],
+        buffer: &mut String,
+        in_json_array: &mut bool,
+        name: &str,
+        sinks: &EventSinks,
+    ) -> Result<(), CollectorError> {
+        buffer.push_str(&String::from_utf8_lossy(chunk));
+        super::drain_json_values(buffer, in_json_array, |value| {
+            let sent = match parse_plugin_event(&value, name) {
+                Ok(PluginEvent::Log(event)) => sinks.logs.send(event).is_ok(),
+                Ok(PluginEvent::Metrics(event)) => sinks.metrics.send(event).is_ok(),
+                Ok(PluginEvent::Disk(event)) => sinks.disk.send(event).is_ok(),
+                Err(e) => {
+                    debug!("Skipping event from '{}': {}", name, e);
+                    true
+                }
+            };
+            if !sent {
+                warn!("Failed to send event from '{}' to channel", name);
+            }
+            Ok(())
+        })
+    }
+}
+
+impl EventSource for ExecCollector {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn start(&mut self) -> Result<(), CollectorError> {
+        ExecCollector::start(self)
+    }
+
+    fn stop(&mut self) -> Result<(), CollectorError> {
+        ExecCollector::stop(self)
+    }
+
+    fn health(&self) -> SourceHealth {
+        if self.is_running() {
+            SourceHealth::Running
+        } else {
+            SourceHealth::Stopped
+        }
+    }
+}
+
+impl Drop for ExecCollector {
+    fn drop(&mut self) {
+        if self.is_running() || self.thread_handle.is_some() {
+            let _ = self.stop();
+        }
+    }
+}
+
+/// Parse one plugin event
+///
+/// - `log`: `message` (required), `level`, `timestamp`, `process` (defaults
+///   to the source name), `process_id`, `subsystem`, `category`
+/// - `metrics`: the fields accepted by `MetricsEvent::from_json`
+/// - `disk`: `disk_name` (required), `read_kb_per_sec`, `write_kb_per_sec`,
+///   `read_ops_per_sec`, `write_ops_per_sec`, `filesystem_path`, `timestamp`
+///
+/// Timestamps may be RFC 3339 strings or epoch seconds/milliseconds and
+/// default to the time the line was read.
+pub(crate) fn parse_plugin_event(value: &Value, source: &str) -> Result<PluginEvent, String> {
+    let kind = value
+        .get("kind")
+        .and_then(Value::as_str)
+        .ok_or_else(|| "missing \"kind\"".to_string())?;
+    let text = |key: &str| value.get(key).and_then(Value::as_str);
+    let number = |key: &str| value.get(key).and_then(Value::as_f64).unwrap_or(0.0);
+    let timestamp = || {
+        value
+            .get("timestamp")
+            .and_then(|timestamp| parse_timestamp(timestamp, None))
+            .unwrap_or_else(Utc::now)
+    };
+
+    match kind {
+        "log" => Ok(PluginEvent::Log(LogEvent {
+            timestamp: timestamp(),
+            message_type: text("level")
+                .map(message_type_for_level)
+                .unwrap_or(MessageType::Info),
+            subsystem: text("subsystem").unwrap_or_default().to_string(),
+            category: text("category").unwrap_or("exec").to_string(),
+            process: text("process").unwrap_or(source).to_string(),
+            process_id: value
+                .get("process_id")
+                .and_then(Value::as_u64)
+                .and_then(|pid| u32::try_from(pid).ok())
+                .unwrap_or(0),
+            message: text("message")
+                .ok_or_else(|| "log event without \"message\"".to_string())?
+                .to_string(),
+        })),
+        "metrics" => {
+            let mut fields = value.clone();
+            if let Some(object) = fields.as_object_mut() {
+                object.remove("kind");
+                object.insert(
+                    "timestamp".to_string(),
+                    Value::String(timestamp().to_rfc3339()),
+                );
+            }
+            MetricsEvent::from_json(&fields.to_string()).map(PluginEvent::Metrics)
+        }
+        "disk" => Ok(PluginEvent::Disk(DiskEvent {
+            timestamp: timestamp(),
+            read_kb_per_sec: number("read_kb_per_sec"),
+            write_kb_per_sec: number("write_kb_per_sec"),
+            read_ops_per_sec: number("read_ops_per_sec"),
+            write_ops_per_sec: number("write_ops_per_sec"),
+            disk_name: text("disk_name")
+                .ok_or_else(|| "disk event without \"disk_name\"".to_string())?
+                .to_string(),
+            filesystem_path: text("filesystem_path").map(str::to_string),
+        })),
+        other => Err(format!("unknown kind \"{}\"", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{MemoryPressure, MetricsSource};
+    use serde_json::json;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn parses_each_event_kind() {
+        let log = parse_plugin_event(
+            &json!({"kind": "log", "level": "error", "message": "tunnel down",
+                    "timestamp": "2024-05-01T10:15:30Z", "subsystem": "wg0"}),
+            "vpn",
+        )
+        .unwrap();
+        let PluginEvent::Log(log) = log else {
+            panic!("expected a log event");
+        };
+        assert_eq!(log.message_type, MessageType::Error);
+        assert_eq!(log.process, "vpn");
+        assert_eq!(log.subsystem, "wg0");
+        assert_eq!(log.category, "exec");
+        assert_eq!(log.timestamp.to_rfc3339(), "2024-05-01T10:15:30+00:00");
+
+        let metrics = parse_plugin_event(
+            &json!({"kind": "metrics", "cpu_power_mw": 1200.0, "memory_pressure": "warning",
+                    "timestamp": 1714558530}),
+            "probe",
+        )
+        .unwrap();
+        let PluginEvent::Metrics(metrics) = metrics else {
+            panic!("expected a metrics event");
+        };
+        assert_eq!(metrics.cpu_power_mw, 1200.0);
+        assert_eq!(metrics.memory_pressure, MemoryPressure::Warning);
+        assert_eq!(metrics.provenance.source, MetricsSource::Json);
+        assert_eq!(metrics.timestamp.timestamp(), 1714558530);
+
+        let disk = parse_plugin_event(
+            &json!({"kind": "disk", "disk_name": "nas0", "write_kb_per_sec": 5120.5}),
+            "probe",
+        )
+        .unwrap();
+        let PluginEvent::Disk(disk) = disk else {
+            panic!("expected a disk event");
+        };
+        assert_eq!(disk.disk_name, "nas0");
+        assert_eq!(disk.write_kb_per_sec, 5120.5);
+        assert_eq!(disk.read_kb_per_sec, 0.0);
+
+        assert!(parse_plugin_event(&json!({"message": "no kind"}), "probe").is_err());
+        assert!(parse_plugin_event(&json!({"kind": "log"}), "probe").is_err());
+        assert!(parse_plugin_event(&json!({"kind": "trace"}), "probe").is_err());
+        assert!(parse_plugin_event(&json!({"kind": "metrics"}), "probe").is_err());
+    }
+
+    #[test]
+    fn routes_command_output_to_the_matching_sinks() {
+        let (logs, log_receiver) = mpsc::channel();
+        let (disk, disk_receiver) = mpsc::channel();
+        let sinks = EventSinks {
+            logs,
+            metrics: mpsc::channel().0,
+            disk,
+            crashes: mpsc::channel().0,
+        };
+        let config = ExecCollectorConfig {
+            name: "probe".to_string(),
+            command: "sh".to_string(),
+            args: vec![
+                "-c".to_string(),
+                r#"echo '{"kind":"log","level":"fault","message":"build farm offline"}'
+                   echo 'not json'
+                   echo '{"kind":"disk","disk_name":"nas0"}'
+                   exec sleep 30"#
+                    .to_string(),
+            ],
+        };
+        let mut collector = ExecCollector::new(config, sinks);
+        collector.start().unwrap();
+
+        let log = log_receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(log.message_type, MessageType::Fault);
+        assert_eq!(log.process, "probe");
+        let disk = disk_receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(disk.disk_name, "nas0");
+
+        collector.stop().unwrap();
+        assert!(!collector.is_running());
+    }
+
+    #[test]
+    fn missing_command_fails_to_start() {
+        let sinks = EventSinks {
+            logs: mpsc::channel().0,
+            metrics: mpsc::channel().0,
+            disk: mpsc::channel().0,
+            crashes: mpsc::channel().0,
+        };
+        let mut collector = ExecCollector::new(
+            ExecCollectorConfig {
+                name: "missing".to_string(),
+                command: "/nonexistent/eyes-probe".to_string(),
+                args: Vec::new(),
+            },
+            sinks,
+        );
+
+        assert!(matches!(
+            collector.start(),
+            Err(CollectorError::SubprocessSpawn(_))
+        ));
+        assert_eq!(collector.health(), SourceHealth::Stopped);
+    }
+}
@@ -0,0 +1,682 @@
+use super::journald_collector::message_type_for_priority;
+use super::source::{EventSource, SourceHealth};
+use crate::config::{LogFileConfig, LogFileFields, LogFileFormat};
+use crate::error::CollectorError;
+use crate::events::{LogEvent, MessageType, Timestamp};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use log::{debug, error, info, warn};
+use regex::Regex;
+use serde_json::Value;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// How often tailed files are checked for new lines
+pub(crate) const FILE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Naive timestamp layouts tried when no `timestamp_format` is configured
+const NAIVE_TIMESTAMP_FORMATS: &[&str] = &["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"];
+
+/// Application log file collector
+///
+/// Follows every file in `[[logging.files]]` from its current end, like
+/// `tail -F`. Each complete line is parsed with the file's regex or JSON
+/// field mapping and sent as a `LogEvent`; lines that do not parse are
+/// skipped. A file that shrinks is read again from the start, and a file
+/// replaced by rename is drained to its end before the new file is read
+/// from the beginning. Files that do not exist yet are picked up when they
+/// appear.
+pub struct FileTailCollector {
+    /// Files to follow
+    files: Vec<LogFileConfig>,
+    /// Interval between checks for new lines
+    poll_interval: Duration,
+    /// Channel to send log events
+    output_channel: Sender<LogEvent>,
+    /// Handle to the background thread
+    thread_handle: Option<JoinHandle<()>>,
+    /// Shared state for controlling the collector
+    running: Arc<Mutex<bool>>,
+}
+
+impl FileTailCollector {
+    /// Create a collector following `files`, checking every `poll_interval`
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::sync::mpsc;
+    /// use std::time::Duration;
+    /// use eyes::collectors::FileTailCollector;
+    /// use eyes::config::LogFileConfig;
+    ///
+    /// let file: LogFileConfig = toml::from_str(
+    ///     r#"
+    ///     path = "/var/log/api/app.log"
+    ///     pattern = '^(?P<level>\w+): (?P<message>.*)$'
+    ///     "#,
+    /// )
+    /// .unwrap();
+    /// let (tx, rx) = mpsc::channel();
+    /// let collector = FileTailCollector::new(vec![file], Duration::from_millis(500), tx);
+    /// ```
+    pub fn new(
+        files: Vec<LogFileConfig>,
+        poll_interval: Duration,
+        channel: Sender<LogEvent>,
+    ) -> Self {
+        Self {
+            files,
+            poll_interval,
+            output_channel: channel,
+            thread_handle: None,
+            running: Arc::new(Mutex::new(false)),
+        }
+    }
+
+    /// Start following the configured files on a background thread
+    ///
+    /// # Errors
+    ///
+    /// Returns `CollectorError::ParseError` if a file's pattern is invalid.
+    pub fn start(&mut self) -> Result<(), CollectorError> {
+        info!(
+            "Starting FileTailCollector for {} file(s) with interval: {:?}",
+            self.files.len(),
+            self.poll_interval
+        );
+
+        {
+            let mut running = self.running.lock().unwrap();
+            if *running {
+                info!("FileTailCollector already running, skipping start");
+                return Ok(());
+            }
+
+            let mut tails = Vec::with_capacity(self.files.len());
+            for file in &self.files {
+                let parser = LogLineParser::new(file)?;
+                tails.push((TailedFile::open_at_end(file.path.clone()), parser));
+            }
+            *running = true;
+
+            let interval = self.poll_interval;
+            let channel = self.output_channel.clone();
+            let running = Arc::clone(&self.running);
+            self.thread_handle = Some(thread::spawn(move || {
+                Self::collector_thread(tails, interval, channel, running);
+            }));
+        }
+
+        info!("FileTailCollector started successfully");
+        Ok(())
+    }
+
+    /// Stop the collector and wait for its thread to finish
+    ///
+    /// # Errors
+    ///
+    /// Returns `CollectorError::SubprocessTerminated` if the thread panicked.
+    pub fn stop(&mut self) -> Result<(), CollectorError> {
+        info!("Stopping FileTailCollector");
+        *self.running.lock().unwrap() = false;
+
+        if let Some(handle) = self.thread_handle.take() {
+            handle.join().map_err(|_| {
+                error!("Failed to join FileTailCollector thread");
+                CollectorError::SubprocessTerminated("Failed to join collector thread".to_string())
+            })?;
+        }
+
+        info!("FileTailCollector stopped successfully");
+        Ok(())
+    }
+
+    /// Check if the collector is currently running
+    pub fn is_running(&self) -> bool {
+        *self.running.lock().unwrap()
+    }
+
+    fn collector_thread(
+        mut tails: Vec<(TailedFile, LogLineParser)>,
+        interval: Duration,
+        channel: Sender<LogEvent>,
+        running: Arc<Mutex<bool>>,
+    ) {
+        'poll: while *running.lock().unwrap() {
+            super::wait_for_retry(interval, &running);
+            if !*running.lock().unwrap() {
+                break;
+            }
+
+            for (tail, parser) in &mut tails {
+                let lines = match tail.read_lines() {
+                    Ok(lines) => lines,
+                    Err(e) => {
+                        warn!("Failed to read {}: {}", tail.path.display(), e);
+                        continue;
+                    }
+                };
+                for line in lines {
+                    let Some(event) = parser.parse_line(&line) else {
+                        debug!("Skipping unparsed line from {}", tail.path.display());
+                        continue;
+                    };
+                    if channel.send(event).is_err() {
+                        info!("Log channel closed, stopping FileTailCollector");
+                        *running.lock().unwrap() = false;
+                        break 'poll;
+                    }
+                }
+            }
+        }
+
+        debug!("FileTailCollector thread exiting");
+    }
+}
+
+impl EventSource for FileTailCollector {
+    fn name(&self) -> &str {
+        "files"
+    }
+
+    fn start(&mut self) -> Result<(), CollectorError> {
+        FileTailCollector::start(self)
+    }
+
+    fn stop(&mut self) -> Result<(), CollectorError> {
+        FileTailCollector::stop(self)
+    }
+
+    fn health(&self) -> SourceHealth {
+        if self.is_running() {
+            SourceHealth::Running
+        } else {
+            SourceHealth::Stopped
+        }
+    }
+}
+
+impl Drop for FileTailCollector {
+    fn drop(&mut self) {
+        if self.is_running() || self.thread_handle.is_some() {
+            let _ = self.stop();
+        }
+    }
+}
+
+/// Device and inode of a file, used to notice rename-based rotation
+#[cfg(unix)]
+fn file_identity(metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_identity(_metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Read position in one followed file
+struct TailedFile {
+    path: PathBuf,
+    /// Open handle; kept after the path is renamed away so the rest is read
+    reader: Option<File>,
+    identity: Option<(u64, u64)>,
+    offset: u64,
+    /// Bytes after the last newline
+    partial: Vec<u8>,
+}
+
+impl TailedFile {
+    /// Start at the current end of `path`, or at the beginning once it exists
+    fn open_at_end(path: PathBuf) -> Self {
+        let mut tail = Self {
+            path,
+            reader: None,
+            identity: None,
+            offset: 0,
+            partial: Vec::new(),
+        };
+        match File::open(&tail.path).and_then(|file| Ok((file.metadata()?, file))) {
+            Ok((metadata, file)) => {
+                tail.identity = file_identity(&metadata);
+                tail.offset = metadata.len();
+                tail.reader = Some(file);
+            }
+            Err(e) => debug!("Waiting for {} to appear: {}", tail.path.display(), e),
+        }
+        tail
+    }
+
+    /// Complete lines written since the last call
+    fn read_lines(&mut self) -> std::io::Result<Vec<String>> {
+        let mut lines = Vec::new();
+        let metadata = match std::fs::metadata(&self.path) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                // Renamed away and not yet recreated; finish the old file
+                self.drain(&mut lines)?;
+                return Ok(lines);
+            }
+            Err(e) => return Err(e),
+        };
+
+        if self.reader.is_some() && file_identity(&metadata) != self.identity {
+            debug!("{} was rotated", self.path.display());
+            self.drain(&mut lines)?;
+            self.flush_partial(&mut lines);
+            self.reader = None;
+        }
+
+        if self.reader.is_none() {
+            let file = File::open(&self.path)?;
+            self.identity = file_identity(&file.metadata()?);
+            self.offset = 0;
+            self.reader = Some(file);
+        } else if metadata.len() < self.offset {
+            debug!("{} was truncated", self.path.display());
+            self.offset = 0;
+            self.partial.clear();
+        }
+
+        self.drain(&mut lines)?;
+        Ok(lines)
+    }
+
+    fn drain(&mut self, lines: &mut Vec<String>) -> std::io::Result<()> {
+        let Some(reader) = self.reader.as_mut() else {
+            return Ok(());
+        };
+        reader.seek(SeekFrom::Start(self.offset))?;
+        let read = reader.read_to_end(&mut self.partial)?;
+        self.offset += read as u64;
+
+        while let Some(newline) = self.partial.iter().position(|byte| *byte == b'\n') {
+            let line = self.partial.drain(..=newline).collect::<Vec<_>>();
+            push_line(lines, &line);
+        }
+        Ok(())
+    }
+
+    /// Emit an unterminated last line, used when the file is replaced
+    fn flush_partial(&mut self, lines: &mut Vec<String>) {
+        let line = std::mem::take(&mut self.partial);
+        push_line(lines, &line);
+    }
+}
+
+fn push_line(lines: &mut Vec<String>, bytes: &[u8]) {
+    let line = String::from_utf8_lossy(bytes);
+    let line = line.trim_end_matches(['\n', '\r']);
+    if !line.trim().is_empty() {
+        lines.push(line.to_string());
+    }
+}
+
+enum LineFormat {
+    Regex(Regex),
+    Json(LogFileFields),
+}
+
+/// Converts lines of one configured file into `LogEvent`s
+pub(crate) struct LogLineParser {
+    format: LineFormat,
+    timestamp_format: Option<String>,
+    /// Process name for lines without one
+    process: String,
+    /// Subsystem for lines without one
+    subsystem: String,
+    /// File name, used as the event category
+    category: String,
+}
+
+impl LogLineParser {
+    /// Build the parser for one `[[logging.files]]` entry
+    ///
+    /// # Errors
+    ///
+    /// Returns `CollectorError::ParseError` if the regex pattern is missing or invalid.
+    pub(crate) fn new(config: &LogFileConfig) -> Result<Self, CollectorError> {
+        let format = match config.format {
+            LogFileFormat::Regex => {
+                let pattern = config.pattern.as_deref().ok_or_else(|| {
+                    CollectorError::ParseError(format!(
+                        "no pattern configured for {}",
+                        config.path.display()
+                    ))
+                })?;
+                LineFormat::Regex(Regex::new(pattern).map_err(|e| {
+                    CollectorError::ParseError(format!(
+                        "invalid pattern for {}: {}",
+                        config.path.display(),
+                        e
+                    ))
+                })?)
+            }
+            LogFileFormat::Json => LineFormat::Json(config.fields.clone()),
+        };
+        let file_name = |path: &Path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        };
+
+        Ok(Self {
+            format,
+            timestamp_format: config.timestamp_format.clone(),
+            process: config.process.clone().unwrap_or_else(|| {
+                config
+                    .path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            }),
+            subsystem: config.subsystem.clone().unwrap_or_default(),
+            category: file_name(&config.path),
+        })
+    }
+
+    /// Parse one line; `None` if it does not match the configured format
+    pub(crate) fn parse_line(&self, line: &str) -> Option<LogEvent> {
+        let fields = match &self.format {
+            LineFormat::Regex(regex) => {
+                let captures = regex.captures(line)?;
+                let group = |name: &str| captures.name(name).map(|m| m.as_str().to_string());
+                LineFields {
+                    timestamp: group("timestamp").map(Value::String),
+                    level: group("level"),
+                    message: group("message")?,
+                    process: group("process"),
+                    pid: group("pid"),
+                    subsystem: group("subsystem"),
+                }
+            }
+            LineFormat::Json(names) => {
+                let value: Value = serde_json::from_str(line).ok()?;
+                let text = |name: &str| json_field(&value, name).and_then(json_text);
+                LineFields {
+                    timestamp: json_field(&value, &names.timestamp).cloned(),
+                    level: text(&names.level),
+                    message: text(&names.message)?,
+                    process: text(&names.process),
+                    pid: text(&names.pid),
+                    subsystem: text(&names.subsystem),
+                }
+            }
+        };
+
+        Some(LogEvent {
+            timestamp: fields
+                .timestamp
+                .as_ref()
+                .and_then(|value| parse_timestamp(value, self.timestamp_format.as_deref()))
+                .unwrap_or_else(Utc::now),
+            message_type: fields
+                .level
+                .as_deref()
+                .map(message_type_for_level)
+                .unwrap_or(MessageType::Info),
+            subsystem: fields.subsystem.unwrap_or_else(|| self.subsystem.clone()),
+            category: self.category.clone(),
+            process: fields.process.unwrap_or_else(|| self.process.clone()),
+            process_id: fields
+                .pid
+                .and_then(|pid| pid.trim().parse().ok())
+                .unwrap_or(0),
+            message: fields.message,
+        })
+    }
+}
+
+/// Raw values pulled out of one line before conversion
+struct LineFields {
+    timestamp: Option<Value>,
+    level: Option<String>,
+    message: String,
+    process: Option<String>,
+    pid: Option<String>,
+    subsystem: Option<String>,
+}
+
+/// Look up `name`, falling back to a dotted path into nested objects
+fn json_field<'a>(value: &'a Value, name: &str) -> Option<&'a Value> {
+    value
+        .get(name)
+        .or_else(|| name.split('.').try_fold(value, |value, key| value.get(key)))
+}
+
+fn json_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::Bool(flag) => Some(flag.to_string()),
+        _ => None,
+    }
+}
+
+/// Parse a timestamp string or epoch number
+///
+/// With a configured format, zone-less timestamps are local time. Without
+/// one, RFC 3339, `YYYY-MM-DD HH:MM:SS` (local) and epoch seconds or
+/// milliseconds are tried in turn.
+pub(crate) fn parse_timestamp(value: &Value, format: Option<&str>) -> Option<Timestamp> {
+    let text = match value {
+        Value::Number(number) => return epoch_timestamp(number.as_f64()?),
+        Value::String(text) => text.trim(),
+        _ => return None,
+    };
+    let local = |naive: NaiveDateTime| {
+        Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|timestamp| timestamp.with_timezone(&Utc))
+    };
+
+    if let Some(format) = format {
+        return DateTime::parse_from_str(text, format)
+            .map(|timestamp| timestamp.with_timezone(&Utc))
+            .ok()
+            .or_else(|| {
+                NaiveDateTime::parse_from_str(text, format)
+                    .ok()
+                    .and_then(local)
+            });
+    }
+
+    DateTime::parse_from_rfc3339(text)
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NAIVE_TIMESTAMP_FORMATS.iter().find_map(|format| {
+                NaiveDateTime::parse_from_str(text, format)
+                    .ok()
+                    .and_then(local)
+            })
+        })
+        .or_else(|| epoch_timestamp(text.parse().ok()?))
+}
+
+/// Epoch seconds, or milliseconds for values too large to be seconds
+fn epoch_timestamp(value: f64) -> Option<Timestamp> {
+    let millis = if value >= 1e11 { value } else { value * 1000.0 };
+    DateTime::from_timestamp_millis(millis as i64)
+}
+
+/// Map a level name or syslog priority onto `MessageType`
+pub(crate) fn message_type_for_level(level: &str) -> MessageType {
+    if let Ok(priority) = level.trim().parse::<u8>() {
+        return message_type_for_priority(priority.min(7));
+    }
+    match level.trim().to_lowercase().as_str() {
+        "fault" | "fatal" | "critical" | "crit" | "emerg" | "emergency" | "alert" | "panic" => {
+            MessageType::Fault
+        }
+        "error" | "err" | "severe" => MessageType::Error,
+        "debug" | "trace" | "verbose" => MessageType::Debug,
+        _ => MessageType::Info,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::mpsc;
+    use tempfile::TempDir;
+
+    fn regex_config(path: PathBuf) -> LogFileConfig {
+        toml::from_str(&format!(
+            r#"
+            path = {:?}
+            pattern = '^(?P<timestamp>\S+) (?P<level>\w+) (?P<process>\w+)\[(?P<pid>\d+)\]: (?P<message>.*)$'
+            subsystem = "com.example.api"
+            "#,
+            path
+        ))
+        .unwrap()
+    }
+
+    fn append(path: &Path, text: &str) {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn parses_regex_lines_into_log_events() {
+        let parser = LogLineParser::new(&regex_config("/var/log/api.log".into())).unwrap();
+
+        let event = parser
+            .parse_line("2024-05-01T10:15:30Z ERROR api[812]: upstream timed out")
+            .unwrap();
+        assert_eq!(event.timestamp.to_rfc3339(), "2024-05-01T10:15:30+00:00");
+        assert_eq!(event.message_type, MessageType::Error);
+        assert_eq!(event.process, "api");
+        assert_eq!(event.process_id, 812);
+        assert_eq!(event.subsystem, "com.example.api");
+        assert_eq!(event.category, "api.log");
+        assert_eq!(event.message, "upstream timed out");
+
+        assert!(parser.parse_line("continuation of a stack trace").is_none());
+    }
+
+    #[test]
+    fn parses_json_lines_with_field_mapping() {
+        let config: LogFileConfig = toml::from_str(
+            r#"
+            path = "/var/log/worker.jsonl"
+            format = "json"
+            [fields]
+            timestamp = "ts"
+            level = "log.level"
+            message = "msg"
+            "#,
+        )
+        .unwrap();
+        let parser = LogLineParser::new(&config).unwrap();
+
+        let event = parser
+            .parse_line(
+                r#"{"ts":1714558530123,"log":{"level":"fatal"},"msg":"queue corrupted","pid":77}"#,
+            )
+            .unwrap();
+        assert_eq!(event.timestamp.timestamp_millis(), 1714558530123);
+        assert_eq!(event.message_type, MessageType::Fault);
+        assert_eq!(event.process, "worker");
+        assert_eq!(event.process_id, 77);
+        assert_eq!(event.message, "queue corrupted");
+
+        assert!(parser.parse_line(r#"{"ts":1,"level":"info"}"#).is_none());
+        assert!(parser.parse_line("not json").is_none());
+    }
+
+    #[test]
+    fn maps_levels_and_timestamps() {
+        assert_eq!(message_type_for_level("WARN"), MessageType::Info);
+        assert_eq!(message_type_for_level("Err"), MessageType::Error);
+        assert_eq!(message_type_for_level("2"), MessageType::Fault);
+        assert_eq!(message_type_for_level("trace"), MessageType::Debug);
+
+        let custom = parse_timestamp(
+            &Value::String("01/May/2024:10:15:30 +0200".to_string()),
+            Some("%d/%b/%Y:%H:%M:%S %z"),
+        )
+        .unwrap();
+        assert_eq!(custom.to_rfc3339(), "2024-05-01T08:15:30+00:00");
+        let epoch = parse_timestamp(&Value::String("1714558530".to_string()), None).unwrap();
+        assert_eq!(epoch.timestamp(), 1714558530);
+        assert!(parse_timestamp(&Value::String("yesterday".to_string()), None).is_none());
+    }
+
+    #[test]
+    fn follows_appends_truncation_and_rename_rotation() {
+        let directory = TempDir::new().unwrap();
+        let path = directory.path().join("app.log");
+        append(&path, "old line\n");
+
+        let mut tail = TailedFile::open_at_end(path.clone());
+        assert!(tail.read_lines().unwrap().is_empty());
+
+        // Partial lines wait for their newline
+        append(&path, "first\nsec");
+        assert_eq!(tail.read_lines().unwrap(), ["first"]);
+        append(&path, "ond\n");
+        assert_eq!(tail.read_lines().unwrap(), ["second"]);
+
+        // copytruncate-style rotation
+        std::fs::write(&path, "after truncate\n").unwrap();
+        assert_eq!(tail.read_lines().unwrap(), ["after truncate"]);
+
+        // Rename rotation: the rest of the old file comes before the new one
+        append(&path, "last in old");
+        std::fs::rename(&path, directory.path().join("app.log.1")).unwrap();
+        assert!(tail.read_lines().unwrap().is_empty());
+        append(&path, "first in new\n");
+        assert_eq!(tail.read_lines().unwrap(), ["last in old", "first in new"]);
+    }
+
+    #[test]
+    fn waits_for_missing_files_and_reads_them_from_the_start() {
+        let directory = TempDir::new().unwrap();
+        let path = directory.path().join("late.log");
+
+        let mut tail = TailedFile::open_at_end(path.clone());
+        assert!(tail.read_lines().unwrap().is_empty());
+        append(&path, "hello\n");
+        assert_eq!(tail.read_lines().unwrap(), ["hello"]);
+    }
+
+    #[test]
+    fn collector_sends_parsed_lines() {
+        let directory = TempDir::new().unwrap();
+        let path = directory.path().join("api.log");
+        append(&path, "2024-05-01T10:00:00Z ERROR api[1]: before start\n");
+
+        let (sender, receiver) = mpsc::channel();
+        let mut collector = FileTailCollector::new(
+            vec![regex_config(path.clone())],
+            Duration::from_millis(20),
+            sender,
+        );
+        collector.start().unwrap();
+        append(
+            &path,
+            "2024-05-01T10:15:30Z ERROR api[812]: upstream timed out\nnoise\n",
+        );
+
+        let event = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(event.message, "upstream timed out");
+        assert!(receiver.recv_timeout(Duration::from_millis(100)).is_err());
+
+        collector.stop().unwrap();
+        assert!(!collector.is_running());
+    }
+}
@@ -0,0 +1,490 @@
+use super::source::{EventSource, SourceHealth};
+use crate::error::CollectorError;
+use crate::events::{LogEvent, MessageType};
+use chrono::DateTime;
+use log::{debug, error, info, warn};
+use serde_json::Value;
+use std::fs::File;
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// Where the journald collector reads entries from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JournalInput {
+    /// Follow the live journal with `journalctl -o json -f`, starting now
+    Follow {
+        /// Most verbose priority to include (`journalctl -p`)
+        priority: String,
+    },
+    /// Read a file written by `journalctl -o json` once
+    File(PathBuf),
+}
+
+/// Log collector for the systemd journal
+///
+/// Produces the same `LogEvent`s as `LogCollector` so the trigger rules work
+/// unchanged on Linux. Journal priorities map onto `MessageType`:
+/// emerg/alert/crit become `Fault`, err becomes `Error`, warning/notice/info
+/// become `Info` and debug becomes `Debug`.
+pub struct JournaldCollector {
+    /// Live journal or export file
+    input: JournalInput,
+    /// Channel to send parsed log events
+    output_channel: Sender<LogEvent>,
+    /// Handle to the background thread
+    thread_handle: Option<JoinHandle<()>>,
+    /// Shared state for controlling the collector
+    running: Arc<Mutex<bool>>,
+}
+
+impl JournaldCollector {
+    /// Create a new JournaldCollector
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::sync::mpsc;
+    /// use eyes::collectors::{JournalInput, JournaldCollector};
+    ///
+    /// let (tx, rx) = mpsc::channel();
+    /// let collector = JournaldCollector::new(
+    ///     JournalInput::Follow { priority: "err".to_string() },
+    ///     tx,
+    /// );
+    /// ```
+    pub fn new(input: JournalInput, channel: Sender<LogEvent>) -> Self {
+        Self {
+            input,
+            output_channel: channel,
+            thread_handle: None,
+            running: Arc::new(Mutex::new(false)),
+        }
+    }
+
+    /// Start the journald collector
+    ///
+    /// When following the journal, the subprocess is restarted with the same
+    /// backoff as `LogCollector`. An export file is read once, after which
+    /// the collector stops by itself.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CollectorError::SubprocessSpawn` if `journalctl` cannot be
+    /// started, or `CollectorError::IoError` if the export file cannot be opened.
+    pub fn start(&mut self) -> Result<(), CollectorError> {
+        info!("Starting JournaldCollector with input: {:?}", self.input);
+
+        {
+            let mut running = self.running.lock().unwrap();
+            if *running {
+                info!("JournaldCollector already running, skipping start");
+                return Ok(());
+            }
+            *running = true;
+        }
+
+        let channel = self.output_channel.clone();
+        let running = Arc::clone(&self.running);
+        let handle = match &self.input {
+            JournalInput::Follow { priority } => {
+                // Test that we can spawn the subprocess before starting the thread
+                match Self::spawn_journalctl(priority) {
+                    Ok(mut child) => {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                    }
+                    Err(e) => {
+                        error!("Failed to spawn journalctl during startup test: {}", e);
+                        *self.running.lock().unwrap() = false;
+                        return Err(e);
+                    }
+                }
+
+                let priority = priority.clone();
+                thread::spawn(move || {
+                    super::supervise_subprocess(
+                        "journalctl",
+                        &running,
+                        || Self::spawn_journalctl(&priority),
+                        |child| Self::process_journal_stream(child, &channel, &running),
+                    );
+                    info!("Journald collector thread finished");
+                })
+            }
+            JournalInput::File(path) => {
+                let file = match File::open(path) {
+                    Ok(file) => file,
+                    Err(e) => {
+                        *self.running.lock().unwrap() = false;
+                        return Err(e.into());
+                    }
+                };
+                let path = path.clone();
+                thread::spawn(move || {
+                    if let Err(e) = Self::read_journal_export(file, &channel, &running) {
+                        error!("Failed to read journal export {}: {}", path.display(), e);
+                    }
+                    *running.lock().unwrap() = false;
+                    info!("Finished reading journal export {}", path.display());
+                })
+            }
+        };
+
+        self.thread_handle = Some(handle);
+        info!("JournaldCollector started successfully");
+        Ok(())
+    }
+
+    /// Stop the journald collector and wait for its thread to finish
+    ///
+    /// # Errors
+    ///
+    /// Returns `CollectorError::SubprocessTerminated` if the thread panicked.
+    pub fn stop(&mut self) -> Result<(), CollectorError> {
+        info!("Stopping JournaldCollector");
+        *self.running.lock().unwrap() = false;
+
+        if let Some(handle) = self.thread_handle.take() {
+            handle.join().map_err(|_| {
+                error!("Failed to join JournaldCollector thread");
+                CollectorError::SubprocessTerminated("Failed to join collector thread".to_string())
+            })?;
+        }
+
+        info!("JournaldCollector stopped successfully");
+        Ok(())
+    }
+
+    /// Check if the collector is currently running
+    pub fn is_running(&self) -> bool {
+        *self.running.lock().unwrap()
+    }
+
+    /// Spawn `journalctl` following new entries at or above `priority`
+    fn spawn_journalctl(priority: &str) -> Result<Child, CollectorError> {
+        debug!("Spawning journalctl with priority: {}", priority);
+
+        let mut child = Command::new("journalctl")
+            .args(["--output", "json", "--follow", "--lines", "0"])
+            .args(["--priority", priority])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| CollectorError::SubprocessSpawn(format!("journalctl: {}", e)))?;
+
+        if let Some(stdout) = child.stdout.as_ref() {
+            if let Err(error) = super::set_nonblocking(stdout) {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(CollectorError::IoError(error));
+            }
+        }
+
+        Ok(child)
+    }
+
+    fn process_journal_stream(
+        child: &mut Child,
+        channel: &Sender<LogEvent>,
+        running: &Arc<Mutex<bool>>,
+    ) -> Result<(), CollectorError> {
+        let mut buffer = String::new();
+        let mut in_json_array = false;
+
+        super::read_subprocess_output(child, running, |chunk| {
+            Self::handle_journal_chunk(chunk, &mut buffer, &mut in_json_array, channel)
+        })
+    }
+
+    fn read_journal_export(
+        mut file: File,
+        channel: &Sender<LogEvent>,
+        running: &Arc<Mutex<bool>>,
+    ) -> Result<(), CollectorError> {
+        let mut buffer = String::new();
+        let mut in_json_array = false;
+        let mut chunk = [0u8; 4096];
+
+        while *running.lock().unwrap() {
+            let n = file.read(&mut chunk)?;
+            if n == 0 {
+                break;
+            }
+            Self::handle_journal_chunk(&chunk[..n], &mut buffer, &mut in_json_array, channel)?;
+        }
+
+        // The export may not end with a newline
+        buffer.push('\n');
+        Self::handle_journal_chunk(&[], &mut buffer, &mut in_json_array, channel)
+    }
+
+    /// Append a chunk of `journalctl -o json` output and emit complete entries
+    pub(crate) fn handle_journal_chunk(
+        chunk: &[u8],
+        buffer: &mut String,
+        in_json_array: &mut bool,
+        channel: &Sender<LogEvent>,
+    ) -> Result<(), CollectorError> {
+        buffer.push_str(&String::from_utf8_lossy(chunk));
+        super::drain_json_values(buffer, in_json_array, |value| {
+            match parse_journal_entry(&value) {
+                Ok(event) => {
+                    if let Err(e) = channel.send(event) {
+                        warn!("Failed to send journal event to channel: {}", e);
+                    }
+                }
+                Err(e) => debug!("Skipping journal entry: {}", e),
+            }
+            Ok(())
+        })
+    }
+}
+
+impl EventSource for JournaldCollector {
+    fn name(&self) -> &str {
+        "log"
+    }
+
+    fn start(&mut self) -> Result<(), CollectorError> {
+        JournaldCollector::start(self)
+    }
+
+    fn stop(&mut self) -> Result<(), CollectorError> {
+        JournaldCollector::stop(self)
+    }
+
+    fn health(&self) -> SourceHealth {
+        if self.is_running() {
+            SourceHealth::Running
+        } else {
+            SourceHealth::Stopped
+        }
+    }
+
+    fn required(&self) -> bool {
+        true
+    }
+}
+
+impl Drop for JournaldCollector {
+    fn drop(&mut self) {
+        if self.is_running() || self.thread_handle.is_some() {
+            let _ = self.stop();
+        }
+    }
+}
+
+/// Map a syslog priority (0-7) onto the Unified Log message types
+pub(crate) fn message_type_for_priority(priority: u8) -> MessageType {
+    match priority {
+        0..=2 => MessageType::Fault,
+        3 => MessageType::Error,
+        7 => MessageType::Debug,
+        _ => MessageType::Info,
+    }
+}
+
+/// Convert one `journalctl -o json` object into a `LogEvent`
+///
+/// Journal fields are strings, except `MESSAGE`, which is a byte array when
+/// it is not valid UTF-8.
+pub(crate) fn parse_journal_entry(value: &Value) -> Result<LogEvent, String> {
+    let field = |name: &str| value.get(name).and_then(Value::as_str);
+
+    let timestamp = field("__REALTIME_TIMESTAMP")
+        .or_else(|| field("_SOURCE_REALTIME_TIMESTAMP"))
+        .ok_or("missing __REALTIME_TIMESTAMP")?;
+    let timestamp = timestamp
+        .parse::<i64>()
+        .ok()
+        .and_then(DateTime::from_timestamp_micros)
+        .ok_or_else(|| format!("invalid journal timestamp '{}'", timestamp))?;
+
+    let message = match value.get("MESSAGE") {
+        Some(Value::String(message)) => message.clone(),
+        Some(Value::Array(bytes)) => String::from_utf8_lossy(
+            &bytes
+                .iter()
+                .filter_map(|byte| byte.as_u64().and_then(|byte| u8::try_from(byte).ok()))
+                .collect::<Vec<_>>(),
+        )
+        .into_owned(),
+        _ => return Err("missing MESSAGE".to_string()),
+    };
+
+    let message_type = match field("PRIORITY") {
+        Some(priority) => message_type_for_priority(
+            priority
+                .parse::<u8>()
+                .map_err(|_| format!("invalid PRIORITY '{}'", priority))?,
+        ),
+        // journald stores stdout lines without a priority at info
+        None => MessageType::Info,
+    };
+
+    Ok(LogEvent {
+        timestamp,
+        message_type,
+        subsystem: field("_SYSTEMD_UNIT")
+            .or_else(|| field("_SYSTEMD_USER_UNIT"))
+            .unwrap_or_default()
+            .to_string(),
+        category: field("_TRANSPORT").unwrap_or_default().to_string(),
+        process: field("SYSLOG_IDENTIFIER")
+            .or_else(|| field("_COMM"))
+            .unwrap_or_default()
+            .to_string(),
+        process_id: field("_PID")
+            .or_else(|| field("SYSLOG_PID"))
+            .and_then(|pid| pid.parse().ok())
+            .unwrap_or(0),
+        message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aggregator::EventSnapshot;
+    use crate::events::Severity;
+    use crate::triggers::{CrashDetectionRule, ErrorFrequencyRule, TriggerRule};
+    use std::sync::mpsc;
+    use tempfile::TempDir;
+
+    const COREDUMP: &str = r#"{"__REALTIME_TIMESTAMP":"1705343445123456","PRIORITY":"2","SYSLOG_IDENTIFIER":"systemd-coredump","_PID":"4242","_SYSTEMD_UNIT":"systemd-coredump@0-4241-0.service","_TRANSPORT":"journal","MESSAGE":"Process 4200 (worker) of user 1000 terminated abnormally with signal 11/SEGV, processing..."}"#;
+
+    fn error_entry(micros: i64, message: &str) -> String {
+        format!(
+            r#"{{"__REALTIME_TIMESTAMP":"{micros}","PRIORITY":"3","_COMM":"backupd","_PID":"77","_TRANSPORT":"syslog","MESSAGE":"{message}"}}"#
+        )
+    }
+
+    #[test]
+    fn maps_journal_fields_onto_log_event() {
+        let event = parse_journal_entry(&serde_json::from_str(COREDUMP).unwrap()).unwrap();
+
+        assert_eq!(event.timestamp.timestamp_micros(), 1705343445123456);
+        assert_eq!(event.message_type, MessageType::Fault);
+        assert_eq!(event.process, "systemd-coredump");
+        assert_eq!(event.process_id, 4242);
+        assert_eq!(event.subsystem, "systemd-coredump@0-4241-0.service");
+        assert_eq!(event.category, "journal");
+        assert!(event.message.contains("signal 11"));
+    }
+
+    #[test]
+    fn maps_priorities_to_message_types() {
+        let expected = [
+            MessageType::Fault,
+            MessageType::Fault,
+            MessageType::Fault,
+            MessageType::Error,
+            MessageType::Info,
+            MessageType::Info,
+            MessageType::Info,
+            MessageType::Debug,
+        ];
+        for (priority, message_type) in expected.into_iter().enumerate() {
+            assert_eq!(message_type_for_priority(priority as u8), message_type);
+        }
+    }
+
+    #[test]
+    fn handles_binary_messages_and_missing_fields() {
+        let binary = serde_json::json!({
+            "__REALTIME_TIMESTAMP": "1705343445000000",
+            "MESSAGE": [104, 105, 255],
+        });
+        let event = parse_journal_entry(&binary).unwrap();
+        assert_eq!(event.message, "hi\u{fffd}");
+        assert_eq!(event.message_type, MessageType::Info);
+        assert_eq!(event.process_id, 0);
+
+        assert!(parse_journal_entry(&serde_json::json!({"MESSAGE": "x"})).is_err());
+        assert!(parse_journal_entry(&serde_json::json!({
+            "__REALTIME_TIMESTAMP": "1705343445000000",
+            "PRIORITY": "high",
+            "MESSAGE": "x",
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn buffers_entries_split_across_chunks() {
+        let (tx, rx) = mpsc::channel();
+        let mut buffer = String::new();
+        let mut in_json_array = false;
+        let output = format!("{}\n{}\n", COREDUMP, error_entry(1705343446000000, "disk"));
+        let (first, second) = output.split_at(57);
+
+        JournaldCollector::handle_journal_chunk(
+            first.as_bytes(),
+            &mut buffer,
+            &mut in_json_array,
+            &tx,
+        )
+        .unwrap();
+        assert!(rx.try_recv().is_err());
+        JournaldCollector::handle_journal_chunk(
+            second.as_bytes(),
+            &mut buffer,
+            &mut in_json_array,
+            &tx,
+        )
+        .unwrap();
+
+        let events = rx.try_iter().collect::<Vec<_>>();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].message, "disk");
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn export_file_feeds_existing_trigger_rules() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("journal.json");
+        let now = chrono::Utc::now().timestamp_micros();
+        let export = [
+            COREDUMP.replace("1705343445123456", &now.to_string()),
+            error_entry(now, "I/O error on sda"),
+            error_entry(now, "I/O error on sdb"),
+            "not json".to_string(),
+        ]
+        .join("\n");
+        std::fs::write(&path, export).unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let mut collector = JournaldCollector::new(JournalInput::File(path), tx);
+        collector.start().unwrap();
+        let events = (0..3)
+            .map(|_| rx.recv_timeout(std::time::Duration::from_secs(5)).unwrap())
+            .collect::<Vec<_>>();
+        collector.stop().unwrap();
+
+        assert_eq!(events.len(), 3);
+        assert!(
+            CrashDetectionRule::with_defaults().evaluate(&EventSnapshot::from_slices(
+                &events,
+                &[],
+                &[]
+            ))
+        );
+        assert!(ErrorFrequencyRule::new(1, 60, Severity::Warning)
+            .evaluate(&EventSnapshot::from_slices(&events, &[], &[])));
+    }
+
+    #[test]
+    fn missing_export_file_fails_to_start() {
+        let temp_dir = TempDir::new().unwrap();
+        let (tx, _rx) = mpsc::channel();
+        let mut collector =
+            JournaldCollector::new(JournalInput::File(temp_dir.path().join("missing")), tx);
+
+        assert!(matches!(collector.start(), Err(CollectorError::IoError(_))));
+        assert!(!collector.is_running());
+    }
+}
@@ -0,0 +1,160 @@
+use crate::error::CollectorError;
+use crate::events::LogEvent;
+use log::debug;
+use serde_json::Value;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// Log entries read from a `log show --style json` export
+#[derive(Debug, Default)]
+pub struct LogArchive {
+    /// Parsed entries sorted by timestamp
+    pub events: Vec<LogEvent>,
+    /// Entries that could not be converted to `LogEvent` (activity and
+    /// signpost entries, `Default`-level messages, malformed objects)
+    pub skipped: usize,
+}
+
+impl LogArchive {
+    /// Keep only events inside the inclusive `[since, until]` range
+    pub fn retain_window(
+        &mut self,
+        since: Option<crate::events::Timestamp>,
+        until: Option<crate::events::Timestamp>,
+    ) {
+        self.events.retain(|event| {
+            since.is_none_or(|since| event.timestamp >= since)
+                && until.is_none_or(|until| event.timestamp <= until)
+        });
+    }
+}
+
+/// Read an exported `log show --style json` archive
+///
+/// Unlike `log stream`, `log show` writes the whole result as a single JSON
+/// array, so the file is parsed in one pass and each entry is converted with
+/// `LogEvent::from_json`. Entries that do not describe a log message are
+/// counted in `LogArchive::skipped`.
+///
+/// # Errors
+///
+/// Returns `CollectorError::IoError` if the file cannot be read, or
+/// `CollectorError::ParseError` if it is not a JSON array.
+pub fn read_log_archive(path: &Path) -> Result<LogArchive, CollectorError> {
+    let reader = BufReader::new(File::open(path)?);
+    let entries = serde_json::from_reader::<_, Vec<Value>>(reader).map_err(|error| {
+        CollectorError::ParseError(format!(
+            "{}: expected a `log show --style json` array: {}",
+            path.display(),
+            error
+        ))
+    })?;
+
+    let mut archive = LogArchive::default();
+    for entry in entries {
+        match LogEvent::from_json(&entry.to_string()) {
+            Ok(event) => archive.events.push(event),
+            Err(error) => {
+                debug!("Skipping archived log entry: {}", error);
+                archive.skipped += 1;
+            }
+        }
+    }
+    archive.events.sort_by_key(|event| event.timestamp);
+
+    debug!(
+        "Read {} log events from {} ({} skipped)",
+        archive.events.len(),
+        path.display(),
+        archive.skipped
+    );
+    Ok(archive)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::MessageType;
+    use chrono::{DateTime, Utc};
+    use tempfile::TempDir;
+
+    const ARCHIVE: &str = r#"[{
+  "timestamp" : "2024-01-15 10:30:47.000000-0800",
+  "messageType" : "Fault",
+  "eventType" : "logEvent",
+  "subsystem" : "com.apple.backupd",
+  "category" : "general",
+  "processImagePath" : "\/System\/Library\/CoreServices\/backupd",
+  "processID" : 77,
+  "eventMessage" : "Backup failed"
+},{
+  "timestamp" : "2024-01-15 10:30:45.000000-0800",
+  "messageType" : "Error",
+  "eventType" : "logEvent",
+  "subsystem" : "com.apple.test",
+  "category" : "general",
+  "process" : "testd",
+  "processID" : 42,
+  "eventMessage" : "Disk I/O error"
+},{
+  "timestamp" : "2024-01-15 10:30:46.000000-0800",
+  "eventType" : "activityCreateEvent",
+  "processID" : 42,
+  "eventMessage" : "activity"
+},{
+  "timestamp" : "2024-01-15 10:30:46.500000-0800",
+  "messageType" : "Default",
+  "eventType" : "logEvent",
+  "subsystem" : "",
+  "category" : "",
+  "process" : "testd",
+  "processID" : 42,
+  "eventMessage" : "routine message"
+}]"#;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn reads_sorted_events_and_counts_skipped_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("archive.json");
+        std::fs::write(&path, ARCHIVE).unwrap();
+
+        let archive = read_log_archive(&path).unwrap();
+
+        assert_eq!(archive.events.len(), 2);
+        assert_eq!(archive.skipped, 2);
+        assert_eq!(archive.events[0].message, "Disk I/O error");
+        assert_eq!(archive.events[1].message_type, MessageType::Fault);
+        assert_eq!(archive.events[1].process, "backupd");
+        assert!(archive.events[0].timestamp < archive.events[1].timestamp);
+    }
+
+    #[test]
+    fn rejects_streaming_output() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("stream.json");
+        std::fs::write(&path, "Filtering the log data using \"x\"\n[{").unwrap();
+
+        let error = read_log_archive(&path).unwrap_err();
+        assert!(matches!(error, CollectorError::ParseError(_)));
+    }
+
+    #[test]
+    fn retain_window_is_inclusive() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("archive.json");
+        std::fs::write(&path, ARCHIVE).unwrap();
+        let mut archive = read_log_archive(&path).unwrap();
+
+        archive.retain_window(Some(at("2024-01-15T18:30:46Z")), None);
+        assert_eq!(archive.events.len(), 1);
+        archive.retain_window(None, Some(at("2024-01-15T18:30:46Z")));
+        assert!(archive.events.is_empty());
+    }
+}
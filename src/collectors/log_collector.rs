@@ -1,3 +1,5 @@
+use super::capture::{CaptureStream, CaptureWriter};
+use super::source::{EventSource, SourceHealth};
 use crate::error::CollectorError;
 use crate::events::LogEvent;
 use log::{debug, error, info, warn};
@@ -6,7 +8,6 @@ use std::process::{Child, Command, Stdio};
 use std::sync::mpsc::Sender;
 use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
-use std::time::Duration;
 
 /// Log stream collector for macOS Unified Log System
 ///
@@ -22,6 +23,8 @@ pub struct LogCollector {
     thread_handle: Option<JoinHandle<()>>,
     /// Shared state for controlling the collector
     running: Arc<Mutex<bool>>,
+    /// Optional capture of the raw `log stream` output for replay
+    capture: Option<CaptureWriter>,
 }
 
 impl LogCollector {
@@ -50,9 +53,15 @@ impl LogCollector {
             output_channel: channel,
             thread_handle: None,
             running: Arc::new(Mutex::new(false)),
+            capture: None,
         }
     }
 
+    /// Record every chunk read from `log stream` to a capture file
+    pub fn set_capture(&mut self, capture: CaptureWriter) {
+        self.capture = Some(capture);
+    }
+
     /// Start the log collector
     ///
     /// Spawns a background thread that manages the `log stream` subprocess.
@@ -101,11 +110,12 @@ impl LogCollector {
         let predicate = self.predicate.clone();
         let channel = self.output_channel.clone();
         let running = Arc::clone(&self.running);
+        let capture = self.capture.clone();
 
         // Spawn background thread
         debug!("Spawning LogCollector background thread");
         let handle = thread::spawn(move || {
-            Self::collector_thread(predicate, channel, running);
+            Self::collector_thread(predicate, channel, running, capture);
         });
 
         self.thread_handle = Some(handle);
@@ -157,134 +167,23 @@ impl LogCollector {
     ///
     /// Runs in a loop, spawning and monitoring the `log stream` subprocess.
     /// Automatically restarts the subprocess with exponential backoff on failure.
-    fn collector_thread(predicate: String, channel: Sender<LogEvent>, running: Arc<Mutex<bool>>) {
+    fn collector_thread(
+        predicate: String,
+        channel: Sender<LogEvent>,
+        running: Arc<Mutex<bool>>,
+        capture: Option<CaptureWriter>,
+    ) {
         info!(
             "LogCollector thread started with predicate: '{}'",
             predicate
         );
 
-        let mut restart_delay = Duration::from_secs(1);
-        let max_delay = Duration::from_secs(60);
-        let mut consecutive_failures = 0;
-        const MAX_CONSECUTIVE_FAILURES: u32 = 5;
-
-        debug!("LogCollector thread configuration: max_failures={}, initial_delay={:?}, max_delay={:?}", 
-               MAX_CONSECUTIVE_FAILURES, restart_delay, max_delay);
-
-        while *running.lock().unwrap() {
-            match Self::spawn_log_stream(&predicate) {
-                Ok(mut child) => {
-                    info!(
-                        "Log stream subprocess started successfully (attempt after {} failures)",
-                        consecutive_failures
-                    );
-
-                    // Process output from the subprocess
-                    let mut had_healthy_run = false;
-                    debug!("Starting log stream processing");
-                    match Self::process_log_stream(&mut child, &channel, &running) {
-                        Ok(_) => {
-                            debug!("Log stream processing completed without errors");
-                            // Check if the subprocess is still running
-                            match child.try_wait() {
-                                Ok(Some(exit_status)) => {
-                                    // Process exited - this could be due to invalid predicate or other issues
-                                    warn!(
-                                        "Log stream subprocess exited with status: {:?} (failure #{}/{})",
-                                        exit_status, consecutive_failures + 1, MAX_CONSECUTIVE_FAILURES
-                                    );
-                                    consecutive_failures += 1;
-                                }
-                                Ok(None) => {
-                                    // Process is still running, this was a normal shutdown
-                                    debug!("Log stream subprocess finished normally (graceful shutdown)");
-                                    had_healthy_run = true;
-                                }
-                                Err(e) => {
-                                    error!(
-                                        "Failed to check subprocess status: {} (failure #{}/{})",
-                                        e,
-                                        consecutive_failures + 1,
-                                        MAX_CONSECUTIVE_FAILURES
-                                    );
-                                    consecutive_failures += 1;
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            error!(
-                                "Error processing log stream: {} (failure #{}/{})",
-                                e,
-                                consecutive_failures + 1,
-                                MAX_CONSECUTIVE_FAILURES
-                            );
-                            consecutive_failures += 1;
-                        }
-                    }
-
-                    // Only reset failure count and delay after a healthy run
-                    if had_healthy_run {
-                        consecutive_failures = 0;
-                        restart_delay = Duration::from_secs(1);
-                    }
-
-                    // Clean up subprocess
-                    if let Err(e) = child.kill() {
-                        warn!("Failed to kill log stream subprocess: {}", e);
-                    }
-                    let _ = child.wait();
-                }
-                Err(e) => {
-                    error!("Failed to spawn log stream subprocess: {}", e);
-                    consecutive_failures += 1;
-                }
-            }
-
-            // Check if we should continue running
-            if !*running.lock().unwrap() {
-                break;
-            }
-
-            // Check for too many consecutive failures - enter degraded mode
-            if consecutive_failures >= MAX_CONSECUTIVE_FAILURES {
-                warn!(
-                    "Too many consecutive failures ({}), entering degraded mode",
-                    consecutive_failures
-                );
-
-                // In degraded mode, wait longer and try less frequently
-                let degraded_delay = Duration::from_secs(60); // Wait 1 minute in degraded mode
-                warn!(
-                    "Entering degraded mode - will retry every {:?}",
-                    degraded_delay
-                );
-
-                super::wait_for_retry(degraded_delay, &running);
-
-                // Reset failure count to give it another chance
-                consecutive_failures = 0;
-                restart_delay = Duration::from_secs(1);
-                continue;
-            }
-
-            // Wait before restarting with exponential backoff
-            if consecutive_failures > 0 {
-                warn!(
-                    "Restarting log stream in {:?} (failure #{}/{})",
-                    restart_delay, consecutive_failures, MAX_CONSECUTIVE_FAILURES
-                );
-                super::wait_for_retry(restart_delay, &running);
-
-                // Exponential backoff
-                restart_delay = std::cmp::min(restart_delay * 2, max_delay);
-            }
-        }
-
-        // Reset running flag when thread exits (due to failures or shutdown)
-        {
-            let mut running_flag = running.lock().unwrap();
-            *running_flag = false;
-        }
+        super::supervise_subprocess(
+            "log stream",
+            &running,
+            || Self::spawn_log_stream(&predicate),
+            |child| Self::process_log_stream(child, &channel, &running, capture.as_ref()),
+        );
 
         info!("Log collector thread finished");
     }
@@ -316,55 +215,38 @@ impl LogCollector {
         child: &mut Child,
         channel: &Sender<LogEvent>,
         running: &Arc<Mutex<bool>>,
+        capture: Option<&CaptureWriter>,
     ) -> Result<(), CollectorError> {
-        use std::io::Read;
-        use std::time::Duration;
-
-        let mut stdout = child
-            .stdout
-            .take()
-            .ok_or_else(|| CollectorError::ParseError("No stdout available".to_string()))?;
-
         let mut buffer = String::new();
         let mut in_json_array = false;
-        let mut temp_buf = [0u8; 4096];
 
-        loop {
-            if !*running.lock().unwrap() {
-                debug!("Stopping log processing due to shutdown signal");
-                break;
+        super::read_subprocess_output(child, running, |chunk| {
+            if let Some(capture) = capture {
+                capture.record(CaptureStream::LogStream, chunk);
             }
+            Self::handle_log_chunk(chunk, &mut buffer, &mut in_json_array, channel)
+        })
+    }
 
-            match stdout.read(&mut temp_buf) {
-                Ok(0) => {
-                    debug!("Log stream subprocess closed stdout");
-                    break;
-                }
-                Ok(n) => {
-                    buffer.push_str(&String::from_utf8_lossy(&temp_buf[..n]));
-
-                    // Drop the leading non-JSON banner if present
-                    if buffer.starts_with("Filtering the log data") {
-                        if let Some(pos) = buffer.find('\n') {
-                            buffer.drain(..=pos);
-                        } else {
-                            continue;
-                        }
-                    }
+    /// Append a chunk of `log stream` output to the buffer and emit complete entries
+    pub(crate) fn handle_log_chunk(
+        chunk: &[u8],
+        buffer: &mut String,
+        in_json_array: &mut bool,
+        channel: &Sender<LogEvent>,
+    ) -> Result<(), CollectorError> {
+        buffer.push_str(&String::from_utf8_lossy(chunk));
 
-                    Self::process_json_buffer(&mut buffer, &mut in_json_array, channel)?;
-                }
-                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
-                    std::thread::sleep(Duration::from_millis(10));
-                    continue;
-                }
-                Err(e) => {
-                    return Err(CollectorError::IoError(e));
-                }
+        // Drop the leading non-JSON banner if present
+        if buffer.starts_with("Filtering the log data") {
+            if let Some(pos) = buffer.find('\n') {
+                buffer.drain(..=pos);
+            } else {
+                return Ok(());
             }
         }
 
-        Ok(())
+        Self::process_json_buffer(buffer, in_json_array, channel)
     }
 
     fn process_json_buffer(
@@ -372,65 +254,9 @@ impl LogCollector {
         in_json_array: &mut bool,
         channel: &Sender<LogEvent>,
     ) -> Result<(), CollectorError> {
-        loop {
-            let whitespace = buffer.len() - buffer.trim_start().len();
-            if whitespace > 0 {
-                buffer.drain(..whitespace);
-            }
-
-            let starts_json_array = buffer.strip_prefix('[').is_some_and(|remainder| {
-                let next = remainder.trim_start();
-                next.is_empty() || next.starts_with('{') || next.starts_with(']')
-            });
-            if starts_json_array {
-                *in_json_array = true;
-                buffer.drain(..1);
-                continue;
-            }
-
-            if buffer.starts_with(']') {
-                *in_json_array = false;
-                buffer.drain(..1);
-                continue;
-            }
-
-            if buffer.starts_with(',') {
-                buffer.drain(..1);
-                continue;
-            }
-
-            if buffer.is_empty() {
-                return Ok(());
-            }
-
-            let mut values = serde_json::Deserializer::from_str(buffer).into_iter::<Value>();
-            match values.next() {
-                Some(Ok(value)) => {
-                    let consumed = values.byte_offset();
-                    buffer.drain(..consumed);
-                    Self::handle_log_value(value, channel)?;
-                }
-                Some(Err(error)) if error.is_eof() => {
-                    if !*in_json_array {
-                        if let Some(newline) = buffer.find('\n') {
-                            debug!("Discarding incomplete log entry: {}", error);
-                            buffer.drain(..=newline);
-                            continue;
-                        }
-                    }
-                    return Ok(());
-                }
-                Some(Err(error)) => {
-                    debug!("Failed to parse buffered log entry: {}", error);
-                    if let Some(newline) = buffer.find('\n') {
-                        buffer.drain(..=newline);
-                    } else {
-                        buffer.clear();
-                    }
-                }
-                None => return Ok(()),
-            }
-        }
+        super::drain_json_values(buffer, in_json_array, |value| {
+            Self::handle_log_value(value, channel)
+        })
     }
 
     /// Handle a parsed JSON value from log stream output.
@@ -476,6 +302,36 @@ impl LogCollector {
     }
 }
 
+impl EventSource for LogCollector {
+    fn name(&self) -> &str {
+        "log"
+    }
+
+    fn start(&mut self) -> Result<(), CollectorError> {
+        LogCollector::start(self)
+    }
+
+    fn stop(&mut self) -> Result<(), CollectorError> {
+        LogCollector::stop(self)
+    }
+
+    fn health(&self) -> SourceHealth {
+        if self.is_running() {
+            SourceHealth::Running
+        } else {
+            SourceHealth::Stopped
+        }
+    }
+
+    fn required(&self) -> bool {
+        true
+    }
+
+    fn set_capture(&mut self, capture: CaptureWriter) {
+        LogCollector::set_capture(self, capture)
+    }
+}
+
 impl Drop for LogCollector {
     fn drop(&mut self) {
         if self.is_running() {
@@ -564,7 +420,7 @@ mod tests {
             .expect("Failed to spawn echo command");
 
         // Process the output
-        let result = LogCollector::process_log_stream(&mut child, &tx, &running);
+        let result = LogCollector::process_log_stream(&mut child, &tx, &running, None);
         assert!(result.is_ok());
 
         // Check that we received the event
@@ -624,7 +480,7 @@ mod tests {
             .expect("Failed to spawn echo command");
 
         // Process the output - should not fail even with malformed JSON
-        let result = LogCollector::process_log_stream(&mut child, &tx, &running);
+        let result = LogCollector::process_log_stream(&mut child, &tx, &running, None);
         assert!(result.is_ok());
 
         // Should not receive any events due to malformed JSON
@@ -649,7 +505,7 @@ mod tests {
             .expect("Failed to spawn echo command");
 
         // Process the output - should handle empty lines gracefully
-        let result = LogCollector::process_log_stream(&mut child, &tx, &running);
+        let result = LogCollector::process_log_stream(&mut child, &tx, &running, None);
         assert!(result.is_ok());
 
         // Should not receive any events due to empty lines
@@ -810,7 +666,7 @@ mod property_tests {
         };
 
         // Process the output - this should not panic or fail
-        let result = LogCollector::process_log_stream(&mut child, &tx, &running);
+        let result = LogCollector::process_log_stream(&mut child, &tx, &running, None);
 
         // The processing should succeed even with malformed input
         if result.is_err() {
@@ -955,7 +811,7 @@ mod property_tests {
         };
 
         // Process the output
-        let result = LogCollector::process_log_stream(&mut child, &tx, &running);
+        let result = LogCollector::process_log_stream(&mut child, &tx, &running, None);
 
         // The processing should succeed
         if result.is_err() {
@@ -984,6 +840,7 @@ mod property_tests {
     }
 
     /// Generate failure scenarios for testing subprocess restart behavior
+    #[cfg(target_os = "macos")]
     #[derive(Debug, Clone)]
     struct SubprocessFailureScenario {
         /// Number of times the subprocess should fail before succeeding
@@ -992,6 +849,7 @@ mod property_tests {
         eventually_succeed: bool,
     }
 
+    #[cfg(target_os = "macos")]
     impl Arbitrary for SubprocessFailureScenario {
         fn arbitrary(g: &mut Gen) -> Self {
             SubprocessFailureScenario {
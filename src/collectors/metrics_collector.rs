@@ -1,3 +1,5 @@
+use super::capture::{CaptureStream, CaptureWriter};
+use super::source::{EventSource, SourceHealth};
 use crate::error::CollectorError;
 use crate::events::{
     MeasurementKind, MemoryPressure, MetricsEvent, MetricsProvenance, MetricsSource, ProcessMetric,
@@ -28,6 +30,8 @@ pub struct MetricsCollector {
     running: Arc<Mutex<bool>>,
     /// Self-monitoring collector for resource pressure detection
     monitoring: Option<Arc<SelfMonitoringCollector>>,
+    /// Optional capture of raw metrics subprocess output for replay
+    capture: Option<CaptureWriter>,
 }
 
 impl MetricsCollector {
@@ -56,6 +60,7 @@ impl MetricsCollector {
             thread_handle: None,
             running: Arc::new(Mutex::new(false)),
             monitoring: None,
+            capture: None,
         }
     }
 
@@ -64,6 +69,11 @@ impl MetricsCollector {
         self.monitoring = Some(monitoring);
     }
 
+    /// Record raw metrics subprocess output and enrichment snapshots to a capture file
+    pub fn set_capture(&mut self, capture: CaptureWriter) {
+        self.capture = Some(capture);
+    }
+
     /// Adapt sampling frequency based on resource pressure
     /// Requirement 7.4: reduce sampling frequency when constrained
     pub fn adapt_sampling_frequency(&self) {
@@ -134,6 +144,7 @@ impl MetricsCollector {
         let channel = self.output_channel.clone();
         let running = Arc::clone(&self.running);
         let monitoring = self.monitoring.clone();
+        let capture = self.capture.clone();
 
         // Spawn background thread
         debug!("Spawning MetricsCollector background thread");
@@ -143,6 +154,7 @@ impl MetricsCollector {
                 channel,
                 running,
                 monitoring,
+                capture,
                 powermetrics_available,
             );
         });
@@ -216,6 +228,7 @@ impl MetricsCollector {
         channel: Sender<MetricsEvent>,
         running: Arc<Mutex<bool>>,
         monitoring: Option<Arc<SelfMonitoringCollector>>,
+        capture: Option<CaptureWriter>,
         mut powermetrics_available: bool,
     ) {
         let initial_interval = *current_interval.lock().unwrap();
@@ -300,6 +313,7 @@ impl MetricsCollector {
                         &channel,
                         &running,
                         is_fallback_mode,
+                        capture.as_ref(),
                     ) {
                         Ok(_) => {
                             // Check if the subprocess is still running
@@ -502,7 +516,9 @@ impl MetricsCollector {
     }
 
     /// Get current memory pressure and used memory by calling vm_stat once
-    fn get_memory_snapshot_from_vm_stat() -> Result<(MemoryPressure, f64), CollectorError> {
+    fn get_memory_snapshot_from_vm_stat(
+        capture: Option<&CaptureWriter>,
+    ) -> Result<(MemoryPressure, f64), CollectorError> {
         use std::process::Command;
 
         let output = Command::new("vm_stat")
@@ -516,7 +532,16 @@ impl MetricsCollector {
             )));
         }
 
-        let output_str = String::from_utf8_lossy(&output.stdout);
+        if let Some(capture) = capture {
+            capture.record(CaptureStream::VmStat, &output.stdout);
+        }
+
+        Ok(Self::parse_vm_stat_output(&output.stdout))
+    }
+
+    /// Derive used memory from `vm_stat` output
+    pub(crate) fn parse_vm_stat_output(output: &[u8]) -> (MemoryPressure, f64) {
+        let output_str = String::from_utf8_lossy(output);
         let lines: Vec<&str> = output_str.lines().collect();
 
         // vm_stat reports page counts, but it does not expose macOS's pressure state.
@@ -576,7 +601,24 @@ impl MetricsCollector {
         let used_mb =
             ((active_pages + inactive_pages + wired_pages) * page_size) as f64 / (1024.0 * 1024.0);
 
-        Ok((MemoryPressure::Unknown, used_mb))
+        (MemoryPressure::Unknown, used_mb)
+    }
+
+    /// Update the most recent fallback event with a `vm_stat` memory snapshot
+    pub(crate) fn apply_memory_snapshot(
+        events: &mut [MetricsEvent],
+        (memory_pressure, memory_used_mb): (MemoryPressure, f64),
+    ) {
+        if let Some(event) = events.last_mut() {
+            event.memory_pressure = memory_pressure;
+            event.memory_used_mb = memory_used_mb;
+            event.provenance.memory_pressure = MeasurementKind::Unavailable;
+            event.provenance.memory_used = MeasurementKind::Derived;
+            debug!(
+                "Enhanced fallback event with derived memory usage: {:.1}MB; pressure unavailable",
+                memory_used_mb
+            );
+        }
     }
 
     /// Process output from the metrics collection subprocess
@@ -586,7 +628,7 @@ impl MetricsCollector {
         channel: &Sender<MetricsEvent>,
         running: &Arc<Mutex<bool>>,
     ) -> Result<(), CollectorError> {
-        Self::process_metrics_output_with_mode(child, channel, running, false)
+        Self::process_metrics_output_with_mode(child, channel, running, false, None)
     }
 
     /// Process output from the metrics collection subprocess with fallback mode support
@@ -595,6 +637,7 @@ impl MetricsCollector {
         channel: &Sender<MetricsEvent>,
         running: &Arc<Mutex<bool>>,
         is_fallback_mode: bool,
+        capture: Option<&CaptureWriter>,
     ) -> Result<(), CollectorError> {
         use std::io::Read;
 
@@ -625,6 +668,14 @@ impl MetricsCollector {
                 Ok(n) => {
                     // Got some data, add it to buffer
                     buffer.extend_from_slice(&temp_buf[..n]);
+                    if let Some(capture) = capture {
+                        let stream = if is_fallback_mode {
+                            CaptureStream::TopFallback
+                        } else {
+                            CaptureStream::Powermetrics
+                        };
+                        capture.record(stream, &temp_buf[..n]);
+                    }
 
                     // Try to parse complete plist documents, JSON lines, or fallback format
                     let mut parsed_events = if is_fallback_mode {
@@ -637,20 +688,8 @@ impl MetricsCollector {
                     if is_fallback_mode && last_vm_stat_time.elapsed() >= vm_stat_interval {
                         if let Some(ref mut events) = parsed_events {
                             // Get current memory pressure from vm_stat
-                            if let Ok((memory_pressure, memory_used_mb)) =
-                                Self::get_memory_snapshot_from_vm_stat()
-                            {
-                                // Update the most recent event with memory pressure info
-                                if let Some(event) = events.last_mut() {
-                                    event.memory_pressure = memory_pressure;
-                                    event.memory_used_mb = memory_used_mb;
-                                    event.provenance.memory_pressure = MeasurementKind::Unavailable;
-                                    event.provenance.memory_used = MeasurementKind::Derived;
-                                    debug!(
-                                        "Enhanced fallback event with derived memory usage: {:.1}MB; pressure unavailable",
-                                        memory_used_mb
-                                    );
-                                }
+                            if let Ok(snapshot) = Self::get_memory_snapshot_from_vm_stat(capture) {
+                                Self::apply_memory_snapshot(events, snapshot);
                             }
                         }
                         last_vm_stat_time = std::time::Instant::now();
@@ -659,6 +698,16 @@ impl MetricsCollector {
                     if let Some(ref mut events) = parsed_events {
                         match Self::get_process_snapshot() {
                             Ok(process_metrics) => {
+                                if let Some(capture) = capture {
+                                    match serde_json::to_vec(&process_metrics) {
+                                        Ok(snapshot) => capture
+                                            .record(CaptureStream::ProcessSnapshot, &snapshot),
+                                        Err(error) => debug!(
+                                            "Failed to serialize process snapshot for capture: {}",
+                                            error
+                                        ),
+                                    }
+                                }
                                 for event in events {
                                     event.process_metrics = process_metrics.clone();
                                 }
@@ -700,7 +749,7 @@ impl MetricsCollector {
     }
 
     /// Try to parse fallback metrics from top/vm_stat output
-    fn try_parse_fallback_buffer(buffer: &mut Vec<u8>) -> Option<Vec<MetricsEvent>> {
+    pub(crate) fn try_parse_fallback_buffer(buffer: &mut Vec<u8>) -> Option<Vec<MetricsEvent>> {
         let buffer_str = String::from_utf8_lossy(buffer);
         let lines: Vec<&str> = buffer_str.lines().collect();
         let mut events = Vec::new();
@@ -833,12 +882,12 @@ impl MetricsCollector {
             )));
         }
 
-        let mut processes = String::from_utf8_lossy(&output.stdout)
+        let processes = String::from_utf8_lossy(&output.stdout)
             .lines()
             .filter_map(Self::parse_process_snapshot_line)
             .collect::<Vec<_>>();
-        processes.sort_by(|left, right| right.2.total_cmp(&left.2));
-        processes.truncate(5);
+        let processes =
+            super::select_process_snapshot(processes, |process| process.1, |process| process.2);
         Ok(processes
             .into_iter()
             .map(
@@ -864,6 +913,7 @@ impl MetricsCollector {
         Some((process_id, cpu_usage_percent, resident_memory_kb / 1024.0))
     }
 
+    #[cfg(target_os = "macos")]
     fn process_name(process_id: u32) -> String {
         let mut buffer = vec![0_u8; libc::PROC_PIDPATHINFO_MAXSIZE as usize];
         // SAFETY: proc_pidpath receives a valid writable buffer and its exact capacity.
@@ -887,6 +937,15 @@ impl MetricsCollector {
             .unwrap_or_else(|| format!("PID {}", process_id))
     }
 
+    #[cfg(not(target_os = "macos"))]
+    fn process_name(process_id: u32) -> String {
+        std::fs::read_to_string(format!("/proc/{}/comm", process_id))
+            .ok()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| format!("PID {}", process_id))
+    }
+
     /// Parse memory information from vm_stat output lines
     fn parse_vm_stat_lines(lines: &[&str]) -> Result<MetricsEvent, String> {
         let mut active_pages = 0u64;
@@ -951,7 +1010,7 @@ impl MetricsCollector {
     }
 
     /// Try to parse complete documents from the buffer
-    fn try_parse_buffer(buffer: &mut Vec<u8>) -> Option<Vec<MetricsEvent>> {
+    pub(crate) fn try_parse_buffer(buffer: &mut Vec<u8>) -> Option<Vec<MetricsEvent>> {
         let mut events = Vec::new();
 
         // Try to parse plist documents (powermetrics XML). We look for complete
@@ -1054,6 +1113,32 @@ impl MetricsCollector {
     }
 }
 
+impl EventSource for MetricsCollector {
+    fn name(&self) -> &str {
+        "metrics"
+    }
+
+    fn start(&mut self) -> Result<(), CollectorError> {
+        MetricsCollector::start(self)
+    }
+
+    fn stop(&mut self) -> Result<(), CollectorError> {
+        MetricsCollector::stop(self)
+    }
+
+    fn health(&self) -> SourceHealth {
+        if self.is_running() {
+            SourceHealth::Running
+        } else {
+            SourceHealth::Stopped
+        }
+    }
+
+    fn set_capture(&mut self, capture: CaptureWriter) {
+        MetricsCollector::set_capture(self, capture)
+    }
+}
+
 impl Drop for MetricsCollector {
     fn drop(&mut self) {
         if self.is_running() || self.thread_handle.is_some() {
@@ -1380,7 +1465,7 @@ mod tests {
     #[test]
     #[ignore] // Requires vm_stat to be available on the system
     fn test_get_memory_snapshot_from_vm_stat() {
-        let result = MetricsCollector::get_memory_snapshot_from_vm_stat();
+        let result = MetricsCollector::get_memory_snapshot_from_vm_stat(None);
 
         // The test should either succeed or fail with a specific error
         match result {
@@ -1,18 +1,85 @@
+use crate::error::CollectorError;
+use log::{debug, error, info, warn};
+use serde_json::Value;
+use std::io::Read;
+use std::process::Child;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 /// Log stream collector for macOS Unified Log System
 pub mod log_collector;
 
+/// Log collector for the systemd journal
+pub mod journald_collector;
+
 /// Metrics collector for system resource monitoring
 pub mod metrics_collector;
 
 /// Disk/filesystem collector for disk I/O monitoring
 pub mod disk_collector;
 
+/// Linux metrics collector reading `/proc`
+pub mod proc_metrics_collector;
+
+/// Crash report collector for DiagnosticReports `.ips` files
+pub mod crash_report_collector;
+
+/// Tailing collector for application log files
+pub mod file_tail_collector;
+
+/// Plugin collector running external commands that print JSON events
+pub mod exec_collector;
+
+/// Syslog receiver for network devices and containers
+pub mod syslog_collector;
+
+/// Raw collector output capture for record-and-replay
+pub mod capture;
+
+/// Reader for exported `log show --style json` archives
+pub mod log_archive;
+
+/// Reader for JSONL files of recorded events
+pub mod event_file;
+
+/// Common event source trait and registry
+pub mod source;
+
+pub use capture::{CaptureDecoder, CaptureRecord, CaptureStream, CaptureWriter};
+pub use crash_report_collector::CrashReportCollector;
 pub use disk_collector::DiskCollector;
+pub use event_file::{read_event_file, RecordedEvents};
+pub use exec_collector::ExecCollector;
+pub use file_tail_collector::FileTailCollector;
+pub use journald_collector::{JournalInput, JournaldCollector};
+pub use log_archive::{read_log_archive, LogArchive};
 pub use log_collector::LogCollector;
 pub use metrics_collector::MetricsCollector;
+pub use proc_metrics_collector::ProcMetricsCollector;
+pub use source::{EventSinks, EventSource, SourceHealth, SourceRegistry, SourceStatus};
+pub use syslog_collector::SyslogCollector;
+
+/// Processes attached to each metrics sample from each ranking
+const PROCESS_SNAPSHOT_SIZE: usize = 5;
+
+/// Keep the largest processes by resident memory and the busiest by CPU
+///
+/// Memory growth shows up among the largest processes and CPU hogs among the
+/// busiest, so each sample carries the five largest followed by the five
+/// busiest of the rest. Idle processes are not added for their CPU usage.
+pub(crate) fn select_process_snapshot<T>(
+    mut processes: Vec<T>,
+    cpu_usage_percent: impl Fn(&T) -> f64,
+    resident_memory: impl Fn(&T) -> f64,
+) -> Vec<T> {
+    processes.sort_by(|left, right| resident_memory(right).total_cmp(&resident_memory(left)));
+    let mut busiest = processes.split_off(PROCESS_SNAPSHOT_SIZE.min(processes.len()));
+    busiest.retain(|process| cpu_usage_percent(process) > 0.0);
+    busiest.sort_by(|left, right| cpu_usage_percent(right).total_cmp(&cpu_usage_percent(left)));
+    busiest.truncate(PROCESS_SNAPSHOT_SIZE);
+    processes.extend(busiest);
+    processes
+}
 
 #[cfg(unix)]
 fn set_nonblocking<T: std::os::fd::AsRawFd>(stream: &T) -> std::io::Result<()> {
@@ -45,10 +112,278 @@ fn wait_for_retry(delay: Duration, running: &Arc<Mutex<bool>>) {
     }
 }
 
+/// Run a subprocess-backed collector until `running` is cleared
+///
+/// Spawns the subprocess, hands it to `process`, and restarts it with
+/// exponential backoff (1s to 60s) when it exits or fails. After five
+/// consecutive failures the loop waits 60 seconds before trying again.
+/// Clears `running` when it returns.
+pub(crate) fn supervise_subprocess(
+    name: &str,
+    running: &Arc<Mutex<bool>>,
+    mut spawn: impl FnMut() -> Result<Child, CollectorError>,
+    mut process: impl FnMut(&mut Child) -> Result<(), CollectorError>,
+) {
+    let mut restart_delay = Duration::from_secs(1);
+    let max_delay = Duration::from_secs(60);
+    let mut consecutive_failures = 0;
+    const MAX_CONSECUTIVE_FAILURES: u32 = 5;
+
+    debug!(
+        "{} supervisor configuration: max_failures={}, initial_delay={:?}, max_delay={:?}",
+        name, MAX_CONSECUTIVE_FAILURES, restart_delay, max_delay
+    );
+
+    while *running.lock().unwrap() {
+        match spawn() {
+            Ok(mut child) => {
+                info!(
+                    "{} subprocess started successfully (attempt after {} failures)",
+                    name, consecutive_failures
+                );
+
+                // Process output from the subprocess
+                let mut had_healthy_run = false;
+                debug!("Starting {} processing", name);
+                match process(&mut child) {
+                    Ok(_) => {
+                        debug!("{} processing completed without errors", name);
+                        // Check if the subprocess is still running
+                        match child.try_wait() {
+                            Ok(Some(exit_status)) => {
+                                // Process exited - this could be due to invalid arguments or other issues
+                                warn!(
+                                    "{} subprocess exited with status: {:?} (failure #{}/{})",
+                                    name,
+                                    exit_status,
+                                    consecutive_failures + 1,
+                                    MAX_CONSECUTIVE_FAILURES
+                                );
+                                consecutive_failures += 1;
+                            }
+                            Ok(None) => {
+                                // Process is still running, this was a normal shutdown
+                                debug!("{} subprocess finished normally (graceful shutdown)", name);
+                                had_healthy_run = true;
+                            }
+                            Err(e) => {
+                                error!(
+                                    "Failed to check {} subprocess status: {} (failure #{}/{})",
+                                    name,
+                                    e,
+                                    consecutive_failures + 1,
+                                    MAX_CONSECUTIVE_FAILURES
+                                );
+                                consecutive_failures += 1;
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        error!(
+                            "Error processing {} output: {} (failure #{}/{})",
+                            name,
+                            e,
+                            consecutive_failures + 1,
+                            MAX_CONSECUTIVE_FAILURES
+                        );
+                        consecutive_failures += 1;
+                    }
+                }
+
+                // Only reset failure count and delay after a healthy run
+                if had_healthy_run {
+                    consecutive_failures = 0;
+                    restart_delay = Duration::from_secs(1);
+                }
+
+                // Clean up subprocess
+                if let Err(e) = child.kill() {
+                    warn!("Failed to kill {} subprocess: {}", name, e);
+                }
+                let _ = child.wait();
+            }
+            Err(e) => {
+                error!("Failed to spawn {} subprocess: {}", name, e);
+                consecutive_failures += 1;
+            }
+        }
+
+        // Check if we should continue running
+        if !*running.lock().unwrap() {
+            break;
+        }
+
+        // Check for too many consecutive failures - enter degraded mode
+        if consecutive_failures >= MAX_CONSECUTIVE_FAILURES {
+            // In degraded mode, wait longer and try less frequently
+            let degraded_delay = Duration::from_secs(60);
+            warn!(
+                "Too many consecutive {} failures ({}), entering degraded mode - will retry every {:?}",
+                name, consecutive_failures, degraded_delay
+            );
+
+            wait_for_retry(degraded_delay, running);
+
+            // Reset failure count to give it another chance
+            consecutive_failures = 0;
+            restart_delay = Duration::from_secs(1);
+            continue;
+        }
+
+        // Wait before restarting with exponential backoff
+        if consecutive_failures > 0 {
+            warn!(
+                "Restarting {} in {:?} (failure #{}/{})",
+                name, restart_delay, consecutive_failures, MAX_CONSECUTIVE_FAILURES
+            );
+            wait_for_retry(restart_delay, running);
+
+            // Exponential backoff
+            restart_delay = std::cmp::min(restart_delay * 2, max_delay);
+        }
+    }
+
+    // Reset running flag when the loop exits (due to failures or shutdown)
+    *running.lock().unwrap() = false;
+}
+
+/// Read a subprocess's non-blocking stdout until EOF or shutdown
+///
+/// Each chunk is passed to `on_chunk` as soon as it is read.
+pub(crate) fn read_subprocess_output(
+    child: &mut Child,
+    running: &Arc<Mutex<bool>>,
+    mut on_chunk: impl FnMut(&[u8]) -> Result<(), CollectorError>,
+) -> Result<(), CollectorError> {
+    let mut stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| CollectorError::ParseError("No stdout available".to_string()))?;
+    let mut temp_buf = [0u8; 4096];
+
+    loop {
+        if !*running.lock().unwrap() {
+            debug!("Stopping subprocess output processing due to shutdown signal");
+            break;
+        }
+
+        match stdout.read(&mut temp_buf) {
+            Ok(0) => {
+                debug!("Subprocess closed stdout");
+                break;
+            }
+            Ok(n) => on_chunk(&temp_buf[..n])?,
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            Err(e) => return Err(CollectorError::IoError(e)),
+        }
+    }
+
+    Ok(())
+}
+
+/// Pass every complete JSON value at the front of `buffer` to `on_value`
+///
+/// Accepts both a streamed JSON array (`log stream --style json`) and
+/// newline-delimited objects (`journalctl -o json`). Incomplete trailing
+/// input stays in the buffer for the next chunk; malformed lines are skipped.
+pub(crate) fn drain_json_values(
+    buffer: &mut String,
+    in_json_array: &mut bool,
+    mut on_value: impl FnMut(Value) -> Result<(), CollectorError>,
+) -> Result<(), CollectorError> {
+    loop {
+        let whitespace = buffer.len() - buffer.trim_start().len();
+        if whitespace > 0 {
+            buffer.drain(..whitespace);
+        }
+
+        let starts_json_array = buffer.strip_prefix('[').is_some_and(|remainder| {
+            let next = remainder.trim_start();
+            next.is_empty() || next.starts_with('{') || next.starts_with(']')
+        });
+        if starts_json_array {
+            *in_json_array = true;
+            buffer.drain(..1);
+            continue;
+        }
+
+        if buffer.starts_with(']') {
+            *in_json_array = false;
+            buffer.drain(..1);
+            continue;
+        }
+
+        if buffer.starts_with(',') {
+            buffer.drain(..1);
+            continue;
+        }
+
+        if buffer.is_empty() {
+            return Ok(());
+        }
+
+        let mut values = serde_json::Deserializer::from_str(buffer).into_iter::<Value>();
+        match values.next() {
+            Some(Ok(value)) => {
+                let consumed = values.byte_offset();
+                buffer.drain(..consumed);
+                on_value(value)?;
+            }
+            Some(Err(error)) if error.is_eof() => {
+                if !*in_json_array {
+                    if let Some(newline) = buffer.find('\n') {
+                        debug!("Discarding incomplete JSON entry: {}", error);
+                        buffer.drain(..=newline);
+                        continue;
+                    }
+                }
+                return Ok(());
+            }
+            Some(Err(error)) => {
+                debug!("Failed to parse buffered JSON entry: {}", error);
+                if let Some(newline) = buffer.find('\n') {
+                    buffer.drain(..=newline);
+                } else {
+                    buffer.clear();
+                }
+            }
+            None => return Ok(()),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn process_snapshot_keeps_largest_and_busiest() {
+        // (process ID, CPU percent, resident megabytes)
+        let processes = (1..=20)
+            .map(|process_id| {
+                let cpu = if process_id % 2 == 0 {
+                    0.0
+                } else {
+                    process_id as f64
+                };
+                (process_id, cpu, 1000.0 - process_id as f64 * 10.0)
+            })
+            .collect::<Vec<_>>();
+
+        let selected = select_process_snapshot(processes, |process| process.1, |process| process.2);
+
+        assert_eq!(
+            selected.iter().map(|process| process.0).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 5, 19, 17, 15, 13, 11]
+        );
+        assert_eq!(
+            select_process_snapshot(vec![(1, 0.0, 1.0)], |p| p.1, |p| p.2).len(),
+            1
+        );
+    }
+
     #[test]
     fn retry_wait_stops_when_collector_stops() {
         let running = Arc::new(Mutex::new(true));
@@ -0,0 +1,693 @@
+use super::source::{EventSource, SourceHealth};
+use crate::error::CollectorError;
+use crate::events::{
+    MeasurementKind, MemoryPressure, MetricsEvent, MetricsProvenance, MetricsSource, ProcessMetric,
+};
+use crate::monitoring::SelfMonitoringCollector;
+use log::{debug, error, info, warn};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Rough CPU power estimate used when no power counters exist (matches the top fallback)
+const ESTIMATED_MW_PER_CPU_PERCENT: f64 = 40.0;
+
+/// PSI `some avg10` percentage at which memory pressure becomes Warning
+const PSI_WARNING_SOME_AVG10: f64 = 10.0;
+
+/// PSI `full avg10` percentage at which memory pressure becomes Critical
+const PSI_CRITICAL_FULL_AVG10: f64 = 5.0;
+
+/// Linux metrics collector reading `/proc`
+///
+/// Samples `/proc/stat`, `/proc/meminfo`, `/proc/pressure/memory` and
+/// `/proc/[pid]/stat` on a fixed interval and sends `MetricsEvent`s with
+/// `MetricsSource::Procfs` provenance. No subprocess or elevated privileges
+/// are needed. CPU usage is computed from the change between two samples, so
+/// the first event arrives one interval after start.
+pub struct ProcMetricsCollector {
+    /// Root of the proc filesystem (normally `/proc`)
+    proc_root: PathBuf,
+    /// Base sampling interval
+    base_sample_interval: Duration,
+    /// Channel to send metrics events
+    output_channel: Sender<MetricsEvent>,
+    /// Handle to the background thread
+    thread_handle: Option<JoinHandle<()>>,
+    /// Shared state for controlling the collector
+    running: Arc<Mutex<bool>>,
+    /// Self-monitoring collector for resource pressure detection
+    monitoring: Option<Arc<SelfMonitoringCollector>>,
+}
+
+impl ProcMetricsCollector {
+    /// Create a collector reading `/proc` every `interval`
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::sync::mpsc;
+    /// use std::time::Duration;
+    /// use eyes::collectors::ProcMetricsCollector;
+    ///
+    /// let (tx, rx) = mpsc::channel();
+    /// let collector = ProcMetricsCollector::new(Duration::from_secs(5), tx);
+    /// ```
+    pub fn new(interval: Duration, channel: Sender<MetricsEvent>) -> Self {
+        Self::with_proc_root(PathBuf::from("/proc"), interval, channel)
+    }
+
+    /// Create a collector reading a proc filesystem mounted elsewhere
+    pub fn with_proc_root(
+        proc_root: PathBuf,
+        interval: Duration,
+        channel: Sender<MetricsEvent>,
+    ) -> Self {
+        Self {
+            proc_root,
+            base_sample_interval: interval,
+            output_channel: channel,
+            thread_handle: None,
+            running: Arc::new(Mutex::new(false)),
+            monitoring: None,
+        }
+    }
+
+    /// Set the self-monitoring collector for resource pressure detection
+    pub fn set_monitoring(&mut self, monitoring: Arc<SelfMonitoringCollector>) {
+        self.monitoring = Some(monitoring);
+    }
+
+    /// Start sampling `/proc` on a background thread
+    ///
+    /// # Errors
+    ///
+    /// Returns `CollectorError::IoError` if `/proc/stat` cannot be read, which
+    /// means the host has no usable proc filesystem.
+    pub fn start(&mut self) -> Result<(), CollectorError> {
+        info!(
+            "Starting ProcMetricsCollector on {} with interval: {:?}",
+            self.proc_root.display(),
+            self.base_sample_interval
+        );
+
+        {
+            let mut running = self.running.lock().unwrap();
+            if *running {
+                info!("ProcMetricsCollector already running, skipping start");
+                return Ok(());
+            }
+
+            let mut sampler = ProcSampler::new(self.proc_root.clone());
+            sampler.sample()?;
+            *running = true;
+
+            let interval = self.base_sample_interval;
+            let channel = self.output_channel.clone();
+            let running = Arc::clone(&self.running);
+            let monitoring = self.monitoring.clone();
+            self.thread_handle = Some(thread::spawn(move || {
+                Self::collector_thread(sampler, interval, channel, running, monitoring);
+            }));
+        }
+
+        info!("ProcMetricsCollector started successfully");
+        Ok(())
+    }
+
+    /// Stop the collector and wait for its thread to finish
+    ///
+    /// # Errors
+    ///
+    /// Returns `CollectorError::SubprocessTerminated` if the thread panicked.
+    pub fn stop(&mut self) -> Result<(), CollectorError> {
+        info!("Stopping ProcMetricsCollector");
+        *self.running.lock().unwrap() = false;
+
+        if let Some(handle) = self.thread_handle.take() {
+            handle.join().map_err(|_| {
+                error!("Failed to join ProcMetricsCollector thread");
+                CollectorError::SubprocessTerminated("Failed to join collector thread".to_string())
+            })?;
+        }
+
+        info!("ProcMetricsCollector stopped successfully");
+        Ok(())
+    }
+
+    /// Check if the collector is currently running
+    pub fn is_running(&self) -> bool {
+        *self.running.lock().unwrap()
+    }
+
+    fn collector_thread(
+        mut sampler: ProcSampler,
+        base_interval: Duration,
+        channel: Sender<MetricsEvent>,
+        running: Arc<Mutex<bool>>,
+        monitoring: Option<Arc<SelfMonitoringCollector>>,
+    ) {
+        let mut interval = base_interval;
+
+        while *running.lock().unwrap() {
+            // Reduce sampling frequency under resource pressure (Requirement 7.4)
+            if let Some(ref monitoring) = monitoring {
+                interval = if monitoring.is_under_resource_pressure() {
+                    interval.mul_f32(1.5).min(Duration::from_secs(60))
+                } else {
+                    interval.mul_f32(0.9).max(base_interval)
+                };
+            }
+
+            super::wait_for_retry(interval, &running);
+            if !*running.lock().unwrap() {
+                break;
+            }
+
+            match sampler.sample() {
+                Ok(Some(event)) => {
+                    if channel.send(event).is_err() {
+                        info!("Metrics channel closed, stopping ProcMetricsCollector");
+                        break;
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Failed to sample /proc metrics: {}", e),
+            }
+        }
+
+        debug!("ProcMetricsCollector thread exiting");
+    }
+}
+
+impl EventSource for ProcMetricsCollector {
+    fn name(&self) -> &str {
+        "metrics"
+    }
+
+    fn start(&mut self) -> Result<(), CollectorError> {
+        ProcMetricsCollector::start(self)
+    }
+
+    fn stop(&mut self) -> Result<(), CollectorError> {
+        ProcMetricsCollector::stop(self)
+    }
+
+    fn health(&self) -> SourceHealth {
+        if self.is_running() {
+            SourceHealth::Running
+        } else {
+            SourceHealth::Stopped
+        }
+    }
+}
+
+impl Drop for ProcMetricsCollector {
+    fn drop(&mut self) {
+        if self.is_running() || self.thread_handle.is_some() {
+            let _ = self.stop();
+        }
+    }
+}
+
+/// Aggregate CPU jiffies from the `cpu` line of `/proc/stat`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CpuTimes {
+    pub busy: u64,
+    pub total: u64,
+    /// Number of `cpuN` lines, used to scale per-process usage
+    pub cpus: usize,
+}
+
+/// Memory totals from `/proc/meminfo`, in kilobytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct MemInfo {
+    pub total_kb: u64,
+    pub available_kb: u64,
+}
+
+/// Ten-second averages from `/proc/pressure/memory`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct MemoryPsi {
+    pub some_avg10: f64,
+    pub full_avg10: f64,
+}
+
+/// Fields read from `/proc/[pid]/stat`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ProcessStat {
+    pub process_id: u32,
+    pub name: String,
+    /// utime + stime in clock ticks
+    pub cpu_ticks: u64,
+    pub rss_pages: u64,
+}
+
+pub(crate) fn parse_cpu_times(stat: &str) -> Option<CpuTimes> {
+    let mut aggregate = None;
+    let mut cpus = 0;
+
+    for line in stat.lines() {
+        let mut fields = line.split_whitespace();
+        match fields.next() {
+            Some("cpu") => {
+                // user nice system idle iowait irq softirq steal; guest time is
+                // already included in user and nice
+                let values = fields
+                    .take(8)
+                    .map(|value| value.parse::<u64>().ok())
+                    .collect::<Option<Vec<_>>>()?;
+                if values.len() < 4 {
+                    return None;
+                }
+                let total = values.iter().sum::<u64>();
+                let idle = values[3] + values.get(4).copied().unwrap_or(0);
+                aggregate = Some((total - idle, total));
+            }
+            Some(label) if label.starts_with("cpu") => cpus += 1,
+            _ => {}
+        }
+    }
+
+    aggregate.map(|(busy, total)| CpuTimes {
+        busy,
+        total,
+        cpus: cpus.max(1),
+    })
+}
+
+pub(crate) fn parse_meminfo(meminfo: &str) -> Option<MemInfo> {
+    let mut total_kb = None;
+    let mut available_kb = None;
+
+    for line in meminfo.lines() {
+        let mut fields = line.split_whitespace();
+        let target = match fields.next() {
+            Some("MemTotal:") => &mut total_kb,
+            Some("MemAvailable:") => &mut available_kb,
+            _ => continue,
+        };
+        *target = fields.next().and_then(|value| value.parse::<u64>().ok());
+    }
+
+    Some(MemInfo {
+        total_kb: total_kb?,
+        available_kb: available_kb?,
+    })
+}
+
+pub(crate) fn parse_memory_psi(pressure: &str) -> Option<MemoryPsi> {
+    let avg10 = |kind: &str| {
+        pressure
+            .lines()
+            .find(|line| line.starts_with(kind))?
+            .split_whitespace()
+            .find_map(|field| field.strip_prefix("avg10="))?
+            .parse::<f64>()
+            .ok()
+    };
+
+    Some(MemoryPsi {
+        some_avg10: avg10("some ")?,
+        // Older kernels only report the `some` line
+        full_avg10: avg10("full ").unwrap_or(0.0),
+    })
+}
+
+/// Map memory stall percentages onto the macOS-style pressure levels
+///
+/// `full` means every non-idle task was stalled on memory, which is the
+/// closest analogue to macOS critical pressure; sustained `some` stalls map
+/// to Warning.
+pub(crate) fn memory_pressure_from_psi(psi: MemoryPsi) -> MemoryPressure {
+    if psi.full_avg10 >= PSI_CRITICAL_FULL_AVG10 {
+        MemoryPressure::Critical
+    } else if psi.some_avg10 >= PSI_WARNING_SOME_AVG10 {
+        MemoryPressure::Warning
+    } else {
+        MemoryPressure::Normal
+    }
+}
+
+/// Estimate pressure from available memory when PSI is not enabled
+pub(crate) fn memory_pressure_from_meminfo(meminfo: MemInfo) -> MemoryPressure {
+    if meminfo.total_kb == 0 {
+        return MemoryPressure::Unknown;
+    }
+
+    let available = meminfo.available_kb as f64 / meminfo.total_kb as f64;
+    if available < 0.05 {
+        MemoryPressure::Critical
+    } else if available < 0.15 {
+        MemoryPressure::Warning
+    } else {
+        MemoryPressure::Normal
+    }
+}
+
+pub(crate) fn parse_process_stat(stat: &str) -> Option<ProcessStat> {
+    // The command name is parenthesized and may itself contain spaces or ')'
+    let name_start = stat.find('(')?;
+    let name_end = stat.rfind(')')?;
+    let process_id = stat[..name_start].trim().parse().ok()?;
+    let name = stat.get(name_start + 1..name_end)?.to_string();
+
+    // Fields after the name start at field 3 (state)
+    let fields = stat[name_end + 1..].split_whitespace().collect::<Vec<_>>();
+    let utime = fields.get(11)?.parse::<u64>().ok()?;
+    let stime = fields.get(12)?.parse::<u64>().ok()?;
+    let rss_pages = fields.get(21)?.parse::<i64>().ok()?.max(0) as u64;
+
+    Some(ProcessStat {
+        process_id,
+        name,
+        cpu_ticks: utime + stime,
+        rss_pages,
+    })
+}
+
+/// Stateful reader that turns successive `/proc` snapshots into events
+pub(crate) struct ProcSampler {
+    proc_root: PathBuf,
+    page_size: u64,
+    previous_cpu: Option<CpuTimes>,
+    previous_process_ticks: HashMap<u32, u64>,
+}
+
+impl ProcSampler {
+    pub(crate) fn new(proc_root: PathBuf) -> Self {
+        Self {
+            proc_root,
+            page_size: system_page_size(),
+            previous_cpu: None,
+            previous_process_ticks: HashMap::new(),
+        }
+    }
+
+    /// Read the next snapshot
+    ///
+    /// Returns `Ok(None)` for the first snapshot, which only establishes the
+    /// CPU baseline.
+    pub(crate) fn sample(&mut self) -> Result<Option<MetricsEvent>, CollectorError> {
+        let stat = std::fs::read_to_string(self.proc_root.join("stat"))?;
+        let cpu = parse_cpu_times(&stat).ok_or_else(|| {
+            CollectorError::ParseError("missing cpu line in /proc/stat".to_string())
+        })?;
+        let processes = self.read_processes();
+        let previous_cpu = self.previous_cpu.replace(cpu);
+        let previous_process_ticks = std::mem::replace(
+            &mut self.previous_process_ticks,
+            processes
+                .iter()
+                .map(|process| (process.process_id, process.cpu_ticks))
+                .collect(),
+        );
+
+        let Some(previous_cpu) = previous_cpu else {
+            return Ok(None);
+        };
+        let total_delta = cpu.total.saturating_sub(previous_cpu.total);
+        if total_delta == 0 {
+            return Ok(None);
+        }
+
+        let cpu_usage_percent =
+            (cpu.busy.saturating_sub(previous_cpu.busy) as f64 / total_delta as f64 * 100.0)
+                .clamp(0.0, 100.0);
+        let ticks_per_cpu = total_delta as f64 / cpu.cpus as f64;
+        let process_metrics =
+            self.process_metrics(processes, &previous_process_ticks, ticks_per_cpu);
+
+        let meminfo = std::fs::read_to_string(self.proc_root.join("meminfo"))
+            .ok()
+            .and_then(|contents| parse_meminfo(&contents));
+        let psi = std::fs::read_to_string(self.proc_root.join("pressure/memory"))
+            .ok()
+            .and_then(|contents| parse_memory_psi(&contents));
+
+        let (memory_pressure, memory_pressure_kind) = match (psi, meminfo) {
+            (Some(psi), _) => (memory_pressure_from_psi(psi), MeasurementKind::Measured),
+            (None, Some(meminfo)) => (
+                memory_pressure_from_meminfo(meminfo),
+                MeasurementKind::Derived,
+            ),
+            (None, None) => (MemoryPressure::Unknown, MeasurementKind::Unavailable),
+        };
+        let (memory_used_mb, memory_used_kind) = match meminfo {
+            Some(meminfo) => (
+                meminfo.total_kb.saturating_sub(meminfo.available_kb) as f64 / 1024.0,
+                MeasurementKind::Derived,
+            ),
+            None => (0.0, MeasurementKind::Unavailable),
+        };
+        let estimated_cpu_power = cpu_usage_percent * ESTIMATED_MW_PER_CPU_PERCENT;
+
+        Ok(Some(MetricsEvent {
+            timestamp: chrono::Utc::now(),
+            cpu_power_mw: estimated_cpu_power,
+            cpu_usage_percent,
+            gpu_power_mw: None,
+            gpu_usage_percent: None,
+            memory_pressure,
+            memory_used_mb,
+            energy_impact: estimated_cpu_power,
+            provenance: MetricsProvenance {
+                source: MetricsSource::Procfs,
+                cpu_usage: MeasurementKind::Measured,
+                cpu_power: MeasurementKind::Estimated,
+                gpu_usage: MeasurementKind::Unavailable,
+                gpu_power: MeasurementKind::Unavailable,
+                memory_pressure: memory_pressure_kind,
+                memory_used: memory_used_kind,
+                energy_impact: MeasurementKind::Estimated,
+            },
+            process_metrics,
+        }))
+    }
+
+    fn read_processes(&self) -> Vec<ProcessStat> {
+        let Ok(entries) = std::fs::read_dir(&self.proc_root) else {
+            return Vec::new();
+        };
+
+        entries
+            .filter_map(Result::ok)
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|name| name.bytes().all(|byte| byte.is_ascii_digit()))
+            })
+            // Processes may exit between listing and reading
+            .filter_map(|entry| std::fs::read_to_string(entry.path().join("stat")).ok())
+            .filter_map(|stat| parse_process_stat(&stat))
+            .collect()
+    }
+
+    fn process_metrics(
+        &self,
+        processes: Vec<ProcessStat>,
+        previous_ticks: &HashMap<u32, u64>,
+        ticks_per_cpu: f64,
+    ) -> Vec<ProcessMetric> {
+        let processes = processes
+            .into_iter()
+            .map(|process| {
+                // Processes first seen in this sample have no baseline
+                let ticks = previous_ticks
+                    .get(&process.process_id)
+                    .map(|previous| process.cpu_ticks.saturating_sub(*previous))
+                    .unwrap_or(0);
+                ProcessMetric {
+                    process_id: process.process_id,
+                    process: process.name,
+                    cpu_usage_percent: ticks as f64 / ticks_per_cpu * 100.0,
+                    resident_memory_mb: (process.rss_pages * self.page_size) as f64
+                        / (1024.0 * 1024.0),
+                }
+            })
+            .collect();
+        super::select_process_snapshot(
+            processes,
+            |process| process.cpu_usage_percent,
+            |process| process.resident_memory_mb,
+        )
+    }
+}
+
+#[cfg(unix)]
+fn system_page_size() -> u64 {
+    // SAFETY: sysconf has no preconditions.
+    let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if size > 0 {
+        size as u64
+    } else {
+        4096
+    }
+}
+
+#[cfg(not(unix))]
+fn system_page_size() -> u64 {
+    4096
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use std::sync::mpsc;
+    use tempfile::TempDir;
+
+    const PSI: &str = "some avg10=12.50 avg60=3.00 avg300=1.00 total=123\n\
+                       full avg10=0.50 avg60=0.10 avg300=0.00 total=45\n";
+
+    fn write_proc(root: &Path, busy: u64, idle: u64, process_ticks: u64) {
+        std::fs::write(
+            root.join("stat"),
+            format!(
+                "cpu  {busy} 0 0 {idle} 0 0 0 0 0 0\ncpu0 1 0 0 1 0 0 0 0 0 0\n\
+                 cpu1 1 0 0 1 0 0 0 0 0 0\nintr 1\n"
+            ),
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("meminfo"),
+            "MemTotal:       16384000 kB\nMemFree:  1000 kB\nMemAvailable:    8192000 kB\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.join("42")).unwrap();
+        std::fs::write(
+            root.join("42/stat"),
+            format!(
+                "42 (web (worker)) S 1 42 42 0 -1 4194304 100 0 0 0 {process_ticks} 0 0 0 \
+                 20 0 4 0 100 1000000 2560 18446744073709551615"
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn parses_cpu_memory_and_psi() {
+        let cpu = parse_cpu_times("cpu  100 5 20 800 50 1 2 3 0 0\ncpu0 1\ncpu1 1\n").unwrap();
+        assert_eq!(cpu.total, 981);
+        assert_eq!(cpu.busy, 131);
+        assert_eq!(cpu.cpus, 2);
+
+        let meminfo = parse_meminfo("MemTotal: 1000 kB\nMemAvailable: 100 kB\n").unwrap();
+        assert_eq!(meminfo.available_kb, 100);
+        assert_eq!(parse_meminfo("MemTotal: 1000 kB\n"), None);
+
+        let psi = parse_memory_psi(PSI).unwrap();
+        assert_eq!(psi.some_avg10, 12.5);
+        assert_eq!(psi.full_avg10, 0.5);
+    }
+
+    #[test]
+    fn maps_psi_and_available_memory_to_pressure() {
+        let psi = |some_avg10, full_avg10| MemoryPsi {
+            some_avg10,
+            full_avg10,
+        };
+        assert_eq!(
+            memory_pressure_from_psi(psi(1.0, 0.0)),
+            MemoryPressure::Normal
+        );
+        assert_eq!(
+            memory_pressure_from_psi(psi(12.0, 1.0)),
+            MemoryPressure::Warning
+        );
+        assert_eq!(
+            memory_pressure_from_psi(psi(40.0, 6.0)),
+            MemoryPressure::Critical
+        );
+
+        let meminfo = |available_kb| MemInfo {
+            total_kb: 1000,
+            available_kb,
+        };
+        assert_eq!(
+            memory_pressure_from_meminfo(meminfo(500)),
+            MemoryPressure::Normal
+        );
+        assert_eq!(
+            memory_pressure_from_meminfo(meminfo(100)),
+            MemoryPressure::Warning
+        );
+        assert_eq!(
+            memory_pressure_from_meminfo(meminfo(10)),
+            MemoryPressure::Critical
+        );
+    }
+
+    #[test]
+    fn parses_process_stat_with_parenthesized_name() {
+        let stat = "42 (web (worker)) S 1 42 42 0 -1 4194304 100 0 0 0 70 30 0 0 \
+                    20 0 4 0 100 1000000 2560 18446744073709551615";
+        let process = parse_process_stat(stat).unwrap();
+        assert_eq!(process.process_id, 42);
+        assert_eq!(process.name, "web (worker)");
+        assert_eq!(process.cpu_ticks, 100);
+        assert_eq!(process.rss_pages, 2560);
+        assert_eq!(parse_process_stat("42 (truncated"), None);
+    }
+
+    #[test]
+    fn sampler_computes_usage_between_snapshots() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write_proc(root, 100, 900, 10);
+        std::fs::create_dir_all(root.join("pressure")).unwrap();
+        std::fs::write(root.join("pressure/memory"), PSI).unwrap();
+
+        let mut sampler = ProcSampler::new(root.to_path_buf());
+        sampler.page_size = 4096;
+        assert!(sampler.sample().unwrap().is_none());
+
+        // 250 of 1000 jiffies busy; the process used 100 ticks of 500 per CPU
+        write_proc(root, 350, 1650, 110);
+        let event = sampler.sample().unwrap().unwrap();
+
+        assert_eq!(event.cpu_usage_percent, 25.0);
+        assert_eq!(event.memory_used_mb, 8000.0);
+        assert_eq!(event.memory_pressure, MemoryPressure::Warning);
+        assert_eq!(event.provenance.source, MetricsSource::Procfs);
+        assert_eq!(event.provenance.memory_pressure, MeasurementKind::Measured);
+        assert_eq!(event.provenance.cpu_power, MeasurementKind::Estimated);
+        assert_eq!(event.process_metrics.len(), 1);
+        assert_eq!(event.process_metrics[0].process, "web (worker)");
+        assert_eq!(event.process_metrics[0].cpu_usage_percent, 20.0);
+        assert_eq!(event.process_metrics[0].resident_memory_mb, 10.0);
+    }
+
+    #[test]
+    fn sampler_falls_back_to_meminfo_without_psi() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write_proc(root, 100, 900, 10);
+        let mut sampler = ProcSampler::new(root.to_path_buf());
+        sampler.sample().unwrap();
+
+        write_proc(root, 200, 1800, 10);
+        let event = sampler.sample().unwrap().unwrap();
+
+        assert_eq!(event.memory_pressure, MemoryPressure::Normal);
+        assert_eq!(event.provenance.memory_pressure, MeasurementKind::Derived);
+    }
+
+    #[test]
+    fn start_fails_without_proc_filesystem() {
+        let temp_dir = TempDir::new().unwrap();
+        let (tx, _rx) = mpsc::channel();
+        let mut collector = ProcMetricsCollector::with_proc_root(
+            temp_dir.path().into(),
+            Duration::from_secs(1),
+            tx,
+        );
+
+        assert!(matches!(collector.start(), Err(CollectorError::IoError(_))));
+        assert!(!collector.is_running());
+    }
+}
@@ -0,0 +1,566 @@
+use super::capture::CaptureWriter;
+use super::file_tail_collector::FILE_POLL_INTERVAL;
+#[cfg(not(target_os = "linux"))]
+use super::MetricsCollector;
+#[cfg(target_os = "linux")]
+use super::ProcMetricsCollector;
+use super::{
+    CrashReportCollector, DiskCollector, ExecCollector, FileTailCollector, JournalInput,
+    JournaldCollector, LogCollector, SyslogCollector,
+};
+use crate::config::{Config, LogSource};
+use crate::error::CollectorError;
+use crate::events::{CrashReport, DiskEvent, LogEvent, MetricsEvent};
+use crate::monitoring::SelfMonitoringCollector;
+use log::{debug, error, info, warn};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Names of the sources registered by `SourceRegistry::from_config`
+pub const BUILTIN_SOURCES: &[&str] =
+    &["log", "metrics", "disk", "crash_reports", "files", "syslog"];
+
+/// Common lifecycle for anything that produces events for the aggregator
+///
+/// Implementations own their background threads and send events on the
+/// channels from `EventSinks`. The registry starts sources in registration
+/// order and stops them before the forwarding threads shut down.
+pub trait EventSource: Send {
+    /// Unique name used in logs, health reports and `collectors.disabled`
+    fn name(&self) -> &str;
+
+    /// Start producing events
+    fn start(&mut self) -> Result<(), CollectorError>;
+
+    /// Stop producing events and join any background threads
+    fn stop(&mut self) -> Result<(), CollectorError>;
+
+    /// Current health of the source
+    fn health(&self) -> SourceHealth;
+
+    /// Whether a start failure should abort startup instead of degrading
+    fn required(&self) -> bool {
+        false
+    }
+
+    /// Record raw input to a capture file, if the source supports replay
+    fn set_capture(&mut self, _capture: CaptureWriter) {}
+}
+
+/// Health reported for a registered source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceHealth {
+    /// The source is producing events
+    Running,
+    /// The source is not running
+    Stopped,
+    /// The source failed to start
+    Failed(String),
+}
+
+/// Health of one registered source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceStatus {
+    pub name: String,
+    pub health: SourceHealth,
+}
+
+/// Channels that event sources publish on
+#[derive(Clone)]
+pub struct EventSinks {
+    pub logs: Sender<LogEvent>,
+    pub metrics: Sender<MetricsEvent>,
+    pub disk: Sender<DiskEvent>,
+    pub crashes: Sender<CrashReport>,
+}
+
+struct RegisteredSource {
+    source: Box<dyn EventSource>,
+    start_error: Option<String>,
+}
+
+/// Ordered set of event sources managed as a unit
+#[derive(Default)]
+pub struct SourceRegistry {
+    sources: Vec<RegisteredSource>,
+}
+
+impl SourceRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry holding the sources enabled by `config`
+    ///
+    /// Built-in sources and `[[collectors.exec]]` entries listed in
+    /// `collectors.disabled` are skipped, as are exec entries whose name is
+    /// already registered.
+    pub fn from_config(
+        config: &Config,
+        sinks: &EventSinks,
+        monitoring: &Arc<SelfMonitoringCollector>,
+    ) -> Self {
+        let mut registry = Self::new();
+        let interval = Duration::from_secs(config.metrics.interval_seconds);
+        let enabled = |name: &str| {
+            let enabled = !config.collectors.disabled.iter().any(|n| n == name);
+            if !enabled {
+                info!("Event source '{}' disabled by configuration", name);
+            }
+            enabled
+        };
+
+        if enabled("log") {
+            match config.logging.source.resolve() {
+                LogSource::Journald => {
+                    let input = match &config.logging.journal_file {
+                        Some(path) => JournalInput::File(path.clone()),
+                        None => JournalInput::Follow {
+                            priority: config.logging.journal_priority.clone(),
+                        },
+                    };
+                    debug!("Initializing journald collector with input: {:?}", input);
+                    registry.push(Box::new(JournaldCollector::new(input, sinks.logs.clone())));
+                }
+                _ => {
+                    debug!(
+                        "Initializing log collector with predicate: '{}'",
+                        config.logging.predicate
+                    );
+                    registry.push(Box::new(LogCollector::new(
+                        config.logging.predicate.clone(),
+                        sinks.logs.clone(),
+                    )));
+                }
+            }
+        }
+
+        // powermetrics only exists on macOS; Linux hosts read /proc instead
+        #[cfg(target_os = "linux")]
+        if enabled("metrics") {
+            debug!(
+                "Initializing /proc metrics collector with interval: {:?}",
+                interval
+            );
+            let mut metrics_collector = ProcMetricsCollector::new(interval, sinks.metrics.clone());
+            metrics_collector.set_monitoring(Arc::clone(monitoring));
+            registry.push(Box::new(metrics_collector));
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        if enabled("metrics") {
+            debug!(
+                "Initializing metrics collector with interval: {:?}",
+                interval
+            );
+            let mut metrics_collector = MetricsCollector::new(interval, sinks.metrics.clone());
+            metrics_collector.set_monitoring(Arc::clone(monitoring));
+            registry.push(Box::new(metrics_collector));
+        }
+
+        if enabled("disk") {
+            debug!("Initializing disk collector with interval: {:?}", interval);
+            let mut disk_collector = DiskCollector::new(interval, sinks.disk.clone());
+            disk_collector.set_monitoring(Arc::clone(monitoring));
+            registry.push(Box::new(disk_collector));
+        }
+
+        // Only registered where a report directory exists (macOS) or is configured
+        if let Some(directory) = config.collectors.resolved_crash_reports_dir() {
+            if enabled("crash_reports") {
+                debug!(
+                    "Initializing crash report collector on {}",
+                    directory.display()
+                );
+                registry.push(Box::new(CrashReportCollector::new(
+                    directory,
+                    interval,
+                    sinks.crashes.clone(),
+                )));
+            }
+        }
+
+        if !config.logging.files.is_empty() && enabled("files") {
+            debug!(
+                "Initializing file tail collector for {} file(s)",
+                config.logging.files.len()
+            );
+            registry.push(Box::new(FileTailCollector::new(
+                config.logging.files.clone(),
+                FILE_POLL_INTERVAL,
+                sinks.logs.clone(),
+            )));
+        }
+
+        if config.logging.syslog.enabled && enabled("syslog") {
+            debug!(
+                "Initializing syslog receiver on {}",
+                config.logging.syslog.bind_address
+            );
+            registry.push(Box::new(SyslogCollector::new(
+                config.logging.syslog.clone(),
+                sinks.logs.clone(),
+            )));
+        }
+
+        for exec in &config.collectors.exec {
+            if enabled(&exec.name) {
+                debug!("Initializing exec collector '{}'", exec.name);
+                let collector = ExecCollector::new(exec.clone(), sinks.clone());
+                if let Err(e) = registry.register(Box::new(collector)) {
+                    warn!("Skipping exec collector: {}", e);
+                }
+            }
+        }
+
+        registry
+    }
+
+    /// Add a source; it is started by the next `start_all`
+    ///
+    /// # Errors
+    ///
+    /// Returns `CollectorError::DuplicateSource` if a source with the same
+    /// name is already registered.
+    pub fn register(&mut self, source: Box<dyn EventSource>) -> Result<(), CollectorError> {
+        if self.names().any(|name| name == source.name()) {
+            return Err(CollectorError::DuplicateSource(source.name().to_string()));
+        }
+        self.push(source);
+        Ok(())
+    }
+
+    /// Take the source registered under `name` out of the registry
+    pub fn remove(&mut self, name: &str) -> Option<Box<dyn EventSource>> {
+        let index = self.names().position(|registered| registered == name)?;
+        Some(self.sources.remove(index).source)
+    }
+
+    /// Stop the source registered under the replacement's name and start the
+    /// replacement in its place
+    ///
+    /// If the replacement fails to start, the previous source is started
+    /// again and stays registered.
+    ///
+    /// # Errors
+    ///
+    /// Returns the replacement's start error, or `CollectorError::UnknownSource`
+    /// if no source with that name is registered.
+    pub fn replace(&mut self, mut replacement: Box<dyn EventSource>) -> Result<(), CollectorError> {
+        let name = replacement.name().to_string();
+        let entry = self
+            .sources
+            .iter_mut()
+            .find(|entry| entry.source.name() == name)
+            .ok_or_else(|| CollectorError::UnknownSource(name.clone()))?;
+
+        if let Err(e) = entry.source.stop() {
+            warn!("Failed to stop event source '{}' for restart: {}", name, e);
+        }
+        match replacement.start() {
+            Ok(()) => {
+                entry.source = replacement;
+                entry.start_error = None;
+                info!("Event source '{}' restarted with new settings", name);
+                Ok(())
+            }
+            Err(e) => {
+                warn!(
+                    "Event source '{}' failed to restart, keeping previous settings: {}",
+                    name, e
+                );
+                entry.start_error = entry.source.start().err().map(|e| e.to_string());
+                Err(e)
+            }
+        }
+    }
+
+    fn push(&mut self, source: Box<dyn EventSource>) {
+        self.sources.push(RegisteredSource {
+            source,
+            start_error: None,
+        });
+    }
+
+    /// Names of the registered sources in start order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.sources.iter().map(|entry| entry.source.name())
+    }
+
+    /// Number of registered sources
+    pub fn len(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// Whether no sources are registered
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    /// Record raw input from every source that supports capture
+    pub fn set_capture(&mut self, capture: &CaptureWriter) {
+        for entry in &mut self.sources {
+            entry.source.set_capture(capture.clone());
+        }
+    }
+
+    /// Start every registered source in order
+    ///
+    /// Optional sources that fail to start are reported as
+    /// `SourceHealth::Failed` and startup continues without them.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the first required source that fails to start.
+    pub fn start_all(&mut self) -> Result<(), CollectorError> {
+        for entry in &mut self.sources {
+            match entry.source.start() {
+                Ok(()) => {
+                    entry.start_error = None;
+                    info!("Event source '{}' started", entry.source.name());
+                }
+                Err(e) if entry.source.required() => {
+                    entry.start_error = Some(e.to_string());
+                    return Err(e);
+                }
+                Err(e) => {
+                    warn!(
+                        "Event source '{}' failed to start, continuing without it: {}",
+                        entry.source.name(),
+                        e
+                    );
+                    entry.start_error = Some(e.to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Stop every registered source, logging failures
+    pub fn stop_all(&mut self) {
+        for entry in &mut self.sources {
+            if let Err(e) = entry.source.stop() {
+                error!(
+                    "Failed to stop event source '{}': {}",
+                    entry.source.name(),
+                    e
+                );
+            }
+        }
+    }
+
+    /// Health of every registered source in start order
+    pub fn health(&self) -> Vec<SourceStatus> {
+        self.sources
+            .iter()
+            .map(|entry| {
+                let health = match (entry.source.health(), &entry.start_error) {
+                    (SourceHealth::Stopped, Some(error)) => SourceHealth::Failed(error.clone()),
+                    (health, _) => health,
+                };
+                SourceStatus {
+                    name: entry.source.name().to_string(),
+                    health,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ExecCollectorConfig;
+    use std::sync::mpsc;
+    use std::sync::Mutex;
+
+    struct FakeSource {
+        name: &'static str,
+        required: bool,
+        fail_start: bool,
+        running: Arc<Mutex<bool>>,
+    }
+
+    impl FakeSource {
+        fn boxed(name: &'static str, required: bool, fail_start: bool) -> Box<Self> {
+            Box::new(Self {
+                name,
+                required,
+                fail_start,
+                running: Arc::new(Mutex::new(false)),
+            })
+        }
+    }
+
+    impl EventSource for FakeSource {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn start(&mut self) -> Result<(), CollectorError> {
+            if self.fail_start {
+                return Err(CollectorError::SubprocessSpawn(format!(
+                    "{} missing",
+                    self.name
+                )));
+            }
+            *self.running.lock().unwrap() = true;
+            Ok(())
+        }
+
+        fn stop(&mut self) -> Result<(), CollectorError> {
+            *self.running.lock().unwrap() = false;
+            Ok(())
+        }
+
+        fn health(&self) -> SourceHealth {
+            if *self.running.lock().unwrap() {
+                SourceHealth::Running
+            } else {
+                SourceHealth::Stopped
+            }
+        }
+
+        fn required(&self) -> bool {
+            self.required
+        }
+    }
+
+    fn sinks() -> EventSinks {
+        EventSinks {
+            logs: mpsc::channel().0,
+            metrics: mpsc::channel().0,
+            disk: mpsc::channel().0,
+            crashes: mpsc::channel().0,
+        }
+    }
+
+    #[test]
+    fn optional_start_failures_degrade_instead_of_aborting() {
+        let mut registry = SourceRegistry::new();
+        registry
+            .register(FakeSource::boxed("a", true, false))
+            .unwrap();
+        registry
+            .register(FakeSource::boxed("b", false, true))
+            .unwrap();
+        registry
+            .register(FakeSource::boxed("c", false, false))
+            .unwrap();
+
+        registry.start_all().unwrap();
+
+        let health = registry.health();
+        assert_eq!(health[0].health, SourceHealth::Running);
+        assert_eq!(
+            health[1].health,
+            SourceHealth::Failed("Failed to spawn subprocess: b missing".to_string())
+        );
+        assert_eq!(health[2].health, SourceHealth::Running);
+
+        registry.stop_all();
+        assert!(registry
+            .health()
+            .iter()
+            .all(|status| status.health != SourceHealth::Running));
+    }
+
+    #[test]
+    fn replace_keeps_previous_source_when_replacement_fails() {
+        let mut registry = SourceRegistry::new();
+        let previous = FakeSource::boxed("log", true, false);
+        let previous_running = Arc::clone(&previous.running);
+        registry.register(previous).unwrap();
+        registry.start_all().unwrap();
+
+        assert!(registry
+            .replace(FakeSource::boxed("log", true, true))
+            .is_err());
+        assert!(*previous_running.lock().unwrap());
+        assert_eq!(registry.health()[0].health, SourceHealth::Running);
+
+        let replacement = FakeSource::boxed("log", true, false);
+        let replacement_running = Arc::clone(&replacement.running);
+        registry.replace(replacement).unwrap();
+        assert!(!*previous_running.lock().unwrap());
+        assert!(*replacement_running.lock().unwrap());
+        assert_eq!(registry.len(), 1);
+
+        assert!(matches!(
+            registry.replace(FakeSource::boxed("disk", false, false)),
+            Err(CollectorError::UnknownSource(name)) if name == "disk"
+        ));
+    }
+
+    #[test]
+    fn required_start_failure_aborts_startup() {
+        let mut registry = SourceRegistry::new();
+        registry
+            .register(FakeSource::boxed("a", true, true))
+            .unwrap();
+        registry
+            .register(FakeSource::boxed("b", false, false))
+            .unwrap();
+
+        assert!(registry.start_all().is_err());
+        assert_eq!(registry.health()[1].health, SourceHealth::Stopped);
+    }
+
+    #[test]
+    fn duplicate_names_are_rejected() {
+        let mut registry = SourceRegistry::new();
+        registry
+            .register(FakeSource::boxed("a", false, false))
+            .unwrap();
+
+        let error = registry
+            .register(FakeSource::boxed("a", false, false))
+            .unwrap_err();
+        assert!(matches!(error, CollectorError::DuplicateSource(name) if name == "a"));
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn from_config_registers_enabled_builtin_sources() {
+        let monitoring = Arc::new(SelfMonitoringCollector::new());
+        let mut config = Config::default();
+        config.collectors.crash_reports_dir = Some("/tmp/DiagnosticReports".into());
+        config.logging.files = vec![toml::from_str(
+            r#"
+            path = "/var/log/api.log"
+            pattern = '(?P<message>.*)'
+            "#,
+        )
+        .unwrap()];
+        config.logging.syslog.enabled = true;
+        config.collectors.exec = vec![ExecCollectorConfig {
+            name: "vpn".to_string(),
+            command: "vpn-probe".to_string(),
+            args: Vec::new(),
+        }];
+        let registry = SourceRegistry::from_config(&config, &sinks(), &monitoring);
+        assert_eq!(
+            registry.names().collect::<Vec<_>>(),
+            [BUILTIN_SOURCES, &["vpn"]].concat()
+        );
+
+        let mut config = Config::default();
+        config.logging.source = LogSource::Journald;
+        config.collectors.disabled = vec!["disk".to_string(), "crash_reports".to_string()];
+        let registry = SourceRegistry::from_config(&config, &sinks(), &monitoring);
+        assert_eq!(registry.names().collect::<Vec<_>>(), ["log", "metrics"]);
+
+        // An exec entry reusing a registered name is skipped
+        let exec = ExecCollectorConfig {
+            name: "disk".to_string(),
+            command: "disk-probe".to_string(),
+            args: Vec::new(),
+        };
+        let mut config = Config::default();
+        config.collectors.exec = vec![exec];
+        let registry = SourceRegistry::from_config(&config, &sinks(), &monitoring);
+        assert_eq!(registry.names().filter(|name| *name == "disk").count(), 1);
+    }
+}
@@ -0,0 +1,667 @@
+use super::journald_collector::message_type_for_priority;
+use super::source::{EventSource, SourceHealth};
+use crate::config::SyslogConfig;
+use crate::error::CollectorError;
+use crate::events::{LogEvent, Timestamp};
+use chrono::{DateTime, Datelike, Local, NaiveDateTime, TimeZone, Utc};
+use log::{debug, error, info, warn};
+use std::io::{ErrorKind, Read};
+use std::net::{SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Largest message accepted; longer TCP frames are discarded
+const MAX_MESSAGE_SIZE: usize = 64 * 1024;
+
+/// How long socket reads block before re-checking `running`
+const READ_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Facility names indexed by facility code
+const FACILITIES: &[&str] = &[
+    "kern",
+    "user",
+    "mail",
+    "daemon",
+    "auth",
+    "syslog",
+    "lpr",
+    "news",
+    "uucp",
+    "cron",
+    "authpriv",
+    "ftp",
+    "ntp",
+    "security",
+    "console",
+    "solaris-cron",
+    "local0",
+    "local1",
+    "local2",
+    "local3",
+    "local4",
+    "local5",
+    "local6",
+    "local7",
+];
+
+/// Syslog receiver for RFC 5424 and RFC 3164 messages
+///
+/// Listens on `logging.syslog.bind_address` over UDP (one message per
+/// datagram) and TCP (newline-delimited or RFC 6587 octet-counted frames).
+/// Each message becomes a `LogEvent`: severity maps onto `MessageType` like
+/// journald priorities, APP-NAME (or the BSD tag) becomes the process,
+/// PROCID the process id, HOSTNAME the subsystem and the facility name the
+/// category. Messages that are not syslog are dropped.
+pub struct SyslogCollector {
+    /// Listener settings
+    config: SyslogConfig,
+    /// Channel to send log events
+    output_channel: Sender<LogEvent>,
+    /// Address of the bound UDP socket
+    udp_addr: Option<SocketAddr>,
+    /// Address of the bound TCP listener
+    tcp_addr: Option<SocketAddr>,
+    /// Handles to the listener threads
+    thread_handles: Vec<JoinHandle<()>>,
+    /// Shared state for controlling the collector
+    running: Arc<Mutex<bool>>,
+}
+
+impl SyslogCollector {
+    /// Create a collector for the given listener settings
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::sync::mpsc;
+    /// use eyes::collectors::SyslogCollector;
+    /// use eyes::config::SyslogConfig;
+    ///
+    /// let (tx, rx) = mpsc::channel();
+    /// let mut collector = SyslogCollector::new(SyslogConfig::default(), tx);
+    /// collector.start().unwrap();
+    /// ```
+    pub fn new(config: SyslogConfig, channel: Sender<LogEvent>) -> Self {
+        Self {
+            config,
+            output_channel: channel,
+            udp_addr: None,
+            tcp_addr: None,
+            thread_handles: Vec::new(),
+            running: Arc::new(Mutex::new(false)),
+        }
+    }
+
+    /// Bind the configured sockets and start receiving on background threads
+    ///
+    /// # Errors
+    ///
+    /// Returns `CollectorError::ParseError` for an invalid bind address and
+    /// `CollectorError::IoError` if a socket cannot be bound.
+    pub fn start(&mut self) -> Result<(), CollectorError> {
+        info!(
+            "Starting SyslogCollector on {} (udp: {}, tcp: {})",
+            self.config.bind_address, self.config.udp, self.config.tcp
+        );
+
+        let mut running = self.running.lock().unwrap();
+        if *running {
+            info!("SyslogCollector already running, skipping start");
+            return Ok(());
+        }
+
+        let address: SocketAddr = self.config.bind_address.parse().map_err(|e| {
+            CollectorError::ParseError(format!(
+                "invalid syslog bind address '{}': {}",
+                self.config.bind_address, e
+            ))
+        })?;
+
+        // Bind everything before spawning so a busy port fails start()
+        let udp = if self.config.udp {
+            let socket = UdpSocket::bind(address)?;
+            socket.set_read_timeout(Some(READ_TIMEOUT))?;
+            Some(socket)
+        } else {
+            None
+        };
+        let tcp = if self.config.tcp {
+            let listener = TcpListener::bind(address)?;
+            listener.set_nonblocking(true)?;
+            Some(listener)
+        } else {
+            None
+        };
+        self.udp_addr = udp.as_ref().map(UdpSocket::local_addr).transpose()?;
+        self.tcp_addr = tcp.as_ref().map(TcpListener::local_addr).transpose()?;
+        *running = true;
+
+        if let Some(socket) = udp {
+            let channel = self.output_channel.clone();
+            let running = Arc::clone(&self.running);
+            self.thread_handles.push(thread::spawn(move || {
+                Self::udp_thread(socket, channel, running);
+            }));
+        }
+        if let Some(listener) = tcp {
+            let channel = self.output_channel.clone();
+            let running = Arc::clone(&self.running);
+            self.thread_handles.push(thread::spawn(move || {
+                Self::tcp_thread(listener, channel, running);
+            }));
+        }
+
+        info!("SyslogCollector started successfully");
+        Ok(())
+    }
+
+    /// Stop receiving and wait for all listener threads to finish
+    ///
+    /// # Errors
+    ///
+    /// Returns `CollectorError::SubprocessTerminated` if a thread panicked.
+    pub fn stop(&mut self) -> Result<(), CollectorError> {
+        info!("Stopping SyslogCollector");
+        *self.running.lock().unwrap() = false;
+
+        let mut result = Ok(());
+        for handle in self.thread_handles.drain(..) {
+            if handle.join().is_err() {
+                error!("Failed to join SyslogCollector thread");
+                result = Err(CollectorError::SubprocessTerminated(
+                    "Failed to join collector thread".to_string(),
+                ));
+            }
+        }
+
+        info!("SyslogCollector stopped");
+        result
+    }
+
+    /// Check if the collector is currently running
+    pub fn is_running(&self) -> bool {
+        *self.running.lock().unwrap()
+    }
+
+    /// Address the UDP socket is bound to, once started
+    pub fn udp_local_addr(&self) -> Option<SocketAddr> {
+        self.udp_addr
+    }
+
+    /// Address the TCP listener is bound to, once started
+    pub fn tcp_local_addr(&self) -> Option<SocketAddr> {
+        self.tcp_addr
+    }
+
+    fn udp_thread(socket: UdpSocket, channel: Sender<LogEvent>, running: Arc<Mutex<bool>>) {
+        let mut buffer = vec![0u8; MAX_MESSAGE_SIZE];
+        while *running.lock().unwrap() {
+            match socket.recv_from(&mut buffer) {
+                Ok((length, peer)) => {
+                    if !deliver(&buffer[..length], &channel, &running) {
+                        break;
+                    }
+                    debug!("Received syslog datagram from {}", peer);
+                }
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                Err(e) => {
+                    warn!("Syslog UDP receive failed: {}", e);
+                    super::wait_for_retry(Duration::from_secs(1), &running);
+                }
+            }
+        }
+
+        debug!("Syslog UDP thread exiting");
+    }
+
+    fn tcp_thread(listener: TcpListener, channel: Sender<LogEvent>, running: Arc<Mutex<bool>>) {
+        let mut connections: Vec<JoinHandle<()>> = Vec::new();
+        while *running.lock().unwrap() {
+            match listener.accept() {
+                Ok((stream, peer)) => {
+                    debug!("Accepted syslog connection from {}", peer);
+                    let channel = channel.clone();
+                    let running = Arc::clone(&running);
+                    connections.push(thread::spawn(move || {
+                        Self::connection_thread(stream, channel, running);
+                    }));
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                    connections.retain(|handle| !handle.is_finished());
+                    thread::sleep(Duration::from_millis(100));
+                }
+                Err(e) => {
+                    warn!("Syslog TCP accept failed: {}", e);
+                    super::wait_for_retry(Duration::from_secs(1), &running);
+                }
+            }
+        }
+
+        for handle in connections {
+            let _ = handle.join();
+        }
+        debug!("Syslog TCP thread exiting");
+    }
+
+    fn connection_thread(
+        mut stream: TcpStream,
+        channel: Sender<LogEvent>,
+        running: Arc<Mutex<bool>>,
+    ) {
+        if let Err(e) = stream
+            .set_nonblocking(false)
+            .and_then(|_| stream.set_read_timeout(Some(READ_TIMEOUT)))
+        {
+            warn!("Failed to configure syslog connection: {}", e);
+            return;
+        }
+
+        let mut buffer = Vec::new();
+        let mut chunk = [0u8; 8192];
+        while *running.lock().unwrap() {
+            match stream.read(&mut chunk) {
+                Ok(0) => {
+                    // A final message without a trailing newline
+                    deliver(&buffer, &channel, &running);
+                    break;
+                }
+                Ok(read) => {
+                    buffer.extend_from_slice(&chunk[..read]);
+                    while let Some(frame) = next_frame(&mut buffer) {
+                        if !deliver(&frame, &channel, &running) {
+                            return;
+                        }
+                    }
+                    if buffer.len() > MAX_MESSAGE_SIZE {
+                        warn!("Discarding oversized syslog frame");
+                        buffer.clear();
+                    }
+                }
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                Err(e) => {
+                    debug!("Syslog connection closed: {}", e);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+impl EventSource for SyslogCollector {
+    fn name(&self) -> &str {
+        "syslog"
+    }
+
+    fn start(&mut self) -> Result<(), CollectorError> {
+        SyslogCollector::start(self)
+    }
+
+    fn stop(&mut self) -> Result<(), CollectorError> {
+        SyslogCollector::stop(self)
+    }
+
+    fn health(&self) -> SourceHealth {
+        if self.is_running() {
+            SourceHealth::Running
+        } else {
+            SourceHealth::Stopped
+        }
+    }
+}
+
+impl Drop for SyslogCollector {
+    fn drop(&mut self) {
+        if self.is_running() || !self.thread_handles.is_empty() {
+            let _ = self.stop();
+        }
+    }
+}
+
+/// Parse and send one message; `false` once the channel is closed
+fn deliver(bytes: &[u8], channel: &Sender<LogEvent>, running: &Arc<Mutex<bool>>) -> bool {
+    let text = String::from_utf8_lossy(bytes);
+    if text.trim().is_empty() {
+        return true;
+    }
+    let Some(event) = parse_syslog_message(&text, Utc::now()) else {
+        debug!("Dropping non-syslog message: {}", text.trim_end());
+        return true;
+    };
+    if channel.send(event).is_err() {
+        info!("Log channel closed, stopping SyslogCollector");
+        *running.lock().unwrap() = false;
+        return false;
+    }
+    true
+}
+
+/// Take the next complete TCP frame off the front of `buffer`
+///
+/// Frames starting with a length and a space are octet-counted (RFC 6587);
+/// anything else ends at a newline or NUL. A frame announcing more than
+/// `MAX_MESSAGE_SIZE` bytes is discarded along with the rest of the buffer.
+fn next_frame(buffer: &mut Vec<u8>) -> Option<Vec<u8>> {
+    let digits = buffer
+        .iter()
+        .take_while(|byte| byte.is_ascii_digit())
+        .count();
+    if digits > 0 && buffer.get(digits) == Some(&b' ') {
+        let length = std::str::from_utf8(&buffer[..digits])
+            .ok()
+            .and_then(|length| length.parse::<usize>().ok())
+            .filter(|length| *length <= MAX_MESSAGE_SIZE);
+        let Some(length) = length else {
+            warn!("Discarding oversized syslog frame");
+            buffer.clear();
+            return None;
+        };
+        let end = digits + 1 + length;
+        if buffer.len() < end {
+            return None;
+        }
+        let frame = buffer[digits + 1..end].to_vec();
+        buffer.drain(..end);
+        return Some(frame);
+    }
+
+    let end = buffer
+        .iter()
+        .position(|byte| *byte == b'\n' || *byte == 0)?;
+    let mut frame: Vec<u8> = buffer.drain(..=end).collect();
+    frame.pop();
+    Some(frame)
+}
+
+/// Parse an RFC 5424 or RFC 3164 message received at `received`
+///
+/// Returns `None` if the message has no valid `<PRI>`. Missing timestamps
+/// fall back to `received`.
+pub(crate) fn parse_syslog_message(message: &str, received: Timestamp) -> Option<LogEvent> {
+    let message = message.trim_end_matches(['\n', '\r', '\0']);
+    let rest = message.strip_prefix('<')?;
+    let close = rest.find('>').filter(|close| (1..=3).contains(close))?;
+    let priority: u8 = rest[..close].parse().ok()?;
+    if priority > 191 {
+        return None;
+    }
+    let rest = &rest[close + 1..];
+
+    let header = match rest.strip_prefix("1 ") {
+        Some(rest) => parse_rfc5424(rest, received)?,
+        None => parse_rfc3164(rest, received),
+    };
+
+    let hostname = header.hostname.unwrap_or_default();
+    Some(LogEvent {
+        timestamp: header.timestamp,
+        message_type: message_type_for_priority(priority % 8),
+        subsystem: hostname.to_string(),
+        category: FACILITIES[usize::from(priority / 8)].to_string(),
+        process: header
+            .app_name
+            .or(Some(hostname).filter(|host| !host.is_empty()))
+            .unwrap_or("syslog")
+            .to_string(),
+        process_id: header.proc_id.and_then(|pid| pid.parse().ok()).unwrap_or(0),
+        message: header.message.trim().to_string(),
+    })
+}
+
+/// Header fields shared by both formats
+struct SyslogHeader<'a> {
+    timestamp: Timestamp,
+    hostname: Option<&'a str>,
+    app_name: Option<&'a str>,
+    proc_id: Option<&'a str>,
+    message: &'a str,
+}
+
+/// `VERSION` already stripped: `TIMESTAMP HOSTNAME APP-NAME PROCID MSGID SD [MSG]`
+fn parse_rfc5424(rest: &str, received: Timestamp) -> Option<SyslogHeader<'_>> {
+    let mut fields = rest.splitn(6, ' ');
+    let timestamp = fields.next()?;
+    let hostname = fields.next()?;
+    let app_name = fields.next()?;
+    let proc_id = fields.next()?;
+    let _msg_id = fields.next()?;
+    let message = skip_structured_data(fields.next()?)?;
+    let message = message.strip_prefix(' ').unwrap_or(message);
+
+    Some(SyslogHeader {
+        timestamp: nil(timestamp)
+            .and_then(|timestamp| DateTime::parse_from_rfc3339(timestamp).ok())
+            .map(|timestamp| timestamp.with_timezone(&Utc))
+            .unwrap_or(received),
+        hostname: nil(hostname),
+        app_name: nil(app_name),
+        proc_id: nil(proc_id),
+        message: message.strip_prefix('\u{feff}').unwrap_or(message),
+    })
+}
+
+/// Return what follows STRUCTURED-DATA: `-` or one or more `[...]` elements
+fn skip_structured_data(data: &str) -> Option<&str> {
+    if let Some(rest) = data.strip_prefix('-') {
+        return Some(rest);
+    }
+
+    let mut rest = data;
+    while rest.starts_with('[') {
+        let mut in_quotes = false;
+        let mut escaped = false;
+        let end = rest.char_indices().find_map(|(index, ch)| {
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_quotes = !in_quotes,
+                ']' if !in_quotes => return Some(index),
+                _ => {}
+            }
+            None
+        })?;
+        rest = &rest[end + 1..];
+    }
+    Some(rest)
+}
+
+/// `Mmm dd hh:mm:ss [HOSTNAME] TAG[PID]: MSG`, with every part optional
+fn parse_rfc3164(rest: &str, received: Timestamp) -> SyslogHeader<'_> {
+    let (timestamp, rest) = match rest
+        .get(..15)
+        .and_then(|text| bsd_timestamp(text, received))
+    {
+        Some(timestamp) => (Some(timestamp), rest[15..].trim_start()),
+        None => (None, rest),
+    };
+
+    let mut hostname = None;
+    let mut rest = rest;
+    if timestamp.is_some() {
+        if let Some((first, remainder)) = rest.split_once(' ') {
+            if !looks_like_tag(first) {
+                hostname = Some(first);
+                rest = remainder.trim_start();
+            }
+        }
+    }
+
+    let mut app_name = None;
+    let mut proc_id = None;
+    if let Some((first, remainder)) = rest.split_once(' ') {
+        if looks_like_tag(first) {
+            let tag = first.trim_end_matches(':');
+            match tag.split_once('[') {
+                Some((name, pid)) => {
+                    app_name = Some(name);
+                    proc_id = Some(pid.trim_end_matches(']'));
+                }
+                None => app_name = Some(tag),
+            }
+            rest = remainder;
+        }
+    }
+
+    SyslogHeader {
+        timestamp: timestamp.unwrap_or(received),
+        hostname,
+        app_name: app_name.filter(|name| !name.is_empty()),
+        proc_id,
+        message: rest,
+    }
+}
+
+fn looks_like_tag(token: &str) -> bool {
+    token.ends_with(':') || (token.contains('[') && token.trim_end_matches(':').ends_with(']'))
+}
+
+/// Local-time BSD timestamp without a year; the year that puts it closest
+/// to `received` without being in the future is assumed
+fn bsd_timestamp(text: &str, received: Timestamp) -> Option<Timestamp> {
+    let year = received.with_timezone(&Local).year();
+    [year, year - 1].into_iter().find_map(|year| {
+        let naive =
+            NaiveDateTime::parse_from_str(&format!("{} {}", year, text), "%Y %b %e %H:%M:%S")
+                .ok()?;
+        let timestamp = Local
+            .from_local_datetime(&naive)
+            .earliest()?
+            .with_timezone(&Utc);
+        (timestamp <= received + chrono::Duration::days(1)).then_some(timestamp)
+    })
+}
+
+fn nil(value: &str) -> Option<&str> {
+    (value != "-" && !value.is_empty()).then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::MessageType;
+    use std::io::Write;
+    use std::sync::mpsc;
+
+    #[test]
+    fn parses_rfc5424_messages() {
+        let received = Utc::now();
+        let event = parse_syslog_message(
+            "<165>1 2024-05-01T10:15:30.003Z switch01 ospfd 2201 ID47 [exampleSDID@32473 iut=\"3\" note=\"a ] b\"][other@1 x=\"y\"] \u{feff}Neighbor down\n",
+            received,
+        )
+        .unwrap();
+        assert_eq!(
+            event.timestamp.to_rfc3339(),
+            "2024-05-01T10:15:30.003+00:00"
+        );
+        assert_eq!(event.message_type, MessageType::Info);
+        assert_eq!(event.category, "local4");
+        assert_eq!(event.subsystem, "switch01");
+        assert_eq!(event.process, "ospfd");
+        assert_eq!(event.process_id, 2201);
+        assert_eq!(event.message, "Neighbor down");
+
+        let event = parse_syslog_message("<11>1 - - - - - -", received).unwrap();
+        assert_eq!(event.timestamp, received);
+        assert_eq!(event.message_type, MessageType::Error);
+        assert_eq!(event.process, "syslog");
+        assert_eq!(event.message, "");
+    }
+
+    #[test]
+    fn parses_rfc3164_messages() {
+        let received = Utc::now();
+        let event = parse_syslog_message(
+            "<27>Jan  2 15:04:05 docker-host api[812]: connection refused",
+            received,
+        )
+        .unwrap();
+        assert_eq!(event.message_type, MessageType::Error);
+        assert_eq!(event.category, "daemon");
+        assert_eq!(event.subsystem, "docker-host");
+        assert_eq!(event.process, "api");
+        assert_eq!(event.process_id, 812);
+        assert_eq!(event.message, "connection refused");
+        let local = event.timestamp.with_timezone(&Local);
+        assert_eq!((local.month(), local.day()), (1, 2));
+        assert!(event.timestamp <= received + chrono::Duration::days(1));
+
+        let event =
+            parse_syslog_message("<10>Oct 11 22:14:15 sshd: auth failure", received).unwrap();
+        assert_eq!(event.message_type, MessageType::Fault);
+        assert_eq!(event.subsystem, "");
+        assert_eq!(event.process, "sshd");
+        assert_eq!(event.message, "auth failure");
+
+        let event = parse_syslog_message("<14>link flapping on port 3", received).unwrap();
+        assert_eq!(event.timestamp, received);
+        assert_eq!(event.process, "syslog");
+        assert_eq!(event.message, "link flapping on port 3");
+
+        assert!(parse_syslog_message("no priority", received).is_none());
+        assert!(parse_syslog_message("<192>1 - - - - - -", received).is_none());
+    }
+
+    #[test]
+    fn splits_octet_counted_and_newline_frames() {
+        let mut buffer = b"11 <13>1 - - -<13>hello\n<13>par".to_vec();
+        assert_eq!(next_frame(&mut buffer).unwrap(), b"<13>1 - - -");
+        assert_eq!(next_frame(&mut buffer).unwrap(), b"<13>hello");
+        assert!(next_frame(&mut buffer).is_none());
+        assert_eq!(buffer, b"<13>par");
+    }
+
+    #[test]
+    fn discards_frames_announcing_more_than_the_maximum_size() {
+        let mut buffer = b"18446744073709551615 <13>hello\n".to_vec();
+        assert!(next_frame(&mut buffer).is_none());
+        assert!(buffer.is_empty());
+
+        let mut buffer = format!("{} <13>hello\n", MAX_MESSAGE_SIZE + 1).into_bytes();
+        assert!(next_frame(&mut buffer).is_none());
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn receives_over_udp_and_tcp() {
+        let (sender, receiver) = mpsc::channel();
+        let config = SyslogConfig {
+            enabled: true,
+            bind_address: "127.0.0.1:0".to_string(),
+            ..SyslogConfig::default()
+        };
+        let mut collector = SyslogCollector::new(config, sender);
+        collector.start().unwrap();
+
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket
+            .send_to(
+                b"<11>1 - router - - - - udp message",
+                collector.udp_local_addr().unwrap(),
+            )
+            .unwrap();
+        let event = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(event.message, "udp message");
+        assert_eq!(event.process, "router");
+
+        let mut stream = TcpStream::connect(collector.tcp_local_addr().unwrap()).unwrap();
+        stream
+            .write_all(b"<11>first: one\n15 <11>second: two")
+            .unwrap();
+        drop(stream);
+        let first = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        let second = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(
+            (first.process.as_str(), first.message.as_str()),
+            ("first", "one")
+        );
+        assert_eq!(
+            (second.process.as_str(), second.message.as_str()),
+            ("second", "two")
+        );
+
+        collector.stop().unwrap();
+        assert!(!collector.is_running());
+    }
+}
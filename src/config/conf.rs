@@ -1,5 +1,12 @@
+use crate::aggregator::{DiskField, MetricField};
+use crate::collectors::source::BUILTIN_SOURCES;
 use crate::error::ConfigError;
-use crate::events::{MemoryPressure, Severity};
+use crate::events::{MemoryPressure, MessageType, Severity};
+use crate::triggers::rules::{
+    DEFAULT_CRASH_KEYWORDS, DEFAULT_DISK_READ_SPIKE_THRESHOLD_KB_PER_SEC,
+    DEFAULT_DISK_WRITE_SPIKE_THRESHOLD_KB_PER_SEC,
+};
+use crate::triggers::{DeclarativeRule, HeartbeatRule, BUILTIN_RULES};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use std::time::Duration;
@@ -42,6 +49,56 @@ pub struct Config {
     /// Local web dashboard configuration
     #[serde(default)]
     pub web: WebConfig,
+
+    /// Event source selection
+    #[serde(default)]
+    pub collectors: CollectorsConfig,
+}
+
+/// Event source selection
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CollectorsConfig {
+    /// Sources that should not be started: built-in names ("log", "metrics",
+    /// "disk", "crash_reports", "files", "syslog") or `[[collectors.exec]]` names
+    #[serde(default)]
+    pub disabled: Vec<String>,
+
+    /// Directory scanned for `.ips` crash reports; defaults to
+    /// `~/Library/Logs/DiagnosticReports` on macOS
+    #[serde(default)]
+    pub crash_reports_dir: Option<PathBuf>,
+
+    /// External commands that emit newline-delimited JSON events
+    #[serde(default)]
+    pub exec: Vec<ExecCollectorConfig>,
+}
+
+/// External plugin command run by an exec source
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ExecCollectorConfig {
+    /// Source name used in logs, health reports and `collectors.disabled`
+    pub name: String,
+
+    /// Program to run; looked up on `PATH` when not a path
+    pub command: String,
+
+    /// Arguments passed to the program
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+impl CollectorsConfig {
+    /// Crash report directory to watch, if any exists for this platform
+    pub fn resolved_crash_reports_dir(&self) -> Option<PathBuf> {
+        if let Some(directory) = &self.crash_reports_dir {
+            return Some(directory.clone());
+        }
+        if !cfg!(target_os = "macos") {
+            return None;
+        }
+        std::env::var_os("HOME")
+            .map(|home| PathBuf::from(home).join("Library/Logs/DiagnosticReports"))
+    }
 }
 
 /// Logging configuration
@@ -50,8 +107,137 @@ pub struct LoggingConfig {
     /// Predicate filter for log stream (Apple's query language)
     #[serde(default = "default_log_predicate")]
     pub predicate: String,
+
+    /// Log backend; `auto` uses journald on Linux and the Unified Log elsewhere
+    #[serde(default)]
+    pub source: LogSource,
+
+    /// Most verbose journald priority to follow (`journalctl -p`)
+    #[serde(default = "default_journal_priority")]
+    pub journal_priority: String,
+
+    /// Read this `journalctl -o json` export instead of following the journal
+    #[serde(default)]
+    pub journal_file: Option<PathBuf>,
+
+    /// Application log files to tail in addition to the system log
+    #[serde(default)]
+    pub files: Vec<LogFileConfig>,
+
+    /// Syslog listener for devices and containers that only speak syslog
+    #[serde(default)]
+    pub syslog: SyslogConfig,
+}
+
+/// Syslog receiver used by the `syslog` source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyslogConfig {
+    /// Whether to listen for syslog messages
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Socket address to listen on; the same port is used for UDP and TCP
+    #[serde(default = "default_syslog_bind_address")]
+    pub bind_address: String,
+
+    /// Accept datagrams over UDP
+    #[serde(default = "default_true")]
+    pub udp: bool,
+
+    /// Accept newline-delimited or octet-counted streams over TCP
+    #[serde(default = "default_true")]
+    pub tcp: bool,
 }
 
+/// One application log file tailed by the `files` source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogFileConfig {
+    /// File to follow; rotation by rename and truncation are handled
+    pub path: PathBuf,
+
+    /// How each line is parsed
+    #[serde(default)]
+    pub format: LogFileFormat,
+
+    /// Regex with named groups `message` (required), `timestamp`, `level`,
+    /// `process`, `pid` and `subsystem`; used when `format = "regex"`
+    #[serde(default)]
+    pub pattern: Option<String>,
+
+    /// JSON field names; used when `format = "json"`
+    #[serde(default)]
+    pub fields: LogFileFields,
+
+    /// chrono format of the timestamp; RFC 3339 and epoch seconds are tried when unset
+    #[serde(default)]
+    pub timestamp_format: Option<String>,
+
+    /// Process name when the line has none; defaults to the file stem
+    #[serde(default)]
+    pub process: Option<String>,
+
+    /// Subsystem when the line has none
+    #[serde(default)]
+    pub subsystem: Option<String>,
+}
+
+/// Line format of a tailed log file
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFileFormat {
+    /// Lines matched against `pattern`
+    #[default]
+    Regex,
+    /// One JSON object per line
+    Json,
+}
+
+/// JSON field names mapped onto `LogEvent`; dotted names reach nested objects
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LogFileFields {
+    #[serde(default = "default_field_timestamp")]
+    pub timestamp: String,
+    #[serde(default = "default_field_level")]
+    pub level: String,
+    #[serde(default = "default_field_message")]
+    pub message: String,
+    #[serde(default = "default_field_process")]
+    pub process: String,
+    #[serde(default = "default_field_pid")]
+    pub pid: String,
+    #[serde(default = "default_field_subsystem")]
+    pub subsystem: String,
+}
+
+/// Backend used for log events
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LogSource {
+    /// journald on Linux, the Unified Log everywhere else
+    #[default]
+    Auto,
+    /// macOS `log stream`
+    UnifiedLog,
+    /// systemd journal via `journalctl`
+    Journald,
+}
+
+impl LogSource {
+    /// Resolve `Auto` for the current platform
+    pub fn resolve(self) -> Self {
+        match self {
+            Self::Auto if cfg!(target_os = "linux") => Self::Journald,
+            Self::Auto => Self::UnifiedLog,
+            source => source,
+        }
+    }
+}
+
+/// Priority names accepted by `journalctl -p`, most severe first
+pub const JOURNAL_PRIORITIES: &[&str] = &[
+    "emerg", "alert", "crit", "err", "warning", "notice", "info", "debug",
+];
+
 /// Metrics collection configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MetricsConfig {
@@ -75,17 +261,711 @@ pub struct BufferConfig {
 /// Trigger configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TriggersConfig {
-    /// Number of errors required to trigger AI analysis
-    #[serde(default = "default_error_threshold")]
-    pub error_threshold: usize,
+    /// Deprecated `error_threshold` key; use `[triggers.error_frequency]
+    /// threshold`, which takes precedence
+    #[serde(
+        default,
+        rename = "error_threshold",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub legacy_error_threshold: Option<usize>,
+
+    /// Deprecated `error_window_seconds` key; use
+    /// `[triggers.error_frequency] window_seconds`, which takes precedence
+    #[serde(
+        default,
+        rename = "error_window_seconds",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub legacy_error_window_seconds: Option<u64>,
+
+    /// Deprecated `memory_threshold` key; use `[triggers.memory_pressure]
+    /// threshold`, which takes precedence
+    #[serde(
+        default,
+        rename = "memory_threshold",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub legacy_memory_threshold: Option<MemoryPressure>,
+
+    /// Minimum time between alerts for the same rule and source (in
+    /// seconds), for rules without their own `cooldown_seconds`
+    #[serde(default = "default_trigger_cooldown_secs")]
+    pub cooldown_seconds: u64,
+
+    /// `ErrorFrequencyRule` settings
+    #[serde(default)]
+    pub error_frequency: ErrorFrequencyConfig,
+
+    /// `MemoryPressureRule` settings
+    #[serde(default)]
+    pub memory_pressure: MemoryPressureConfig,
+
+    /// `CrashDetectionRule` settings
+    #[serde(default)]
+    pub crash_detection: CrashDetectionConfig,
+
+    /// `ResourceSpikeRule` settings
+    #[serde(default)]
+    pub resource_spike: ResourceSpikeConfig,
+
+    /// `DiskIOSpikeRule` settings
+    #[serde(default)]
+    pub disk_io_spike: DiskIOSpikeConfig,
+
+    /// Adaptive anomaly detection over learned metric baselines
+    #[serde(default)]
+    pub anomaly: AnomalyConfig,
+
+    /// Alerts on new error templates and template rate jumps
+    #[serde(default)]
+    pub templates: TemplatesConfig,
+
+    /// Respawn loop detection from process identities
+    #[serde(default)]
+    pub respawn: RespawnConfig,
+
+    /// Per-process memory leak detection from process metrics
+    #[serde(default)]
+    pub memory_leak: MemoryLeakConfig,
+
+    /// Sustained per-process CPU usage from process metrics
+    #[serde(default)]
+    pub cpu_hog: CpuHogConfig,
+
+    /// User-defined rules from `[[triggers.rules]]`
+    #[serde(default)]
+    pub rules: Vec<TriggerRuleConfig>,
+
+    /// Expected log sources from `[[triggers.heartbeats]]`
+    #[serde(default)]
+    pub heartbeats: Vec<HeartbeatConfig>,
+
+    /// Correlations of other rules from `[[triggers.composite]]`
+    #[serde(default)]
+    pub composite: Vec<CompositeRuleConfig>,
+}
+
+/// `ErrorFrequencyRule` settings from `[triggers.error_frequency]`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorFrequencyConfig {
+    /// Whether to add the rule to the trigger engine
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// The rule fires when more distinct errors than this arrive within the
+    /// window; see [`TriggersConfig::error_threshold`] for the default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<usize>,
+
+    /// Time window for error counting (in seconds); see
+    /// [`TriggersConfig::error_window_seconds`] for the default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_seconds: Option<u64>,
+
+    /// Severity of the alert candidate
+    #[serde(default = "default_rule_severity")]
+    pub severity: Severity,
+
+    /// Minimum time between alerts for the same source (in seconds);
+    /// `triggers.cooldown_seconds` when unset
+    #[serde(default)]
+    pub cooldown_seconds: Option<u64>,
+
+    /// Only fire again for a source after its condition has cleared
+    #[serde(default)]
+    pub rearm: bool,
+}
+
+/// `MemoryPressureRule` settings from `[triggers.memory_pressure]`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryPressureConfig {
+    /// Whether to add the rule to the trigger engine
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Memory pressure level that fires the rule; see
+    /// [`TriggersConfig::memory_threshold`] for the default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<MemoryPressure>,
+
+    /// Severity when the observed pressure does not set one
+    #[serde(default = "default_rule_severity")]
+    pub severity: Severity,
+
+    /// Minimum time between alerts (in seconds); `triggers.cooldown_seconds`
+    /// when unset
+    #[serde(default)]
+    pub cooldown_seconds: Option<u64>,
+
+    /// Only fire again after memory pressure has dropped below the threshold
+    #[serde(default)]
+    pub rearm: bool,
+}
+
+/// `CrashDetectionRule` settings from `[triggers.crash_detection]`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashDetectionConfig {
+    /// Whether to add the rule to the trigger engine
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Case-insensitive phrases that mark a log message as a crash
+    #[serde(default = "default_crash_keywords")]
+    pub keywords: Vec<String>,
+
+    /// Severity of the alert candidate
+    #[serde(default = "default_crash_severity")]
+    pub severity: Severity,
+
+    /// Minimum time between alerts for the same process (in seconds);
+    /// `triggers.cooldown_seconds` when unset
+    #[serde(default)]
+    pub cooldown_seconds: Option<u64>,
+
+    /// Only fire again for a process after its crash messages have left the
+    /// evaluation window
+    #[serde(default)]
+    pub rearm: bool,
+}
+
+/// `ResourceSpikeRule` settings from `[triggers.resource_spike]`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceSpikeConfig {
+    /// Whether to add the rule to the trigger engine
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Rise in CPU power over the window that counts as a spike (in milliwatts)
+    #[serde(default = "default_resource_spike_cpu_mw")]
+    pub cpu_threshold_mw: f64,
+
+    /// Rise in GPU power over the window that counts as a spike (in milliwatts)
+    #[serde(default = "default_resource_spike_gpu_mw")]
+    pub gpu_threshold_mw: f64,
+
+    /// Window the rise is measured over (in seconds)
+    #[serde(default = "default_spike_window_secs")]
+    pub window_seconds: u64,
+
+    /// Severity of the alert candidate
+    #[serde(default = "default_rule_severity")]
+    pub severity: Severity,
+
+    /// Minimum time between alerts (in seconds); `triggers.cooldown_seconds`
+    /// when unset
+    #[serde(default)]
+    pub cooldown_seconds: Option<u64>,
+
+    /// Only fire again after power has stopped spiking
+    #[serde(default)]
+    pub rearm: bool,
+}
+
+/// `DiskIOSpikeRule` settings from `[triggers.disk_io_spike]`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskIOSpikeConfig {
+    /// Whether to add the rule to the trigger engine
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Rise in read rate over the window that counts as a spike (in KB/s)
+    #[serde(default = "default_disk_spike_read_kb_per_sec")]
+    pub read_threshold_kb_per_sec: f64,
+
+    /// Rise in write rate over the window that counts as a spike (in KB/s)
+    #[serde(default = "default_disk_spike_write_kb_per_sec")]
+    pub write_threshold_kb_per_sec: f64,
+
+    /// Window the rise is measured over (in seconds)
+    #[serde(default = "default_spike_window_secs")]
+    pub window_seconds: u64,
+
+    /// Severity of the alert candidate
+    #[serde(default = "default_rule_severity")]
+    pub severity: Severity,
+
+    /// Minimum time between alerts for the same device or process (in
+    /// seconds); `triggers.cooldown_seconds` when unset
+    #[serde(default)]
+    pub cooldown_seconds: Option<u64>,
+
+    /// Only fire again for a source after its I/O has stopped spiking
+    #[serde(default)]
+    pub rearm: bool,
+}
+
+/// Enable flag, cooldown and re-arm of a built-in rule, with the cooldown
+/// default applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuiltinRuleControls {
+    /// Whether to add the rule to the trigger engine
+    pub enabled: bool,
+    /// Minimum time between alerts for the same source (in seconds)
+    pub cooldown_seconds: u64,
+    /// Only fire again for a source after its condition has cleared
+    pub rearm: bool,
+}
+
+/// Anomaly detection from `[triggers.anomaly]`
+///
+/// Each watched series keeps an exponentially weighted mean and variance.
+/// A series enters an excursion after `sustain_samples` consecutive samples
+/// at least `enter_z` standard deviations above its baseline, and leaves it
+/// on the first sample below `exit_z`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyConfig {
+    /// Whether to add `AnomalyRule` to the trigger engine
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Weight of each new sample in the moving baseline, in (0, 1]
+    #[serde(default = "default_anomaly_alpha")]
+    pub alpha: f64,
+
+    /// z-score a sample must reach to count toward an excursion
+    #[serde(default = "default_anomaly_enter_z")]
+    pub enter_z: f64,
+
+    /// z-score below which an excursion ends
+    #[serde(default = "default_anomaly_exit_z")]
+    pub exit_z: f64,
+
+    /// Consecutive samples at or above `enter_z` before the rule fires
+    #[serde(default = "default_anomaly_sustain_samples")]
+    pub sustain_samples: usize,
+
+    /// Samples a series learns from before it is scored
+    #[serde(default = "default_anomaly_warmup_samples")]
+    pub warmup_samples: usize,
+
+    /// System-wide metrics fields to watch
+    #[serde(default = "default_anomaly_metrics")]
+    pub metrics: Vec<MetricField>,
+
+    /// Disk fields to watch on every device
+    #[serde(default = "default_anomaly_disk")]
+    pub disk: Vec<DiskField>,
+
+    /// Severity of the alert candidate
+    #[serde(default = "default_rule_severity")]
+    pub severity: Severity,
+
+    /// Minimum time between alerts for the same source (in seconds);
+    /// `triggers.cooldown_seconds` when unset
+    #[serde(default)]
+    pub cooldown_seconds: Option<u64>,
+
+    /// Only fire again for a source after its condition has cleared
+    #[serde(default)]
+    pub rearm: bool,
+}
+
+/// Log template alerts from `[triggers.templates]`
+///
+/// A template is new when its first error or fault arrived within the last
+/// `window_seconds`, after the miner has been learning for
+/// `learning_seconds`. A known template's rate jumps when its count in the
+/// window is at least `min_count` and `rate_factor` times its long-run rate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplatesConfig {
+    /// Whether to add `LogTemplateRule` to the trigger engine
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Window in which new templates and rates are checked (in seconds)
+    #[serde(default = "default_templates_window_secs")]
+    pub window_seconds: u64,
+
+    /// Time after the first mined message before templates count as new,
+    /// and the minimum age of a template before its rate is compared
+    #[serde(default = "default_templates_learning_secs")]
+    pub learning_seconds: u64,
+
+    /// Multiple of the long-run rate that counts as a jump
+    #[serde(default = "default_templates_rate_factor")]
+    pub rate_factor: f64,
+
+    /// Matches in the window required before a rate jump fires
+    #[serde(default = "default_templates_min_count")]
+    pub min_count: u64,
+
+    /// Severity of the alert candidate
+    #[serde(default = "default_rule_severity")]
+    pub severity: Severity,
+
+    /// Minimum time between alerts for the same source (in seconds);
+    /// `triggers.cooldown_seconds` when unset
+    #[serde(default)]
+    pub cooldown_seconds: Option<u64>,
+
+    /// Only fire again for a source after its condition has cleared
+    #[serde(default)]
+    pub rearm: bool,
+}
+
+/// One declarative trigger rule from `[[triggers.rules]]`
+///
+/// The log fields (`process`, `subsystem`, `category`, `message`,
+/// `message_types`) form a single log condition that holds when at least
+/// `count` matching events arrived within `window_seconds`. Every entry in
+/// `metrics`, `memory_pressure` and `expression` must hold as well. A rule
+/// needs at least one condition.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerRuleConfig {
+    /// Rule name shown in alerts and used as the cooldown key
+    pub name: String,
+
+    /// Severity of the alert candidate
+    #[serde(default = "default_rule_severity")]
+    pub severity: Severity,
+
+    /// Exact process name
+    #[serde(default)]
+    pub process: Option<String>,
+
+    /// Exact subsystem
+    #[serde(default)]
+    pub subsystem: Option<String>,
+
+    /// Exact category
+    #[serde(default)]
+    pub category: Option<String>,
+
+    /// Regex searched for in the message
+    #[serde(default)]
+    pub message: Option<String>,
+
+    /// Accepted message types; any type when empty
+    #[serde(default)]
+    pub message_types: Vec<MessageType>,
+
+    /// Matching log events needed within the window
+    #[serde(default = "default_rule_count")]
+    pub count: usize,
+
+    /// Window for both the log count and metric conditions (in seconds)
+    #[serde(default = "default_rule_window_secs")]
+    pub window_seconds: u64,
+
+    /// Count matches per process or subsystem, with one alert per source
+    #[serde(default)]
+    pub group_by: Option<RuleGroupBy>,
+
+    /// Numeric metric comparisons that must all hold
+    #[serde(default)]
+    pub metrics: Vec<MetricConditionConfig>,
+
+    /// Memory pressure level that must be reached within the window
+    #[serde(default)]
+    pub memory_pressure: Option<MemoryPressure>,
+
+    /// Rule expression that must hold; aggregates without `within` use
+    /// `window_seconds`
+    #[serde(default)]
+    pub expression: Option<String>,
+
+    /// Trigger reason template; see `docs/trigger-rules.md` for placeholders
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// One expected log source from `[[triggers.heartbeats]]`
+///
+/// The source is the log events of `process` and/or `subsystem`, narrowed to
+/// those whose message matches `message` when set. It is silent when fewer
+/// than `min_count` of them arrived in the last `max_gap_seconds`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeartbeatConfig {
+    /// Rule name shown in alerts
+    pub name: String,
+
+    /// Exact process name
+    #[serde(default)]
+    pub process: Option<String>,
+
+    /// Exact subsystem
+    #[serde(default)]
+    pub subsystem: Option<String>,
+
+    /// Regex the heartbeat message must match; any message when unset
+    #[serde(default)]
+    pub message: Option<String>,
+
+    /// Longest expected gap between heartbeats (in seconds)
+    #[serde(default = "default_heartbeat_max_gap_secs")]
+    pub max_gap_seconds: u64,
+
+    /// Heartbeats expected within each `max_gap_seconds`
+    #[serde(default = "default_heartbeat_min_count")]
+    pub min_count: usize,
+
+    /// Severity of the alert candidate
+    #[serde(default = "default_rule_severity")]
+    pub severity: Severity,
+}
+
+/// Respawn loop detection from `[triggers.respawn]`
+///
+/// A process respawns when a new PID appears for it after its previous PID
+/// was last seen. The rule fires when a process respawns `min_respawns`
+/// times within `window_seconds`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RespawnConfig {
+    /// Whether to add `RespawnLoopRule` to the trigger engine
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Window in which respawns are counted (in seconds)
+    #[serde(default = "default_respawn_window_secs")]
+    pub window_seconds: u64,
+
+    /// Respawns within the window that make a loop
+    #[serde(default = "default_respawn_min_respawns")]
+    pub min_respawns: usize,
+
+    /// Processes that legitimately start a new PID for each job
+    #[serde(default = "default_respawn_ignore_processes")]
+    pub ignore_processes: Vec<String>,
+
+    /// Severity of the alert candidate
+    #[serde(default = "default_rule_severity")]
+    pub severity: Severity,
+
+    /// Minimum time between alerts for the same source (in seconds);
+    /// `triggers.cooldown_seconds` when unset
+    #[serde(default)]
+    pub cooldown_seconds: Option<u64>,
+
+    /// Only fire again for a source after its condition has cleared
+    #[serde(default)]
+    pub rearm: bool,
+}
+
+/// Per-process memory leak detection from `[triggers.memory_leak]`
+///
+/// The rule keeps one resident memory sample per minute for each process
+/// instance and fires when the samples over `window_seconds` rise steadily
+/// and the fitted trend grows by at least `min_growth_mb`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryLeakConfig {
+    /// Whether to add `MemoryLeakRule` to the trigger engine
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Window over which the memory trend is fitted (in seconds)
+    #[serde(default = "default_memory_leak_window_secs")]
+    pub window_seconds: u64,
+
+    /// Per-minute samples required before a trend is judged
+    #[serde(default = "default_memory_leak_min_samples")]
+    pub min_samples: usize,
+
+    /// Growth of the fitted trend across the samples that counts as a leak
+    /// (in megabytes)
+    #[serde(default = "default_memory_leak_min_growth_mb")]
+    pub min_growth_mb: f64,
+
+    /// Fraction of sample-to-sample steps that must not decrease
+    #[serde(default = "default_memory_leak_min_rising_fraction")]
+    pub min_rising_fraction: f64,
+
+    /// How far ahead the reported projected size looks (in seconds)
+    #[serde(default = "default_memory_leak_projection_secs")]
+    pub projection_seconds: u64,
+
+    /// Processes whose memory growth is expected, such as caches
+    #[serde(default)]
+    pub ignore_processes: Vec<String>,
+
+    /// Severity of the alert candidate
+    #[serde(default = "default_rule_severity")]
+    pub severity: Severity,
+
+    /// Minimum time between alerts for the same source (in seconds);
+    /// `triggers.cooldown_seconds` when unset
+    #[serde(default)]
+    pub cooldown_seconds: Option<u64>,
+
+    /// Only fire again for a source after its condition has cleared
+    #[serde(default)]
+    pub rearm: bool,
+}
+
+/// Sustained per-process CPU usage from `[triggers.cpu_hog]`
+///
+/// The rule fires when one process instance stays at or above
+/// `cpu_percent` for `duration_seconds`. Processes in `allow_processes` are
+/// never reported; `overrides` set other limits for named processes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CpuHogConfig {
+    /// Whether to add `CpuHogRule` to the trigger engine
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// CPU usage a process must stay at or above, where 100 is one core
+    #[serde(default = "default_cpu_hog_percent")]
+    pub cpu_percent: f64,
+
+    /// How long the usage must last (in seconds)
+    #[serde(default = "default_cpu_hog_duration_secs")]
+    pub duration_seconds: u64,
+
+    /// Processes expected to use a lot of CPU, such as compilers and indexers
+    #[serde(default = "default_cpu_hog_allow_processes")]
+    pub allow_processes: Vec<String>,
+
+    /// Limits for individual processes from `[[triggers.cpu_hog.overrides]]`
+    #[serde(default)]
+    pub overrides: Vec<CpuHogOverride>,
+
+    /// Severity of the alert candidate
+    #[serde(default = "default_rule_severity")]
+    pub severity: Severity,
+
+    /// Minimum time between alerts for the same source (in seconds);
+    /// `triggers.cooldown_seconds` when unset
+    #[serde(default)]
+    pub cooldown_seconds: Option<u64>,
+
+    /// Only fire again for a source after its condition has cleared
+    #[serde(default)]
+    pub rearm: bool,
+}
+
+/// Per-process limits from `[[triggers.cpu_hog.overrides]]`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CpuHogOverride {
+    /// Exact process name
+    pub process: String,
+
+    /// CPU usage for this process; the rule's `cpu_percent` when unset
+    #[serde(default)]
+    pub cpu_percent: Option<f64>,
+
+    /// Duration for this process; the rule's `duration_seconds` when unset
+    #[serde(default)]
+    pub duration_seconds: Option<u64>,
+}
+
+/// One composite rule from `[[triggers.composite]]`
+///
+/// `rules` names built-in rules, `[[triggers.rules]]` entries or
+/// `[[triggers.heartbeats]]` entries. The composite evaluates its own copies
+/// of them, so they keep firing on their own as well.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompositeRuleConfig {
+    /// Rule name shown in alerts and used as the cooldown key
+    pub name: String,
+
+    /// How the child rules' firings are correlated
+    pub mode: CompositeMode,
+
+    /// Child rules, in order for `sequence`; `absence` takes exactly two
+    pub rules: Vec<String>,
+
+    /// Window the correlated firings must fall within (in seconds)
+    #[serde(default = "default_composite_window_secs")]
+    pub window_seconds: u64,
+
+    /// Severity of the alert candidate; the highest severity among the
+    /// firings that matched when unset
+    #[serde(default)]
+    pub severity: Option<Severity>,
+}
+
+/// How a composite rule correlates its children
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CompositeMode {
+    /// Every child fired within the window
+    All,
+    /// The children fired in the listed order within the window
+    Sequence,
+    /// The first child fired and the second did not within the window
+    Absence,
+}
+
+/// Log field a declarative rule groups its matches by
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleGroupBy {
+    Process,
+    Subsystem,
+}
+
+/// Comparison of a metric over a declarative rule's window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricConditionConfig {
+    /// Metrics field to compare
+    pub field: MetricField,
+
+    /// How samples in the window are reduced to one value
+    #[serde(default)]
+    pub aggregate: MetricAggregate,
+
+    /// Comparison operator
+    pub op: ComparisonOp,
+
+    /// Right-hand side of the comparison
+    pub value: f64,
+}
+
+/// Reduction of the metric samples in a window
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricAggregate {
+    /// Most recent sample
+    #[default]
+    Last,
+    Min,
+    Max,
+    Avg,
+}
+
+/// Numeric comparison operator
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ComparisonOp {
+    #[serde(rename = ">")]
+    Gt,
+    #[serde(rename = ">=")]
+    Ge,
+    #[serde(rename = "<")]
+    Lt,
+    #[serde(rename = "<=")]
+    Le,
+    #[serde(rename = "==")]
+    Eq,
+    #[serde(rename = "!=")]
+    Ne,
+}
 
-    /// Time window for error counting (in seconds)
-    #[serde(default = "default_error_window_secs")]
-    pub error_window_seconds: u64,
+impl ComparisonOp {
+    /// Apply the comparison as `lhs <op> rhs`
+    pub fn compare(self, lhs: f64, rhs: f64) -> bool {
+        match self {
+            Self::Gt => lhs > rhs,
+            Self::Ge => lhs >= rhs,
+            Self::Lt => lhs < rhs,
+            Self::Le => lhs <= rhs,
+            Self::Eq => lhs == rhs,
+            Self::Ne => lhs != rhs,
+        }
+    }
+}
 
-    /// Memory pressure level that triggers AI analysis
-    #[serde(default = "default_memory_threshold")]
-    pub memory_threshold: MemoryPressure,
+impl std::fmt::Display for ComparisonOp {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let symbol = match self {
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Eq => "==",
+            Self::Ne => "!=",
+        };
+        formatter.write_str(symbol)
+    }
 }
 
 /// AI backend configuration
@@ -118,6 +998,10 @@ pub struct StorageConfig {
     /// SQLite database that stores alert history
     #[serde(default = "default_database_path")]
     pub database_path: PathBuf,
+    /// JSON file that carries the rolling buffer and trigger cooldowns
+    /// across restarts
+    #[serde(default = "default_state_path")]
+    pub state_path: PathBuf,
 }
 
 /// Local alert dashboard configuration
@@ -130,6 +1014,10 @@ pub struct WebConfig {
     /// Socket address used by the dashboard server
     #[serde(default = "default_web_bind_address")]
     pub bind_address: String,
+
+    /// Accept OTLP/HTTP JSON logs and metrics on `/v1/logs` and `/v1/metrics`
+    #[serde(default)]
+    pub otlp: bool,
 }
 
 /// AI backend configuration options
@@ -165,6 +1053,42 @@ fn default_log_predicate() -> String {
     "messageType == error OR messageType == fault".to_string()
 }
 
+fn default_journal_priority() -> String {
+    "err".to_string()
+}
+
+fn default_field_timestamp() -> String {
+    "timestamp".to_string()
+}
+
+fn default_field_level() -> String {
+    "level".to_string()
+}
+
+fn default_field_message() -> String {
+    "message".to_string()
+}
+
+fn default_field_process() -> String {
+    "process".to_string()
+}
+
+fn default_field_pid() -> String {
+    "pid".to_string()
+}
+
+fn default_field_subsystem() -> String {
+    "subsystem".to_string()
+}
+
+fn default_syslog_bind_address() -> String {
+    "127.0.0.1:5514".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
 fn default_metrics_interval_secs() -> u64 {
     5
 }
@@ -189,6 +1113,170 @@ fn default_memory_threshold() -> MemoryPressure {
     MemoryPressure::Warning
 }
 
+fn default_rule_severity() -> Severity {
+    Severity::Warning
+}
+
+fn default_trigger_cooldown_secs() -> u64 {
+    300
+}
+
+fn default_crash_keywords() -> Vec<String> {
+    DEFAULT_CRASH_KEYWORDS
+        .iter()
+        .map(|keyword| keyword.to_string())
+        .collect()
+}
+
+fn default_crash_severity() -> Severity {
+    Severity::Critical
+}
+
+fn default_resource_spike_cpu_mw() -> f64 {
+    1000.0
+}
+
+fn default_resource_spike_gpu_mw() -> f64 {
+    2000.0
+}
+
+fn default_spike_window_secs() -> u64 {
+    30
+}
+
+fn default_disk_spike_read_kb_per_sec() -> f64 {
+    DEFAULT_DISK_READ_SPIKE_THRESHOLD_KB_PER_SEC
+}
+
+fn default_disk_spike_write_kb_per_sec() -> f64 {
+    DEFAULT_DISK_WRITE_SPIKE_THRESHOLD_KB_PER_SEC
+}
+
+fn default_rule_count() -> usize {
+    1
+}
+
+fn default_rule_window_secs() -> u64 {
+    60
+}
+
+fn default_anomaly_alpha() -> f64 {
+    0.05
+}
+
+fn default_anomaly_enter_z() -> f64 {
+    4.0
+}
+
+fn default_anomaly_exit_z() -> f64 {
+    2.0
+}
+
+fn default_anomaly_sustain_samples() -> usize {
+    3
+}
+
+fn default_anomaly_warmup_samples() -> usize {
+    30
+}
+
+fn default_anomaly_metrics() -> Vec<MetricField> {
+    vec![
+        MetricField::CpuUsagePercent,
+        MetricField::CpuPowerMw,
+        MetricField::GpuPowerMw,
+        MetricField::MemoryUsedMb,
+    ]
+}
+
+fn default_anomaly_disk() -> Vec<DiskField> {
+    vec![DiskField::ReadKbPerSec, DiskField::WriteKbPerSec]
+}
+
+fn default_templates_window_secs() -> u64 {
+    60
+}
+
+fn default_templates_learning_secs() -> u64 {
+    3600
+}
+
+fn default_templates_rate_factor() -> f64 {
+    10.0
+}
+
+fn default_templates_min_count() -> u64 {
+    10
+}
+
+fn default_heartbeat_max_gap_secs() -> u64 {
+    300
+}
+
+fn default_heartbeat_min_count() -> usize {
+    1
+}
+
+fn default_respawn_window_secs() -> u64 {
+    600
+}
+
+fn default_respawn_min_respawns() -> usize {
+    3
+}
+
+fn default_respawn_ignore_processes() -> Vec<String> {
+    vec!["xpcproxy".to_string(), "mdworker_shared".to_string()]
+}
+
+fn default_memory_leak_window_secs() -> u64 {
+    3600
+}
+
+fn default_memory_leak_min_samples() -> usize {
+    10
+}
+
+fn default_memory_leak_min_growth_mb() -> f64 {
+    100.0
+}
+
+fn default_memory_leak_min_rising_fraction() -> f64 {
+    0.8
+}
+
+fn default_memory_leak_projection_secs() -> u64 {
+    3600
+}
+
+fn default_cpu_hog_percent() -> f64 {
+    90.0
+}
+
+fn default_cpu_hog_duration_secs() -> u64 {
+    300
+}
+
+fn default_cpu_hog_allow_processes() -> Vec<String> {
+    [
+        "clang",
+        "swift-frontend",
+        "rustc",
+        "cc1",
+        "cc1plus",
+        "ld",
+        "SourceKitService",
+        "XCBBuildService",
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect()
+}
+
+fn default_composite_window_secs() -> u64 {
+    300
+}
+
 fn default_automatic_analysis() -> bool {
     true
 }
@@ -205,6 +1293,10 @@ fn default_database_path() -> PathBuf {
     PathBuf::from("eyes.db")
 }
 
+fn default_state_path() -> PathBuf {
+    PathBuf::from("eyes-state.json")
+}
+
 fn default_web_enabled() -> bool {
     true
 }
@@ -251,6 +1343,35 @@ impl Default for LoggingConfig {
     fn default() -> Self {
         Self {
             predicate: default_log_predicate(),
+            source: LogSource::default(),
+            journal_priority: default_journal_priority(),
+            journal_file: None,
+            files: Vec::new(),
+            syslog: SyslogConfig::default(),
+        }
+    }
+}
+
+impl Default for SyslogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: default_syslog_bind_address(),
+            udp: true,
+            tcp: true,
+        }
+    }
+}
+
+impl Default for LogFileFields {
+    fn default() -> Self {
+        Self {
+            timestamp: default_field_timestamp(),
+            level: default_field_level(),
+            message: default_field_message(),
+            process: default_field_process(),
+            pid: default_field_pid(),
+            subsystem: default_field_subsystem(),
         }
     }
 }
@@ -272,12 +1393,337 @@ impl Default for BufferConfig {
     }
 }
 
+impl TriggersConfig {
+    /// `ErrorFrequencyRule` threshold: `[triggers.error_frequency]
+    /// threshold`, else the deprecated `triggers.error_threshold`, else 5
+    pub fn error_threshold(&self) -> usize {
+        self.error_frequency
+            .threshold
+            .or(self.legacy_error_threshold)
+            .unwrap_or_else(default_error_threshold)
+    }
+
+    /// `ErrorFrequencyRule` window: `[triggers.error_frequency]
+    /// window_seconds`, else the deprecated `triggers.error_window_seconds`,
+    /// else 10
+    pub fn error_window_seconds(&self) -> u64 {
+        self.error_frequency
+            .window_seconds
+            .or(self.legacy_error_window_seconds)
+            .unwrap_or_else(default_error_window_secs)
+    }
+
+    /// `MemoryPressureRule` threshold: `[triggers.memory_pressure]
+    /// threshold`, else the deprecated `triggers.memory_threshold`, else
+    /// `Warning`
+    pub fn memory_threshold(&self) -> MemoryPressure {
+        self.memory_pressure
+            .threshold
+            .or(self.legacy_memory_threshold)
+            .unwrap_or_else(default_memory_threshold)
+    }
+
+    /// Deprecated top-level keys set in this configuration, with the keys
+    /// that replace them
+    pub fn deprecated_keys(&self) -> Vec<(&'static str, &'static str)> {
+        [
+            (
+                self.legacy_error_threshold.is_some(),
+                "triggers.error_threshold",
+                "triggers.error_frequency.threshold",
+            ),
+            (
+                self.legacy_error_window_seconds.is_some(),
+                "triggers.error_window_seconds",
+                "triggers.error_frequency.window_seconds",
+            ),
+            (
+                self.legacy_memory_threshold.is_some(),
+                "triggers.memory_threshold",
+                "triggers.memory_pressure.threshold",
+            ),
+        ]
+        .into_iter()
+        .filter(|(set, _, _)| *set)
+        .map(|(_, key, replacement)| (key, replacement))
+        .collect()
+    }
+
+    /// Enable flag, cooldown and re-arm of a built-in rule from its
+    /// `[triggers.<rule>]` section, or `None` for other names
+    pub fn builtin_controls(&self, name: &str) -> Option<BuiltinRuleControls> {
+        let (enabled, cooldown_seconds, rearm) = match name {
+            "ErrorFrequencyRule" => {
+                let rule = &self.error_frequency;
+                (rule.enabled, rule.cooldown_seconds, rule.rearm)
+            }
+            "MemoryPressureRule" => {
+                let rule = &self.memory_pressure;
+                (rule.enabled, rule.cooldown_seconds, rule.rearm)
+            }
+            "CrashDetectionRule" => {
+                let rule = &self.crash_detection;
+                (rule.enabled, rule.cooldown_seconds, rule.rearm)
+            }
+            "ResourceSpikeRule" => {
+                let rule = &self.resource_spike;
+                (rule.enabled, rule.cooldown_seconds, rule.rearm)
+            }
+            "DiskIOSpikeRule" => {
+                let rule = &self.disk_io_spike;
+                (rule.enabled, rule.cooldown_seconds, rule.rearm)
+            }
+            "AnomalyRule" => {
+                let rule = &self.anomaly;
+                (rule.enabled, rule.cooldown_seconds, rule.rearm)
+            }
+            "LogTemplateRule" => {
+                let rule = &self.templates;
+                (rule.enabled, rule.cooldown_seconds, rule.rearm)
+            }
+            "RespawnLoopRule" => {
+                let rule = &self.respawn;
+                (rule.enabled, rule.cooldown_seconds, rule.rearm)
+            }
+            "MemoryLeakRule" => {
+                let rule = &self.memory_leak;
+                (rule.enabled, rule.cooldown_seconds, rule.rearm)
+            }
+            "CpuHogRule" => {
+                let rule = &self.cpu_hog;
+                (rule.enabled, rule.cooldown_seconds, rule.rearm)
+            }
+            _ => return None,
+        };
+        Some(BuiltinRuleControls {
+            enabled,
+            cooldown_seconds: cooldown_seconds.unwrap_or(self.cooldown_seconds),
+            rearm,
+        })
+    }
+
+    /// Settings the named rule is built from, without its enable flag,
+    /// cooldown and re-arm, or `None` if no rule has that name
+    ///
+    /// A reload keeps the running instance of a rule, and the state it has
+    /// learned, while these are unchanged. A composite's settings include
+    /// those of its child rules.
+    pub fn rule_settings(&self, name: &str) -> Option<serde_json::Value> {
+        let settings = match name {
+            "ErrorFrequencyRule" => serde_json::json!({
+                "threshold": self.error_threshold(),
+                "window_seconds": self.error_window_seconds(),
+                "severity": self.error_frequency.severity,
+            }),
+            "MemoryPressureRule" => serde_json::json!({
+                "threshold": self.memory_threshold(),
+                "severity": self.memory_pressure.severity,
+            }),
+            "CrashDetectionRule" => serde_json::to_value(&self.crash_detection).ok()?,
+            "ResourceSpikeRule" => serde_json::to_value(&self.resource_spike).ok()?,
+            "DiskIOSpikeRule" => serde_json::to_value(&self.disk_io_spike).ok()?,
+            "AnomalyRule" => serde_json::to_value(&self.anomaly).ok()?,
+            "LogTemplateRule" => serde_json::to_value(&self.templates).ok()?,
+            "RespawnLoopRule" => serde_json::to_value(&self.respawn).ok()?,
+            "MemoryLeakRule" => serde_json::to_value(&self.memory_leak).ok()?,
+            "CpuHogRule" => serde_json::to_value(&self.cpu_hog).ok()?,
+            _ => {
+                if let Some(rule) = self.rules.iter().find(|rule| rule.name == name) {
+                    serde_json::to_value(rule).ok()?
+                } else if let Some(heartbeat) = self.heartbeats.iter().find(|h| h.name == name) {
+                    serde_json::to_value(heartbeat).ok()?
+                } else {
+                    let composite = self.composite.iter().find(|c| c.name == name)?;
+                    let children: Vec<_> = composite
+                        .rules
+                        .iter()
+                        .map(|child| self.rule_settings(child))
+                        .collect();
+                    serde_json::json!({
+                        "composite": without_controls(serde_json::to_value(composite).ok()?),
+                        "children": children,
+                    })
+                }
+            }
+        };
+        Some(without_controls(settings))
+    }
+}
+
+/// Drop the enable flag, cooldown and re-arm from a rule's serialized settings
+fn without_controls(mut settings: serde_json::Value) -> serde_json::Value {
+    if let Some(fields) = settings.as_object_mut() {
+        for control in ["enabled", "cooldown_seconds", "rearm"] {
+            fields.remove(control);
+        }
+    }
+    settings
+}
+
 impl Default for TriggersConfig {
     fn default() -> Self {
         Self {
-            error_threshold: default_error_threshold(),
-            error_window_seconds: default_error_window_secs(),
-            memory_threshold: default_memory_threshold(),
+            legacy_error_threshold: None,
+            legacy_error_window_seconds: None,
+            legacy_memory_threshold: None,
+            cooldown_seconds: default_trigger_cooldown_secs(),
+            error_frequency: ErrorFrequencyConfig::default(),
+            memory_pressure: MemoryPressureConfig::default(),
+            crash_detection: CrashDetectionConfig::default(),
+            resource_spike: ResourceSpikeConfig::default(),
+            disk_io_spike: DiskIOSpikeConfig::default(),
+            anomaly: AnomalyConfig::default(),
+            templates: TemplatesConfig::default(),
+            respawn: RespawnConfig::default(),
+            memory_leak: MemoryLeakConfig::default(),
+            cpu_hog: CpuHogConfig::default(),
+            rules: Vec::new(),
+            heartbeats: Vec::new(),
+            composite: Vec::new(),
+        }
+    }
+}
+
+impl Default for ErrorFrequencyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: None,
+            window_seconds: None,
+            severity: default_rule_severity(),
+            cooldown_seconds: None,
+            rearm: false,
+        }
+    }
+}
+
+impl Default for MemoryPressureConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: None,
+            severity: default_rule_severity(),
+            cooldown_seconds: None,
+            rearm: false,
+        }
+    }
+}
+
+impl Default for CrashDetectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            keywords: default_crash_keywords(),
+            severity: default_crash_severity(),
+            cooldown_seconds: None,
+            rearm: false,
+        }
+    }
+}
+
+impl Default for ResourceSpikeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            cpu_threshold_mw: default_resource_spike_cpu_mw(),
+            gpu_threshold_mw: default_resource_spike_gpu_mw(),
+            window_seconds: default_spike_window_secs(),
+            severity: default_rule_severity(),
+            cooldown_seconds: None,
+            rearm: false,
+        }
+    }
+}
+
+impl Default for DiskIOSpikeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            read_threshold_kb_per_sec: default_disk_spike_read_kb_per_sec(),
+            write_threshold_kb_per_sec: default_disk_spike_write_kb_per_sec(),
+            window_seconds: default_spike_window_secs(),
+            severity: default_rule_severity(),
+            cooldown_seconds: None,
+            rearm: false,
+        }
+    }
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            alpha: default_anomaly_alpha(),
+            enter_z: default_anomaly_enter_z(),
+            exit_z: default_anomaly_exit_z(),
+            sustain_samples: default_anomaly_sustain_samples(),
+            warmup_samples: default_anomaly_warmup_samples(),
+            metrics: default_anomaly_metrics(),
+            disk: default_anomaly_disk(),
+            severity: default_rule_severity(),
+            cooldown_seconds: None,
+            rearm: false,
+        }
+    }
+}
+
+impl Default for TemplatesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_seconds: default_templates_window_secs(),
+            learning_seconds: default_templates_learning_secs(),
+            rate_factor: default_templates_rate_factor(),
+            min_count: default_templates_min_count(),
+            severity: default_rule_severity(),
+            cooldown_seconds: None,
+            rearm: false,
+        }
+    }
+}
+
+impl Default for RespawnConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_seconds: default_respawn_window_secs(),
+            min_respawns: default_respawn_min_respawns(),
+            ignore_processes: default_respawn_ignore_processes(),
+            severity: default_rule_severity(),
+            cooldown_seconds: None,
+            rearm: false,
+        }
+    }
+}
+
+impl Default for MemoryLeakConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_seconds: default_memory_leak_window_secs(),
+            min_samples: default_memory_leak_min_samples(),
+            min_growth_mb: default_memory_leak_min_growth_mb(),
+            min_rising_fraction: default_memory_leak_min_rising_fraction(),
+            projection_seconds: default_memory_leak_projection_secs(),
+            ignore_processes: Vec::new(),
+            severity: default_rule_severity(),
+            cooldown_seconds: None,
+            rearm: false,
+        }
+    }
+}
+
+impl Default for CpuHogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            cpu_percent: default_cpu_hog_percent(),
+            duration_seconds: default_cpu_hog_duration_secs(),
+            allow_processes: default_cpu_hog_allow_processes(),
+            overrides: Vec::new(),
+            severity: default_rule_severity(),
+            cooldown_seconds: None,
+            rearm: false,
         }
     }
 }
@@ -295,6 +1741,7 @@ impl Default for StorageConfig {
     fn default() -> Self {
         Self {
             database_path: default_database_path(),
+            state_path: default_state_path(),
         }
     }
 }
@@ -304,6 +1751,7 @@ impl Default for WebConfig {
         Self {
             enabled: default_web_enabled(),
             bind_address: default_web_bind_address(),
+            otlp: false,
         }
     }
 }
@@ -347,61 +1795,308 @@ impl Config {
 
         // Parse TOML
         let config: Config = toml::from_str(&contents)?;
+        for (key, replacement) in config.triggers.deprecated_keys() {
+            log::warn!(
+                "{} in {} is deprecated; set {} instead",
+                key,
+                path.display(),
+                replacement
+            );
+        }
 
         // Validate the configuration
         config.validate()?;
 
-        Ok(config)
-    }
+        Ok(config)
+    }
+
+    /// Validate configuration values
+    ///
+    /// Ensures all configuration values are within acceptable ranges and
+    /// logically consistent.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ConfigError::ValidationError` if any values are invalid.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        // Validate metrics interval (must be at least 1 second)
+        if self.metrics.interval_seconds == 0 {
+            return Err(ConfigError::ValidationError(
+                "metrics.interval_seconds must be at least 1".to_string(),
+            ));
+        }
+
+        // Validate buffer max age (must be at least 1 second)
+        if self.buffer.max_age_seconds == 0 {
+            return Err(ConfigError::ValidationError(
+                "buffer.max_age_seconds must be at least 1".to_string(),
+            ));
+        }
+
+        // Validate buffer max size (must be at least 1)
+        if self.buffer.max_size == 0 {
+            return Err(ConfigError::ValidationError(
+                "buffer.max_size must be at least 1".to_string(),
+            ));
+        }
+
+        let spike_window_in_buffer =
+            |window_seconds: u64| (1..=self.buffer.max_age_seconds).contains(&window_seconds);
+        if self.triggers.error_threshold() == 0 {
+            return Err(ConfigError::ValidationError(
+                "triggers.error_frequency.threshold must be at least 1".to_string(),
+            ));
+        }
+        if !spike_window_in_buffer(self.triggers.error_window_seconds()) {
+            return Err(ConfigError::ValidationError(format!(
+                "triggers.error_frequency.window_seconds must be between 1 and buffer.max_age_seconds ({})",
+                self.buffer.max_age_seconds
+            )));
+        }
+        if self.triggers.memory_threshold() == MemoryPressure::Unknown {
+            return Err(ConfigError::ValidationError(
+                "triggers.memory_pressure.threshold must be Warning or Critical".to_string(),
+            ));
+        }
+
+        let crash_detection = &self.triggers.crash_detection;
+        if crash_detection.keywords.is_empty()
+            || crash_detection
+                .keywords
+                .iter()
+                .any(|keyword| keyword.trim().is_empty())
+        {
+            return Err(ConfigError::ValidationError(
+                "triggers.crash_detection.keywords must list at least one non-empty phrase"
+                    .to_string(),
+            ));
+        }
+
+        let resource_spike = &self.triggers.resource_spike;
+        if !(resource_spike.cpu_threshold_mw > 0.0 && resource_spike.gpu_threshold_mw > 0.0) {
+            return Err(ConfigError::ValidationError(
+                "triggers.resource_spike cpu_threshold_mw and gpu_threshold_mw must be greater than 0"
+                    .to_string(),
+            ));
+        }
+        if !spike_window_in_buffer(resource_spike.window_seconds) {
+            return Err(ConfigError::ValidationError(format!(
+                "triggers.resource_spike.window_seconds must be between 1 and buffer.max_age_seconds ({})",
+                self.buffer.max_age_seconds
+            )));
+        }
+        let disk_io_spike = &self.triggers.disk_io_spike;
+        if !(disk_io_spike.read_threshold_kb_per_sec > 0.0
+            && disk_io_spike.write_threshold_kb_per_sec > 0.0)
+        {
+            return Err(ConfigError::ValidationError(
+                "triggers.disk_io_spike read_threshold_kb_per_sec and write_threshold_kb_per_sec must be greater than 0"
+                    .to_string(),
+            ));
+        }
+        if !spike_window_in_buffer(disk_io_spike.window_seconds) {
+            return Err(ConfigError::ValidationError(format!(
+                "triggers.disk_io_spike.window_seconds must be between 1 and buffer.max_age_seconds ({})",
+                self.buffer.max_age_seconds
+            )));
+        }
 
-    /// Validate configuration values
-    ///
-    /// Ensures all configuration values are within acceptable ranges and
-    /// logically consistent.
-    ///
-    /// # Errors
-    ///
-    /// Returns a `ConfigError::ValidationError` if any values are invalid.
-    pub fn validate(&self) -> Result<(), ConfigError> {
-        // Validate metrics interval (must be at least 1 second)
-        if self.metrics.interval_seconds == 0 {
+        let anomaly = &self.triggers.anomaly;
+        if !(anomaly.alpha > 0.0 && anomaly.alpha <= 1.0) {
             return Err(ConfigError::ValidationError(
-                "metrics.interval_seconds must be at least 1".to_string(),
+                "triggers.anomaly.alpha must be greater than 0 and at most 1".to_string(),
+            ));
+        }
+        if !(anomaly.exit_z >= 0.0 && anomaly.exit_z < anomaly.enter_z) {
+            return Err(ConfigError::ValidationError(format!(
+                "triggers.anomaly.exit_z must be at least 0 and below enter_z ({})",
+                anomaly.enter_z
+            )));
+        }
+        if anomaly.sustain_samples == 0 {
+            return Err(ConfigError::ValidationError(
+                "triggers.anomaly.sustain_samples must be at least 1".to_string(),
             ));
         }
 
-        // Validate buffer max age (must be at least 1 second)
-        if self.buffer.max_age_seconds == 0 {
+        let templates = &self.triggers.templates;
+        if templates.window_seconds == 0 || templates.window_seconds > self.buffer.max_age_seconds {
+            return Err(ConfigError::ValidationError(format!(
+                "triggers.templates.window_seconds must be between 1 and buffer.max_age_seconds ({})",
+                self.buffer.max_age_seconds
+            )));
+        }
+        if templates.rate_factor.is_nan() || templates.rate_factor <= 1.0 {
             return Err(ConfigError::ValidationError(
-                "buffer.max_age_seconds must be at least 1".to_string(),
+                "triggers.templates.rate_factor must be greater than 1".to_string(),
+            ));
+        }
+        if templates.min_count == 0 {
+            return Err(ConfigError::ValidationError(
+                "triggers.templates.min_count must be at least 1".to_string(),
             ));
         }
 
-        // Validate buffer max size (must be at least 1)
-        if self.buffer.max_size == 0 {
+        if self.triggers.respawn.window_seconds == 0 {
             return Err(ConfigError::ValidationError(
-                "buffer.max_size must be at least 1".to_string(),
+                "triggers.respawn.window_seconds must be at least 1".to_string(),
+            ));
+        }
+        if self.triggers.respawn.min_respawns == 0 {
+            return Err(ConfigError::ValidationError(
+                "triggers.respawn.min_respawns must be at least 1".to_string(),
             ));
         }
 
-        // Validate error threshold (must be at least 1)
-        if self.triggers.error_threshold == 0 {
+        let memory_leak = &self.triggers.memory_leak;
+        if memory_leak.window_seconds < 120 {
+            return Err(ConfigError::ValidationError(
+                "triggers.memory_leak.window_seconds must be at least 120".to_string(),
+            ));
+        }
+        if memory_leak.min_samples < 3 {
+            return Err(ConfigError::ValidationError(
+                "triggers.memory_leak.min_samples must be at least 3".to_string(),
+            ));
+        }
+        if memory_leak.min_growth_mb.is_nan() || memory_leak.min_growth_mb <= 0.0 {
+            return Err(ConfigError::ValidationError(
+                "triggers.memory_leak.min_growth_mb must be greater than 0".to_string(),
+            ));
+        }
+        if !(memory_leak.min_rising_fraction > 0.0 && memory_leak.min_rising_fraction <= 1.0) {
             return Err(ConfigError::ValidationError(
-                "triggers.error_threshold must be at least 1".to_string(),
+                "triggers.memory_leak.min_rising_fraction must be greater than 0 and at most 1"
+                    .to_string(),
             ));
         }
 
-        // Validate error window (must be at least 1 second)
-        if self.triggers.error_window_seconds == 0 {
+        let cpu_hog = &self.triggers.cpu_hog;
+        let valid_percent = |percent: f64| percent > 0.0;
+        if !valid_percent(cpu_hog.cpu_percent) {
             return Err(ConfigError::ValidationError(
-                "triggers.error_window_seconds must be at least 1".to_string(),
+                "triggers.cpu_hog.cpu_percent must be greater than 0".to_string(),
             ));
         }
-        if self.triggers.memory_threshold == MemoryPressure::Unknown {
+        if cpu_hog.duration_seconds == 0 {
             return Err(ConfigError::ValidationError(
-                "triggers.memory_threshold must be Warning or Critical".to_string(),
+                "triggers.cpu_hog.duration_seconds must be at least 1".to_string(),
             ));
         }
+        let mut overridden = std::collections::HashSet::new();
+        for entry in &cpu_hog.overrides {
+            if entry.process.is_empty() || !overridden.insert(entry.process.as_str()) {
+                return Err(ConfigError::ValidationError(format!(
+                    "triggers.cpu_hog.overrides process '{}' must be non-empty and unique",
+                    entry.process
+                )));
+            }
+            if entry
+                .cpu_percent
+                .is_some_and(|percent| !valid_percent(percent))
+                || entry.duration_seconds == Some(0)
+            {
+                return Err(ConfigError::ValidationError(format!(
+                    "triggers.cpu_hog.overrides '{}' needs cpu_percent greater than 0 and duration_seconds of at least 1",
+                    entry.process
+                )));
+            }
+        }
+
+        // Compile declarative rules so regex and template errors surface here
+        let mut rule_names = std::collections::HashSet::new();
+        for rule in &self.triggers.rules {
+            let compiled = DeclarativeRule::compile(rule)?;
+            if BUILTIN_RULES.contains(&rule.name.as_str()) {
+                return Err(ConfigError::ValidationError(format!(
+                    "triggers.rules name '{}' is already used by another rule",
+                    rule.name
+                )));
+            }
+            if !rule_names.insert(rule.name.as_str()) {
+                return Err(ConfigError::ValidationError(format!(
+                    "triggers.rules name '{}' is used more than once",
+                    rule.name
+                )));
+            }
+            let window_seconds = compiled.max_window_seconds();
+            if window_seconds > self.buffer.max_age_seconds {
+                return Err(ConfigError::ValidationError(format!(
+                    "triggers.rules '{}' reads a {}s window, longer than buffer.max_age_seconds ({})",
+                    rule.name, window_seconds, self.buffer.max_age_seconds
+                )));
+            }
+        }
+
+        for heartbeat in &self.triggers.heartbeats {
+            HeartbeatRule::compile(heartbeat)?;
+            if BUILTIN_RULES.contains(&heartbeat.name.as_str())
+                || !rule_names.insert(heartbeat.name.as_str())
+            {
+                return Err(ConfigError::ValidationError(format!(
+                    "triggers.heartbeats name '{}' is already used by another rule",
+                    heartbeat.name
+                )));
+            }
+        }
+
+        for composite in &self.triggers.composite {
+            if BUILTIN_RULES.contains(&composite.name.as_str())
+                || !rule_names.insert(composite.name.as_str())
+            {
+                return Err(ConfigError::ValidationError(format!(
+                    "triggers.composite name '{}' is already used by another rule",
+                    composite.name
+                )));
+            }
+        }
+        for composite in &self.triggers.composite {
+            let expected = match composite.mode {
+                CompositeMode::Absence => composite.rules.len() == 2,
+                CompositeMode::All | CompositeMode::Sequence => composite.rules.len() >= 2,
+            };
+            if !expected {
+                return Err(ConfigError::ValidationError(format!(
+                    "triggers.composite '{}' needs {} rules",
+                    composite.name,
+                    if composite.mode == CompositeMode::Absence {
+                        "exactly 2"
+                    } else {
+                        "at least 2"
+                    }
+                )));
+            }
+            if composite.window_seconds == 0 {
+                return Err(ConfigError::ValidationError(format!(
+                    "triggers.composite '{}' window_seconds must be at least 1",
+                    composite.name
+                )));
+            }
+            let mut children = std::collections::HashSet::new();
+            for child in &composite.rules {
+                let known = BUILTIN_RULES.contains(&child.as_str())
+                    || self.triggers.rules.iter().any(|rule| &rule.name == child)
+                    || self
+                        .triggers
+                        .heartbeats
+                        .iter()
+                        .any(|heartbeat| &heartbeat.name == child);
+                if !known {
+                    return Err(ConfigError::ValidationError(format!(
+                        "triggers.composite '{}' refers to unknown rule '{}'; expected one of {} or a triggers.rules or triggers.heartbeats name",
+                        composite.name,
+                        child,
+                        BUILTIN_RULES.join(", ")
+                    )));
+                }
+                if !children.insert(child.as_str()) {
+                    return Err(ConfigError::ValidationError(format!(
+                        "triggers.composite '{}' lists rule '{}' more than once",
+                        composite.name, child
+                    )));
+                }
+            }
+        }
 
         // Validate alert rate limit (must be at least 1)
         if self.alerts.rate_limit_per_minute == 0 {
@@ -416,6 +2111,12 @@ impl Config {
             ));
         }
 
+        if self.storage.state_path.as_os_str().is_empty() {
+            return Err(ConfigError::ValidationError(
+                "storage.state_path cannot be empty".to_string(),
+            ));
+        }
+
         if self
             .web
             .bind_address
@@ -428,6 +2129,88 @@ impl Config {
             ));
         }
 
+        let priority = self.logging.journal_priority.as_str();
+        if !JOURNAL_PRIORITIES.contains(&priority)
+            && !matches!(priority.parse::<u8>(), Ok(level) if level <= 7)
+        {
+            return Err(ConfigError::ValidationError(format!(
+                "logging.journal_priority must be 0-7 or one of {}",
+                JOURNAL_PRIORITIES.join(", ")
+            )));
+        }
+
+        for file in &self.logging.files {
+            if file.path.as_os_str().is_empty() {
+                return Err(ConfigError::ValidationError(
+                    "logging.files path cannot be empty".to_string(),
+                ));
+            }
+            if file.format == LogFileFormat::Regex {
+                let pattern = file.pattern.as_deref().ok_or_else(|| {
+                    ConfigError::ValidationError(format!(
+                        "logging.files entry for {} needs a pattern when format = \"regex\"",
+                        file.path.display()
+                    ))
+                })?;
+                let regex = regex::Regex::new(pattern).map_err(|e| {
+                    ConfigError::ValidationError(format!(
+                        "logging.files pattern for {} is invalid: {}",
+                        file.path.display(),
+                        e
+                    ))
+                })?;
+                if !regex.capture_names().any(|name| name == Some("message")) {
+                    return Err(ConfigError::ValidationError(format!(
+                        "logging.files pattern for {} must have a named group 'message'",
+                        file.path.display()
+                    )));
+                }
+            }
+        }
+
+        let syslog = &self.logging.syslog;
+        if syslog.enabled {
+            if syslog.bind_address.parse::<std::net::SocketAddr>().is_err() {
+                return Err(ConfigError::ValidationError(
+                    "logging.syslog.bind_address must be a valid socket address such as 127.0.0.1:5514"
+                        .to_string(),
+                ));
+            }
+            if !syslog.udp && !syslog.tcp {
+                return Err(ConfigError::ValidationError(
+                    "logging.syslog needs udp or tcp enabled".to_string(),
+                ));
+            }
+        }
+
+        let mut exec_names: Vec<&str> = Vec::new();
+        for exec in &self.collectors.exec {
+            if exec.name.is_empty() || exec.command.is_empty() {
+                return Err(ConfigError::ValidationError(
+                    "collectors.exec entries need a name and a command".to_string(),
+                ));
+            }
+            if BUILTIN_SOURCES.contains(&exec.name.as_str())
+                || exec_names.contains(&exec.name.as_str())
+            {
+                return Err(ConfigError::ValidationError(format!(
+                    "collectors.exec name '{}' is already used by another source",
+                    exec.name
+                )));
+            }
+            exec_names.push(&exec.name);
+        }
+
+        for name in &self.collectors.disabled {
+            if !BUILTIN_SOURCES.contains(&name.as_str()) && !exec_names.contains(&name.as_str()) {
+                return Err(ConfigError::ValidationError(format!(
+                    "collectors.disabled contains unknown source '{}'; expected one of {} or a collectors.exec name",
+                    name,
+                    BUILTIN_SOURCES.join(", ")
+                )));
+            }
+        }
+
         // Validate AI backend configuration
         match &self.ai.backend {
             AIBackendConfig::Ollama { endpoint, model } => {
@@ -483,7 +2266,7 @@ impl Config {
 
     /// Get error window as a Duration
     pub fn error_window(&self) -> Duration {
-        Duration::from_secs(self.triggers.error_window_seconds)
+        Duration::from_secs(self.triggers.error_window_seconds())
     }
 }
 
@@ -503,13 +2286,14 @@ mod tests {
         assert_eq!(config.metrics.interval_seconds, 5);
         assert_eq!(config.buffer.max_age_seconds, 60);
         assert_eq!(config.buffer.max_size, 1000);
-        assert_eq!(config.triggers.error_threshold, 5);
-        assert_eq!(config.triggers.error_window_seconds, 10);
-        assert_eq!(config.triggers.memory_threshold, MemoryPressure::Warning);
+        assert_eq!(config.triggers.error_threshold(), 5);
+        assert_eq!(config.triggers.error_window_seconds(), 10);
+        assert_eq!(config.triggers.memory_threshold(), MemoryPressure::Warning);
         assert!(config.ai.automatic_analysis);
         assert_eq!(config.alerts.rate_limit_per_minute, 3);
         assert_eq!(config.alerts.minimum_severity, Severity::Warning);
         assert_eq!(config.storage.database_path, PathBuf::from("eyes.db"));
+        assert_eq!(config.storage.state_path, PathBuf::from("eyes-state.json"));
         assert!(config.web.enabled);
         assert_eq!(config.web.bind_address, "127.0.0.1:8787");
 
@@ -530,10 +2314,12 @@ mod tests {
             max_age_seconds = 120
             max_size = 2000
 
-            [triggers]
-            error_threshold = 10
-            error_window_seconds = 20
-            memory_threshold = "Critical"
+            [triggers.error_frequency]
+            threshold = 10
+            window_seconds = 20
+
+            [triggers.memory_pressure]
+            threshold = "Critical"
 
             [ai]
             automatic_analysis = false
@@ -557,9 +2343,10 @@ mod tests {
         assert_eq!(config.metrics.interval_seconds, 10);
         assert_eq!(config.buffer.max_age_seconds, 120);
         assert_eq!(config.buffer.max_size, 2000);
-        assert_eq!(config.triggers.error_threshold, 10);
-        assert_eq!(config.triggers.error_window_seconds, 20);
-        assert_eq!(config.triggers.memory_threshold, MemoryPressure::Critical);
+        assert_eq!(config.triggers.error_threshold(), 10);
+        assert_eq!(config.triggers.error_window_seconds(), 20);
+        assert_eq!(config.triggers.memory_threshold(), MemoryPressure::Critical);
+        assert!(config.triggers.deprecated_keys().is_empty());
         assert!(!config.ai.automatic_analysis);
         assert_eq!(config.alerts.rate_limit_per_minute, 5);
         assert_eq!(
@@ -618,8 +2405,8 @@ mod tests {
     #[test]
     fn test_config_with_partial_values_uses_defaults() {
         let toml_content = r#"
-            [triggers]
-            error_threshold = 15
+            [triggers.error_frequency]
+            threshold = 15
         "#;
 
         let mut temp_file = NamedTempFile::new().unwrap();
@@ -627,7 +2414,7 @@ mod tests {
         temp_file.flush().unwrap();
 
         let config = Config::from_file(temp_file.path()).unwrap();
-        assert_eq!(config.triggers.error_threshold, 15);
+        assert_eq!(config.triggers.error_threshold(), 15);
         // Other values should be defaults
         assert_eq!(config.metrics.interval_seconds, 5);
         assert_eq!(config.buffer.max_size, 1000);
@@ -668,43 +2455,389 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_config_deprecated_trigger_keys_are_fallbacks() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [triggers]
+            error_threshold = 8
+            error_window_seconds = 30
+            memory_threshold = "Critical"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.triggers.error_threshold(), 8);
+        assert_eq!(config.triggers.error_window_seconds(), 30);
+        assert_eq!(config.triggers.memory_threshold(), MemoryPressure::Critical);
+        assert_eq!(
+            config.triggers.deprecated_keys(),
+            vec![
+                (
+                    "triggers.error_threshold",
+                    "triggers.error_frequency.threshold"
+                ),
+                (
+                    "triggers.error_window_seconds",
+                    "triggers.error_frequency.window_seconds"
+                ),
+                (
+                    "triggers.memory_threshold",
+                    "triggers.memory_pressure.threshold"
+                ),
+            ]
+        );
+        assert!(config.validate().is_ok());
+
+        // The rule sections take precedence
+        config.triggers.error_frequency.threshold = Some(3);
+        config.triggers.memory_pressure.threshold = Some(MemoryPressure::Warning);
+        assert_eq!(config.triggers.error_threshold(), 3);
+        assert_eq!(config.triggers.memory_threshold(), MemoryPressure::Warning);
+
+        // A deprecated key is validated like its replacement
+        config.triggers.legacy_error_window_seconds = Some(0);
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_config_validation_zero_error_threshold() {
-        let config = Config {
-            triggers: TriggersConfig {
-                error_threshold: 0,
-                error_window_seconds: 10,
-                memory_threshold: MemoryPressure::Warning,
-            },
-            ..Default::default()
-        };
+        let mut config = Config::default();
+        config.triggers.error_frequency.threshold = Some(0);
         assert!(config.validate().is_err());
     }
 
     #[test]
-    fn test_config_validation_zero_error_window() {
-        let config = Config {
-            triggers: TriggersConfig {
-                error_threshold: 5,
-                error_window_seconds: 0,
-                memory_threshold: MemoryPressure::Warning,
-            },
-            ..Default::default()
-        };
+    fn test_config_validation_error_window_within_buffer() {
+        let mut config = Config::default();
+        config.triggers.error_frequency.window_seconds = Some(0);
+        assert!(config.validate().is_err());
+        config.triggers.error_frequency.window_seconds = Some(config.buffer.max_age_seconds + 1);
         assert!(config.validate().is_err());
+        config.triggers.error_frequency.window_seconds = Some(config.buffer.max_age_seconds);
+        assert!(config.validate().is_ok());
     }
 
     #[test]
     fn test_config_validation_unknown_memory_threshold() {
-        let config = Config {
-            triggers: TriggersConfig {
-                memory_threshold: MemoryPressure::Unknown,
-                ..TriggersConfig::default()
-            },
-            ..Default::default()
-        };
+        let mut config = Config::default();
+        config.triggers.memory_pressure.threshold = Some(MemoryPressure::Unknown);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_anomaly_section() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [triggers.anomaly]
+            enter_z = 5.0
+            metrics = ["cpu_power_mw"]
+            disk = ["write_ops_per_sec"]
+            "#,
+        )
+        .unwrap();
+        let anomaly = &config.triggers.anomaly;
+        assert!(anomaly.enabled);
+        assert_eq!(anomaly.enter_z, 5.0);
+        assert_eq!(anomaly.exit_z, 2.0);
+        assert_eq!(anomaly.metrics, vec![MetricField::CpuPowerMw]);
+        assert_eq!(anomaly.disk, vec![DiskField::WriteOpsPerSec]);
+        assert!(config.validate().is_ok());
+
+        config.triggers.anomaly.exit_z = 5.0;
+        assert!(config.validate().is_err());
+        config.triggers.anomaly.exit_z = 2.0;
+        config.triggers.anomaly.alpha = 0.0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_templates_section() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [triggers.templates]
+            window_seconds = 30
+            rate_factor = 5.0
+            "#,
+        )
+        .unwrap();
+        let templates = &config.triggers.templates;
+        assert!(templates.enabled);
+        assert_eq!(templates.window_seconds, 30);
+        assert_eq!(templates.learning_seconds, 3600);
+        assert_eq!(templates.rate_factor, 5.0);
+        assert_eq!(templates.min_count, 10);
+        assert!(config.validate().is_ok());
+
+        config.triggers.templates.window_seconds = config.buffer.max_age_seconds + 1;
+        assert!(config.validate().is_err());
+        config.triggers.templates.window_seconds = 60;
+        config.triggers.templates.rate_factor = 1.0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_respawn_section() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [triggers.respawn]
+            min_respawns = 5
+            ignore_processes = ["sh"]
+            "#,
+        )
+        .unwrap();
+        let respawn = &config.triggers.respawn;
+        assert!(respawn.enabled);
+        assert_eq!(respawn.window_seconds, 600);
+        assert_eq!(respawn.min_respawns, 5);
+        assert_eq!(respawn.ignore_processes, vec!["sh".to_string()]);
+        assert!(config.validate().is_ok());
+
+        config.triggers.respawn.min_respawns = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_memory_leak_section() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [triggers.memory_leak]
+            window_seconds = 7200
+            min_growth_mb = 250.0
+            ignore_processes = ["mds_stores"]
+            "#,
+        )
+        .unwrap();
+        let memory_leak = &config.triggers.memory_leak;
+        assert!(memory_leak.enabled);
+        assert_eq!(memory_leak.window_seconds, 7200);
+        assert_eq!(memory_leak.min_samples, 10);
+        assert_eq!(memory_leak.min_growth_mb, 250.0);
+        assert_eq!(memory_leak.min_rising_fraction, 0.8);
+        assert_eq!(memory_leak.ignore_processes, vec!["mds_stores".to_string()]);
+        assert!(config.validate().is_ok());
+
+        config.triggers.memory_leak.min_rising_fraction = 1.5;
+        assert!(config.validate().is_err());
+        config.triggers.memory_leak.min_rising_fraction = 0.8;
+        config.triggers.memory_leak.window_seconds = 60;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_cpu_hog_section() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [triggers.cpu_hog]
+            cpu_percent = 80.0
+            allow_processes = ["ffmpeg"]
+
+            [[triggers.cpu_hog.overrides]]
+            process = "kernel_task"
+            cpu_percent = 200.0
+
+            [[triggers.cpu_hog.overrides]]
+            process = "backupd"
+            duration_seconds = 1800
+            "#,
+        )
+        .unwrap();
+        let cpu_hog = &config.triggers.cpu_hog;
+        assert!(cpu_hog.enabled);
+        assert_eq!(cpu_hog.cpu_percent, 80.0);
+        assert_eq!(cpu_hog.duration_seconds, 300);
+        assert_eq!(cpu_hog.allow_processes, vec!["ffmpeg".to_string()]);
+        assert_eq!(cpu_hog.overrides[0].cpu_percent, Some(200.0));
+        assert_eq!(cpu_hog.overrides[1].duration_seconds, Some(1800));
+        assert!(config.validate().is_ok());
+
+        config.triggers.cpu_hog.overrides[1].process = "kernel_task".to_string();
+        assert!(config.validate().is_err());
+        config.triggers.cpu_hog.overrides[1].process = "backupd".to_string();
+        config.triggers.cpu_hog.overrides[0].cpu_percent = Some(0.0);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_builtin_rule_sections() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [triggers]
+            cooldown_seconds = 600
+
+            [triggers.resource_spike]
+            cpu_threshold_mw = 1500.0
+            window_seconds = 60
+            cooldown_seconds = 120
+            rearm = true
+
+            [triggers.disk_io_spike]
+            enabled = false
+
+            [triggers.crash_detection]
+            keywords = ["EXC_BAD_ACCESS"]
+            severity = "warning"
+            "#,
+        )
+        .unwrap();
+        let triggers = &config.triggers;
+        assert_eq!(triggers.resource_spike.cpu_threshold_mw, 1500.0);
+        assert_eq!(triggers.resource_spike.gpu_threshold_mw, 2000.0);
+        assert_eq!(triggers.crash_detection.severity, Severity::Warning);
+        assert_eq!(
+            triggers.builtin_controls("ResourceSpikeRule"),
+            Some(BuiltinRuleControls {
+                enabled: true,
+                cooldown_seconds: 120,
+                rearm: true,
+            })
+        );
+        assert_eq!(
+            triggers.builtin_controls("DiskIOSpikeRule"),
+            Some(BuiltinRuleControls {
+                enabled: false,
+                cooldown_seconds: 600,
+                rearm: false,
+            })
+        );
+        for name in BUILTIN_RULES {
+            assert!(triggers.builtin_controls(name).is_some(), "{}", name);
+        }
+        assert_eq!(triggers.builtin_controls("spike-then-crash"), None);
+        assert!(config.validate().is_ok());
+
+        config.triggers.resource_spike.window_seconds = config.buffer.max_age_seconds + 1;
+        assert!(config.validate().is_err());
+        config.triggers.resource_spike.window_seconds = 60;
+        config.triggers.crash_detection.keywords.clear();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_heartbeats() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [[triggers.heartbeats]]
+            name = "backupd heartbeat"
+            process = "backupd"
+            message = "^Backup (completed|skipped)"
+            max_gap_seconds = 3900
+
+            [[triggers.heartbeats]]
+            name = "sync activity"
+            subsystem = "com.example.sync"
+            min_count = 20
+            "#,
+        )
+        .unwrap();
+        let heartbeats = &config.triggers.heartbeats;
+        assert_eq!(heartbeats[0].process.as_deref(), Some("backupd"));
+        assert_eq!(heartbeats[0].max_gap_seconds, 3900);
+        assert_eq!(heartbeats[0].min_count, 1);
+        assert_eq!(heartbeats[1].max_gap_seconds, 300);
+        assert_eq!(heartbeats[1].min_count, 20);
+        assert!(config.validate().is_ok());
+
+        config.triggers.heartbeats[1].subsystem = None;
+        assert!(config.validate().is_err());
+        config.triggers.heartbeats[1].subsystem = Some("com.example.sync".to_string());
+        config.triggers.heartbeats[0].message = Some("(".to_string());
+        assert!(config.validate().is_err());
+        config.triggers.heartbeats[0].message = None;
+        config.triggers.heartbeats[0].name = "sync activity".to_string();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_composite_rules() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [[triggers.rules]]
+            name = "kernel-io-errors"
+            process = "kernel"
+            message = "I/O error"
+
+            [[triggers.composite]]
+            name = "io-story"
+            mode = "sequence"
+            rules = ["DiskIOSpikeRule", "kernel-io-errors", "CrashDetectionRule"]
+            window_seconds = 600
+            "#,
+        )
+        .unwrap();
+        let composite = &config.triggers.composite[0];
+        assert_eq!(composite.mode, CompositeMode::Sequence);
+        assert_eq!(composite.window_seconds, 600);
+        assert_eq!(composite.severity, None);
+        assert!(config.validate().is_ok());
+
+        config.triggers.composite[0].mode = CompositeMode::Absence;
+        assert!(config.validate().is_err());
+        config.triggers.composite[0].mode = CompositeMode::All;
+        config.triggers.composite[0].rules[1] = "kernel-io-error".to_string();
+        let error = config.validate().unwrap_err().to_string();
+        assert!(
+            error.contains("unknown rule 'kernel-io-error'"),
+            "{}",
+            error
+        );
+        config.triggers.composite[0].rules[1] = "kernel-io-errors".to_string();
+        config.triggers.composite[0].name = "kernel-io-errors".to_string();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_declarative_trigger_rules() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [[triggers.rules]]
+            name = "kernel-io-errors"
+            severity = "critical"
+            process = "kernel"
+            message = "I/O error"
+            count = 3
+            window_seconds = 30
+
+            [[triggers.rules.metrics]]
+            field = "memory_used_mb"
+            aggregate = "max"
+            op = ">"
+            value = 8192
+            "#,
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+        let rule = &config.triggers.rules[0];
+        assert_eq!(rule.severity, Severity::Critical);
+        assert_eq!(rule.metrics[0].field, MetricField::MemoryUsedMb);
+
+        config.triggers.rules[0].message = Some("I/O (error".to_string());
+        let error = config.validate().unwrap_err().to_string();
+        assert!(error.contains("'kernel-io-errors' message regex is invalid"));
+
+        config.triggers.rules[0].message = None;
+        config.triggers.rules[0].window_seconds = 120;
+        assert!(config.validate().is_err());
+
+        config.triggers.rules[0].window_seconds = 30;
+        config.triggers.rules[0].expression =
+            Some("count(log where message_type == fault) > 0 within 5m".to_string());
+        assert!(config.validate().is_err());
+
+        config.triggers.rules[0].expression =
+            Some("count(log where message_type == fault) > 0 within 30s".to_string());
+        assert!(config.validate().is_ok());
+        config.triggers.rules[0].expression = Some("count(log where pid > ) > 0".to_string());
+        let error = config.validate().unwrap_err().to_string();
+        assert!(error.contains("line 1, column 23"), "{}", error);
 
+        config.triggers.rules[0].expression = None;
+        config.triggers.rules.push(config.triggers.rules[0].clone());
         assert!(config.validate().is_err());
+
+        config.triggers.rules.pop();
+        config.triggers.rules[0].name = "CrashDetectionRule".to_string();
+        let error = config.validate().unwrap_err().to_string();
+        assert!(error.contains("already used by another rule"), "{}", error);
     }
 
     #[test]
@@ -724,18 +2857,158 @@ mod tests {
         let config = Config {
             storage: StorageConfig {
                 database_path: PathBuf::new(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_validation_empty_state_path() {
+        let config = Config {
+            storage: StorageConfig {
+                state_path: PathBuf::new(),
+                ..Default::default()
             },
             ..Default::default()
         };
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_config_journald_logging() {
+        let config: Config = toml::from_str(
+            r#"
+            [logging]
+            source = "journald"
+            journal_priority = "warning"
+            journal_file = "boot.json"
+            "#,
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.logging.source.resolve(), LogSource::Journald);
+        assert_eq!(
+            config.logging.journal_file,
+            Some(PathBuf::from("boot.json"))
+        );
+
+        let mut config = Config::default();
+        assert_eq!(config.logging.journal_priority, "err");
+        config.logging.journal_priority = "8".to_string();
+        assert!(config.validate().is_err());
+        config.logging.journal_priority = "loud".to_string();
+        assert!(config.validate().is_err());
+        config.logging.journal_priority = "3".to_string();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_config_log_files() {
+        let config: Config = toml::from_str(
+            r#"
+            [[logging.files]]
+            path = "/var/log/api/app.log"
+            pattern = '^(?P<timestamp>\S+) (?P<level>\w+) (?P<message>.*)$'
+
+            [[logging.files]]
+            path = "/var/log/worker.jsonl"
+            format = "json"
+            process = "worker"
+            [logging.files.fields]
+            level = "severity"
+            message = "msg"
+            "#,
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.logging.files.len(), 2);
+        assert_eq!(config.logging.files[0].format, LogFileFormat::Regex);
+        assert_eq!(config.logging.files[1].format, LogFileFormat::Json);
+        assert_eq!(config.logging.files[1].fields.level, "severity");
+        assert_eq!(config.logging.files[1].fields.timestamp, "timestamp");
+
+        let mut config = config;
+        config.logging.files[0].pattern = Some("^(?P<level>\\w+)".to_string());
+        assert!(config.validate().is_err());
+        config.logging.files[0].pattern = Some("(".to_string());
+        assert!(config.validate().is_err());
+        config.logging.files[0].pattern = None;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_syslog() {
+        let config: Config = toml::from_str(
+            r#"
+            [logging.syslog]
+            enabled = true
+            bind_address = "0.0.0.0:514"
+            tcp = false
+            "#,
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert!(config.logging.syslog.udp);
+        assert!(!config.logging.syslog.tcp);
+        assert!(!Config::default().logging.syslog.enabled);
+
+        let mut config = config;
+        config.logging.syslog.udp = false;
+        assert!(config.validate().is_err());
+        config.logging.syslog.udp = true;
+        config.logging.syslog.bind_address = "syslog".to_string();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_exec_collectors() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [collectors]
+            disabled = ["vpn"]
+
+            [[collectors.exec]]
+            name = "vpn"
+            command = "/usr/local/bin/vpn-probe"
+            args = ["--json"]
+
+            [[collectors.exec]]
+            name = "build_farm"
+            command = "build-farm-health"
+            "#,
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.collectors.exec[0].args, ["--json"]);
+        assert!(config.collectors.exec[1].args.is_empty());
+
+        config.collectors.exec[1].name = "vpn".to_string();
+        assert!(config.validate().is_err());
+        config.collectors.exec[1].name = "disk".to_string();
+        assert!(config.validate().is_err());
+        config.collectors.exec[1].name = "build_farm".to_string();
+        config.collectors.exec[1].command = String::new();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_validation_unknown_disabled_collector() {
+        let mut config: Config = toml::from_str("[collectors]\ndisabled = [\"disk\"]").unwrap();
+        assert!(config.validate().is_ok());
+
+        config.collectors.disabled.push("powermetrics".to_string());
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_config_validation_invalid_web_bind_address() {
         let config = Config {
             web: WebConfig {
                 enabled: true,
                 bind_address: "localhost".to_string(),
+                otlp: false,
             },
             ..Default::default()
         };
@@ -1,4 +1,14 @@
 /// Configuration management
 pub mod conf;
+/// Configuration file watching and reload diffs
+pub mod watch;
 
-pub use conf::{AIBackendConfig, Config, StorageConfig};
+pub use conf::{
+    AIBackendConfig, AnomalyConfig, BuiltinRuleControls, CollectorsConfig, ComparisonOp,
+    CompositeMode, CompositeRuleConfig, Config, CpuHogConfig, CpuHogOverride, CrashDetectionConfig,
+    DiskIOSpikeConfig, ErrorFrequencyConfig, ExecCollectorConfig, HeartbeatConfig, LogFileConfig,
+    LogFileFields, LogFileFormat, LogSource, MemoryLeakConfig, MemoryPressureConfig,
+    MetricAggregate, MetricConditionConfig, ResourceSpikeConfig, RespawnConfig, RuleGroupBy,
+    StorageConfig, SyslogConfig, TemplatesConfig, TriggerRuleConfig,
+};
+pub use watch::{keep_source_settings, ConfigChanges, ConfigWatcher};
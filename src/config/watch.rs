@@ -0,0 +1,260 @@
+//! Configuration reload support
+//!
+//! The daemon polls its configuration file with a `ConfigWatcher` and also
+//! reloads on SIGHUP. `ConfigChanges` tells it which parts of a reloaded
+//! configuration differ from the running one, so only those are reapplied.
+
+use super::Config;
+use crate::triggers::BUILTIN_RULES;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set by the SIGHUP handler, cleared by `take_reload_request`
+static RELOAD_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(unix)]
+extern "C" fn request_reload(_signal: libc::c_int) {
+    RELOAD_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+/// Make SIGHUP request a configuration reload instead of terminating
+///
+/// A no-op on platforms without SIGHUP.
+pub fn install_reload_signal() {
+    #[cfg(unix)]
+    {
+        let handler = request_reload as extern "C" fn(libc::c_int);
+        // SAFETY: the handler only stores to an atomic, which is async-signal-safe
+        unsafe {
+            libc::signal(libc::SIGHUP, handler as libc::sighandler_t);
+        }
+    }
+}
+
+/// Whether a reload was requested by signal since the last call
+pub fn take_reload_request() -> bool {
+    RELOAD_REQUESTED.swap(false, Ordering::SeqCst)
+}
+
+/// Detects edits to a configuration file by comparing its contents
+///
+/// Editors often replace the file instead of writing it in place, so the
+/// path is read again on every poll rather than watched through a handle.
+#[derive(Debug)]
+pub struct ConfigWatcher {
+    path: PathBuf,
+    contents: Option<Vec<u8>>,
+}
+
+impl ConfigWatcher {
+    /// Watch `path`, taking its current contents as the baseline
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let contents = std::fs::read(&path).ok();
+        Self { path, contents }
+    }
+
+    /// The watched file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the file changed since the previous poll
+    ///
+    /// A file that cannot be read, for example in the middle of being
+    /// replaced, is not reported until it can be read again.
+    pub fn poll(&mut self) -> bool {
+        match std::fs::read(&self.path) {
+            Ok(contents) if self.contents.as_ref() != Some(&contents) => {
+                self.contents = Some(contents);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Parts of a reloaded configuration that differ from the running one
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigChanges {
+    /// Event sources whose settings changed, by source name
+    pub sources: Vec<&'static str>,
+    /// `[triggers]` changed, so the rule set is rebuilt
+    pub triggers: bool,
+    /// Rules whose settings changed, by rule name; the other rules keep
+    /// their running instances across the rebuild
+    pub rules: Vec<String>,
+    /// `[alerts]` changed
+    pub alerts: bool,
+    /// `ai.automatic_analysis` changed
+    pub automatic_analysis: bool,
+    /// `[ai.backend]` changed, so a new backend is created
+    pub ai_backend: bool,
+    /// Changed sections that only take effect after a restart
+    pub restart_required: Vec<&'static str>,
+}
+
+impl ConfigChanges {
+    /// Compare the running configuration with a reloaded one
+    pub fn between(running: &Config, reloaded: &Config) -> Self {
+        let old = &running.logging;
+        let new = &reloaded.logging;
+        let mut sources = Vec::new();
+        if differs(&old.predicate, &new.predicate)
+            || differs(&old.source, &new.source)
+            || differs(&old.journal_priority, &new.journal_priority)
+            || differs(&old.journal_file, &new.journal_file)
+        {
+            sources.push("log");
+        }
+        if differs(&running.metrics, &reloaded.metrics) {
+            sources.extend(["metrics", "disk", "crash_reports"]);
+        }
+        if differs(&old.files, &new.files) {
+            sources.push("files");
+        }
+        if differs(&old.syslog, &new.syslog) {
+            sources.push("syslog");
+        }
+
+        let restart_required = [
+            ("buffer", differs(&running.buffer, &reloaded.buffer)),
+            ("storage", differs(&running.storage, &reloaded.storage)),
+            ("web", differs(&running.web, &reloaded.web)),
+            (
+                "collectors",
+                differs(&running.collectors, &reloaded.collectors),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(section, changed)| changed.then_some(section))
+        .collect();
+
+        let mut rules: Vec<String> = Vec::new();
+        for name in rule_names(running).chain(rule_names(reloaded)) {
+            if !rules.iter().any(|changed| changed == name)
+                && running.triggers.rule_settings(name) != reloaded.triggers.rule_settings(name)
+            {
+                rules.push(name.to_string());
+            }
+        }
+
+        Self {
+            sources,
+            triggers: differs(&running.triggers, &reloaded.triggers),
+            rules,
+            alerts: differs(&running.alerts, &reloaded.alerts),
+            automatic_analysis: running.ai.automatic_analysis != reloaded.ai.automatic_analysis,
+            ai_backend: differs(&running.ai.backend, &reloaded.ai.backend),
+            restart_required,
+        }
+    }
+
+    /// Whether nothing changed
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Put the running settings of an event source back into a reloaded
+/// configuration, for a source that failed to restart with the new ones
+///
+/// The next reload then sees the source's settings as changed and retries.
+/// The metrics, disk and crash report sources share `[metrics]`.
+pub fn keep_source_settings(reloaded: &mut Config, running: &Config, source: &str) {
+    let old = &running.logging;
+    let new = &mut reloaded.logging;
+    match source {
+        "log" => {
+            new.predicate = old.predicate.clone();
+            new.source = old.source;
+            new.journal_priority = old.journal_priority.clone();
+            new.journal_file = old.journal_file.clone();
+        }
+        "metrics" | "disk" | "crash_reports" => reloaded.metrics = running.metrics.clone(),
+        "files" => new.files = old.files.clone(),
+        "syslog" => new.syslog = old.syslog.clone(),
+        _ => {}
+    }
+}
+
+/// Built-in and configured rule names
+fn rule_names(config: &Config) -> impl Iterator<Item = &str> {
+    let triggers = &config.triggers;
+    BUILTIN_RULES
+        .iter()
+        .copied()
+        .chain(triggers.rules.iter().map(|rule| rule.name.as_str()))
+        .chain(triggers.heartbeats.iter().map(|rule| rule.name.as_str()))
+        .chain(triggers.composite.iter().map(|rule| rule.name.as_str()))
+}
+
+/// Configuration sections have no `PartialEq`; compare their serialized form
+fn differs<T: Serialize>(old: &T, new: &T) -> bool {
+    serde_json::to_value(old).ok() != serde_json::to_value(new).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn watcher_reports_each_edit_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        std::fs::write(&path, "[alerts]\nrate_limit_per_minute = 3\n").unwrap();
+        let mut watcher = ConfigWatcher::new(&path);
+        assert!(!watcher.poll());
+
+        std::fs::write(&path, "[alerts]\nrate_limit_per_minute = 5\n").unwrap();
+        assert!(watcher.poll());
+        assert!(!watcher.poll());
+
+        // A file being replaced is picked up once it is back
+        std::fs::remove_file(&path).unwrap();
+        assert!(!watcher.poll());
+        std::fs::write(&path, "[alerts]\nrate_limit_per_minute = 1\n").unwrap();
+        assert!(watcher.poll());
+    }
+
+    #[test]
+    fn changes_name_the_sections_to_reapply() {
+        let running = Config::default();
+        assert!(ConfigChanges::between(&running, &running.clone()).is_empty());
+
+        let mut reloaded = running.clone();
+        reloaded.logging.predicate = "subsystem == \"com.example\"".to_string();
+        reloaded.metrics.interval_seconds = 10;
+        reloaded.triggers.cooldown_seconds = 60;
+        reloaded.buffer.max_size = 5000;
+        let changes = ConfigChanges::between(&running, &reloaded);
+        assert_eq!(
+            changes.sources,
+            vec!["log", "metrics", "disk", "crash_reports"]
+        );
+        assert!(changes.triggers);
+        assert!(changes.rules.is_empty());
+        assert!(!changes.alerts);
+        assert!(!changes.ai_backend);
+        assert_eq!(changes.restart_required, vec!["buffer"]);
+
+        keep_source_settings(&mut reloaded, &running, "log");
+        let changes = ConfigChanges::between(&running, &reloaded);
+        assert_eq!(changes.sources, vec!["metrics", "disk", "crash_reports"]);
+    }
+
+    #[test]
+    fn changes_name_only_the_rules_to_rebuild() {
+        let running = Config::default();
+        let mut reloaded = running.clone();
+        reloaded.triggers.cpu_hog.cooldown_seconds = Some(900);
+        reloaded.triggers.memory_leak.enabled = false;
+        reloaded.triggers.error_frequency.threshold = Some(6);
+        reloaded.triggers.anomaly.warmup_samples += 1;
+        let changes = ConfigChanges::between(&running, &reloaded);
+        assert!(changes.triggers);
+        assert_eq!(changes.rules, vec!["ErrorFrequencyRule", "AnomalyRule"]);
+    }
+}
@@ -14,6 +14,12 @@ pub enum CollectorError {
 
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
+
+    #[error("Event source already registered: {0}")]
+    DuplicateSource(String),
+
+    #[error("Event source not registered: {0}")]
+    UnknownSource(String),
 }
 
 /// Errors that can occur during AI analysis
@@ -84,3 +90,59 @@ pub enum ConfigError {
     #[error("TOML parse error: {0}")]
     TomlError(#[from] toml::de::Error),
 }
+
+/// Errors that can occur while saving or loading persisted state
+#[derive(Error, Debug)]
+pub enum StateError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Invalid state file: {0}")]
+    FormatError(String),
+}
+
+/// Errors reported while compiling a trigger rule expression
+///
+/// `offset` is the byte offset of the offending token in the source; `line`
+/// and `column` are 1-based, with columns counted in characters.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ExpressionError {
+    #[error("syntax error at line {line}, column {column}: {message}")]
+    Syntax {
+        message: String,
+        offset: usize,
+        line: usize,
+        column: usize,
+    },
+
+    #[error("type error at line {line}, column {column}: {message}")]
+    Type {
+        message: String,
+        offset: usize,
+        line: usize,
+        column: usize,
+    },
+}
+
+impl ExpressionError {
+    /// Byte offset of the offending token
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::Syntax { offset, .. } | Self::Type { offset, .. } => *offset,
+        }
+    }
+
+    /// 1-based line of the offending token
+    pub fn line(&self) -> usize {
+        match self {
+            Self::Syntax { line, .. } | Self::Type { line, .. } => *line,
+        }
+    }
+
+    /// 1-based column of the offending token
+    pub fn column(&self) -> usize {
+        match self {
+            Self::Syntax { column, .. } | Self::Type { column, .. } => *column,
+        }
+    }
+}
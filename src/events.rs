@@ -180,7 +180,9 @@ pub enum MessageType {
 pub enum MetricsSource {
     Powermetrics,
     TopVmStat,
+    Procfs,
     Json,
+    Otlp,
     #[default]
     Unknown,
 }
@@ -190,7 +192,9 @@ impl std::fmt::Display for MetricsSource {
         match self {
             Self::Powermetrics => write!(formatter, "powermetrics"),
             Self::TopVmStat => write!(formatter, "top/vm_stat"),
+            Self::Procfs => write!(formatter, "/proc"),
             Self::Json => write!(formatter, "JSON input"),
+            Self::Otlp => write!(formatter, "OTLP"),
             Self::Unknown => write!(formatter, "unknown"),
         }
     }
@@ -657,6 +661,107 @@ impl DiskEvent {
     }
 }
 
+/// Kind of diagnostic report written by the system crash reporter
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum CrashReportKind {
+    /// The process terminated abnormally
+    Crash,
+    /// The process stopped responding
+    Hang,
+}
+
+/// One frame from the crashing thread's backtrace
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StackFrame {
+    /// Binary image containing the frame (e.g., "libobjc.A.dylib")
+    pub image: String,
+    /// Symbol name, if the report was symbolicated
+    pub symbol: Option<String>,
+    /// Offset of the frame within the image
+    pub image_offset: u64,
+}
+
+impl std::fmt::Display for StackFrame {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.symbol {
+            Some(symbol) => write!(formatter, "{} {}", self.image, symbol),
+            None => write!(formatter, "{} +0x{:x}", self.image, self.image_offset),
+        }
+    }
+}
+
+/// Crash or hang report parsed from a DiagnosticReports `.ips` file
+///
+/// Carries the faulting process, the exception and termination details and
+/// the top frames of the crashing thread so analysis can see where the
+/// process actually died instead of inferring it from log keywords.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CrashReport {
+    /// When the report was captured
+    pub timestamp: Timestamp,
+    /// Whether this is a crash or a hang report
+    pub kind: CrashReportKind,
+    /// Name of the process that crashed
+    pub process: String,
+    /// Process ID
+    pub process_id: u32,
+    /// Bundle identifier, if the process is an app bundle
+    pub bundle_id: Option<String>,
+    /// Exception type (e.g., "EXC_BAD_ACCESS")
+    pub exception_type: Option<String>,
+    /// Signal delivered with the exception (e.g., "SIGSEGV")
+    pub exception_signal: Option<String>,
+    /// Termination reason (e.g., "Namespace SIGNAL, Code 11, Segmentation fault: 11")
+    pub termination_reason: Option<String>,
+    /// Top frames of the crashing thread, innermost first
+    pub frames: Vec<StackFrame>,
+    /// Path of the report file
+    pub report_path: String,
+}
+
+impl CrashReport {
+    /// One-line description used for log events and prompts
+    pub fn summary(&self) -> String {
+        let mut summary = match self.kind {
+            CrashReportKind::Crash => format!("{} crashed", self.process),
+            CrashReportKind::Hang => format!("{} stopped responding", self.process),
+        };
+        match (&self.exception_type, &self.exception_signal) {
+            (Some(exception), Some(signal)) => {
+                summary.push_str(&format!(": {} ({})", exception, signal))
+            }
+            (Some(exception), None) => summary.push_str(&format!(": {}", exception)),
+            (None, Some(signal)) => summary.push_str(&format!(": {}", signal)),
+            (None, None) => {}
+        }
+        if let Some(frame) = self.frames.first() {
+            summary.push_str(&format!(" at {}", frame));
+        }
+        summary
+    }
+
+    /// Log event that lets the log-based trigger rules see this report
+    ///
+    /// Crashes become faults and hangs become errors, attributed to the
+    /// crashing process and its bundle identifier.
+    pub fn to_log_event(&self) -> LogEvent {
+        let (message_type, category) = match self.kind {
+            CrashReportKind::Crash => (MessageType::Fault, "crash_report"),
+            CrashReportKind::Hang => (MessageType::Error, "hang_report"),
+        };
+        LogEvent {
+            timestamp: self.timestamp,
+            message_type,
+            subsystem: self.bundle_id.clone().unwrap_or_default(),
+            category: category.to_string(),
+            process: self.process.clone(),
+            process_id: self.process_id,
+            message: self.summary(),
+        }
+    }
+}
+
 /// Severity level for AI-generated insights and alerts
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[serde(rename_all = "lowercase")]
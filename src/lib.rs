@@ -31,8 +31,17 @@ pub mod web;
 /// MCP server for agent-driven alert triage
 pub mod mcp;
 
+/// OpenTelemetry OTLP/HTTP JSON ingestion
+pub mod otlp;
+
+/// Rolling buffer and trigger cooldowns persisted across restarts
+pub mod state;
+
+/// Replay of recorded collector captures through the trigger pipeline
+pub mod replay;
+
 // Re-export commonly used types
-pub use error::{AlertError, AnalysisError, CollectorError, ConfigError};
+pub use error::{AlertError, AnalysisError, CollectorError, ConfigError, StateError};
 pub use events::{
     DiskEvent, LogEvent, MeasurementKind, MemoryPressure, MessageType, MetricsEvent,
     MetricsProvenance, MetricsSource, ProcessMetric, Severity, Timestamp,
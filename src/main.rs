@@ -1,21 +1,31 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use eyes::aggregator::EventAggregator;
-use eyes::ai::{AIAnalyzer, MockBackend, OllamaBackend, OpenAIBackend};
-use eyes::alerts::AlertManager;
-use eyes::collectors::{DiskCollector, LogCollector, MetricsCollector};
-use eyes::config::{AIBackendConfig, Config};
+use eyes::ai::{AIAnalyzer, LLMBackend, MockBackend, OllamaBackend, OpenAIBackend};
+use eyes::alerts::{AlertManager, AlertStore};
+use eyes::collectors::capture::read_capture;
+use eyes::collectors::{read_event_file, read_log_archive};
+use eyes::collectors::{CaptureWriter, EventSinks, SourceRegistry};
+use eyes::config::watch::{install_reload_signal, take_reload_request};
+use eyes::config::{keep_source_settings, AIBackendConfig, Config, ConfigChanges, ConfigWatcher};
 use eyes::error::ConfigError;
-use eyes::events::{DiskEvent, LogEvent, MetricsEvent, Severity};
+use eyes::events::Timestamp;
+use eyes::events::{CrashReport, DiskEvent, LogEvent, MetricsEvent};
 use eyes::monitoring::SelfMonitoringCollector;
+use eyes::replay::{
+    evaluate_log_archive, evaluate_recorded_events, replay_capture, ReplayOptions, ReplaySummary,
+};
+use eyes::state::SavedState;
 use eyes::triggers::{
-    CrashDetectionRule, DiskIOSpikeRule, ErrorFrequencyRule, MemoryPressureRule, ResourceSpikeRule,
-    TriggerContext, TriggerEngine,
+    AnomalyRule, CompositeRule, CpuHogRule, CrashDetectionRule, DeclarativeRule, DiskIOSpikeRule,
+    ErrorFrequencyRule, HeartbeatRule, LogTemplateRule, MemoryLeakRule, MemoryPressureRule,
+    ResourceSpikeRule, RespawnLoopRule, RulePolicy, TriggerContext, TriggerCooldowns,
+    TriggerEngine, TriggerRule, BUILTIN_RULES,
 };
 use eyes::web;
 use log::{debug, error, info, warn};
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::{
     self, Receiver, RecvTimeoutError, Sender, SyncSender, TryRecvError, TrySendError,
 };
@@ -56,6 +66,92 @@ struct Cli {
         help = "Enable native macOS desktop notifications (disabled by default)"
     )]
     enable_notifications: bool,
+
+    /// Record raw collector output for later replay
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Record raw log, metrics and disk collector output to a capture file"
+    )]
+    capture: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Alternative run modes
+#[derive(Subcommand)]
+enum Command {
+    /// Replay a capture file through the parsers, trigger rules and alert store
+    Replay {
+        /// Capture file written with --capture
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Playback speed multiplier (1 = captured pace)
+        #[arg(long, default_value_t = 1.0, value_parser = parse_replay_speed)]
+        speed: f64,
+
+        /// Alert database to record candidates in (defaults to storage.database_path)
+        #[arg(long, value_name = "FILE")]
+        database: Option<PathBuf>,
+    },
+
+    /// Evaluate trigger rules over an exported `log show --style json` archive
+    Import {
+        /// JSON array written by `log show --style json`
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Ignore entries before this RFC 3339 timestamp
+        #[arg(long, value_name = "TIME", value_parser = parse_timestamp)]
+        since: Option<chrono::DateTime<chrono::Utc>>,
+
+        /// Ignore entries after this RFC 3339 timestamp
+        #[arg(long, value_name = "TIME", value_parser = parse_timestamp)]
+        until: Option<chrono::DateTime<chrono::Utc>>,
+
+        /// Alert database to record candidates in (defaults to storage.database_path)
+        #[arg(long, value_name = "FILE")]
+        database: Option<PathBuf>,
+    },
+
+    /// Work with the configured trigger rules
+    Rules {
+        #[command(subcommand)]
+        command: RulesCommand,
+    },
+}
+
+/// Trigger rule tools
+#[derive(Subcommand)]
+enum RulesCommand {
+    /// Backtest the configured rules against a recorded event file
+    Test {
+        /// JSONL file of LogEvent, MetricsEvent and DiskEvent records
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Evidence events to print per kind for each trigger
+        #[arg(long, value_name = "COUNT", default_value_t = 3)]
+        evidence: usize,
+    },
+}
+
+fn parse_timestamp(value: &str) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|timestamp| timestamp.with_timezone(&chrono::Utc))
+        .map_err(|error| format!("invalid RFC 3339 timestamp '{value}': {error}"))
+}
+
+fn parse_replay_speed(value: &str) -> Result<f64, String> {
+    let speed = value
+        .parse::<f64>()
+        .map_err(|error| format!("invalid speed '{value}': {error}"))?;
+    if !speed.is_finite() || speed <= 0.0 {
+        return Err(format!("speed must be a positive number, got '{value}'"));
+    }
+    Ok(speed)
 }
 
 impl Cli {
@@ -115,18 +211,32 @@ impl Cli {
 }
 
 /// Messages sent to the analysis thread
-#[derive(Debug)]
 enum AnalysisMessage {
     LogEvent(LogEvent),
     MetricsEvent(MetricsEvent),
     DiskEvent(DiskEvent),
+    CrashReport(CrashReport),
+    Reload(Box<AnalysisReload>),
     Shutdown,
 }
 
+/// Settings the analysis thread swaps in when the configuration is reloaded
+struct AnalysisReload {
+    /// Rebuilt rules, when `[triggers]` changed
+    trigger_engine: Option<TriggerEngine>,
+    /// Rules whose settings changed; the others keep their running instance
+    rebuilt_rules: Vec<String>,
+    trigger_cooldown: Duration,
+    automatic_analysis: bool,
+    /// New backend for the AI worker, when `[ai.backend]` changed
+    ai_backend: Option<Arc<dyn LLMBackend>>,
+}
+
 const ANALYSIS_QUEUE_CAPACITY: usize = 1024;
 const MANUAL_ANALYSIS_QUEUE_CAPACITY: usize = 16;
-const TRIGGER_COOLDOWN: Duration = Duration::from_secs(5 * 60);
+const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(2);
 const TRIGGER_EVALUATION_INTERVAL: Duration = Duration::from_secs(1);
+const STATE_SAVE_INTERVAL: Duration = Duration::from_secs(60);
 
 fn trigger_evaluation_due(
     last_evaluation: &mut std::time::Instant,
@@ -140,10 +250,17 @@ fn trigger_evaluation_due(
     true
 }
 
+/// The longest cooldown of any rule in the engine, or the default if longer
+fn longest_rule_cooldown(trigger_engine: &TriggerEngine, default: Duration) -> Duration {
+    trigger_engine
+        .longest_cooldown()
+        .map_or(default, |longest| longest.max(default))
+}
+
 enum AIWork {
     Analyze {
         candidate_id: Option<i64>,
-        context: TriggerContext,
+        context: Box<TriggerContext>,
     },
     Shutdown,
 }
@@ -166,7 +283,7 @@ fn dispatch_next_manual_analysis(
     let candidate_id = request.candidate_id;
     let work = AIWork::Analyze {
         candidate_id: Some(candidate_id),
-        context: request.context.clone(),
+        context: Box::new(request.context.clone()),
     };
 
     match ai_sender.try_send(work) {
@@ -182,20 +299,201 @@ fn dispatch_next_manual_analysis(
     }
 }
 
+/// Create the LLM backend selected by `[ai.backend]`
+fn build_ai_backend(backend: &AIBackendConfig) -> Arc<dyn LLMBackend> {
+    match backend {
+        AIBackendConfig::Ollama { endpoint, model } => {
+            info!(
+                "Using Ollama backend: endpoint={}, model={}",
+                endpoint, model
+            );
+            Arc::new(OllamaBackend::new(endpoint.clone(), model.clone()))
+        }
+        AIBackendConfig::OpenAI {
+            api_key,
+            model,
+            base_url,
+        } => {
+            info!("Using OpenAI backend: model={}", model);
+            debug!(
+                "OpenAI API key configured: {}",
+                if api_key.is_empty() { "NO" } else { "YES" }
+            );
+            Arc::new(OpenAIBackend::with_base_url(
+                api_key.clone(),
+                model.clone(),
+                base_url.clone(),
+            ))
+        }
+        AIBackendConfig::Mock => {
+            info!("Using Mock backend for testing");
+            Arc::new(MockBackend::success())
+        }
+    }
+}
+
+/// Copy the aggregator state and cooldowns for saving
+///
+/// The aggregator is locked only while its state is copied, not while the
+/// file is written.
+fn checkpoint_state(
+    event_aggregator: &Mutex<EventAggregator>,
+    last_triggered: HashMap<String, Timestamp>,
+) -> Option<SavedState> {
+    match event_aggregator.lock() {
+        Ok(aggregator) => Some(SavedState::new(aggregator.export_state(), last_triggered)),
+        Err(e) => {
+            error!("Failed to lock event aggregator for saving state: {}", e);
+            None
+        }
+    }
+}
+
+/// Build a fresh instance of the named built-in rule, configured from `config`
+fn builtin_rule(name: &str, config: &Config) -> Option<Box<dyn TriggerRule>> {
+    let rule: Box<dyn TriggerRule> = match name {
+        "ErrorFrequencyRule" => Box::new(ErrorFrequencyRule::new(
+            config.triggers.error_threshold(),
+            config.triggers.error_window_seconds() as i64,
+            config.triggers.error_frequency.severity,
+        )),
+        "MemoryPressureRule" => Box::new(MemoryPressureRule::new(
+            config.triggers.memory_threshold(),
+            config.triggers.memory_pressure.severity,
+        )),
+        "CrashDetectionRule" => Box::new(CrashDetectionRule::new(
+            config.triggers.crash_detection.keywords.clone(),
+            config.triggers.crash_detection.severity,
+        )),
+        "ResourceSpikeRule" => {
+            let spike = &config.triggers.resource_spike;
+            Box::new(ResourceSpikeRule::new(
+                spike.cpu_threshold_mw,
+                spike.gpu_threshold_mw,
+                spike.window_seconds as i64,
+                spike.severity,
+            ))
+        }
+        "DiskIOSpikeRule" => {
+            let spike = &config.triggers.disk_io_spike;
+            Box::new(DiskIOSpikeRule::new(
+                spike.read_threshold_kb_per_sec,
+                spike.write_threshold_kb_per_sec,
+                spike.window_seconds as i64,
+                spike.severity,
+            ))
+        }
+        "AnomalyRule" => Box::new(AnomalyRule::new(&config.triggers.anomaly)),
+        "LogTemplateRule" => Box::new(LogTemplateRule::new(&config.triggers.templates)),
+        "RespawnLoopRule" => Box::new(RespawnLoopRule::new(&config.triggers.respawn)),
+        "MemoryLeakRule" => Box::new(MemoryLeakRule::new(&config.triggers.memory_leak)),
+        "CpuHogRule" => Box::new(CpuHogRule::new(&config.triggers.cpu_hog)),
+        _ => return None,
+    };
+    Some(rule)
+}
+
+/// Build a fresh instance of a built-in, `[[triggers.rules]]` or
+/// `[[triggers.heartbeats]]` rule by name
+fn named_rule(name: &str, config: &Config) -> Option<Box<dyn TriggerRule>> {
+    if let Some(rule) = builtin_rule(name, config) {
+        return Some(rule);
+    }
+    let compiled: Result<Box<dyn TriggerRule>, _> =
+        if let Some(rule) = config.triggers.rules.iter().find(|rule| rule.name == name) {
+            DeclarativeRule::compile(rule).map(|rule| Box::new(rule) as _)
+        } else {
+            let heartbeat = config
+                .triggers
+                .heartbeats
+                .iter()
+                .find(|heartbeat| heartbeat.name == name)?;
+            HeartbeatRule::compile(heartbeat).map(|rule| Box::new(rule) as _)
+        };
+    match compiled {
+        Ok(rule) => Some(rule),
+        Err(e) => {
+            error!("Skipping trigger rule: {}", e);
+            None
+        }
+    }
+}
+
+/// Build the trigger engine with the enabled built-in rules and any
+/// `[[triggers.rules]]`, `[[triggers.heartbeats]]` and `[[triggers.composite]]`
+fn build_trigger_engine(config: &Config) -> TriggerEngine {
+    debug!("Initializing trigger engine with built-in rules");
+    let mut trigger_engine = TriggerEngine::new();
+
+    for name in BUILTIN_RULES {
+        let Some(controls) = config.triggers.builtin_controls(name) else {
+            continue;
+        };
+        if !controls.enabled {
+            debug!("Skipping disabled {}", name);
+            continue;
+        }
+        if let Some(rule) = builtin_rule(name, config) {
+            debug!(
+                "Adding {} (cooldown {}s{})",
+                name,
+                controls.cooldown_seconds,
+                if controls.rearm { ", re-arms" } else { "" }
+            );
+            trigger_engine.add_rule_with_policy(
+                rule,
+                RulePolicy {
+                    cooldown: Some(Duration::from_secs(controls.cooldown_seconds)),
+                    rearm: controls.rearm,
+                },
+            );
+        }
+    }
+
+    for rule in &config.triggers.rules {
+        match DeclarativeRule::compile(rule) {
+            Ok(rule) => trigger_engine.add_rule(Box::new(rule)),
+            Err(e) => error!("Skipping trigger rule: {}", e),
+        }
+    }
+
+    for heartbeat in &config.triggers.heartbeats {
+        match HeartbeatRule::compile(heartbeat) {
+            Ok(rule) => trigger_engine.add_rule(Box::new(rule)),
+            Err(e) => error!("Skipping heartbeat rule: {}", e),
+        }
+    }
+
+    for composite in &config.triggers.composite {
+        match CompositeRule::from_config(composite, |name| named_rule(name, config)) {
+            Some(rule) => {
+                debug!(
+                    "Adding composite rule '{}': {:?} of {} within {}s",
+                    composite.name,
+                    composite.mode,
+                    composite.rules.join(", "),
+                    composite.window_seconds
+                );
+                trigger_engine.add_rule(Box::new(rule));
+            }
+            None => error!(
+                "Skipping composite rule '{}': a child rule could not be built",
+                composite.name
+            ),
+        }
+    }
+
+    trigger_engine
+}
+
 /// Main application struct that orchestrates all system observer components
 ///
 /// SystemObserver coordinates the data flow between collectors, aggregator,
 /// trigger engine, AI analyzer, and alert manager. It manages the lifecycle
 /// of all components and handles graceful shutdown.
 pub struct SystemObserver {
-    /// Log collector for streaming macOS Unified Logs
-    log_collector: LogCollector,
-
-    /// Metrics collector for system resource monitoring
-    metrics_collector: MetricsCollector,
-
-    /// Disk collector for filesystem/disk I/O monitoring
-    disk_collector: DiskCollector,
+    /// Event sources (log, metrics, disk and any registered extras)
+    sources: SourceRegistry,
 
     /// Event aggregator with rolling buffer
     event_aggregator: Arc<Mutex<EventAggregator>>,
@@ -212,20 +510,14 @@ pub struct SystemObserver {
     /// Alert manager for delivering notifications
     alert_manager: Arc<Mutex<AlertManager>>,
 
-    /// Channel for log events from collector to aggregator
-    #[allow(dead_code)] // Used by collectors but appears unused
-    log_sender: Sender<LogEvent>,
-    log_receiver: Receiver<LogEvent>,
+    /// Channels event sources send on
+    sinks: EventSinks,
 
-    /// Channel for metrics events from collector to aggregator
-    #[allow(dead_code)] // Used by collectors but appears unused
-    metrics_sender: Sender<MetricsEvent>,
+    /// Receiving ends of the event source channels
+    log_receiver: Receiver<LogEvent>,
     metrics_receiver: Receiver<MetricsEvent>,
-
-    /// Channel for disk events from collector to aggregator
-    #[allow(dead_code)] // Used by collectors but appears unused
-    disk_sender: Sender<DiskEvent>,
     disk_receiver: Receiver<DiskEvent>,
+    crash_receiver: Receiver<CrashReport>,
 
     /// Shutdown signal
     shutdown_sender: Sender<()>,
@@ -252,6 +544,24 @@ pub struct SystemObserver {
 
     /// Address used by the alert dashboard when enabled
     web_bind_address: Option<SocketAddr>,
+
+    /// Whether the dashboard also accepts OTLP/HTTP logs and metrics
+    web_otlp: bool,
+
+    /// File the rolling buffer and trigger cooldowns are saved to on stop
+    state_path: PathBuf,
+
+    /// Last time each trigger fired, handed to and back from the analysis thread
+    trigger_cooldowns: Arc<Mutex<HashMap<String, Timestamp>>>,
+
+    /// Configuration currently applied, compared against reloaded ones
+    config: Config,
+
+    /// Capture file for raw collector output, also given to restarted sources
+    capture: Option<CaptureWriter>,
+
+    /// Configuration file polled for edits
+    config_watcher: Option<ConfigWatcher>,
 }
 
 impl SystemObserver {
@@ -294,6 +604,7 @@ impl SystemObserver {
         let (log_sender, log_receiver) = mpsc::channel();
         let (metrics_sender, metrics_receiver) = mpsc::channel();
         let (disk_sender, disk_receiver) = mpsc::channel();
+        let (crash_sender, crash_receiver) = mpsc::channel();
         let (shutdown_sender, shutdown_receiver) = mpsc::channel();
         let (manual_analysis_sender, manual_analysis_receiver) =
             mpsc::sync_channel(MANUAL_ANALYSIS_QUEUE_CAPACITY);
@@ -308,115 +619,26 @@ impl SystemObserver {
             config.buffer.max_size,
         )));
 
-        // Initialize collectors
-        debug!(
-            "Initializing log collector with predicate: '{}'",
-            config.logging.predicate
-        );
-        let log_collector = LogCollector::new(config.logging.predicate.clone(), log_sender.clone());
-
-        debug!(
-            "Initializing metrics collector with interval: {:?}",
-            Duration::from_secs(config.metrics.interval_seconds)
-        );
-        let mut metrics_collector = MetricsCollector::new(
-            Duration::from_secs(config.metrics.interval_seconds),
-            metrics_sender.clone(),
-        );
-
         // Initialize self-monitoring collector first (needed for adaptive sampling)
         debug!("Initializing self-monitoring collector");
         let self_monitoring = Arc::new(SelfMonitoringCollector::new());
 
-        // Set monitoring for adaptive sampling (Requirement 7.4)
-        metrics_collector.set_monitoring(Arc::clone(&self_monitoring));
-
-        debug!(
-            "Initializing disk collector with interval: {:?}",
-            Duration::from_secs(config.metrics.interval_seconds)
-        );
-        let mut disk_collector = DiskCollector::new(
-            Duration::from_secs(config.metrics.interval_seconds),
-            disk_sender.clone(),
-        );
-
-        // Set monitoring for adaptive sampling (Requirement 7.4)
-        disk_collector.set_monitoring(Arc::clone(&self_monitoring));
+        // Initialize event sources; metrics and disk use self-monitoring for
+        // adaptive sampling (Requirement 7.4)
+        let sinks = EventSinks {
+            logs: log_sender,
+            metrics: metrics_sender,
+            disk: disk_sender,
+            crashes: crash_sender,
+        };
+        let sources = SourceRegistry::from_config(&config, &sinks, &self_monitoring);
 
         // Initialize trigger engine with built-in rules
-        debug!("Initializing trigger engine with built-in rules");
-        let mut trigger_engine = TriggerEngine::new();
-
-        debug!(
-            "Adding ErrorFrequencyRule: threshold={}, window={}s",
-            config.triggers.error_threshold, config.triggers.error_window_seconds
-        );
-        trigger_engine.add_rule(Box::new(ErrorFrequencyRule::new(
-            config.triggers.error_threshold,
-            config.triggers.error_window_seconds as i64,
-            Severity::Warning,
-        )));
-
-        debug!(
-            "Adding MemoryPressureRule: threshold={:?}",
-            config.triggers.memory_threshold
-        );
-        trigger_engine.add_rule(Box::new(MemoryPressureRule::new(
-            config.triggers.memory_threshold,
-            Severity::Warning,
-        )));
-
-        debug!("Adding CrashDetectionRule with curated crash signatures");
-        trigger_engine.add_rule(Box::new(CrashDetectionRule::with_defaults()));
-
-        debug!("Adding ResourceSpikeRule: cpu_threshold=1000mW, gpu_threshold=2000mW, window=30s");
-        trigger_engine.add_rule(Box::new(ResourceSpikeRule::new(
-            1000.0, // CPU threshold in milliwatts
-            2000.0, // GPU threshold in milliwatts
-            30,     // window seconds
-            Severity::Warning,
-        )));
-
-        let disk_io_spike_rule = DiskIOSpikeRule::with_defaults();
-        debug!(
-            "Adding DiskIOSpikeRule: read_threshold={}KB/s, write_threshold={}KB/s, window={}s",
-            disk_io_spike_rule.read_spike_threshold_kb_per_sec,
-            disk_io_spike_rule.write_spike_threshold_kb_per_sec,
-            disk_io_spike_rule.comparison_window_seconds
-        );
-        trigger_engine.add_rule(Box::new(disk_io_spike_rule));
+        let trigger_engine = build_trigger_engine(&config);
 
         // Initialize AI analyzer with configured backend
         debug!("Initializing AI analyzer");
-        let mut ai_analyzer = match &config.ai.backend {
-            AIBackendConfig::Ollama { endpoint, model } => {
-                info!(
-                    "Using Ollama backend: endpoint={}, model={}",
-                    endpoint, model
-                );
-                let backend = OllamaBackend::new(endpoint.clone(), model.clone());
-                AIAnalyzer::with_backend(Arc::new(backend))
-            }
-            AIBackendConfig::OpenAI {
-                api_key,
-                model,
-                base_url,
-            } => {
-                info!("Using OpenAI backend: model={}", model);
-                debug!(
-                    "OpenAI API key configured: {}",
-                    if api_key.is_empty() { "NO" } else { "YES" }
-                );
-                let backend =
-                    OpenAIBackend::with_base_url(api_key.clone(), model.clone(), base_url.clone());
-                AIAnalyzer::with_backend(Arc::new(backend))
-            }
-            AIBackendConfig::Mock => {
-                info!("Using Mock backend for testing");
-                let backend = MockBackend::success();
-                AIAnalyzer::with_backend(Arc::new(backend))
-            }
-        };
+        let mut ai_analyzer = AIAnalyzer::with_backend(build_ai_backend(&config.ai.backend));
 
         // Set up monitoring on AI analyzer
         ai_analyzer.set_monitoring(self_monitoring.clone());
@@ -448,20 +670,17 @@ impl SystemObserver {
         };
 
         Ok(SystemObserver {
-            log_collector,
-            metrics_collector,
-            disk_collector,
+            sources,
             event_aggregator,
             trigger_engine: Some(trigger_engine),
             ai_analyzer: Some(ai_analyzer),
             automatic_analysis: config.ai.automatic_analysis,
             alert_manager,
-            log_sender,
+            sinks,
             log_receiver,
-            metrics_sender,
             metrics_receiver,
-            disk_sender,
             disk_receiver,
+            crash_receiver,
             shutdown_sender,
             shutdown_receiver,
             shutdown_senders: Vec::new(),
@@ -470,18 +689,45 @@ impl SystemObserver {
             manual_analysis_sender,
             manual_analysis_receiver: Some(manual_analysis_receiver),
             self_monitoring,
-            web_database_path: config.storage.database_path,
+            web_database_path: config.storage.database_path.clone(),
             web_bind_address,
+            web_otlp: config.web.otlp,
+            state_path: config.storage.state_path.clone(),
+            trigger_cooldowns: Arc::new(Mutex::new(HashMap::new())),
+            config,
+            capture: None,
+            config_watcher: None,
         })
     }
 
-    /// Load configuration from file or use defaults
+    /// Resolve the configuration file to load, if any
     ///
     /// Follows the documented configuration discovery order:
     /// 1. Explicit CLI path (if provided)
     /// 2. SYSTEM_OBSERVER_CONFIG environment variable
-    /// 3. ~/.config/macos-system-observer/config.toml
-    /// 4. Default configuration
+    /// 3. ~/.config/macos-system-observer/config.toml, if it exists
+    pub fn config_file_path(config_path: Option<&str>) -> Option<PathBuf> {
+        if let Some(explicit_path) = config_path {
+            // 1. Explicit CLI path takes highest precedence
+            Some(PathBuf::from(explicit_path))
+        } else if let Ok(env_path) = std::env::var("SYSTEM_OBSERVER_CONFIG") {
+            // 2. Environment variable
+            Some(PathBuf::from(env_path))
+        } else {
+            // 3. Default path: ~/.config/macos-system-observer/config.toml
+            let home_dir = std::env::var_os("HOME")?;
+            let default_path = Path::new(&home_dir)
+                .join(".config")
+                .join("macos-system-observer")
+                .join("config.toml");
+            default_path.exists().then_some(default_path)
+        }
+    }
+
+    /// Load configuration from file or use defaults
+    ///
+    /// The file is found with [`SystemObserver::config_file_path`]; without
+    /// one the default configuration is used.
     ///
     /// # Arguments
     ///
@@ -491,36 +737,13 @@ impl SystemObserver {
     ///
     /// Loaded configuration, or defaults when no configuration source exists
     pub fn load_config(config_path: Option<&str>) -> Result<Config, ConfigError> {
-        // Determine config file path using documented precedence
-        let config_file_path = if let Some(explicit_path) = config_path {
-            // 1. Explicit CLI path takes highest precedence
-            Some(explicit_path.to_string())
-        } else if let Ok(env_path) = std::env::var("SYSTEM_OBSERVER_CONFIG") {
-            // 2. Environment variable
-            Some(env_path)
-        } else {
-            // 3. Default path: ~/.config/macos-system-observer/config.toml
-            if let Some(home_dir) = std::env::var_os("HOME") {
-                let default_path = std::path::Path::new(&home_dir)
-                    .join(".config")
-                    .join("macos-system-observer")
-                    .join("config.toml");
-
-                if default_path.exists() {
-                    Some(default_path.to_string_lossy().to_string())
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        };
+        let config_file_path = Self::config_file_path(config_path);
 
         match config_file_path {
             Some(path) => {
-                info!("Loading configuration from: {}", path);
-                let config = Config::from_file(std::path::Path::new(&path))?;
-                info!("Configuration loaded successfully from: {}", path);
+                info!("Loading configuration from: {}", path.display());
+                let config = Config::from_file(&path)?;
+                info!("Configuration loaded successfully from: {}", path.display());
                 debug!(
                     "Loaded config: log_predicate='{}', metrics_interval={}s, buffer_max_age={}s",
                     config.logging.predicate,
@@ -542,6 +765,14 @@ impl SystemObserver {
         }
     }
 
+    /// Record raw output from every registered source to a capture file
+    ///
+    /// Only sources registered before this call are captured.
+    pub fn set_capture(&mut self, capture: CaptureWriter) {
+        self.sources.set_capture(&capture);
+        self.capture = Some(capture);
+    }
+
     /// Start the system observer and all its components
     ///
     /// This method spawns all necessary threads and begins monitoring.
@@ -560,11 +791,16 @@ impl SystemObserver {
                 bind_address,
                 shutdown_receiver,
                 self.manual_analysis_sender.clone(),
+                self.web_otlp.then(|| self.sinks.clone()),
             )?;
             self.shutdown_senders.push(shutdown_sender);
             self.thread_handles.push(web_thread);
         }
 
+        // Restore the previous run's buffer and cooldowns before any
+        // thread can add events or evaluate triggers
+        self.restore_state();
+
         // Spawn analysis thread first (creates the analysis_sender)
         let analysis_thread = self.spawn_analysis_thread()?;
         self.thread_handles.push(analysis_thread);
@@ -579,41 +815,16 @@ impl SystemObserver {
         let disk_forwarding_thread = self.spawn_disk_forwarding_thread()?;
         self.thread_handles.push(disk_forwarding_thread);
 
+        let crash_forwarding_thread = self.spawn_crash_forwarding_thread()?;
+        self.thread_handles.push(crash_forwarding_thread);
+
         // Spawn notification thread
         let notification_thread = self.spawn_notification_thread()?;
         self.thread_handles.push(notification_thread);
 
-        // Start collectors last
-        self.log_collector.start()?;
-        info!("Log collector started");
-
-        // Try to start metrics collector, but continue in degraded mode if it fails (Requirement 7.2)
-        match self.metrics_collector.start() {
-            Ok(()) => {
-                info!("Metrics collector started");
-            }
-            Err(e) => {
-                warn!(
-                    "Metrics collector failed to start, continuing with log monitoring only: {}",
-                    e
-                );
-                // Continue operation without metrics collection (degraded mode)
-            }
-        }
-
-        // Try to start disk collector, but continue if it fails
-        match self.disk_collector.start() {
-            Ok(()) => {
-                info!("Disk collector started");
-            }
-            Err(e) => {
-                warn!(
-                    "Disk collector failed to start, continuing without disk monitoring: {}",
-                    e
-                );
-                // Continue operation without disk collection
-            }
-        }
+        // Start sources last; optional sources such as metrics and disk
+        // degrade instead of aborting startup (Requirement 7.2)
+        self.sources.start_all()?;
 
         info!("All SystemObserver components started successfully");
         Ok(())
@@ -625,18 +836,8 @@ impl SystemObserver {
     pub fn stop(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         info!("Stopping SystemObserver components");
 
-        // Keep the forwarding threads alive until collectors stop producing events.
-        if let Err(e) = self.log_collector.stop() {
-            error!("Failed to stop log collector: {}", e);
-        }
-
-        if let Err(e) = self.metrics_collector.stop() {
-            error!("Failed to stop metrics collector: {}", e);
-        }
-
-        if let Err(e) = self.disk_collector.stop() {
-            error!("Failed to stop disk collector: {}", e);
-        }
+        // Keep the forwarding threads alive until sources stop producing events.
+        self.sources.stop_all();
 
         for sender in &self.shutdown_senders {
             if let Err(e) = sender.send(()) {
@@ -657,44 +858,253 @@ impl SystemObserver {
             }
         }
 
+        self.save_state();
+
         info!("SystemObserver stopped successfully");
         Ok(())
     }
 
+    /// Load the state saved by the previous run, if any
+    ///
+    /// Events older than the buffer's `max_age` and expired cooldowns are
+    /// discarded. A missing or unreadable state file starts from empty.
+    fn restore_state(&mut self) {
+        let saved = match SavedState::load(&self.state_path) {
+            Ok(Some(saved)) => saved,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("Ignoring state file {}: {}", self.state_path.display(), e);
+                return;
+            }
+        };
+
+        let restored_events = match self.event_aggregator.lock() {
+            Ok(mut aggregator) => {
+                let restored = aggregator.restore_state(saved.aggregator);
+                // A checkpoint may predate templates mined before a crash
+                if !saved.clean_shutdown {
+                    aggregator.restart_template_learning();
+                }
+                restored
+            }
+            Err(e) => {
+                error!("Failed to lock event aggregator for restore: {}", e);
+                0
+            }
+        };
+        if let Ok(mut cooldowns) = self.trigger_cooldowns.lock() {
+            *cooldowns = saved.last_triggered;
+        }
+        info!(
+            "Restored {} buffered events from {} (saved {})",
+            restored_events,
+            self.state_path.display(),
+            saved.saved_at
+        );
+    }
+
+    /// Save the rolling buffer and trigger cooldowns for the next run
+    fn save_state(&self) {
+        let cooldowns = self
+            .trigger_cooldowns
+            .lock()
+            .map(|cooldowns| cooldowns.clone())
+            .unwrap_or_default();
+        if let Some(state) = checkpoint_state(&self.event_aggregator, cooldowns) {
+            match state.on_shutdown().save(&self.state_path) {
+                Ok(()) => info!("Saved state to {}", self.state_path.display()),
+                Err(e) => error!(
+                    "Failed to save state to {}: {}",
+                    self.state_path.display(),
+                    e
+                ),
+            }
+        }
+    }
+
     /// Wait for shutdown signal (blocking)
     ///
     /// This method blocks until a shutdown signal is received or an error occurs.
-    pub fn wait_for_shutdown(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// While waiting it reloads the configuration when the watched file changes
+    /// or a reload signal arrives.
+    pub fn wait_for_shutdown(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         info!("Waiting for shutdown signal...");
 
-        // Set up signal handling for graceful shutdown
-        let shutdown_receiver = &self.shutdown_receiver;
-
-        // Block until shutdown signal received
-        match shutdown_receiver.recv() {
-            Ok(()) => {
-                info!("Shutdown signal received");
-                Ok(())
+        loop {
+            match self.shutdown_receiver.recv_timeout(CONFIG_POLL_INTERVAL) {
+                Ok(()) => {
+                    info!("Shutdown signal received");
+                    return Ok(());
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    let requested = take_reload_request();
+                    let edited = self
+                        .config_watcher
+                        .as_mut()
+                        .is_some_and(|watcher| watcher.poll());
+                    if requested || edited {
+                        self.reload_watched_config();
+                    }
+                }
+                Err(e) => {
+                    error!("Error waiting for shutdown: {}", e);
+                    return Err(Box::new(e));
+                }
             }
+        }
+    }
+
+    /// Reload the configuration when `path` changes or SIGHUP is received
+    pub fn watch_config(&mut self, path: PathBuf) {
+        info!("Watching {} for configuration changes", path.display());
+        self.config_watcher = Some(ConfigWatcher::new(path));
+    }
+
+    /// Read the watched configuration file again and apply it
+    ///
+    /// A file that fails to parse or validate is rejected with an error and
+    /// the running configuration stays in effect.
+    fn reload_watched_config(&mut self) {
+        let Some(path) = self
+            .config_watcher
+            .as_ref()
+            .map(|watcher| watcher.path().to_path_buf())
+        else {
+            warn!("Reload requested, but eyes was started without a configuration file");
+            return;
+        };
+        info!("Reloading configuration from {}", path.display());
+        let config = match Config::from_file(&path) {
+            Ok(config) => config,
             Err(e) => {
-                error!("Error waiting for shutdown: {}", e);
-                Err(Box::new(e))
+                error!(
+                    "Rejected configuration from {}, keeping the running configuration: {}",
+                    path.display(),
+                    e
+                );
+                return;
             }
+        };
+        if let Err(e) = self.reload(config) {
+            error!(
+                "Failed to apply configuration from {}, keeping the running configuration: {}",
+                path.display(),
+                e
+            );
         }
     }
 
+    /// Apply a validated configuration without restarting
+    ///
+    /// Event sources whose settings changed are restarted, trigger rules whose
+    /// settings changed are rebuilt and swapped in at once, and the alert
+    /// limits, automatic analysis flag and AI backend are updated. Changes to
+    /// `[buffer]`, `[storage]`, `[web]` and `[collectors]` are reported and
+    /// only take effect after a restart.
+    ///
+    /// A source that fails to restart keeps running with its previous
+    /// settings, and the next reload tries its new settings again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the analysis thread has stopped.
+    pub fn reload(
+        &mut self,
+        mut config: Config,
+    ) -> Result<ConfigChanges, Box<dyn std::error::Error + Send + Sync>> {
+        let changes = ConfigChanges::between(&self.config, &config);
+        if changes.is_empty() {
+            info!("Configuration unchanged");
+            return Ok(changes);
+        }
+        for section in &changes.restart_required {
+            warn!(
+                "Configuration section [{}] changed; restart eyes to apply it",
+                section
+            );
+        }
+
+        if !changes.sources.is_empty() {
+            let mut reloaded_sources =
+                SourceRegistry::from_config(&config, &self.sinks, &self.self_monitoring);
+            // A source that fails to restart keeps running with its previous
+            // settings; the other sources and sections are still applied
+            let mut failed_sources = Vec::new();
+            for name in &changes.sources {
+                let running = self.sources.names().any(|registered| registered == *name);
+                match reloaded_sources.remove(name) {
+                    Some(mut source) if running => {
+                        if let Some(capture) = &self.capture {
+                            source.set_capture(capture.clone());
+                        }
+                        if self.sources.replace(source).is_err() {
+                            failed_sources.push(*name);
+                        }
+                    }
+                    None if !running => {}
+                    _ => warn!(
+                        "Event source '{}' was added or removed; restart eyes to apply it",
+                        name
+                    ),
+                }
+            }
+            for name in failed_sources {
+                keep_source_settings(&mut config, &self.config, name);
+            }
+        }
+
+        if changes.triggers || changes.automatic_analysis || changes.ai_backend {
+            let trigger_engine = changes.triggers.then(|| build_trigger_engine(&config));
+            let ai_backend = changes
+                .ai_backend
+                .then(|| build_ai_backend(&config.ai.backend));
+            match &self.analysis_sender {
+                Some(sender) => sender.send(AnalysisMessage::Reload(Box::new(AnalysisReload {
+                    trigger_engine,
+                    rebuilt_rules: changes.rules.clone(),
+                    trigger_cooldown: Duration::from_secs(config.triggers.cooldown_seconds),
+                    automatic_analysis: config.ai.automatic_analysis,
+                    ai_backend,
+                })))?,
+                // Not started yet: the analysis thread will pick these up
+                None => {
+                    if let (Some(running), Some(reloaded)) =
+                        (&mut self.trigger_engine, trigger_engine)
+                    {
+                        running.replace_rules(reloaded, &changes.rules);
+                    }
+                    if let (Some(analyzer), Some(backend)) = (&mut self.ai_analyzer, ai_backend) {
+                        analyzer.set_backend(backend);
+                    }
+                }
+            }
+            self.automatic_analysis = config.ai.automatic_analysis;
+        }
+
+        if changes.alerts {
+            if let Ok(mut manager) = self.alert_manager.lock() {
+                manager.set_rate_limit(config.alerts.rate_limit_per_minute);
+                manager.set_minimum_severity(config.alerts.minimum_severity);
+            }
+        }
+
+        info!("Applied reloaded configuration: {:?}", changes);
+        self.config = config;
+        Ok(changes)
+    }
+
     /// Spawn the analysis thread that processes events and triggers AI analysis
     fn spawn_analysis_thread(
         &mut self,
     ) -> Result<JoinHandle<()>, Box<dyn std::error::Error + Send + Sync>> {
         let event_aggregator = Arc::clone(&self.event_aggregator);
         let alert_manager = Arc::clone(&self.alert_manager);
-        let trigger_engine = self
+        let mut trigger_engine = self
             .trigger_engine
             .take()
             .ok_or("Trigger engine unavailable")?;
-        let ai_analyzer = self.ai_analyzer.take().ok_or("AI analyzer unavailable")?;
-        let automatic_analysis = self.automatic_analysis;
+        let mut ai_analyzer = self.ai_analyzer.take().ok_or("AI analyzer unavailable")?;
+        let mut automatic_analysis = self.automatic_analysis;
         let manual_analysis_receiver = self
             .manual_analysis_receiver
             .take()
@@ -709,11 +1119,16 @@ impl SystemObserver {
 
         // Clone self-monitoring for the analysis thread
         let self_monitoring_clone = self.self_monitoring.clone_collector();
+        let trigger_cooldowns = Arc::clone(&self.trigger_cooldowns);
+        let trigger_cooldown = Duration::from_secs(self.config.triggers.cooldown_seconds);
+        let mut longest_cooldown = longest_rule_cooldown(&trigger_engine, trigger_cooldown);
+        let state_path = self.state_path.clone();
 
         let handle = std::thread::spawn(move || {
             info!("Analysis thread started");
 
             let (ai_sender, ai_receiver) = mpsc::sync_channel::<AIWork>(1);
+            let (backend_sender, backend_receiver) = mpsc::channel::<Arc<dyn LLMBackend>>();
             let ai_alert_manager = Arc::clone(&alert_manager);
             let ai_handle = std::thread::spawn(move || {
                 let rt = match tokio::runtime::Runtime::new() {
@@ -725,6 +1140,10 @@ impl SystemObserver {
                 };
 
                 loop {
+                    for backend in backend_receiver.try_iter() {
+                        info!("AI worker switched to the reloaded backend");
+                        ai_analyzer.set_backend(backend);
+                    }
                     match ai_receiver.recv_timeout(Duration::from_millis(250)) {
                         Ok(AIWork::Analyze {
                             candidate_id,
@@ -785,7 +1204,15 @@ impl SystemObserver {
             let mut log_events_processed = 0u64;
             let mut metrics_events_processed = 0u64;
             let mut last_metrics_report = std::time::Instant::now();
-            let mut last_triggered = HashMap::<String, std::time::Instant>::new();
+            // The first checkpoint replaces the shutdown state the run started from
+            let mut last_state_save = std::time::Instant::now() - STATE_SAVE_INTERVAL;
+            let mut cooldowns = TriggerCooldowns::with_history(
+                trigger_cooldown,
+                trigger_cooldowns
+                    .lock()
+                    .map(|mut saved| std::mem::take(&mut *saved))
+                    .unwrap_or_default(),
+            );
             let mut last_trigger_evaluation =
                 std::time::Instant::now() - TRIGGER_EVALUATION_INTERVAL;
             let mut queued_manual_analyses = VecDeque::new();
@@ -813,6 +1240,35 @@ impl SystemObserver {
                             // Note: We could add disk_events_processed counter if needed
                         }
                     }
+                    Ok(AnalysisMessage::CrashReport(report)) => {
+                        if let Ok(mut aggregator) = event_aggregator.lock() {
+                            // Log rules such as CrashDetectionRule see the report as a fault
+                            aggregator.add_log(report.to_log_event());
+                            aggregator.add_crash(report);
+                            aggregator.prune_old_entries();
+                            log_events_processed += 1;
+                        }
+                    }
+                    Ok(AnalysisMessage::Reload(reload)) => {
+                        let AnalysisReload {
+                            trigger_engine: reloaded_engine,
+                            rebuilt_rules,
+                            trigger_cooldown: reloaded_cooldown,
+                            automatic_analysis: reloaded_automatic_analysis,
+                            ai_backend,
+                        } = *reload;
+                        if let Some(reloaded_engine) = reloaded_engine {
+                            trigger_engine.replace_rules(reloaded_engine, &rebuilt_rules);
+                        }
+                        cooldowns.default = reloaded_cooldown;
+                        longest_cooldown =
+                            longest_rule_cooldown(&trigger_engine, reloaded_cooldown);
+                        automatic_analysis = reloaded_automatic_analysis;
+                        if let Some(backend) = ai_backend {
+                            let _ = backend_sender.send(backend);
+                        }
+                        info!("Analysis thread applied reloaded configuration");
+                    }
                     Ok(AnalysisMessage::Shutdown) => {
                         info!("Analysis thread received shutdown signal");
                         break;
@@ -862,6 +1318,22 @@ impl SystemObserver {
                     last_metrics_report = std::time::Instant::now();
                 }
 
+                // Checkpoint the state so a crash loses at most one interval
+                if last_state_save.elapsed() >= STATE_SAVE_INTERVAL {
+                    let last_triggered = cooldowns.recent(chrono::Utc::now(), longest_cooldown);
+                    if let Some(state) = checkpoint_state(&event_aggregator, last_triggered) {
+                        match state.save(&state_path) {
+                            Ok(()) => debug!("Checkpointed state to {}", state_path.display()),
+                            Err(e) => error!(
+                                "Failed to checkpoint state to {}: {}",
+                                state_path.display(),
+                                e
+                            ),
+                        }
+                    }
+                    last_state_save = std::time::Instant::now();
+                }
+
                 // Check triggers and run AI analysis if needed
                 if !trigger_evaluation_due(&mut last_trigger_evaluation, std::time::Instant::now())
                 {
@@ -869,32 +1341,14 @@ impl SystemObserver {
                 }
 
                 if let Ok(aggregator) = event_aggregator.lock() {
-                    let recent_logs_refs = aggregator.get_recent_logs(chrono::Duration::minutes(5));
-                    let recent_metrics_refs =
-                        aggregator.get_recent_metrics(chrono::Duration::minutes(5));
-                    let recent_disk_refs = aggregator.get_recent_disk(chrono::Duration::minutes(5));
-
-                    // Convert references to owned values
-                    let recent_logs: Vec<LogEvent> =
-                        recent_logs_refs.into_iter().cloned().collect();
-                    let recent_metrics: Vec<MetricsEvent> =
-                        recent_metrics_refs.into_iter().cloned().collect();
-                    let recent_disk: Vec<DiskEvent> =
-                        recent_disk_refs.into_iter().cloned().collect();
-
-                    let contexts =
-                        trigger_engine.evaluate(&recent_logs, &recent_metrics, &recent_disk);
+                    let snapshot = aggregator.snapshot(chrono::Duration::minutes(5));
+                    let recent_crashes = snapshot.crashes().iter().collect::<Vec<_>>();
+                    let contexts = trigger_engine.evaluate_with_policy(&snapshot, &mut cooldowns);
 
                     // Process new triggers
-                    for context in contexts {
-                        let now = std::time::Instant::now();
+                    for mut context in contexts {
+                        context.attach_crash_reports(&recent_crashes);
                         let trigger_key = context.cooldown_key();
-                        if last_triggered
-                            .get(&trigger_key)
-                            .is_some_and(|last| now.duration_since(*last) < TRIGGER_COOLDOWN)
-                        {
-                            continue;
-                        }
                         let candidate_id = match alert_manager.lock() {
                             Ok(mut manager) => match manager.record_analysis_candidate(&context) {
                                 Ok(candidate_id) => candidate_id,
@@ -915,18 +1369,14 @@ impl SystemObserver {
                                     "Automatic AI analysis is disabled; use Analyze now to run it manually",
                                 );
                             }
-                            last_triggered.insert(trigger_key.clone(), now);
                             info!("Trigger recorded for manual analysis: {}", trigger_key);
                             continue;
                         }
                         match ai_sender.try_send(AIWork::Analyze {
                             candidate_id,
-                            context,
+                            context: Box::new(context),
                         }) {
-                            Ok(()) => {
-                                last_triggered.insert(trigger_key.clone(), now);
-                                info!("Trigger activated: {}", trigger_key);
-                            }
+                            Ok(()) => info!("Trigger activated: {}", trigger_key),
                             Err(TrySendError::Full(AIWork::Analyze { candidate_id, .. })) => {
                                 if let Ok(manager) = alert_manager.lock() {
                                     manager.mark_analysis_failed(
@@ -934,7 +1384,6 @@ impl SystemObserver {
                                         "AI worker was busy; analysis was not started",
                                     );
                                 }
-                                last_triggered.insert(trigger_key.clone(), now);
                                 debug!("AI worker busy; coalescing trigger");
                             }
                             Err(TrySendError::Disconnected(AIWork::Analyze {
@@ -969,6 +1418,9 @@ impl SystemObserver {
 
             let _ = ai_sender.send(AIWork::Shutdown);
             let _ = ai_handle.join();
+            if let Ok(mut saved) = trigger_cooldowns.lock() {
+                *saved = cooldowns.recent(chrono::Utc::now(), longest_cooldown);
+            }
             info!("Analysis thread stopped");
         });
 
@@ -1148,6 +1600,63 @@ impl SystemObserver {
         Ok(handle)
     }
 
+    /// Spawn thread that forwards crash reports to analysis thread
+    fn spawn_crash_forwarding_thread(
+        &mut self,
+    ) -> Result<JoinHandle<()>, Box<dyn std::error::Error + Send + Sync>> {
+        // Create a dedicated shutdown channel for this thread
+        let (shutdown_sender, shutdown_receiver) = mpsc::channel();
+        self.shutdown_senders.push(shutdown_sender);
+
+        let analysis_sender = self
+            .analysis_sender
+            .as_ref()
+            .ok_or("Analysis sender not initialized")?
+            .clone();
+
+        // Move the crash_receiver into the thread
+        let crash_receiver = std::mem::replace(&mut self.crash_receiver, {
+            let (_, dummy_receiver) = mpsc::channel();
+            dummy_receiver
+        });
+
+        let handle = std::thread::spawn(move || {
+            info!("Crash report forwarding thread started");
+
+            loop {
+                // Check for shutdown signal (non-blocking)
+                if shutdown_receiver.try_recv().is_ok() {
+                    info!("Crash report forwarding thread received shutdown signal");
+                    break;
+                }
+
+                // Forward crash reports to analysis thread
+                match crash_receiver.recv_timeout(Duration::from_millis(100)) {
+                    Ok(report) => {
+                        match analysis_sender.try_send(AnalysisMessage::CrashReport(report)) {
+                            Ok(()) => {}
+                            Err(TrySendError::Full(_)) => {
+                                warn!("Analysis queue full; dropping crash report");
+                            }
+                            Err(TrySendError::Disconnected(_)) => break,
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => {
+                        // Timeout is expected, continue
+                    }
+                    Err(RecvTimeoutError::Disconnected) => {
+                        info!("Crash report receiver disconnected");
+                        break;
+                    }
+                }
+            }
+
+            info!("Crash report forwarding thread stopped");
+        });
+
+        Ok(handle)
+    }
+
     /// Spawn the notification thread that processes queued alerts
     fn spawn_notification_thread(
         &mut self,
@@ -1193,6 +1702,216 @@ impl SystemObserver {
     }
 }
 
+/// Replay a capture file through the trigger pipeline and print what fired
+fn run_replay(
+    config: &Config,
+    file: &Path,
+    speed: f64,
+    database: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let records = read_capture(file)?;
+    let mut aggregator = EventAggregator::new(
+        chrono::Duration::seconds(config.buffer.max_age_seconds as i64),
+        config.buffer.max_size,
+    );
+    let trigger_engine = build_trigger_engine(config);
+    let mut store = AlertStore::open(database.unwrap_or(&config.storage.database_path))?;
+    let options = ReplayOptions {
+        speed,
+        evaluation_interval: TRIGGER_EVALUATION_INTERVAL,
+        cooldown: Duration::from_secs(config.triggers.cooldown_seconds),
+    };
+
+    let summary = replay_capture(
+        &records,
+        &mut aggregator,
+        &trigger_engine,
+        Some(&mut store),
+        &options,
+    )?;
+
+    print_recorded_triggers(&summary);
+    println!(
+        "Replayed {} records: {} log, {} metrics, {} disk events; {} triggers recorded",
+        summary.records,
+        summary.log_events,
+        summary.metrics_events,
+        summary.disk_events,
+        summary.candidate_ids.len()
+    );
+    Ok(())
+}
+
+/// Evaluate an exported log archive in event time and record the candidates
+fn run_import(
+    config: &Config,
+    file: &Path,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    until: Option<chrono::DateTime<chrono::Utc>>,
+    database: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut archive = read_log_archive(file)?;
+    archive.retain_window(since, until);
+    let trigger_engine = build_trigger_engine(config);
+    let mut store = AlertStore::open(database.unwrap_or(&config.storage.database_path))?;
+    let options = ReplayOptions {
+        evaluation_interval: TRIGGER_EVALUATION_INTERVAL,
+        cooldown: Duration::from_secs(config.triggers.cooldown_seconds),
+        ..ReplayOptions::default()
+    };
+
+    let summary =
+        evaluate_log_archive(&archive.events, &trigger_engine, Some(&mut store), &options);
+
+    print_recorded_triggers(&summary);
+    println!(
+        "Imported {} log events ({} entries skipped); {} triggers recorded",
+        summary.log_events,
+        archive.skipped,
+        summary.candidate_ids.len()
+    );
+    Ok(())
+}
+
+/// Backtest the configured rules over a recorded event file without recording
+/// any candidates
+fn run_rules_test(
+    config: &Config,
+    file: &Path,
+    evidence: usize,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let events = read_event_file(file)?;
+    let trigger_engine = build_trigger_engine(config);
+    let options = ReplayOptions {
+        evaluation_interval: TRIGGER_EVALUATION_INTERVAL,
+        cooldown: Duration::from_secs(config.triggers.cooldown_seconds),
+        ..ReplayOptions::default()
+    };
+
+    let summary = evaluate_recorded_events(
+        &events.log_events,
+        &events.metrics_events,
+        &events.disk_events,
+        &trigger_engine,
+        None,
+        &options,
+    );
+
+    print!("{}", rules_test_report(&summary, evidence));
+    Ok(())
+}
+
+/// Each trigger with its reason and latest evidence, then a count per rule
+fn rules_test_report(summary: &ReplaySummary, evidence: usize) -> String {
+    let mut report = String::new();
+    let mut fired = BTreeMap::<&str, usize>::new();
+    for context in &summary.triggers {
+        *fired.entry(&context.triggered_by).or_default() += 1;
+        report.push_str(&format!(
+            "[{}] {} ({:?})",
+            context.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            context.triggered_by,
+            context.expected_severity
+        ));
+        if let Some(source) = &context.trigger_source {
+            report.push_str(&format!(" {}", source));
+        }
+        report.push_str(&format!("\n  {}\n", context.trigger_reason));
+
+        let mut push_evidence = |kind: &str, lines: Vec<String>| {
+            let shown = lines.len().min(evidence);
+            for line in &lines[lines.len() - shown..] {
+                report.push_str(&format!("  {} {}\n", kind, line));
+            }
+            if lines.len() > shown {
+                report.push_str(&format!(
+                    "  ... {} earlier {} events\n",
+                    lines.len() - shown,
+                    kind
+                ));
+            }
+        };
+        push_evidence(
+            "log",
+            context
+                .log_events
+                .iter()
+                .map(|event| {
+                    format!(
+                        "{} {}[{}] {:?}: {}",
+                        event.timestamp.format("%H:%M:%S"),
+                        event.process,
+                        event.process_id,
+                        event.message_type,
+                        event.message
+                    )
+                })
+                .collect(),
+        );
+        push_evidence(
+            "metrics",
+            context
+                .metrics_events
+                .iter()
+                .map(|event| {
+                    format!(
+                        "{} cpu {:.1}% memory {:.0} MB ({:?} pressure)",
+                        event.timestamp.format("%H:%M:%S"),
+                        event.cpu_usage_percent,
+                        event.memory_used_mb,
+                        event.memory_pressure
+                    )
+                })
+                .collect(),
+        );
+        push_evidence(
+            "disk",
+            context
+                .disk_events
+                .iter()
+                .map(|event| {
+                    format!(
+                        "{} {} read {:.0} KB/s write {:.0} KB/s",
+                        event.timestamp.format("%H:%M:%S"),
+                        event.disk_name,
+                        event.read_kb_per_sec,
+                        event.write_kb_per_sec
+                    )
+                })
+                .collect(),
+        );
+    }
+
+    if !fired.is_empty() {
+        report.push('\n');
+    }
+    for (rule, count) in &fired {
+        report.push_str(&format!("{}: {} triggers\n", rule, count));
+    }
+    report.push_str(&format!(
+        "Tested {} events: {} log, {} metrics, {} disk; {} triggers\n",
+        summary.records,
+        summary.log_events,
+        summary.metrics_events,
+        summary.disk_events,
+        summary.triggers.len()
+    ));
+    report
+}
+
+fn print_recorded_triggers(summary: &ReplaySummary) {
+    for (context, candidate_id) in summary.triggers.iter().zip(&summary.candidate_ids) {
+        println!(
+            "[{}] candidate {} {} ({:?}): {}",
+            context.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            candidate_id,
+            context.triggered_by,
+            context.expected_severity,
+            context.trigger_reason
+        );
+    }
+}
+
 fn main() {
     // Parse command-line arguments
     let cli = Cli::parse();
@@ -1228,6 +1947,42 @@ fn main() {
         }
     };
 
+    match &cli.command {
+        Some(Command::Replay {
+            file,
+            speed,
+            database,
+        }) => {
+            if let Err(e) = run_replay(&config, file, *speed, database.as_deref()) {
+                error!("Replay failed: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Import {
+            file,
+            since,
+            until,
+            database,
+        }) => {
+            if let Err(e) = run_import(&config, file, *since, *until, database.as_deref()) {
+                error!("Import failed: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Rules {
+            command: RulesCommand::Test { file, evidence },
+        }) => {
+            if let Err(e) = run_rules_test(&config, file, *evidence) {
+                error!("Rule test failed: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        None => {}
+    }
+
     // Create system observer
     let mut observer =
         match SystemObserver::new_with_notifications(config, cli.enable_notifications) {
@@ -1238,8 +1993,31 @@ fn main() {
             }
         };
 
+    if let Some(capture_path) = &cli.capture {
+        match CaptureWriter::create(capture_path) {
+            Ok(capture) => {
+                info!("Recording collector output to {}", capture_path.display());
+                observer.set_capture(capture);
+            }
+            Err(e) => {
+                error!(
+                    "Failed to create capture file {}: {}",
+                    capture_path.display(),
+                    e
+                );
+                std::process::exit(1);
+            }
+        }
+    }
+
     info!("SystemObserver initialized successfully");
 
+    // SIGHUP reloads the configuration instead of terminating
+    install_reload_signal();
+    if let Some(path) = SystemObserver::config_file_path(config_path) {
+        observer.watch_config(path);
+    }
+
     // Start the observer
     if let Err(e) = observer.start() {
         error!("Failed to start SystemObserver: {}", e);
@@ -1279,6 +2057,7 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use eyes::events::MessageType;
 
     #[test]
     fn test_trigger_evaluation_is_debounced() {
@@ -1303,6 +2082,105 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_builtin_rules_are_built_by_name() {
+        let mut config = Config::default();
+        for name in BUILTIN_RULES {
+            assert_eq!(builtin_rule(name, &config).unwrap().name(), *name);
+        }
+        assert_eq!(
+            build_trigger_engine(&config).rule_count(),
+            BUILTIN_RULES.len()
+        );
+
+        config.triggers.composite = toml::from_str::<Config>(
+            r#"
+            [[triggers.composite]]
+            name = "spike-then-crash"
+            mode = "sequence"
+            rules = ["DiskIOSpikeRule", "CrashDetectionRule"]
+            "#,
+        )
+        .unwrap()
+        .triggers
+        .composite;
+        config.triggers.anomaly.enabled = false;
+        assert_eq!(
+            build_trigger_engine(&config).rule_count(),
+            BUILTIN_RULES.len()
+        );
+    }
+
+    #[test]
+    fn reload_applies_changed_sections_and_reports_restart_only_ones() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.ai.backend = AIBackendConfig::Mock;
+        config.web.enabled = false;
+        config.storage.database_path = temp_dir.path().join("eyes.db");
+        config.storage.state_path = temp_dir.path().join("eyes-state.json");
+        let mut observer = SystemObserver::new(config.clone()).unwrap();
+
+        assert!(observer.reload(config.clone()).unwrap().is_empty());
+
+        let mut reloaded = config;
+        reloaded.triggers.anomaly.enabled = false;
+        reloaded.alerts.rate_limit_per_minute = 10;
+        reloaded.ai.automatic_analysis = false;
+        reloaded.buffer.max_size = 10;
+        let changes = observer.reload(reloaded).unwrap();
+        assert!(changes.triggers);
+        assert!(changes.alerts);
+        assert!(changes.automatic_analysis);
+        assert!(changes.sources.is_empty());
+        assert_eq!(changes.restart_required, vec!["buffer"]);
+        assert_eq!(
+            observer.trigger_engine.as_ref().unwrap().rule_count(),
+            BUILTIN_RULES.len() - 1
+        );
+        assert!(!observer.automatic_analysis);
+        assert_eq!(observer.config.alerts.rate_limit_per_minute, 10);
+    }
+
+    #[test]
+    fn restoring_a_checkpoint_restarts_template_learning() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.ai.backend = AIBackendConfig::Mock;
+        config.web.enabled = false;
+        config.storage.database_path = temp_dir.path().join("eyes.db");
+        config.storage.state_path = temp_dir.path().join("eyes-state.json");
+
+        let mut aggregator = EventAggregator::new(chrono::Duration::minutes(5), 100);
+        aggregator.add_log(LogEvent {
+            timestamp: chrono::Utc::now() - chrono::Duration::hours(2),
+            message_type: MessageType::Error,
+            subsystem: "com.apple.test".to_string(),
+            category: "test".to_string(),
+            process: "backupd".to_string(),
+            process_id: 42,
+            message: "Volume 7 not mounted".to_string(),
+        });
+        let started_at = aggregator.templates().started_at();
+        let aggregator = Mutex::new(aggregator);
+
+        for (clean_shutdown, restarted) in [(true, false), (false, true)] {
+            let mut state = checkpoint_state(&aggregator, HashMap::new()).unwrap();
+            state.clean_shutdown = clean_shutdown;
+            state.save(&config.storage.state_path).unwrap();
+
+            let mut observer = SystemObserver::new(config.clone()).unwrap();
+            observer.restore_state();
+            let learning_since = observer
+                .event_aggregator
+                .lock()
+                .unwrap()
+                .templates()
+                .learning_since();
+            assert_eq!(learning_since != started_at, restarted);
+        }
+    }
+
     #[test]
     fn manual_analysis_waits_until_the_ai_worker_has_capacity() {
         let (sender, receiver) = mpsc::sync_channel(1);
@@ -1342,6 +2220,121 @@ mod tests {
         assert!(enabled.enable_notifications);
     }
 
+    #[test]
+    fn replay_subcommand_parses_speed_and_database() {
+        let cli = Cli::try_parse_from([
+            "eyes",
+            "replay",
+            "session.jsonl",
+            "--speed",
+            "20",
+            "--database",
+            "replay.db",
+        ])
+        .unwrap();
+
+        match cli.command {
+            Some(Command::Replay {
+                file,
+                speed,
+                database,
+            }) => {
+                assert_eq!(file, PathBuf::from("session.jsonl"));
+                assert_eq!(speed, 20.0);
+                assert_eq!(database, Some(PathBuf::from("replay.db")));
+            }
+            _ => panic!("replay subcommand was not parsed"),
+        }
+        assert!(Cli::try_parse_from(["eyes", "replay", "session.jsonl", "--speed", "0"]).is_err());
+        assert!(Cli::try_parse_from(["eyes", "--capture", "session.jsonl"])
+            .unwrap()
+            .capture
+            .is_some());
+    }
+
+    #[test]
+    fn import_subcommand_parses_incident_window() {
+        let cli = Cli::try_parse_from([
+            "eyes",
+            "import",
+            "sysdiagnose.json",
+            "--since",
+            "2024-01-15T10:00:00Z",
+            "--until",
+            "2024-01-15T11:00:00-08:00",
+        ])
+        .unwrap();
+
+        match cli.command {
+            Some(Command::Import {
+                file, since, until, ..
+            }) => {
+                assert_eq!(file, PathBuf::from("sysdiagnose.json"));
+                assert_eq!(
+                    since,
+                    Some(parse_timestamp("2024-01-15T10:00:00Z").unwrap())
+                );
+                assert_eq!(
+                    until,
+                    Some(parse_timestamp("2024-01-15T19:00:00Z").unwrap())
+                );
+            }
+            _ => panic!("import subcommand was not parsed"),
+        }
+        assert!(Cli::try_parse_from(["eyes", "import", "x.json", "--since", "yesterday"]).is_err());
+    }
+
+    #[test]
+    fn rules_test_subcommand_parses_evidence_count() {
+        let cli = Cli::try_parse_from(["eyes", "rules", "test", "week.jsonl", "--evidence", "5"])
+            .unwrap();
+
+        match cli.command {
+            Some(Command::Rules {
+                command: RulesCommand::Test { file, evidence },
+            }) => {
+                assert_eq!(file, PathBuf::from("week.jsonl"));
+                assert_eq!(evidence, 5);
+            }
+            _ => panic!("rules test subcommand was not parsed"),
+        }
+        assert!(Cli::try_parse_from(["eyes", "rules", "test"]).is_err());
+    }
+
+    #[test]
+    fn rules_test_report_lists_triggers_with_latest_evidence() {
+        let start = chrono::Utc::now() - chrono::Duration::days(7);
+        let events = (0..4)
+            .map(|index| LogEvent {
+                timestamp: start + chrono::Duration::seconds(index),
+                message_type: MessageType::Error,
+                subsystem: "com.example.backup".to_string(),
+                category: "default".to_string(),
+                process: "backupd".to_string(),
+                process_id: 42,
+                message: format!("Backup failed: attempt {index}"),
+            })
+            .collect::<Vec<_>>();
+        let mut engine = TriggerEngine::new();
+        engine.add_rule(Box::new(ErrorFrequencyRule::new(
+            3,
+            60,
+            eyes::events::Severity::Warning,
+        )));
+
+        let summary =
+            evaluate_recorded_events(&events, &[], &[], &engine, None, &ReplayOptions::default());
+        let report = rules_test_report(&summary, 2);
+
+        assert!(report.contains("ErrorFrequencyRule (Warning)"), "{report}");
+        assert!(report.contains(&summary.triggers[0].trigger_reason));
+        assert!(!report.contains("attempt 1"), "{report}");
+        assert!(report.contains("backupd[42] Error: Backup failed: attempt 3"));
+        assert!(report.contains("... 2 earlier log events"));
+        assert!(report.contains("ErrorFrequencyRule: 1 triggers"));
+        assert!(report.ends_with("Tested 4 events: 4 log, 0 metrics, 0 disk; 1 triggers\n"));
+    }
+
     #[test]
     fn test_cli_validation_with_existing_file() {
         // Create a temporary file for testing
@@ -1352,6 +2345,8 @@ mod tests {
             config: Some(temp_file.clone()),
             verbose: false,
             enable_notifications: false,
+            capture: None,
+            command: None,
         };
 
         assert!(cli.validate().is_ok());
@@ -1366,6 +2361,8 @@ mod tests {
             config: Some(PathBuf::from("/nonexistent/config.toml")),
             verbose: false,
             enable_notifications: false,
+            capture: None,
+            command: None,
         };
 
         // Path existence is checked when the configuration is loaded.
@@ -1385,6 +2382,8 @@ mod tests {
             config: Some(PathBuf::from("/tmp")),
             verbose: false,
             enable_notifications: false,
+            capture: None,
+            command: None,
         };
 
         // Should fail - directories are not valid config files
@@ -1397,6 +2396,8 @@ mod tests {
             config: None,
             verbose: false,
             enable_notifications: false,
+            capture: None,
+            command: None,
         };
 
         assert!(cli.validate().is_ok());
@@ -1408,6 +2409,8 @@ mod tests {
             config: Some(PathBuf::from("config.toml")),
             verbose: false,
             enable_notifications: false,
+            capture: None,
+            command: None,
         };
 
         let result = cli.config_path_str().unwrap();
@@ -1420,6 +2423,8 @@ mod tests {
             config: None,
             verbose: false,
             enable_notifications: false,
+            capture: None,
+            command: None,
         };
 
         let result = cli.config_path_str().unwrap();
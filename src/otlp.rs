@@ -0,0 +1,497 @@
+//! OTLP/HTTP JSON payloads and their conversion into Eyes events
+//!
+//! Only the parts of the OpenTelemetry export requests that map onto
+//! `LogEvent` and `MetricsEvent` are modelled; everything else in a payload
+//! is ignored. 64-bit integers may be encoded as JSON numbers or strings,
+//! as the OTLP JSON mapping allows both.
+
+use crate::collectors::file_tail_collector::message_type_for_level;
+use crate::events::{
+    LogEvent, MeasurementKind, MemoryPressure, MessageType, MetricsEvent, MetricsProvenance,
+    MetricsSource, ProcessMetric, Timestamp,
+};
+use chrono::DateTime;
+use serde::Deserialize;
+
+/// Category given to every log event received over OTLP
+pub const OTLP_CATEGORY: &str = "otlp";
+
+/// Attribute keys that carry the CPU or memory state of a data point
+const STATE_KEYS: &[&str] = &[
+    "state",
+    "cpu.state",
+    "system.cpu.state",
+    "system.memory.state",
+];
+
+/// Body of a `POST /v1/logs` request
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportLogsServiceRequest {
+    pub resource_logs: Vec<ResourceLogs>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ResourceLogs {
+    pub resource: Resource,
+    pub scope_logs: Vec<ScopeLogs>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ScopeLogs {
+    pub scope: InstrumentationScope,
+    pub log_records: Vec<LogRecord>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LogRecord {
+    pub time_unix_nano: Option<JsonInt>,
+    pub observed_time_unix_nano: Option<JsonInt>,
+    pub severity_number: Option<JsonInt>,
+    pub severity_text: String,
+    pub body: Option<AnyValue>,
+    pub attributes: Vec<KeyValue>,
+}
+
+/// Body of a `POST /v1/metrics` request
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportMetricsServiceRequest {
+    pub resource_metrics: Vec<ResourceMetrics>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ResourceMetrics {
+    pub resource: Resource,
+    pub scope_metrics: Vec<ScopeMetrics>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ScopeMetrics {
+    pub metrics: Vec<Metric>,
+}
+
+/// A gauge or sum metric; histograms and summaries are ignored
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Metric {
+    pub name: String,
+    pub gauge: Option<NumberDataPoints>,
+    pub sum: Option<NumberDataPoints>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NumberDataPoints {
+    pub data_points: Vec<NumberDataPoint>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NumberDataPoint {
+    pub attributes: Vec<KeyValue>,
+    pub time_unix_nano: Option<JsonInt>,
+    pub as_double: Option<f64>,
+    pub as_int: Option<JsonInt>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Resource {
+    pub attributes: Vec<KeyValue>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct InstrumentationScope {
+    pub name: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct KeyValue {
+    pub key: String,
+    pub value: AnyValue,
+}
+
+/// OTLP attribute or body value; exactly one field is normally set
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AnyValue {
+    pub string_value: Option<String>,
+    pub bool_value: Option<bool>,
+    pub int_value: Option<JsonInt>,
+    pub double_value: Option<f64>,
+    pub array_value: Option<ArrayValue>,
+    pub kvlist_value: Option<KeyValueList>,
+    pub bytes_value: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ArrayValue {
+    pub values: Vec<AnyValue>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct KeyValueList {
+    pub values: Vec<KeyValue>,
+}
+
+/// 64-bit integer encoded as a JSON number or a decimal string
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum JsonInt {
+    Number(serde_json::Number),
+    Text(String),
+}
+
+impl JsonInt {
+    fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::Number(number) => number.as_i64(),
+            Self::Text(text) => text.parse().ok(),
+        }
+    }
+
+    fn as_u64(&self) -> Option<u64> {
+        match self {
+            Self::Number(number) => number.as_u64(),
+            Self::Text(text) => text.parse().ok(),
+        }
+    }
+}
+
+impl AnyValue {
+    /// Render the value as text; arrays and maps are flattened
+    pub fn to_text(&self) -> String {
+        if let Some(text) = &self.string_value {
+            text.clone()
+        } else if let Some(flag) = self.bool_value {
+            flag.to_string()
+        } else if let Some(number) = self.int_value.as_ref().and_then(JsonInt::as_i64) {
+            number.to_string()
+        } else if let Some(number) = self.double_value {
+            number.to_string()
+        } else if let Some(array) = &self.array_value {
+            let values: Vec<_> = array.values.iter().map(AnyValue::to_text).collect();
+            format!("[{}]", values.join(", "))
+        } else if let Some(list) = &self.kvlist_value {
+            let values: Vec<_> = list
+                .values
+                .iter()
+                .map(|pair| format!("{}={}", pair.key, pair.value.to_text()))
+                .collect();
+            format!("{{{}}}", values.join(", "))
+        } else {
+            self.bytes_value.clone().unwrap_or_default()
+        }
+    }
+}
+
+fn attribute<'a>(attributes: &'a [KeyValue], key: &str) -> Option<&'a AnyValue> {
+    attributes
+        .iter()
+        .find(|pair| pair.key == key)
+        .map(|pair| &pair.value)
+}
+
+fn nanos_to_timestamp(nanos: Option<&JsonInt>) -> Option<Timestamp> {
+    let nanos = nanos?.as_u64().filter(|nanos| *nanos > 0)?;
+    i64::try_from(nanos)
+        .ok()
+        .map(DateTime::from_timestamp_nanos)
+}
+
+/// Map an OTLP severity number, or the severity text when it is unset
+fn message_type_for_severity(number: i64, text: &str) -> MessageType {
+    match number {
+        1..=8 => MessageType::Debug,
+        9..=16 => MessageType::Info,
+        17..=20 => MessageType::Error,
+        21..=24 => MessageType::Fault,
+        _ => message_type_for_level(text),
+    }
+}
+
+/// Process name and id from the `service.name` and `process.pid` resource attributes
+fn resource_process(resource: &Resource) -> (String, u32) {
+    let name = attribute(&resource.attributes, "service.name")
+        .map(AnyValue::to_text)
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "otlp".to_string());
+    let pid = attribute(&resource.attributes, "process.pid")
+        .and_then(|value| value.to_text().parse().ok())
+        .unwrap_or(0);
+    (name, pid)
+}
+
+/// Convert every log record in `request` into a `LogEvent`
+///
+/// Records without a timestamp are stamped with `received`. The resource's
+/// `service.name` becomes the process and the instrumentation scope the
+/// subsystem.
+pub fn log_events(request: &ExportLogsServiceRequest, received: Timestamp) -> Vec<LogEvent> {
+    let mut events = Vec::new();
+    for resource_logs in &request.resource_logs {
+        let (process, process_id) = resource_process(&resource_logs.resource);
+        for scope_logs in &resource_logs.scope_logs {
+            for record in &scope_logs.log_records {
+                let mut message = record
+                    .body
+                    .as_ref()
+                    .map(AnyValue::to_text)
+                    .unwrap_or_default();
+                if message.is_empty() {
+                    if let Some(exception) = attribute(&record.attributes, "exception.message") {
+                        message = exception.to_text();
+                    }
+                }
+
+                events.push(LogEvent {
+                    timestamp: nanos_to_timestamp(record.time_unix_nano.as_ref())
+                        .or_else(|| nanos_to_timestamp(record.observed_time_unix_nano.as_ref()))
+                        .unwrap_or(received),
+                    message_type: message_type_for_severity(
+                        record
+                            .severity_number
+                            .as_ref()
+                            .and_then(JsonInt::as_i64)
+                            .unwrap_or(0),
+                        &record.severity_text,
+                    ),
+                    subsystem: scope_logs.scope.name.clone(),
+                    category: OTLP_CATEGORY.to_string(),
+                    process: process.clone(),
+                    process_id,
+                    message,
+                });
+            }
+        }
+    }
+    events
+}
+
+/// Convert each resource in `request` into one `MetricsEvent`
+///
+/// Recognises the OpenTelemetry semantic-convention metrics
+/// `system.cpu.utilization`, `system.memory.usage`,
+/// `process.cpu.utilization` and `process.memory.usage`. Resources that
+/// report none of them produce no event. Power, GPU and memory pressure are
+/// not available over OTLP and are marked `Unavailable`.
+pub fn metrics_events(
+    request: &ExportMetricsServiceRequest,
+    received: Timestamp,
+) -> Vec<MetricsEvent> {
+    request
+        .resource_metrics
+        .iter()
+        .filter_map(|resource_metrics| resource_metrics_event(resource_metrics, received))
+        .collect()
+}
+
+fn resource_metrics_event(
+    resource_metrics: &ResourceMetrics,
+    received: Timestamp,
+) -> Option<MetricsEvent> {
+    let points = |name: &str| -> Vec<&NumberDataPoint> {
+        resource_metrics
+            .scope_metrics
+            .iter()
+            .flat_map(|scope| &scope.metrics)
+            .filter(|metric| metric.name == name)
+            .flat_map(|metric| metric.gauge.iter().chain(metric.sum.iter()))
+            .flat_map(|points| &points.data_points)
+            .collect()
+    };
+
+    let system_cpu = points("system.cpu.utilization");
+    let system_memory = points("system.memory.usage");
+    let process_cpu = points("process.cpu.utilization");
+    let process_memory = points("process.memory.usage");
+
+    let process_cpu_percent = utilization_percent(&process_cpu);
+    let process_memory_mb = used_megabytes(&process_memory);
+    let cpu_usage = utilization_percent(&system_cpu).or(process_cpu_percent);
+    let memory_used = used_megabytes(&system_memory).or(process_memory_mb);
+    if cpu_usage.is_none() && memory_used.is_none() {
+        return None;
+    }
+
+    let timestamp = [&system_cpu, &system_memory, &process_cpu, &process_memory]
+        .into_iter()
+        .flatten()
+        .filter_map(|point| nanos_to_timestamp(point.time_unix_nano.as_ref()))
+        .max()
+        .unwrap_or(received);
+    let (process, process_id) = resource_process(&resource_metrics.resource);
+    let process_metrics = if process_cpu_percent.is_some() || process_memory_mb.is_some() {
+        vec![ProcessMetric {
+            process_id,
+            process,
+            cpu_usage_percent: process_cpu_percent.unwrap_or(0.0),
+            resident_memory_mb: process_memory_mb.unwrap_or(0.0),
+        }]
+    } else {
+        Vec::new()
+    };
+    let measured = |value: Option<f64>| {
+        if value.is_some() {
+            MeasurementKind::Measured
+        } else {
+            MeasurementKind::Unavailable
+        }
+    };
+
+    Some(MetricsEvent {
+        timestamp,
+        cpu_power_mw: 0.0,
+        cpu_usage_percent: cpu_usage.unwrap_or(0.0),
+        gpu_power_mw: None,
+        gpu_usage_percent: None,
+        memory_pressure: MemoryPressure::Unknown,
+        memory_used_mb: memory_used.unwrap_or(0.0),
+        energy_impact: 0.0,
+        provenance: MetricsProvenance {
+            source: MetricsSource::Otlp,
+            cpu_usage: measured(cpu_usage),
+            memory_used: measured(memory_used),
+            ..MetricsProvenance::default()
+        },
+        process_metrics,
+    })
+}
+
+fn point_value(point: &NumberDataPoint) -> Option<f64> {
+    point
+        .as_double
+        .or_else(|| point.as_int.as_ref()?.as_i64().map(|value| value as f64))
+}
+
+fn point_state(point: &NumberDataPoint) -> Option<String> {
+    STATE_KEYS
+        .iter()
+        .find_map(|key| attribute(&point.attributes, key))
+        .map(AnyValue::to_text)
+}
+
+/// Busy percentage from 0-1 utilization points
+///
+/// Points split by state are read as `100 - idle`; unsplit points are averaged.
+fn utilization_percent(points: &[&NumberDataPoint]) -> Option<f64> {
+    let idle: Vec<f64> = points
+        .iter()
+        .filter(|point| point_state(point).as_deref() == Some("idle"))
+        .filter_map(|point| point_value(point))
+        .collect();
+    let ratio = if !idle.is_empty() {
+        1.0 - idle.iter().sum::<f64>() / idle.len() as f64
+    } else if points.iter().any(|point| point_state(point).is_some()) {
+        return None;
+    } else {
+        let values: Vec<f64> = points
+            .iter()
+            .filter_map(|point| point_value(point))
+            .collect();
+        if values.is_empty() {
+            return None;
+        }
+        values.iter().sum::<f64>() / values.len() as f64
+    };
+    Some((ratio * 100.0).clamp(0.0, 100.0))
+}
+
+/// Megabytes from byte-valued usage points, counting only the `used` state when split
+fn used_megabytes(points: &[&NumberDataPoint]) -> Option<f64> {
+    let split = points.iter().any(|point| point_state(point).is_some());
+    let bytes: Vec<f64> = points
+        .iter()
+        .filter(|point| !split || point_state(point).as_deref() == Some("used"))
+        .filter_map(|point| point_value(point))
+        .collect();
+    (!bytes.is_empty()).then(|| bytes.iter().sum::<f64>() / (1024.0 * 1024.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn converts_log_records() {
+        let request: ExportLogsServiceRequest = serde_json::from_str(
+            r#"{"resourceLogs":[{
+                "resource":{"attributes":[
+                    {"key":"service.name","value":{"stringValue":"checkout"}},
+                    {"key":"process.pid","value":{"intValue":"4242"}}]},
+                "scopeLogs":[{"scope":{"name":"checkout.db"},"logRecords":[
+                    {"timeUnixNano":"1714558530000000000","severityNumber":17,
+                     "body":{"stringValue":"connection pool exhausted"}},
+                    {"observedTimeUnixNano":1714558531000000000,"severityText":"fatal",
+                     "body":{"kvlistValue":{"values":[{"key":"code","value":{"intValue":7}}]}}},
+                    {"severityNumber":9,"attributes":[
+                        {"key":"exception.message","value":{"stringValue":"retrying"}}]}
+                ]}]}]}"#,
+        )
+        .unwrap();
+        let received = Utc::now();
+
+        let events = log_events(&request, received);
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].timestamp.timestamp(), 1714558530);
+        assert_eq!(events[0].message_type, MessageType::Error);
+        assert_eq!(events[0].process, "checkout");
+        assert_eq!(events[0].process_id, 4242);
+        assert_eq!(events[0].subsystem, "checkout.db");
+        assert_eq!(events[0].category, OTLP_CATEGORY);
+        assert_eq!(events[0].message, "connection pool exhausted");
+        assert_eq!(events[1].timestamp.timestamp(), 1714558531);
+        assert_eq!(events[1].message_type, MessageType::Fault);
+        assert_eq!(events[1].message, "{code=7}");
+        assert_eq!(events[2].timestamp, received);
+        assert_eq!(events[2].message_type, MessageType::Info);
+        assert_eq!(events[2].message, "retrying");
+    }
+
+    #[test]
+    fn converts_semantic_convention_metrics() {
+        let request: ExportMetricsServiceRequest = serde_json::from_str(
+            r#"{"resourceMetrics":[
+                {"resource":{"attributes":[{"key":"service.name","value":{"stringValue":"api"}}]},
+                 "scopeMetrics":[{"metrics":[
+                    {"name":"system.cpu.utilization","gauge":{"dataPoints":[
+                        {"timeUnixNano":"1714558530000000000","asDouble":0.25,
+                         "attributes":[{"key":"state","value":{"stringValue":"idle"}}]},
+                        {"asDouble":0.75,"attributes":[{"key":"state","value":{"stringValue":"idle"}}]},
+                        {"asDouble":0.5,"attributes":[{"key":"state","value":{"stringValue":"user"}}]}]}},
+                    {"name":"system.memory.usage","sum":{"dataPoints":[
+                        {"asInt":"2147483648","attributes":[{"key":"state","value":{"stringValue":"used"}}]},
+                        {"asInt":"1073741824","attributes":[{"key":"state","value":{"stringValue":"free"}}]}]}},
+                    {"name":"process.memory.usage","sum":{"dataPoints":[{"asInt":104857600}]}},
+                    {"name":"http.server.duration","histogram":{"dataPoints":[]}}]}]},
+                {"resource":{},"scopeMetrics":[{"metrics":[
+                    {"name":"queue.depth","gauge":{"dataPoints":[{"asInt":3}]}}]}]}
+            ]}"#,
+        )
+        .unwrap();
+
+        let events = metrics_events(&request, Utc::now());
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.timestamp.timestamp(), 1714558530);
+        assert_eq!(event.cpu_usage_percent, 50.0);
+        assert_eq!(event.memory_used_mb, 2048.0);
+        assert_eq!(event.provenance.source, MetricsSource::Otlp);
+        assert_eq!(event.provenance.cpu_usage, MeasurementKind::Measured);
+        assert_eq!(event.provenance.cpu_power, MeasurementKind::Unavailable);
+        assert_eq!(event.process_metrics.len(), 1);
+        assert_eq!(event.process_metrics[0].process, "api");
+        assert_eq!(event.process_metrics[0].resident_memory_mb, 100.0);
+    }
+}
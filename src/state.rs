@@ -0,0 +1,172 @@
+//! Rolling buffer and trigger cooldowns persisted across restarts
+//!
+//! The observer writes its aggregator buffers, rollups, log templates and the
+//! time each trigger last fired to a JSON file, periodically while it runs and
+//! once more on shutdown; on startup it reads them back so rules see the
+//! minutes before the restart and cooldowns keep running instead of
+//! resetting. Entries that aged out while the file sat on disk are discarded
+//! when the state is restored.
+
+use crate::aggregator::AggregatorState;
+use crate::error::StateError;
+use crate::events::Timestamp;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// Everything the observer carries over a restart
+#[derive(Default, Serialize, Deserialize)]
+pub struct SavedState {
+    /// When the state was written
+    pub saved_at: Timestamp,
+    /// Aggregator buffers and rollups
+    pub aggregator: AggregatorState,
+    /// Last time each trigger fired, keyed by cooldown key
+    #[serde(default)]
+    pub last_triggered: HashMap<String, Timestamp>,
+    /// Whether the state was written on shutdown rather than as a periodic
+    /// checkpoint, after which the observer may have kept running and crashed
+    #[serde(default = "default_clean_shutdown")]
+    pub clean_shutdown: bool,
+}
+
+/// State files from before periodic checkpoints were only written on shutdown
+fn default_clean_shutdown() -> bool {
+    true
+}
+
+impl SavedState {
+    /// Bundle aggregator state and cooldowns as a periodic checkpoint,
+    /// stamped with the current time
+    pub fn new(aggregator: AggregatorState, last_triggered: HashMap<String, Timestamp>) -> Self {
+        Self {
+            saved_at: Utc::now(),
+            aggregator,
+            last_triggered,
+            clean_shutdown: false,
+        }
+    }
+
+    /// Mark the state as written on shutdown
+    pub fn on_shutdown(mut self) -> Self {
+        self.clean_shutdown = true;
+        self
+    }
+
+    /// Write the state to `path`
+    ///
+    /// The state is written to a sibling temporary file and renamed into
+    /// place, so a crash mid-write never leaves a truncated state file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written or renamed
+    pub fn save(&self, path: &Path) -> Result<(), StateError> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+
+        let json = serde_json::to_vec(self).map_err(|e| StateError::FormatError(e.to_string()))?;
+        let mut tmp_name = path.as_os_str().to_owned();
+        tmp_name.push(".tmp");
+        let tmp_path = Path::new(&tmp_name);
+        fs::write(tmp_path, json)?;
+        fs::rename(tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Read state previously written by [`save`](Self::save)
+    ///
+    /// # Returns
+    ///
+    /// `Ok(None)` when no state file exists yet
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not valid state
+    pub fn load(path: &Path) -> Result<Option<Self>, StateError> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|e| StateError::FormatError(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aggregator::EventAggregator;
+    use crate::events::{LogEvent, MessageType};
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("state").join("eyes-state.json");
+        let mut aggregator = EventAggregator::new(Duration::minutes(60), 100);
+        aggregator.add_log(LogEvent {
+            timestamp: Utc::now(),
+            message_type: MessageType::Error,
+            subsystem: "com.apple.test".to_string(),
+            category: "test".to_string(),
+            process: "testd".to_string(),
+            process_id: 42,
+            message: "boom".to_string(),
+        });
+        let fired_at = Utc::now() - Duration::seconds(30);
+        let cooldowns = HashMap::from([("error_frequency".to_string(), fired_at)]);
+
+        SavedState::new(aggregator.export_state(), cooldowns)
+            .save(&path)
+            .unwrap();
+        let loaded = SavedState::load(&path).unwrap().unwrap();
+
+        assert_eq!(loaded.aggregator.logs.len(), 1);
+        assert_eq!(
+            loaded.last_triggered.get("error_frequency"),
+            Some(&fired_at)
+        );
+        assert!(!loaded.clean_shutdown);
+        assert!(!path.with_extension("json.tmp").exists());
+
+        SavedState::new(aggregator.export_state(), HashMap::new())
+            .on_shutdown()
+            .save(&path)
+            .unwrap();
+        assert!(SavedState::load(&path).unwrap().unwrap().clean_shutdown);
+    }
+
+    #[test]
+    fn test_state_without_shutdown_flag_was_written_on_shutdown() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("eyes-state.json");
+        fs::write(
+            &path,
+            r#"{"saved_at":"2025-01-01T00:00:00Z","aggregator":{"logs":[],"metrics":[],"disk":[],"crashes":[]}}"#,
+        )
+        .unwrap();
+        assert!(SavedState::load(&path).unwrap().unwrap().clean_shutdown);
+    }
+
+    #[test]
+    fn test_load_missing_and_corrupt_files() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("eyes-state.json");
+        assert!(SavedState::load(&path).unwrap().is_none());
+
+        fs::write(&path, "not json").unwrap();
+        assert!(matches!(
+            SavedState::load(&path),
+            Err(StateError::FormatError(_))
+        ));
+    }
+}
@@ -0,0 +1,500 @@
+//! Adaptive anomaly detection over learned metric baselines
+//!
+//! `AnomalyRule` keeps an exponentially weighted mean and variance for each
+//! watched metrics field and for the rates of each disk device, scores every
+//! new sample as a z-score against that baseline, and fires while a series is
+//! in a sustained upward excursion. Entering an excursion takes several
+//! consecutive samples at or above `enter_z`; leaving it takes one sample
+//! below `exit_z`, so a series hovering around the threshold does not flap.
+
+use crate::aggregator::{DiskField, EventSnapshot, MetricField, RollupKey};
+use crate::config::AnomalyConfig;
+use crate::events::{DiskEvent, LogEvent, MetricsEvent, Severity, Timestamp};
+use crate::triggers::{RelevantLogGroup, TriggerRule};
+use chrono::Duration;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Mutex;
+
+/// Excursions without a new sample for this long are no longer reported
+const STALE_EXCURSION_SECONDS: i64 = 120;
+
+/// Samples from before an excursion began that are included as context
+const CONTEXT_LEAD_SECONDS: i64 = 60;
+
+/// Smallest standard deviation, as a fraction of the baseline mean
+const RELATIVE_NOISE_FLOOR: f64 = 0.05;
+
+/// Samples are clipped to this many standard deviations before being learned
+const LEARNING_CLIP_Z: f64 = 2.0;
+
+/// Smallest standard deviation of a series, in the field's own unit
+///
+/// Idle machines report nearly constant values, and without a floor the
+/// variance would shrink until rounding noise scored as an anomaly.
+fn absolute_noise_floor(key: &RollupKey) -> f64 {
+    match key {
+        RollupKey::Metric(MetricField::CpuUsagePercent | MetricField::GpuUsagePercent) => 1.0,
+        RollupKey::Metric(MetricField::CpuPowerMw | MetricField::GpuPowerMw) => 25.0,
+        RollupKey::Metric(MetricField::MemoryUsedMb) => 32.0,
+        RollupKey::Metric(MetricField::EnergyImpact) => 1.0,
+        RollupKey::Disk {
+            field: DiskField::ReadKbPerSec | DiskField::WriteKbPerSec,
+            ..
+        } => 64.0,
+        RollupKey::Disk { .. } => 4.0,
+    }
+}
+
+/// Exponentially weighted mean and variance of one series
+#[derive(Debug, Clone, Copy, Default)]
+struct Baseline {
+    mean: f64,
+    variance: f64,
+    samples: usize,
+}
+
+impl Baseline {
+    fn std_dev(&self, absolute_floor: f64) -> f64 {
+        self.variance
+            .sqrt()
+            .max(absolute_floor)
+            .max(self.mean.abs() * RELATIVE_NOISE_FLOOR)
+    }
+
+    /// Fold in one sample
+    ///
+    /// Early samples are weighted as a plain running average, so the
+    /// baseline after warm-up does not depend on the first reading.
+    fn update(&mut self, value: f64, alpha: f64) {
+        self.samples += 1;
+        let alpha = alpha.max(1.0 / self.samples as f64);
+        let diff = value - self.mean;
+        let increment = alpha * diff;
+        self.mean += increment;
+        self.variance = (1.0 - alpha) * (self.variance + diff * increment);
+    }
+}
+
+/// A series running above its baseline
+#[derive(Debug, Clone, Copy)]
+struct Excursion {
+    /// First sample of the run that opened the excursion
+    since: Timestamp,
+    /// Baseline mean and deviation when the excursion opened
+    mean: f64,
+    std_dev: f64,
+    latest: f64,
+    latest_z: f64,
+    peak: f64,
+    peak_z: f64,
+}
+
+#[derive(Debug, Default)]
+struct Series {
+    baseline: Baseline,
+    /// Consecutive samples at or above `enter_z`, and when the run began
+    streak: usize,
+    streak_since: Option<Timestamp>,
+    excursion: Option<Excursion>,
+    last_sample: Option<Timestamp>,
+}
+
+#[derive(Debug, Default)]
+struct AnomalyState {
+    series: BTreeMap<RollupKey, Series>,
+    /// Newest metrics and disk samples already folded into the baselines
+    metrics_seen: Option<Timestamp>,
+    disk_seen: Option<Timestamp>,
+}
+
+/// Trigger rule that fires on sustained excursions above learned baselines
+///
+/// Unlike `ResourceSpikeRule` and `DiskIOSpikeRule`, which compare rises
+/// against fixed thresholds, this rule learns what is normal for each series
+/// on this machine. The rule is stateful: each evaluation folds the samples
+/// that are newer than the last evaluation into the baselines.
+pub struct AnomalyRule {
+    /// Weight of each new sample in the moving baseline
+    pub alpha: f64,
+    /// z-score a sample must reach to count toward an excursion
+    pub enter_z: f64,
+    /// z-score below which an excursion ends
+    pub exit_z: f64,
+    /// Consecutive samples at or above `enter_z` before the rule fires
+    pub sustain_samples: usize,
+    /// Samples a series learns from before it is scored
+    pub warmup_samples: usize,
+    /// System-wide metrics fields to watch
+    pub metrics: Vec<MetricField>,
+    /// Disk fields to watch on every device
+    pub disk: Vec<DiskField>,
+    /// Severity level to assign when this rule triggers
+    pub severity: Severity,
+    state: Mutex<AnomalyState>,
+}
+
+impl AnomalyRule {
+    /// Create an anomaly rule from `[triggers.anomaly]`
+    pub fn new(config: &AnomalyConfig) -> Self {
+        Self {
+            alpha: config.alpha,
+            enter_z: config.enter_z,
+            exit_z: config.exit_z,
+            sustain_samples: config.sustain_samples,
+            warmup_samples: config.warmup_samples,
+            metrics: config.metrics.clone(),
+            disk: config.disk.clone(),
+            severity: config.severity,
+            state: Mutex::new(AnomalyState::default()),
+        }
+    }
+
+    /// Create an anomaly rule with the default configuration
+    pub fn with_defaults() -> Self {
+        Self::new(&AnomalyConfig::default())
+    }
+
+    /// Fold samples newer than the last evaluation into the baselines
+    fn ingest(&self, state: &mut AnomalyState, events: &EventSnapshot<'_>) {
+        let mut metrics = events
+            .metrics()
+            .iter()
+            .filter(|event| state.metrics_seen.is_none_or(|seen| event.timestamp > seen))
+            .collect::<Vec<_>>();
+        metrics.sort_by_key(|event| event.timestamp);
+        for event in metrics {
+            for &field in &self.metrics {
+                if let Some(value) = field.value(event) {
+                    self.observe(state, RollupKey::Metric(field), value, event.timestamp);
+                }
+            }
+            state.metrics_seen = Some(event.timestamp);
+        }
+
+        let mut disk = events
+            .disk()
+            .iter()
+            .filter(|event| state.disk_seen.is_none_or(|seen| event.timestamp > seen))
+            .collect::<Vec<_>>();
+        disk.sort_by_key(|event| event.timestamp);
+        for event in disk {
+            for &field in &self.disk {
+                let key = RollupKey::Disk {
+                    disk_name: event.disk_name.clone(),
+                    field,
+                };
+                self.observe(state, key, field.value(event), event.timestamp);
+            }
+            state.disk_seen = Some(event.timestamp);
+        }
+    }
+
+    /// Score one sample against its series' baseline, then learn from it
+    fn observe(&self, state: &mut AnomalyState, key: RollupKey, value: f64, timestamp: Timestamp) {
+        let floor = absolute_noise_floor(&key);
+        let series = state.series.entry(key).or_default();
+        series.last_sample = Some(timestamp);
+
+        if series.baseline.samples < self.warmup_samples {
+            series.baseline.update(value, self.alpha);
+            return;
+        }
+
+        let mean = series.baseline.mean;
+        let std_dev = series.baseline.std_dev(floor);
+        let z = (value - mean) / std_dev;
+
+        if let Some(excursion) = &mut series.excursion {
+            if z < self.exit_z {
+                series.excursion = None;
+                series.streak = 0;
+                series.streak_since = None;
+            } else {
+                excursion.latest = value;
+                excursion.latest_z = z;
+                if z > excursion.peak_z {
+                    excursion.peak = value;
+                    excursion.peak_z = z;
+                }
+            }
+        } else if z >= self.enter_z {
+            series.streak += 1;
+            let since = *series.streak_since.get_or_insert(timestamp);
+            if series.streak >= self.sustain_samples {
+                series.excursion = Some(Excursion {
+                    since,
+                    mean,
+                    std_dev,
+                    latest: value,
+                    latest_z: z,
+                    peak: value,
+                    peak_z: z,
+                });
+            }
+        } else {
+            series.streak = 0;
+            series.streak_since = None;
+        }
+
+        // Clip the sample so a single outlier cannot inflate the variance;
+        // a lasting level shift still moves the baseline and is learned
+        let bound = LEARNING_CLIP_Z * std_dev;
+        series
+            .baseline
+            .update(value.clamp(mean - bound, mean + bound), self.alpha);
+    }
+
+    /// Excursions that are still receiving samples
+    fn active_excursions(&self, now: Timestamp) -> Vec<(RollupKey, Excursion)> {
+        let state = self.state.lock().unwrap();
+        let cutoff = now - Duration::seconds(STALE_EXCURSION_SECONDS);
+        state
+            .series
+            .iter()
+            .filter(|(_, series)| series.last_sample.is_some_and(|last| last >= cutoff))
+            .filter_map(|(key, series)| series.excursion.map(|excursion| (key.clone(), excursion)))
+            .collect()
+    }
+
+    fn describe(key: &RollupKey, excursion: &Excursion) -> String {
+        format!(
+            "{} at {:.1} ({:.1} standard deviations, peak {:.1}) against a learned baseline of {:.1} ± {:.1} since {}",
+            key,
+            excursion.latest,
+            excursion.latest_z,
+            excursion.peak,
+            excursion.mean,
+            excursion.std_dev,
+            excursion.since.format("%H:%M:%S UTC")
+        )
+    }
+}
+
+impl TriggerRule for AnomalyRule {
+    fn evaluate(&self, events: &EventSnapshot<'_>) -> bool {
+        {
+            let mut state = self.state.lock().unwrap();
+            self.ingest(&mut state, events);
+        }
+        !self.active_excursions(events.now()).is_empty()
+    }
+
+    fn name(&self) -> &str {
+        "AnomalyRule"
+    }
+
+    fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    fn relevant_logs<'a>(&self, _events: &EventSnapshot<'a>) -> Vec<&'a LogEvent> {
+        Vec::new()
+    }
+
+    /// One context per anomalous series, so each has its own cooldown
+    fn relevant_log_groups<'a>(&self, events: &EventSnapshot<'a>) -> Vec<RelevantLogGroup<'a>> {
+        self.active_excursions(events.now())
+            .into_iter()
+            .map(|(key, _)| RelevantLogGroup {
+                source: Some(key.to_string()),
+                events: Vec::new(),
+            })
+            .collect()
+    }
+
+    fn relevant_metrics<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a MetricsEvent> {
+        let Some(since) = self
+            .active_excursions(events.now())
+            .iter()
+            .filter(|(key, _)| matches!(key, RollupKey::Metric(_)))
+            .map(|(_, excursion)| excursion.since)
+            .min()
+        else {
+            return Vec::new();
+        };
+        let cutoff = since - Duration::seconds(CONTEXT_LEAD_SECONDS);
+        events
+            .metrics()
+            .iter()
+            .filter(|event| event.timestamp >= cutoff)
+            .collect()
+    }
+
+    fn relevant_disk_events<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a DiskEvent> {
+        let excursions = self.active_excursions(events.now());
+        let disks = excursions
+            .iter()
+            .filter_map(|(key, excursion)| match key {
+                RollupKey::Disk { disk_name, .. } => Some((disk_name.as_str(), excursion.since)),
+                RollupKey::Metric(_) => None,
+            })
+            .collect::<Vec<_>>();
+        let Some(since) = disks.iter().map(|(_, since)| *since).min() else {
+            return Vec::new();
+        };
+        let names = disks.iter().map(|(name, _)| *name).collect::<HashSet<_>>();
+        let cutoff = since - Duration::seconds(CONTEXT_LEAD_SECONDS);
+        events
+            .disk()
+            .iter()
+            .filter(|event| event.timestamp >= cutoff && names.contains(event.disk_name.as_str()))
+            .collect()
+    }
+
+    fn trigger_reason(&self, events: &EventSnapshot<'_>, source: Option<&str>) -> String {
+        let evidence = self
+            .active_excursions(events.now())
+            .iter()
+            .filter(|(key, _)| source.is_none_or(|source| key.to_string() == source))
+            .map(|(key, excursion)| Self::describe(key, excursion))
+            .collect::<Vec<_>>()
+            .join("; ");
+        format!("Rule '{}' triggered: {}", self.name(), evidence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{MeasurementKind, MemoryPressure, MetricsProvenance, MetricsSource};
+    use chrono::Utc;
+
+    fn power_events(cpu_power: &[f64]) -> Vec<MetricsEvent> {
+        let start = Utc::now() - Duration::seconds(5 * cpu_power.len() as i64);
+        cpu_power
+            .iter()
+            .enumerate()
+            .map(|(index, &cpu_power_mw)| MetricsEvent {
+                timestamp: start + Duration::seconds(5 * index as i64),
+                cpu_power_mw,
+                cpu_usage_percent: 0.0,
+                gpu_power_mw: None,
+                gpu_usage_percent: None,
+                memory_pressure: MemoryPressure::Normal,
+                memory_used_mb: 0.0,
+                energy_impact: 0.0,
+                provenance: MetricsProvenance {
+                    source: MetricsSource::Powermetrics,
+                    cpu_power: MeasurementKind::Measured,
+                    ..Default::default()
+                },
+                process_metrics: Vec::new(),
+            })
+            .collect()
+    }
+
+    fn disk_event(disk_name: &str, write_kb_per_sec: f64, seconds_ago: i64) -> DiskEvent {
+        DiskEvent {
+            timestamp: Utc::now() - Duration::seconds(seconds_ago),
+            read_kb_per_sec: 0.0,
+            write_kb_per_sec,
+            read_ops_per_sec: 0.0,
+            write_ops_per_sec: 0.0,
+            disk_name: disk_name.to_string(),
+            filesystem_path: None,
+        }
+    }
+
+    /// Evaluate after each sample, as the analysis loop does
+    fn evaluate_each(rule: &AnomalyRule, metrics: &[MetricsEvent]) -> Vec<bool> {
+        (1..=metrics.len())
+            .map(|end| rule.evaluate(&EventSnapshot::from_slices(&[], &metrics[..end], &[])))
+            .collect()
+    }
+
+    /// A noisy idle baseline around 800mW
+    fn baseline(samples: usize) -> Vec<f64> {
+        (0..samples)
+            .map(|index| 800.0 + [-40.0, 0.0, 40.0, 20.0, -20.0][index % 5])
+            .collect()
+    }
+
+    #[test]
+    fn test_anomaly_rule_fires_on_sustained_excursion_with_hysteresis() {
+        let rule = AnomalyRule::with_defaults();
+        let mut power = baseline(40);
+        // A single outlier is not sustained
+        power.push(2000.0);
+        power.extend(baseline(5));
+        // Three samples well above the baseline open an excursion
+        power.extend([1600.0, 1600.0, 1600.0]);
+        // Between exit_z and enter_z the excursion stays open
+        power.push(1000.0);
+        // Back to normal closes it
+        power.push(800.0);
+
+        let fired = evaluate_each(&rule, &power_events(&power));
+        assert!(fired[..48].iter().all(|fired| !fired));
+        assert!(fired[48], "third sustained sample should fire");
+        assert!(fired[49], "hysteresis keeps the excursion open");
+        assert!(!fired[50]);
+    }
+
+    #[test]
+    fn test_anomaly_rule_learns_level_shifts() {
+        let rule = AnomalyRule::with_defaults();
+        let mut power = baseline(40);
+        power.extend(std::iter::repeat_n(1600.0, 200));
+        let metrics = power_events(&power);
+        // Early samples are too old to be reported, so inspect the series
+        let in_excursion = (1..=metrics.len())
+            .map(|end| {
+                rule.evaluate(&EventSnapshot::from_slices(&[], &metrics[..end], &[]));
+                rule.state.lock().unwrap().series[&RollupKey::Metric(MetricField::CpuPowerMw)]
+                    .excursion
+                    .is_some()
+            })
+            .collect::<Vec<_>>();
+
+        assert!(in_excursion[42]);
+        assert!(
+            !in_excursion[239],
+            "a lasting level becomes the new baseline"
+        );
+
+        // A busy machine's baseline tolerates swings an idle one would flag
+        let busy = AnomalyRule::with_defaults();
+        let swings = (0..60)
+            .map(|index| 20_000.0 + [-2000.0, 2000.0][index % 2])
+            .collect::<Vec<_>>();
+        assert!(!evaluate_each(&busy, &power_events(&swings))
+            .into_iter()
+            .any(|fired| fired));
+    }
+
+    #[test]
+    fn test_anomaly_rule_tracks_each_disk_separately() {
+        let rule = AnomalyRule::with_defaults();
+        let mut disk = Vec::new();
+        for index in 0..40 {
+            let seconds_ago = 300 - 5 * index;
+            disk.push(disk_event(
+                "disk0",
+                1000.0 + (index % 3) as f64 * 50.0,
+                seconds_ago,
+            ));
+            disk.push(disk_event("disk1", 50_000.0, seconds_ago));
+        }
+        for seconds_ago in [15, 10, 5] {
+            disk.push(disk_event("disk0", 40_000.0, seconds_ago));
+            disk.push(disk_event("disk1", 50_000.0, seconds_ago));
+        }
+
+        let snapshot = EventSnapshot::from_slices(&[], &[], &disk);
+        assert!(rule.evaluate(&snapshot));
+        let groups = rule.relevant_log_groups(&snapshot);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].source.as_deref(), Some("disk0.write_kb_per_sec"));
+        let reason = rule.trigger_reason(&snapshot, groups[0].source.as_deref());
+        assert!(
+            reason.contains("disk0.write_kb_per_sec at 40000.0"),
+            "{}",
+            reason
+        );
+        assert!(rule
+            .relevant_disk_events(&snapshot)
+            .iter()
+            .all(|event| event.disk_name == "disk0"));
+        assert!(rule.relevant_metrics(&snapshot).is_empty());
+    }
+}
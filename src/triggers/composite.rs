@@ -0,0 +1,507 @@
+//! Correlations of other rules' firings
+//!
+//! A [`CompositeRule`] owns a set of child rules, evaluates them on every
+//! pass and remembers when each one fired. It fires when the children fired
+//! together within a window (`all`), in a given order within it
+//! (`sequence`), or when the first child fired without the second
+//! (`absence`). Its trigger context merges the events each matched child
+//! pointed at when it fired, so a disk I/O spike, the memory pressure that
+//! followed and the crash that ended it reach the analyzer as one story.
+
+use crate::aggregator::EventSnapshot;
+use crate::config::{CompositeMode, CompositeRuleConfig};
+use crate::events::{DiskEvent, LogEvent, MetricsEvent, Severity, Timestamp};
+use crate::triggers::{RetainedEvents, TriggerRule};
+use chrono::Duration;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// One evaluation in which a child rule fired
+#[derive(Debug, Clone)]
+struct Firing {
+    at: Timestamp,
+    severity: Severity,
+    reason: String,
+    /// Copies of the child's relevant events at the time, shared with the
+    /// previous firing when they are unchanged
+    evidence: Arc<RetainedEvents>,
+}
+
+#[derive(Debug, Default)]
+struct CompositeState {
+    /// Firings within the window, oldest first, per child
+    firings: Vec<VecDeque<Firing>>,
+    /// Children and firings that satisfied the correlation on the last pass
+    matched: Vec<(usize, Firing)>,
+}
+
+/// Trigger rule that fires on a correlation of its child rules
+///
+/// Children are evaluated against each snapshot even while the composite is
+/// in cooldown, since the correlation depends on their firing history.
+/// Firings are stamped with the snapshot's evaluation time, so the window
+/// also holds when recorded events are backtested.
+pub struct CompositeRule {
+    name: String,
+    mode: CompositeMode,
+    children: Vec<Box<dyn TriggerRule>>,
+    window: Duration,
+    severity: Option<Severity>,
+    state: Mutex<CompositeState>,
+}
+
+impl CompositeRule {
+    /// Create a composite rule over already-built children
+    ///
+    /// `severity` overrides the highest severity among the matched firings.
+    pub fn new(
+        name: impl Into<String>,
+        mode: CompositeMode,
+        children: Vec<Box<dyn TriggerRule>>,
+        window_seconds: i64,
+        severity: Option<Severity>,
+    ) -> Self {
+        let state = CompositeState {
+            firings: vec![VecDeque::new(); children.len()],
+            matched: Vec::new(),
+        };
+        Self {
+            name: name.into(),
+            mode,
+            children,
+            window: Duration::seconds(window_seconds),
+            severity,
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Create a composite rule from a `[[triggers.composite]]` entry
+    ///
+    /// `build` returns a fresh instance of the named child rule.
+    pub fn from_config(
+        config: &CompositeRuleConfig,
+        mut build: impl FnMut(&str) -> Option<Box<dyn TriggerRule>>,
+    ) -> Option<Self> {
+        let children = config
+            .rules
+            .iter()
+            .map(|name| build(name))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self::new(
+            &config.name,
+            config.mode,
+            children,
+            config.window_seconds as i64,
+            config.severity,
+        ))
+    }
+
+    fn evaluate_at(&self, events: &EventSnapshot<'_>, now: Timestamp) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let cutoff = now - self.window;
+        let mut fired_now = vec![false; self.children.len()];
+
+        for (index, child) in self.children.iter().enumerate() {
+            let firings = &mut state.firings[index];
+            if child.evaluate(events) {
+                fired_now[index] = true;
+                let evidence = Self::child_evidence(child.as_ref(), events);
+                let evidence = match firings.back() {
+                    Some(last) if *last.evidence == evidence => Arc::clone(&last.evidence),
+                    _ => Arc::new(evidence),
+                };
+                firings.push_back(Firing {
+                    at: now,
+                    severity: child.severity_for(events),
+                    reason: Self::child_reason(child.as_ref(), events),
+                    evidence,
+                });
+            }
+            while firings.front().is_some_and(|firing| firing.at < cutoff) {
+                firings.pop_front();
+            }
+        }
+
+        let firings = &state.firings;
+        let matched = match self.mode {
+            CompositeMode::All => firings
+                .iter()
+                .enumerate()
+                .map(|(index, firings)| firings.back().map(|firing| (index, firing.clone())))
+                .collect::<Option<Vec<_>>>(),
+            CompositeMode::Sequence => {
+                let mut chain = Vec::with_capacity(firings.len());
+                let mut after: Option<Timestamp> = None;
+                for (index, firings) in firings.iter().enumerate() {
+                    let Some(firing) = firings
+                        .iter()
+                        .find(|firing| after.is_none_or(|after| firing.at > after))
+                    else {
+                        break;
+                    };
+                    after = Some(firing.at);
+                    chain.push((index, firing.clone()));
+                }
+                (chain.len() == firings.len()).then_some(chain)
+            }
+            CompositeMode::Absence => (fired_now[0] && firings[1].is_empty())
+                .then(|| firings[0].back().map(|firing| vec![(0, firing.clone())]))
+                .flatten(),
+        };
+
+        state.matched = matched.unwrap_or_default();
+        !state.matched.is_empty()
+    }
+
+    /// A child's reasons for every context it would produce
+    fn child_reason(child: &dyn TriggerRule, events: &EventSnapshot<'_>) -> String {
+        child
+            .relevant_log_groups(events)
+            .iter()
+            .map(|group| child.trigger_reason(events, group.source.as_deref()))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    /// Copies of the events a child points at in this snapshot
+    fn child_evidence(child: &dyn TriggerRule, events: &EventSnapshot<'_>) -> RetainedEvents {
+        RetainedEvents {
+            logs: child
+                .relevant_log_groups(events)
+                .into_iter()
+                .flat_map(|group| group.events)
+                .cloned()
+                .collect(),
+            metrics: child
+                .relevant_metrics(events)
+                .into_iter()
+                .cloned()
+                .collect(),
+            disk: child
+                .relevant_disk_events(events)
+                .into_iter()
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Append the events not already in `merged`
+    fn extend_unique<T: Clone + PartialEq>(merged: &mut Vec<T>, events: &[T]) {
+        for event in events {
+            if !merged.contains(event) {
+                merged.push(event.clone());
+            }
+        }
+    }
+
+    fn window_description(&self) -> String {
+        format!("within {}s", self.window.num_seconds())
+    }
+}
+
+impl TriggerRule for CompositeRule {
+    fn evaluate(&self, events: &EventSnapshot<'_>) -> bool {
+        self.evaluate_at(events, events.now())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn severity(&self) -> Severity {
+        self.severity.unwrap_or_else(|| {
+            self.children
+                .iter()
+                .map(|child| child.severity())
+                .max()
+                .unwrap_or(Severity::Warning)
+        })
+    }
+
+    fn severity_for(&self, _events: &EventSnapshot<'_>) -> Severity {
+        let state = self.state.lock().unwrap();
+        self.severity
+            .or_else(|| {
+                state
+                    .matched
+                    .iter()
+                    .map(|(_, firing)| firing.severity)
+                    .max()
+            })
+            .unwrap_or_else(|| self.severity())
+    }
+
+    /// Nothing from the current snapshot; see [`Self::retained_events`]
+    fn relevant_logs<'a>(&self, _events: &EventSnapshot<'a>) -> Vec<&'a LogEvent> {
+        Vec::new()
+    }
+
+    fn relevant_metrics<'a>(&self, _events: &EventSnapshot<'a>) -> Vec<&'a MetricsEvent> {
+        Vec::new()
+    }
+
+    fn relevant_disk_events<'a>(&self, _events: &EventSnapshot<'a>) -> Vec<&'a DiskEvent> {
+        Vec::new()
+    }
+
+    /// The matched firings' evidence, listing each event once
+    ///
+    /// Children that fired earlier in the window contribute the events they
+    /// pointed at then, rather than whatever the current snapshot holds.
+    fn retained_events(&self) -> RetainedEvents {
+        let state = self.state.lock().unwrap();
+        let mut merged = RetainedEvents::default();
+        for (_, firing) in &state.matched {
+            Self::extend_unique(&mut merged.logs, &firing.evidence.logs);
+            Self::extend_unique(&mut merged.metrics, &firing.evidence.metrics);
+            Self::extend_unique(&mut merged.disk, &firing.evidence.disk);
+        }
+        merged
+    }
+
+    fn trigger_reason(&self, _events: &EventSnapshot<'_>, _source: Option<&str>) -> String {
+        let state = self.state.lock().unwrap();
+        let child_name = |index: usize| self.children[index].name();
+        let correlation = match self.mode {
+            CompositeMode::All => format!(
+                "{} all fired {}",
+                state
+                    .matched
+                    .iter()
+                    .map(|(index, _)| child_name(*index))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                self.window_description()
+            ),
+            CompositeMode::Sequence => format!(
+                "{} {}",
+                state
+                    .matched
+                    .iter()
+                    .map(|(index, firing)| format!(
+                        "{} ({})",
+                        child_name(*index),
+                        firing.at.format("%H:%M:%S UTC")
+                    ))
+                    .collect::<Vec<_>>()
+                    .join(", then "),
+                self.window_description()
+            ),
+            CompositeMode::Absence => format!(
+                "{} fired without {} {}",
+                child_name(0),
+                child_name(1),
+                self.window_description()
+            ),
+        };
+        let evidence = state
+            .matched
+            .iter()
+            .map(|(_, firing)| firing.reason.as_str())
+            .collect::<Vec<_>>()
+            .join("; ");
+        format!(
+            "Rule '{}' triggered: {}. {}",
+            self.name, correlation, evidence
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::MessageType;
+    use crate::triggers::TriggerEngine;
+    use chrono::Utc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// Child rule that fires while its switch is on and claims logs from one process
+    struct Switch {
+        name: &'static str,
+        on: Arc<AtomicBool>,
+        severity: Severity,
+    }
+
+    impl TriggerRule for Switch {
+        fn evaluate(&self, _events: &EventSnapshot<'_>) -> bool {
+            self.on.load(Ordering::SeqCst)
+        }
+
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn severity(&self) -> Severity {
+            self.severity
+        }
+
+        fn relevant_logs<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a LogEvent> {
+            events.logs().for_process(self.name).collect()
+        }
+
+        fn relevant_metrics<'a>(&self, _events: &EventSnapshot<'a>) -> Vec<&'a MetricsEvent> {
+            Vec::new()
+        }
+
+        fn relevant_disk_events<'a>(&self, _events: &EventSnapshot<'a>) -> Vec<&'a DiskEvent> {
+            Vec::new()
+        }
+    }
+
+    fn composite(
+        mode: CompositeMode,
+        children: &[(&'static str, Severity)],
+    ) -> (CompositeRule, Vec<Arc<AtomicBool>>) {
+        let switches = children
+            .iter()
+            .map(|_| Arc::new(AtomicBool::new(false)))
+            .collect::<Vec<_>>();
+        let rules = children
+            .iter()
+            .zip(&switches)
+            .map(|(&(name, severity), on)| {
+                Box::new(Switch {
+                    name,
+                    on: on.clone(),
+                    severity,
+                }) as Box<dyn TriggerRule>
+            })
+            .collect();
+        (
+            CompositeRule::new("story", mode, rules, 300, None),
+            switches,
+        )
+    }
+
+    fn log_event(process: &str) -> LogEvent {
+        LogEvent {
+            timestamp: Utc::now(),
+            message_type: MessageType::Error,
+            subsystem: "com.apple.test".to_string(),
+            category: "default".to_string(),
+            process: process.to_string(),
+            process_id: 7,
+            message: format!("{} failed", process),
+        }
+    }
+
+    #[test]
+    fn test_sequence_requires_order_within_window() {
+        let (rule, switches) = composite(
+            CompositeMode::Sequence,
+            &[
+                ("disk", Severity::Warning),
+                ("memory", Severity::Warning),
+                ("crash", Severity::Critical),
+            ],
+        );
+        let logs = vec![log_event("disk"), log_event("crash"), log_event("other")];
+        let snapshot = EventSnapshot::from_slices(&logs, &[], &[]);
+        let start = Utc::now();
+        let step = |seconds: i64, on: [bool; 3]| {
+            for (switch, on) in switches.iter().zip(on) {
+                switch.store(on, Ordering::SeqCst);
+            }
+            rule.evaluate_at(&snapshot, start + Duration::seconds(seconds))
+        };
+
+        // Out of order: the crash comes before the memory pressure
+        assert!(!step(0, [true, false, false]));
+        assert!(!step(10, [false, false, true]));
+        assert!(!step(20, [false, true, false]));
+        // The crash that follows completes the sequence
+        assert!(step(30, [false, false, true]));
+        assert_eq!(rule.severity_for(&snapshot), Severity::Critical);
+        assert_eq!(
+            rule.retained_events()
+                .logs
+                .iter()
+                .map(|event| event.process.as_str())
+                .collect::<Vec<_>>(),
+            vec!["disk", "crash"]
+        );
+        let reason = rule.trigger_reason(&snapshot, None);
+        assert!(
+            reason.starts_with("Rule 'story' triggered: disk ("),
+            "{}",
+            reason
+        );
+        assert!(reason.contains("), then memory ("), "{}", reason);
+        assert!(
+            reason.contains("within 300s. Rule 'disk' triggered"),
+            "{}",
+            reason
+        );
+
+        // Once the disk firing leaves the window the sequence no longer holds
+        assert!(!step(301, [false, false, true]));
+    }
+
+    #[test]
+    fn test_all_and_absence() {
+        let (all, switches) = composite(
+            CompositeMode::All,
+            &[("a", Severity::Warning), ("b", Severity::Info)],
+        );
+        let snapshot = EventSnapshot::from_slices(&[], &[], &[]);
+        let now = Utc::now();
+        switches[1].store(true, Ordering::SeqCst);
+        assert!(!all.evaluate_at(&snapshot, now));
+        switches[1].store(false, Ordering::SeqCst);
+        switches[0].store(true, Ordering::SeqCst);
+        assert!(all.evaluate_at(&snapshot, now + Duration::seconds(60)));
+        assert_eq!(all.severity_for(&snapshot), Severity::Warning);
+
+        let (absence, switches) = composite(
+            CompositeMode::Absence,
+            &[
+                ("pressure", Severity::Warning),
+                ("spike", Severity::Warning),
+            ],
+        );
+        switches[0].store(true, Ordering::SeqCst);
+        assert!(absence.evaluate_at(&snapshot, now));
+        assert!(absence
+            .trigger_reason(&snapshot, None)
+            .contains("pressure fired without spike within 300s"));
+        switches[1].store(true, Ordering::SeqCst);
+        assert!(!absence.evaluate_at(&snapshot, now + Duration::seconds(10)));
+        switches[1].store(false, Ordering::SeqCst);
+        assert!(!absence.evaluate_at(&snapshot, now + Duration::seconds(200)));
+        assert!(absence.evaluate_at(&snapshot, now + Duration::seconds(311)));
+    }
+
+    #[test]
+    fn test_context_keeps_evidence_from_earlier_firings() {
+        let (rule, switches) = composite(
+            CompositeMode::Sequence,
+            &[("disk", Severity::Warning), ("crash", Severity::Critical)],
+        );
+        let mut engine = TriggerEngine::new();
+        engine.add_rule(Box::new(rule));
+        let start = Utc::now();
+
+        // The disk rule fires while its log is in the window
+        let earlier = vec![log_event("disk")];
+        switches[0].store(true, Ordering::SeqCst);
+        let snapshot = EventSnapshot::from_slices(&earlier, &[], &[]).at(start);
+        assert!(engine.evaluate(&snapshot).is_empty());
+
+        // By the time of the crash, only the crash log is left
+        let later = vec![log_event("crash")];
+        switches[0].store(false, Ordering::SeqCst);
+        switches[1].store(true, Ordering::SeqCst);
+        let snapshot =
+            EventSnapshot::from_slices(&later, &[], &[]).at(start + Duration::seconds(200));
+        let contexts = engine.evaluate(&snapshot);
+        assert_eq!(contexts.len(), 1);
+        assert_eq!(
+            contexts[0]
+                .log_events
+                .iter()
+                .map(|event| event.process.as_str())
+                .collect::<Vec<_>>(),
+            vec!["disk", "crash"]
+        );
+    }
+}
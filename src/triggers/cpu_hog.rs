@@ -0,0 +1,372 @@
+//! Sustained per-process CPU usage from process metrics
+//!
+//! `ResourceSpikeRule` compares system-wide power between samples and cannot
+//! say which process is responsible. `CpuHogRule` follows the
+//! `cpu_usage_percent` of every process instance in the per-process snapshots
+//! of metrics events and fires when one stays above its limit for long
+//! enough, naming the process in the alert.
+
+use crate::aggregator::EventSnapshot;
+use crate::config::CpuHogConfig;
+use crate::events::{DiskEvent, LogEvent, MetricsEvent, Severity, Timestamp};
+use crate::triggers::{RelevantLogGroup, TriggerRule};
+use chrono::Duration;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::Mutex;
+
+/// Longest gap between an instance's samples that keeps its streak going
+const MAX_SAMPLE_GAP: Duration = Duration::seconds(60);
+
+/// CPU usage and duration an instance must reach
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Limit {
+    cpu_percent: f64,
+    duration: Duration,
+}
+
+/// An uninterrupted run of samples at or above the limit
+#[derive(Debug, Clone, PartialEq)]
+struct Streak {
+    since: Timestamp,
+    last_seen: Timestamp,
+    samples: usize,
+    total_percent: f64,
+    peak_percent: f64,
+}
+
+impl Streak {
+    fn starting(at: Timestamp) -> Self {
+        Self {
+            since: at,
+            last_seen: at,
+            samples: 0,
+            total_percent: 0.0,
+            peak_percent: 0.0,
+        }
+    }
+
+    fn record(&mut self, at: Timestamp, percent: f64) {
+        self.last_seen = at;
+        self.samples += 1;
+        self.total_percent += percent;
+        self.peak_percent = self.peak_percent.max(percent);
+    }
+}
+
+#[derive(Debug, Default)]
+struct HogState {
+    /// Current streak per process name and PID
+    streaks: BTreeMap<(String, u32), Streak>,
+    /// Newest metrics event already observed
+    metrics_seen: Option<Timestamp>,
+}
+
+/// A process instance that has used too much CPU for too long
+#[derive(Debug, Clone, PartialEq)]
+struct Hog {
+    process: String,
+    process_id: u32,
+    limit: Limit,
+    streak: Streak,
+}
+
+/// Trigger rule for processes that stay above a CPU limit
+///
+/// Each instance's streak carries over between evaluations, so `duration`
+/// is not bounded by the event buffer. Per-process overrides replace the
+/// limit or the duration for named processes.
+pub struct CpuHogRule {
+    /// Limit for processes without an override
+    pub cpu_percent: f64,
+    /// How long the usage must last for processes without an override
+    pub duration: Duration,
+    /// Processes that are never reported
+    pub allow_processes: HashSet<String>,
+    /// Severity level to assign when this rule triggers
+    pub severity: Severity,
+    overrides: HashMap<String, Limit>,
+    state: Mutex<HogState>,
+}
+
+impl CpuHogRule {
+    /// Create a CPU hog rule from `[triggers.cpu_hog]`
+    pub fn new(config: &CpuHogConfig) -> Self {
+        let duration = Duration::seconds(config.duration_seconds as i64);
+        let overrides = config
+            .overrides
+            .iter()
+            .map(|entry| {
+                let limit = Limit {
+                    cpu_percent: entry.cpu_percent.unwrap_or(config.cpu_percent),
+                    duration: entry
+                        .duration_seconds
+                        .map_or(duration, |seconds| Duration::seconds(seconds as i64)),
+                };
+                (entry.process.clone(), limit)
+            })
+            .collect();
+        Self {
+            cpu_percent: config.cpu_percent,
+            duration,
+            allow_processes: config.allow_processes.iter().cloned().collect(),
+            severity: config.severity,
+            overrides,
+            state: Mutex::new(HogState::default()),
+        }
+    }
+
+    /// Create a CPU hog rule with the default configuration
+    pub fn with_defaults() -> Self {
+        Self::new(&CpuHogConfig::default())
+    }
+
+    fn limit(&self, process: &str) -> Limit {
+        self.overrides.get(process).copied().unwrap_or(Limit {
+            cpu_percent: self.cpu_percent,
+            duration: self.duration,
+        })
+    }
+
+    /// Fold process snapshots newer than the last evaluation into the streaks
+    fn ingest(&self, state: &mut HogState, events: &EventSnapshot<'_>) {
+        let mut fresh = events
+            .metrics()
+            .iter()
+            .filter(|event| state.metrics_seen.is_none_or(|seen| event.timestamp > seen))
+            .collect::<Vec<_>>();
+        fresh.sort_by_key(|event| event.timestamp);
+        for event in fresh {
+            for process in &event.process_metrics {
+                if self.allow_processes.contains(&process.process) {
+                    continue;
+                }
+                let key = (process.process.clone(), process.process_id);
+                let percent = process.cpu_usage_percent;
+                if percent < self.limit(&process.process).cpu_percent {
+                    state.streaks.remove(&key);
+                    continue;
+                }
+                let streak = state
+                    .streaks
+                    .entry(key)
+                    .or_insert_with(|| Streak::starting(event.timestamp));
+                if event.timestamp - streak.last_seen > MAX_SAMPLE_GAP {
+                    *streak = Streak::starting(event.timestamp);
+                }
+                streak.record(event.timestamp, percent);
+            }
+            state.metrics_seen = state.metrics_seen.max(Some(event.timestamp));
+        }
+    }
+
+    /// Process instances whose streak has lasted long enough and is current
+    fn hogs(&self, now: Timestamp) -> Vec<Hog> {
+        let state = self.state.lock().unwrap();
+        state
+            .streaks
+            .iter()
+            .filter_map(|((process, process_id), streak)| {
+                let limit = self.limit(process);
+                (streak.last_seen - streak.since >= limit.duration
+                    && now - streak.last_seen <= MAX_SAMPLE_GAP)
+                    .then(|| Hog {
+                        process: process.clone(),
+                        process_id: *process_id,
+                        limit,
+                        streak: streak.clone(),
+                    })
+            })
+            .collect()
+    }
+
+    fn evaluate_at(&self, events: &EventSnapshot<'_>, now: Timestamp) -> bool {
+        {
+            let mut state = self.state.lock().unwrap();
+            self.ingest(&mut state, events);
+            // Instances gone from the snapshots have stopped or exited
+            state
+                .streaks
+                .retain(|_, streak| now - streak.last_seen <= MAX_SAMPLE_GAP);
+        }
+        !self.hogs(now).is_empty()
+    }
+
+    fn describe(hog: &Hog) -> String {
+        format!(
+            "{} (PID {}) at or above {:.0}% CPU for {}s (average {:.0}%, peak {:.0}%)",
+            hog.process,
+            hog.process_id,
+            hog.limit.cpu_percent,
+            (hog.streak.last_seen - hog.streak.since).num_seconds(),
+            hog.streak.total_percent / hog.streak.samples as f64,
+            hog.streak.peak_percent
+        )
+    }
+}
+
+impl TriggerRule for CpuHogRule {
+    fn evaluate(&self, events: &EventSnapshot<'_>) -> bool {
+        self.evaluate_at(events, events.now())
+    }
+
+    fn name(&self) -> &str {
+        "CpuHogRule"
+    }
+
+    fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    fn relevant_logs<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a LogEvent> {
+        self.relevant_log_groups(events)
+            .into_iter()
+            .flat_map(|group| group.events)
+            .collect()
+    }
+
+    /// One context per busy process, holding its log events
+    fn relevant_log_groups<'a>(&self, events: &EventSnapshot<'a>) -> Vec<RelevantLogGroup<'a>> {
+        let logs = events.logs();
+        let processes = self
+            .hogs(events.now())
+            .into_iter()
+            .map(|hog| hog.process)
+            .collect::<BTreeSet<_>>();
+        processes
+            .into_iter()
+            .map(|process| RelevantLogGroup {
+                events: logs.for_process(&process).collect(),
+                source: Some(process),
+            })
+            .collect()
+    }
+
+    /// Metrics events whose process snapshot includes a busy instance
+    fn relevant_metrics<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a MetricsEvent> {
+        let busy = self
+            .hogs(events.now())
+            .into_iter()
+            .map(|hog| (hog.process, hog.process_id))
+            .collect::<HashSet<_>>();
+        events
+            .metrics()
+            .iter()
+            .filter(|event| {
+                event
+                    .process_metrics
+                    .iter()
+                    .any(|process| busy.contains(&(process.process.clone(), process.process_id)))
+            })
+            .collect()
+    }
+
+    fn relevant_disk_events<'a>(&self, _events: &EventSnapshot<'a>) -> Vec<&'a DiskEvent> {
+        Vec::new()
+    }
+
+    fn trigger_reason(&self, events: &EventSnapshot<'_>, source: Option<&str>) -> String {
+        let evidence = self
+            .hogs(events.now())
+            .iter()
+            .filter(|hog| source.is_none_or(|source| hog.process == source))
+            .map(Self::describe)
+            .collect::<Vec<_>>()
+            .join("; ");
+        format!("Rule '{}' triggered: {}", self.name(), evidence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CpuHogOverride;
+    use crate::triggers::tests::process_metrics_event;
+    use chrono::Utc;
+
+    #[test]
+    fn test_sustained_usage_fires_and_names_the_process() {
+        let rule = CpuHogRule::with_defaults();
+        let start = Utc::now() - Duration::minutes(10);
+        let at = |seconds| start + Duration::seconds(seconds);
+        // Samples every 5 seconds: photoanalysisd pegs two cores, clang is
+        // allowed, and mds dips below the limit once a minute
+        let metrics = (0..=61)
+            .map(|tick| {
+                let mds = if tick % 12 == 11 { 40.0 } else { 150.0 };
+                process_metrics_event(
+                    &[
+                        (
+                            "photoanalysisd",
+                            812,
+                            190.0 + (tick % 3) as f64 * 5.0,
+                            100.0,
+                        ),
+                        ("clang", 900, 100.0, 100.0),
+                        ("mds", 77, mds, 100.0),
+                    ],
+                    at(tick * 5),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let early = EventSnapshot::from_slices(&[], &metrics[..30], &[]);
+        assert!(!rule.evaluate_at(&early, at(150)));
+        let snapshot = EventSnapshot::from_slices(&[], &metrics, &[]);
+        assert!(rule.evaluate_at(&snapshot, at(305)));
+
+        let hogs = rule.hogs(at(305));
+        assert_eq!(hogs.len(), 1);
+        assert_eq!(
+            CpuHogRule::describe(&hogs[0]),
+            "photoanalysisd (PID 812) at or above 90% CPU for 305s (average 195%, peak 200%)"
+        );
+
+        // Once the process leaves the snapshots the streak ends
+        assert!(!rule.evaluate_at(&EventSnapshot::from_slices(&[], &[], &[]), at(400)));
+    }
+
+    #[test]
+    fn test_overrides_set_per_process_limits() {
+        let rule = CpuHogRule::new(&CpuHogConfig {
+            overrides: vec![
+                CpuHogOverride {
+                    process: "kernel_task".to_string(),
+                    cpu_percent: Some(300.0),
+                    duration_seconds: None,
+                },
+                CpuHogOverride {
+                    process: "backupd".to_string(),
+                    cpu_percent: None,
+                    duration_seconds: Some(60),
+                },
+            ],
+            ..CpuHogConfig::default()
+        });
+        let start = Utc::now() - Duration::seconds(60);
+        let metrics = (0..=12)
+            .map(|tick| {
+                process_metrics_event(
+                    &[
+                        ("kernel_task", 0, 250.0, 100.0),
+                        ("backupd", 310, 95.0, 100.0),
+                    ],
+                    start + Duration::seconds(tick * 5),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let now = start + Duration::seconds(60);
+        let snapshot = EventSnapshot::from_slices(&[], &metrics, &[]);
+        assert!(rule.evaluate_at(&snapshot, now));
+        let hogs = rule.hogs(now);
+        assert_eq!(
+            hogs.iter()
+                .map(|hog| hog.process.as_str())
+                .collect::<Vec<_>>(),
+            vec!["backupd"]
+        );
+        assert!(rule
+            .trigger_reason(&snapshot, Some("backupd"))
+            .starts_with("Rule 'CpuHogRule' triggered: backupd (PID 310)"));
+    }
+}
@@ -0,0 +1,675 @@
+//! Declarative trigger rules from `[[triggers.rules]]`
+//!
+//! Each configured rule is compiled once at startup into a [`DeclarativeRule`]:
+//! the message regex is built, the reason template is parsed and every
+//! placeholder checked, and any `expression` parsed and type-checked, so a
+//! bad rule is rejected by `Config::validate` instead of failing silently at
+//! evaluation time.
+
+use crate::aggregator::{EventSnapshot, MetricField};
+use crate::config::{MetricAggregate, MetricConditionConfig, RuleGroupBy, TriggerRuleConfig};
+use crate::error::ConfigError;
+use crate::events::{
+    DiskEvent, LogEvent, MeasurementKind, MemoryPressure, MessageType, MetricsEvent, Severity,
+    Timestamp,
+};
+use crate::triggers::{Expression, RelevantLogGroup, TriggerRule};
+use chrono::Duration;
+use regex::Regex;
+use std::collections::BTreeMap;
+
+/// Log event fields a declarative rule matches on
+struct LogFilter {
+    process: Option<String>,
+    subsystem: Option<String>,
+    category: Option<String>,
+    message: Option<Regex>,
+    message_types: Vec<MessageType>,
+}
+
+impl LogFilter {
+    fn matches(&self, event: &LogEvent) -> bool {
+        self.process.as_ref().is_none_or(|p| event.process == *p)
+            && self
+                .subsystem
+                .as_ref()
+                .is_none_or(|s| event.subsystem == *s)
+            && self.category.as_ref().is_none_or(|c| event.category == *c)
+            && (self.message_types.is_empty() || self.message_types.contains(&event.message_type))
+            && self
+                .message
+                .as_ref()
+                .is_none_or(|regex| regex.is_match(&event.message))
+    }
+}
+
+/// Value substituted into a reason template
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Placeholder {
+    Name,
+    Count,
+    WindowSeconds,
+    Process,
+    Subsystem,
+    Message,
+    Source,
+    Severity,
+    Metric(MetricField),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TemplatePart {
+    Literal(String),
+    Placeholder(Placeholder),
+}
+
+/// Trigger rule compiled from a `[[triggers.rules]]` entry
+///
+/// The rule fires when its log condition (at least `count` matching events
+/// within the window, per source when `group_by` is set), every metric
+/// condition and its expression hold at the same time.
+pub struct DeclarativeRule {
+    name: String,
+    severity: Severity,
+    log_filter: Option<LogFilter>,
+    count: usize,
+    window_seconds: i64,
+    group_by: Option<RuleGroupBy>,
+    metrics: Vec<MetricConditionConfig>,
+    memory_pressure: Option<MemoryPressure>,
+    expression: Option<Expression>,
+    reason: Option<Vec<TemplatePart>>,
+}
+
+impl DeclarativeRule {
+    /// Compile a configured rule
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConfigError::ValidationError` naming the rule if it has no
+    /// condition, an invalid message regex, a zero count or window, an
+    /// expression with a syntax or type error, or a reason template with an
+    /// unknown placeholder.
+    pub fn compile(config: &TriggerRuleConfig) -> Result<Self, ConfigError> {
+        let name = config.name.trim();
+        if name.is_empty() {
+            return Err(ConfigError::ValidationError(
+                "triggers.rules name cannot be empty".to_string(),
+            ));
+        }
+        let invalid = |message: String| {
+            ConfigError::ValidationError(format!("triggers.rules '{}' {}", name, message))
+        };
+
+        if config.count == 0 {
+            return Err(invalid("count must be at least 1".to_string()));
+        }
+        if config.window_seconds == 0 {
+            return Err(invalid("window_seconds must be at least 1".to_string()));
+        }
+
+        let message = config
+            .message
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .map_err(|e| invalid(format!("message regex is invalid: {}", e)))?;
+        let has_log_condition = config.process.is_some()
+            || config.subsystem.is_some()
+            || config.category.is_some()
+            || message.is_some()
+            || !config.message_types.is_empty();
+        let log_filter = has_log_condition.then(|| LogFilter {
+            process: config.process.clone(),
+            subsystem: config.subsystem.clone(),
+            category: config.category.clone(),
+            message,
+            message_types: config.message_types.clone(),
+        });
+
+        let expression = config
+            .expression
+            .as_deref()
+            .map(|source| Expression::parse(source, config.window_seconds as i64))
+            .transpose()
+            .map_err(|e| invalid(format!("expression has a {}", e)))?;
+
+        if log_filter.is_none()
+            && config.metrics.is_empty()
+            && config.memory_pressure.is_none()
+            && expression.is_none()
+        {
+            return Err(invalid(
+                "needs a log condition, a metrics condition, memory_pressure or an expression"
+                    .to_string(),
+            ));
+        }
+        if log_filter.is_none() && config.group_by.is_some() {
+            return Err(invalid("group_by needs a log condition".to_string()));
+        }
+        if matches!(
+            config.memory_pressure,
+            Some(MemoryPressure::Unknown | MemoryPressure::Normal)
+        ) {
+            return Err(invalid(
+                "memory_pressure must be Warning or Critical".to_string(),
+            ));
+        }
+        if let Some(condition) = config.metrics.iter().find(|c| !c.value.is_finite()) {
+            return Err(invalid(format!(
+                "metrics condition on {} needs a finite value",
+                condition.field
+            )));
+        }
+
+        let reason = config
+            .reason
+            .as_deref()
+            .map(|template| parse_template(template, &config.metrics))
+            .transpose()
+            .map_err(invalid)?;
+
+        Ok(Self {
+            name: name.to_string(),
+            severity: config.severity,
+            log_filter,
+            count: config.count,
+            window_seconds: config.window_seconds as i64,
+            group_by: config.group_by,
+            metrics: config.metrics.clone(),
+            memory_pressure: config.memory_pressure,
+            expression,
+            reason,
+        })
+    }
+
+    /// Longest window the rule reads, including its expression's `within` windows
+    pub fn max_window_seconds(&self) -> u64 {
+        let expression_window = self
+            .expression
+            .as_ref()
+            .map_or(0, Expression::max_window_seconds);
+        self.window_seconds.max(expression_window) as u64
+    }
+
+    fn cutoff(&self, now: Timestamp) -> Timestamp {
+        now - Duration::seconds(self.window_seconds)
+    }
+
+    /// Log events inside the window that pass the filter
+    fn matching_logs<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a LogEvent> {
+        let Some(filter) = &self.log_filter else {
+            return Vec::new();
+        };
+        let log_events = events.logs();
+        let cutoff = self.cutoff(events.now());
+        let candidates: Box<dyn Iterator<Item = &'a LogEvent> + '_> =
+            match (&filter.process, &filter.subsystem) {
+                (Some(process), _) => log_events.for_process(process),
+                (None, Some(subsystem)) => log_events.for_subsystem(subsystem),
+                (None, None) => Box::new(log_events.iter()),
+            };
+        candidates
+            .filter(|event| event.timestamp >= cutoff && filter.matches(event))
+            .collect()
+    }
+
+    /// Sources whose matches reach `count`, with their events
+    ///
+    /// A rule without a log condition yields one sourceless group so
+    /// metric-only and expression rules still produce a single context; it
+    /// holds the log events the expression matched, if any.
+    fn triggering_groups<'a>(
+        &self,
+        events: &EventSnapshot<'a>,
+    ) -> Vec<(Option<String>, Vec<&'a LogEvent>)> {
+        if self.log_filter.is_none() {
+            let logs = self
+                .expression
+                .as_ref()
+                .map(|expression| expression.relevant_logs(events))
+                .unwrap_or_default();
+            return vec![(None, logs)];
+        }
+
+        let matches = self.matching_logs(events);
+        let Some(group_by) = self.group_by else {
+            return if matches.len() >= self.count {
+                vec![(None, matches)]
+            } else {
+                Vec::new()
+            };
+        };
+
+        let mut groups = BTreeMap::<&str, Vec<&LogEvent>>::new();
+        for event in matches {
+            let key = match group_by {
+                RuleGroupBy::Process => event.process.as_str(),
+                RuleGroupBy::Subsystem => event.subsystem.as_str(),
+            };
+            groups.entry(key).or_default().push(event);
+        }
+        groups
+            .into_iter()
+            .filter(|(_, events)| events.len() >= self.count)
+            .map(|(source, events)| (Some(source.to_string()), events))
+            .collect()
+    }
+
+    /// A metric condition's aggregate over the window, if any sample measured it
+    fn metric_value(
+        &self,
+        condition: &MetricConditionConfig,
+        events: &EventSnapshot<'_>,
+    ) -> Option<f64> {
+        let cutoff = self.cutoff(events.now());
+        let values = events
+            .metrics()
+            .iter()
+            .filter(|event| event.timestamp >= cutoff)
+            .filter_map(|event| condition.field.value(event));
+        match condition.aggregate {
+            MetricAggregate::Last => values.last(),
+            MetricAggregate::Min => values.reduce(f64::min),
+            MetricAggregate::Max => values.reduce(f64::max),
+            MetricAggregate::Avg => {
+                let (sum, count) = values.fold((0.0, 0usize), |(sum, count), value| {
+                    (sum + value, count + 1)
+                });
+                (count > 0).then(|| sum / count as f64)
+            }
+        }
+    }
+
+    fn metrics_hold(&self, events: &EventSnapshot<'_>) -> bool {
+        let cutoff = self.cutoff(events.now());
+        self.metrics.iter().all(|condition| {
+            self.metric_value(condition, events)
+                .is_some_and(|value| condition.op.compare(value, condition.value))
+        }) && self.memory_pressure.is_none_or(|level| {
+            events.metrics().iter().any(|event| {
+                event.timestamp >= cutoff
+                    && event.provenance.memory_pressure == MeasurementKind::Measured
+                    && event.memory_pressure >= level
+            })
+        })
+    }
+
+    fn default_reason(
+        &self,
+        source: Option<&str>,
+        logs: &[&LogEvent],
+        events: &EventSnapshot<'_>,
+    ) -> String {
+        let mut conditions = Vec::new();
+        if self.log_filter.is_some() {
+            let mut matched = format!(
+                "{} matching log events within {}s",
+                logs.len(),
+                self.window_seconds
+            );
+            if let Some(source) = source {
+                matched.push_str(&format!(" from {}", source));
+            }
+            conditions.push(matched);
+        }
+        for condition in &self.metrics {
+            let value = self
+                .metric_value(condition, events)
+                .map_or_else(|| "n/a".to_string(), |value| format!("{:.1}", value));
+            conditions.push(format!(
+                "{} {:?} {} {} {}",
+                condition.field, condition.aggregate, value, condition.op, condition.value
+            ));
+        }
+        if let Some(level) = self.memory_pressure {
+            conditions.push(format!("memory pressure reached {:?}", level));
+        }
+        if let Some(expression) = &self.expression {
+            conditions.push(format!("{} held", expression.source()));
+        }
+        format!("Rule '{}': {}", self.name, conditions.join("; "))
+    }
+}
+
+/// Parse a reason template into literal text and placeholders
+///
+/// Placeholders are written `{name}`; `{{` and `}}` produce literal braces.
+/// Metric placeholders such as `{cpu_usage_percent}` are only accepted for
+/// fields the rule has a metrics condition on.
+fn parse_template(
+    template: &str,
+    metrics: &[MetricConditionConfig],
+) -> Result<Vec<TemplatePart>, String> {
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let mut chars = template.char_indices().peekable();
+
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '{' if chars.peek().is_some_and(|(_, next)| *next == '{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek().is_some_and(|(_, next)| *next == '}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '{' => {
+                let rest = &template[offset + 1..];
+                let end = rest
+                    .find('}')
+                    .ok_or_else(|| format!("reason has an unclosed '{{' at offset {}", offset))?;
+                let key = &rest[..end];
+                let placeholder = placeholder(key, metrics)
+                    .ok_or_else(|| format!("reason has an unknown placeholder {{{}}}", key))?;
+                if !literal.is_empty() {
+                    parts.push(TemplatePart::Literal(std::mem::take(&mut literal)));
+                }
+                parts.push(TemplatePart::Placeholder(placeholder));
+                for _ in 0..=end {
+                    chars.next();
+                }
+            }
+            '}' => {
+                return Err(format!("reason has an unmatched '}}' at offset {}", offset));
+            }
+            c => literal.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        parts.push(TemplatePart::Literal(literal));
+    }
+    Ok(parts)
+}
+
+fn placeholder(key: &str, metrics: &[MetricConditionConfig]) -> Option<Placeholder> {
+    let placeholder = match key {
+        "name" => Placeholder::Name,
+        "count" => Placeholder::Count,
+        "window_seconds" => Placeholder::WindowSeconds,
+        "process" => Placeholder::Process,
+        "subsystem" => Placeholder::Subsystem,
+        "message" => Placeholder::Message,
+        "source" => Placeholder::Source,
+        "severity" => Placeholder::Severity,
+        _ => Placeholder::Metric(
+            metrics
+                .iter()
+                .map(|condition| condition.field)
+                .find(|field| field.to_string() == key)?,
+        ),
+    };
+    Some(placeholder)
+}
+
+impl TriggerRule for DeclarativeRule {
+    fn evaluate(&self, events: &EventSnapshot<'_>) -> bool {
+        self.metrics_hold(events)
+            && self
+                .expression
+                .as_ref()
+                .is_none_or(|expression| expression.evaluate(events))
+            && !self.triggering_groups(events).is_empty()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    fn relevant_logs<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a LogEvent> {
+        self.triggering_groups(events)
+            .into_iter()
+            .flat_map(|(_, events)| events)
+            .collect()
+    }
+
+    fn relevant_log_groups<'a>(&self, events: &EventSnapshot<'a>) -> Vec<RelevantLogGroup<'a>> {
+        self.triggering_groups(events)
+            .into_iter()
+            .map(|(source, events)| RelevantLogGroup { source, events })
+            .collect()
+    }
+
+    fn relevant_metrics<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a MetricsEvent> {
+        if self.metrics.is_empty() && self.memory_pressure.is_none() {
+            return self
+                .expression
+                .as_ref()
+                .map(|expression| expression.relevant_metrics(events))
+                .unwrap_or_default();
+        }
+        let cutoff = self.cutoff(events.now());
+        events
+            .metrics()
+            .iter()
+            .filter(|event| event.timestamp >= cutoff)
+            .collect()
+    }
+
+    fn relevant_disk_events<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a DiskEvent> {
+        self.expression
+            .as_ref()
+            .map(|expression| expression.relevant_disk_events(events))
+            .unwrap_or_default()
+    }
+
+    fn trigger_reason(&self, events: &EventSnapshot<'_>, source: Option<&str>) -> String {
+        let logs = self
+            .triggering_groups(events)
+            .into_iter()
+            .find(|(group, _)| group.as_deref() == source)
+            .map(|(_, events)| events)
+            .unwrap_or_default();
+
+        let Some(template) = &self.reason else {
+            return self.default_reason(source, &logs, events);
+        };
+
+        let latest = logs.last();
+        let mut reason = String::new();
+        for part in template {
+            match part {
+                TemplatePart::Literal(text) => reason.push_str(text),
+                TemplatePart::Placeholder(placeholder) => {
+                    let value = match placeholder {
+                        Placeholder::Name => self.name.clone(),
+                        Placeholder::Count => logs.len().to_string(),
+                        Placeholder::WindowSeconds => self.window_seconds.to_string(),
+                        Placeholder::Process => {
+                            latest.map(|e| e.process.clone()).unwrap_or_default()
+                        }
+                        Placeholder::Subsystem => {
+                            latest.map(|e| e.subsystem.clone()).unwrap_or_default()
+                        }
+                        Placeholder::Message => {
+                            latest.map(|e| e.message.clone()).unwrap_or_default()
+                        }
+                        Placeholder::Source => source.unwrap_or_default().to_string(),
+                        Placeholder::Severity => format!("{:?}", self.severity),
+                        Placeholder::Metric(field) => self
+                            .metrics
+                            .iter()
+                            .find(|condition| condition.field == *field)
+                            .and_then(|condition| self.metric_value(condition, events))
+                            .map_or_else(|| "n/a".to_string(), |value| format!("{:.1}", value)),
+                    };
+                    reason.push_str(&value);
+                }
+            }
+        }
+        reason
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ComparisonOp;
+    use crate::events::{MetricsProvenance, MetricsSource};
+    use crate::triggers::TriggerEngine;
+    use chrono::Utc;
+
+    fn rule_config(toml: &str) -> TriggerRuleConfig {
+        toml::from_str(toml).unwrap()
+    }
+
+    fn log_event(process: &str, message: &str) -> LogEvent {
+        LogEvent {
+            timestamp: Utc::now(),
+            message_type: MessageType::Error,
+            subsystem: "com.apple.storage".to_string(),
+            category: "io".to_string(),
+            process: process.to_string(),
+            process_id: 1,
+            message: message.to_string(),
+        }
+    }
+
+    fn metrics_event(cpu_usage_percent: f64) -> MetricsEvent {
+        MetricsEvent {
+            timestamp: Utc::now(),
+            cpu_power_mw: 0.0,
+            cpu_usage_percent,
+            gpu_power_mw: None,
+            gpu_usage_percent: None,
+            memory_pressure: MemoryPressure::Normal,
+            memory_used_mb: 0.0,
+            energy_impact: 0.0,
+            provenance: MetricsProvenance {
+                source: MetricsSource::Procfs,
+                cpu_usage: MeasurementKind::Measured,
+                ..Default::default()
+            },
+            process_metrics: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_compile_rejects_invalid_rules() {
+        let errors = [
+            r#"name = "empty""#,
+            r#"name = "regex"
+               message = "I/O (error""#,
+            r#"name = "template"
+               process = "kernel"
+               reason = "{process} {cpu_usage_percent}""#,
+            r#"name = "brace"
+               process = "kernel"
+               reason = "{process""#,
+            r#"name = "grouped"
+               group_by = "process"
+               memory_pressure = "Warning""#,
+        ];
+        for toml in errors {
+            let error = DeclarativeRule::compile(&rule_config(toml)).err().unwrap();
+            assert!(error.to_string().contains("triggers.rules '"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_grouped_log_rule_fires_per_source_with_template() {
+        let rule = DeclarativeRule::compile(&rule_config(
+            r#"
+            name = "io-errors"
+            severity = "critical"
+            message = "I/O error"
+            message_types = ["error", "fault"]
+            count = 2
+            group_by = "process"
+            reason = "{count} I/O errors from {source} in {window_seconds}s: {message}"
+            "#,
+        ))
+        .unwrap();
+        let logs = vec![
+            log_event("kernel", "disk0: I/O error"),
+            log_event("kernel", "disk1: I/O error"),
+            log_event("mds", "I/O error reading index"),
+            log_event("kernel", "unrelated"),
+        ];
+        let snapshot = EventSnapshot::from_slices(&logs, &[], &[]);
+        assert!(rule.evaluate(&snapshot));
+
+        let mut engine = TriggerEngine::new();
+        engine.add_rule(Box::new(rule));
+        let contexts = engine.evaluate(&snapshot);
+        assert_eq!(contexts.len(), 1);
+        assert_eq!(contexts[0].trigger_source.as_deref(), Some("kernel"));
+        assert_eq!(contexts[0].expected_severity, Severity::Critical);
+        assert_eq!(contexts[0].log_events.len(), 2);
+        assert_eq!(
+            contexts[0].trigger_reason,
+            "2 I/O errors from kernel in 60s: disk1: I/O error"
+        );
+    }
+
+    #[test]
+    fn test_metric_conditions_combine_with_log_condition() {
+        let config = rule_config(
+            r#"
+            name = "busy-kernel"
+            process = "kernel"
+            reason = "cpu at {cpu_usage_percent}%"
+
+            [[metrics]]
+            field = "cpu_usage_percent"
+            aggregate = "avg"
+            op = ">="
+            value = 80.0
+            "#,
+        );
+        assert_eq!(config.metrics[0].op, ComparisonOp::Ge);
+        let rule = DeclarativeRule::compile(&config).unwrap();
+        let logs = vec![log_event("kernel", "panic")];
+
+        let idle = vec![metrics_event(50.0), metrics_event(90.0)];
+        assert!(!rule.evaluate(&EventSnapshot::from_slices(&logs, &idle, &[])));
+
+        let busy = vec![metrics_event(85.0), metrics_event(95.0)];
+        let snapshot = EventSnapshot::from_slices(&logs, &busy, &[]);
+        assert!(rule.evaluate(&snapshot));
+        assert_eq!(rule.trigger_reason(&snapshot, None), "cpu at 90.0%");
+        assert!(!rule.evaluate(&EventSnapshot::from_slices(&[], &busy, &[])));
+    }
+
+    #[test]
+    fn test_expression_rule_collects_context_from_expression() {
+        let rule = DeclarativeRule::compile(&rule_config(
+            r#"
+            name = "io-errors-under-load"
+            expression = """
+                count(log where message ~ "I/O error") >= 2 within 30s
+                and avg(metrics.cpu_usage_percent) > 80
+            """
+            "#,
+        ))
+        .unwrap();
+        assert_eq!(rule.max_window_seconds(), 60);
+        let logs = vec![
+            log_event("kernel", "disk0: I/O error"),
+            log_event("mds", "I/O error reading index"),
+            log_event("kernel", "unrelated"),
+        ];
+        let metrics = vec![metrics_event(85.0), metrics_event(95.0)];
+        let snapshot = EventSnapshot::from_slices(&logs, &metrics, &[]);
+
+        assert!(rule.evaluate(&snapshot));
+        assert_eq!(rule.relevant_logs(&snapshot).len(), 2);
+        assert_eq!(rule.relevant_metrics(&snapshot).len(), 2);
+        assert!(!rule.evaluate(&EventSnapshot::from_slices(&logs[..1], &metrics, &[])));
+
+        let error = DeclarativeRule::compile(&rule_config(
+            r#"
+            name = "typo"
+            expression = "count(log where mesage ~ \"x\") > 0"
+            "#,
+        ))
+        .err()
+        .unwrap();
+        assert!(error.to_string().contains("line 1, column 17"), "{}", error);
+    }
+}
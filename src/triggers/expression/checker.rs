@@ -0,0 +1,627 @@
+//! Type checking of the syntax tree against the event schemas
+//!
+//! Resolves field names, enum values and aggregate windows, compiles regex
+//! literals, and produces the typed tree the evaluator walks.
+
+use super::parser::{Ast, CompareOp, Name, Node};
+use super::{type_error, Span};
+use crate::aggregator::{DiskField, MetricField};
+use crate::error::ExpressionError;
+use crate::events::{MemoryPressure, MessageType};
+use regex::Regex;
+
+/// Value types in the expression language
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ValueType {
+    Bool,
+    Number,
+    Str,
+    MessageType,
+    MemoryPressure,
+}
+
+impl ValueType {
+    fn describe(self) -> &'static str {
+        match self {
+            Self::Bool => "a condition",
+            Self::Number => "a number",
+            Self::Str => "a string",
+            Self::MessageType => "a message type",
+            Self::MemoryPressure => "a memory pressure level",
+        }
+    }
+
+    fn is_ordered(self) -> bool {
+        matches!(self, Self::Number | Self::MemoryPressure)
+    }
+}
+
+/// Event stream an aggregate reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Source {
+    Log,
+    Metrics,
+    Disk,
+}
+
+impl Source {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "log" | "logs" => Some(Self::Log),
+            "metrics" => Some(Self::Metrics),
+            "disk" => Some(Self::Disk),
+            _ => None,
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Self::Log => "log",
+            Self::Metrics => "metrics",
+            Self::Disk => "disk",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum LogField {
+    Process,
+    Subsystem,
+    Category,
+    Message,
+    MessageType,
+    ProcessId,
+}
+
+/// A field of one event kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Field {
+    Log(LogField),
+    Metric(MetricField),
+    MemoryPressure,
+    Disk(DiskField),
+    DiskName,
+    FilesystemPath,
+}
+
+impl Field {
+    fn lookup(source: Source, name: &str) -> Option<Self> {
+        let field = match source {
+            Source::Log => Self::Log(match name {
+                "process" => LogField::Process,
+                "subsystem" => LogField::Subsystem,
+                "category" => LogField::Category,
+                "message" => LogField::Message,
+                "message_type" => LogField::MessageType,
+                "process_id" | "pid" => LogField::ProcessId,
+                _ => return None,
+            }),
+            Source::Metrics => match name {
+                "memory_pressure" => Self::MemoryPressure,
+                _ => Self::Metric(
+                    MetricField::ALL
+                        .into_iter()
+                        .find(|field| field.to_string() == name)?,
+                ),
+            },
+            Source::Disk => match name {
+                "disk_name" => Self::DiskName,
+                "filesystem_path" => Self::FilesystemPath,
+                _ => Self::Disk(
+                    DiskField::ALL
+                        .into_iter()
+                        .find(|field| field.to_string() == name)?,
+                ),
+            },
+        };
+        Some(field)
+    }
+
+    fn field_type(self) -> ValueType {
+        match self {
+            Self::Log(LogField::MessageType) => ValueType::MessageType,
+            Self::Log(LogField::ProcessId) => ValueType::Number,
+            Self::Log(_) | Self::DiskName | Self::FilesystemPath => ValueType::Str,
+            Self::Metric(_) | Self::Disk(_) => ValueType::Number,
+            Self::MemoryPressure => ValueType::MemoryPressure,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum AggregateFn {
+    Count,
+    Last,
+    Min,
+    Max,
+    Avg,
+    Sum,
+}
+
+impl AggregateFn {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "count" => Some(Self::Count),
+            "last" => Some(Self::Last),
+            "min" => Some(Self::Min),
+            "max" => Some(Self::Max),
+            "avg" => Some(Self::Avg),
+            "sum" => Some(Self::Sum),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Literal {
+    Bool(bool),
+    Number(f64),
+    Str(String),
+    MessageType(MessageType),
+    MemoryPressure(MemoryPressure),
+}
+
+/// Index lookup that narrows the events an aggregate scans
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum IndexHint {
+    Process(String),
+    Subsystem(String),
+}
+
+#[derive(Debug)]
+pub(super) struct Aggregate {
+    pub function: AggregateFn,
+    pub source: Source,
+    pub field: Option<Field>,
+    pub filter: Option<Typed>,
+    pub window_seconds: i64,
+    pub index: Option<IndexHint>,
+}
+
+/// Type-checked expression tree
+#[derive(Debug)]
+pub(super) enum Typed {
+    Literal(Literal),
+    Field(Field),
+    Not(Box<Typed>),
+    And(Box<Typed>, Box<Typed>),
+    Or(Box<Typed>, Box<Typed>),
+    /// Equality or ordering; never `Match`/`NotMatch`
+    Compare(CompareOp, Box<Typed>, Box<Typed>),
+    Match {
+        negated: bool,
+        value: Box<Typed>,
+        regex: Regex,
+    },
+    Aggregate(Box<Aggregate>),
+}
+
+/// Operand that may still need the other side of a comparison to resolve
+enum Operand {
+    Typed(Typed, ValueType),
+    /// Bare identifier that is not a field in scope, such as `Warning`
+    Name(String, Span),
+}
+
+pub(super) struct Checker<'s> {
+    source: &'s str,
+    /// Event kind whose fields are in scope inside a `where` clause
+    scope: Option<Source>,
+    window_seconds: i64,
+    pub max_window_seconds: i64,
+}
+
+impl<'s> Checker<'s> {
+    pub(super) fn new(source: &'s str, default_window_seconds: i64) -> Self {
+        Self {
+            source,
+            scope: None,
+            window_seconds: default_window_seconds,
+            max_window_seconds: 0,
+        }
+    }
+
+    /// Check the root of an expression, which must be a condition
+    pub(super) fn check_root(&mut self, node: &Node) -> Result<Typed, ExpressionError> {
+        self.expect_bool(node, "the expression")
+    }
+
+    fn error(&self, span: Span, message: &str) -> ExpressionError {
+        type_error(self.source, span, message)
+    }
+
+    fn expect_bool(&mut self, node: &Node, what: &str) -> Result<Typed, ExpressionError> {
+        let (typed, ty) = self.check(node)?;
+        if ty != ValueType::Bool {
+            return Err(self.error(
+                node.span,
+                &format!("{} must be a condition, found {}", what, ty.describe()),
+            ));
+        }
+        Ok(typed)
+    }
+
+    fn check(&mut self, node: &Node) -> Result<(Typed, ValueType), ExpressionError> {
+        match self.operand(node)? {
+            Operand::Typed(typed, ty) => Ok((typed, ty)),
+            Operand::Name(name, span) => Err(self.unknown_name(&name, span)),
+        }
+    }
+
+    fn unknown_name(&self, name: &str, span: Span) -> ExpressionError {
+        if let Some(source) = self.scope {
+            return self.error(
+                span,
+                &format!("unknown field '{}' on {} events", name, source.describe()),
+            );
+        }
+        let owner = [Source::Log, Source::Metrics, Source::Disk]
+            .into_iter()
+            .find(|source| Field::lookup(*source, name).is_some());
+        match owner {
+            Some(source) => self.error(
+                span,
+                &format!(
+                    "field '{}' is only available inside an aggregate, e.g. count({} where {} == ...)",
+                    name,
+                    source.describe(),
+                    name
+                ),
+            ),
+            None if message_type(name).is_some() || memory_pressure(name).is_some() => self.error(
+                span,
+                &format!("'{}' can only be compared with a field", name),
+            ),
+            None => self.error(span, &format!("unknown name '{}'", name)),
+        }
+    }
+
+    fn operand(&mut self, node: &Node) -> Result<Operand, ExpressionError> {
+        let checked = match &node.ast {
+            Ast::Bool(value) => (Typed::Literal(Literal::Bool(*value)), ValueType::Bool),
+            Ast::Number(value) => (Typed::Literal(Literal::Number(*value)), ValueType::Number),
+            Ast::Str(value) => (Typed::Literal(Literal::Str(value.clone())), ValueType::Str),
+            Ast::Ident(name) => match self.scope.and_then(|source| Field::lookup(source, name)) {
+                Some(field) => (Typed::Field(field), field.field_type()),
+                None => return Ok(Operand::Name(name.clone(), node.span)),
+            },
+            Ast::Qualified { source, field } => {
+                let field = self.qualified(source, field, node.span)?;
+                (Typed::Field(field), field.field_type())
+            }
+            Ast::Not(operand) => {
+                let operand = self.expect_bool(operand, "the operand of 'not'")?;
+                (Typed::Not(Box::new(operand)), ValueType::Bool)
+            }
+            Ast::And(lhs, rhs) => {
+                let lhs = self.expect_bool(lhs, "each side of 'and'")?;
+                let rhs = self.expect_bool(rhs, "each side of 'and'")?;
+                (Typed::And(Box::new(lhs), Box::new(rhs)), ValueType::Bool)
+            }
+            Ast::Or(lhs, rhs) => {
+                let lhs = self.expect_bool(lhs, "each side of 'or'")?;
+                let rhs = self.expect_bool(rhs, "each side of 'or'")?;
+                (Typed::Or(Box::new(lhs), Box::new(rhs)), ValueType::Bool)
+            }
+            Ast::Compare { op, lhs, rhs } => (self.compare(*op, lhs, rhs)?, ValueType::Bool),
+            Ast::Call {
+                function,
+                source,
+                field,
+                filter,
+            } => self.aggregate(node.span, function, source, field.as_ref(), filter)?,
+            Ast::Within { inner, seconds, .. } => {
+                let outer = std::mem::replace(&mut self.window_seconds, *seconds);
+                let checked = self.check(inner);
+                self.window_seconds = outer;
+                checked?
+            }
+        };
+        Ok(Operand::Typed(checked.0, checked.1))
+    }
+
+    fn qualified(&self, source: &Name, field: &Name, span: Span) -> Result<Field, ExpressionError> {
+        let Some(event_source) = Source::parse(&source.text) else {
+            return Err(self.error(
+                source.span,
+                &format!(
+                    "unknown event source '{}'; expected log, metrics or disk",
+                    source.text
+                ),
+            ));
+        };
+        let resolved = Field::lookup(event_source, &field.text).ok_or_else(|| {
+            self.error(
+                field.span,
+                &format!(
+                    "unknown field '{}' on {} events",
+                    field.text,
+                    event_source.describe()
+                ),
+            )
+        })?;
+        match self.scope {
+            Some(scope) if scope == event_source => Ok(resolved),
+            Some(scope) => Err(self.error(
+                span,
+                &format!(
+                    "{} fields cannot be used inside a filter over {} events",
+                    event_source.describe(),
+                    scope.describe()
+                ),
+            )),
+            None => Err(self.error(
+                span,
+                &format!(
+                    "{}.{} must be wrapped in an aggregate, e.g. last({}.{})",
+                    source.text, field.text, source.text, field.text
+                ),
+            )),
+        }
+    }
+
+    fn compare(&mut self, op: CompareOp, lhs: &Node, rhs: &Node) -> Result<Typed, ExpressionError> {
+        if matches!(op, CompareOp::Match | CompareOp::NotMatch) {
+            let (value, ty) = self.check(lhs)?;
+            if ty != ValueType::Str {
+                return Err(self.error(
+                    lhs.span,
+                    &format!(
+                        "'{}' needs a string on the left, found {}",
+                        op.symbol(),
+                        ty.describe()
+                    ),
+                ));
+            }
+            let Ast::Str(pattern) = &rhs.ast else {
+                return Err(self.error(
+                    rhs.span,
+                    &format!(
+                        "the right side of '{}' must be a regex string literal",
+                        op.symbol()
+                    ),
+                ));
+            };
+            let regex = Regex::new(pattern)
+                .map_err(|e| self.error(rhs.span, &format!("invalid regex: {}", e)))?;
+            return Ok(Typed::Match {
+                negated: op == CompareOp::NotMatch,
+                value: Box::new(value),
+                regex,
+            });
+        }
+
+        let lhs_operand = self.operand(lhs)?;
+        let rhs_operand = self.operand(rhs)?;
+        let ((lhs_typed, lhs_ty), (rhs_typed, rhs_ty)) = match (lhs_operand, rhs_operand) {
+            (Operand::Name(name, span), Operand::Name(..)) => {
+                return Err(self.unknown_name(&name, span))
+            }
+            (Operand::Typed(typed, ty), Operand::Name(name, span)) => {
+                let resolved = self.resolve_name(&name, span, ty)?;
+                ((typed, ty), (resolved, ty))
+            }
+            (Operand::Name(name, span), Operand::Typed(typed, ty)) => {
+                let resolved = self.resolve_name(&name, span, ty)?;
+                ((resolved, ty), (typed, ty))
+            }
+            (Operand::Typed(lhs_typed, lhs_ty), Operand::Typed(rhs_typed, rhs_ty)) => (
+                self.coerce(lhs_typed, lhs_ty, rhs_ty, lhs.span)?,
+                self.coerce(rhs_typed, rhs_ty, lhs_ty, rhs.span)?,
+            ),
+        };
+
+        if lhs_ty != rhs_ty {
+            return Err(self.error(
+                rhs.span,
+                &format!(
+                    "cannot compare {} with {}",
+                    lhs_ty.describe(),
+                    rhs_ty.describe()
+                ),
+            ));
+        }
+        if !matches!(op, CompareOp::Eq | CompareOp::Ne) && !lhs_ty.is_ordered() {
+            return Err(self.error(
+                lhs.span.to(rhs.span),
+                &format!(
+                    "'{}' needs numbers or memory pressure levels, found {}",
+                    op.symbol(),
+                    lhs_ty.describe()
+                ),
+            ));
+        }
+        Ok(Typed::Compare(op, Box::new(lhs_typed), Box::new(rhs_typed)))
+    }
+
+    /// Resolve a bare name against the type of the other side of a comparison
+    fn resolve_name(
+        &self,
+        name: &str,
+        span: Span,
+        target: ValueType,
+    ) -> Result<Typed, ExpressionError> {
+        match target {
+            ValueType::MessageType => message_type(name)
+                .map(|value| Typed::Literal(Literal::MessageType(value)))
+                .ok_or_else(|| {
+                    self.error(
+                        span,
+                        &format!(
+                            "'{}' is not a message type; expected error, fault, info or debug",
+                            name
+                        ),
+                    )
+                }),
+            ValueType::MemoryPressure => memory_pressure(name)
+                .map(|value| Typed::Literal(Literal::MemoryPressure(value)))
+                .ok_or_else(|| {
+                    self.error(
+                        span,
+                        &format!(
+                            "'{}' is not a memory pressure level; expected Normal, Warning, Critical or Unknown",
+                            name
+                        ),
+                    )
+                }),
+            _ => Err(self.unknown_name(name, span)),
+        }
+    }
+
+    /// Turn a string literal into an enum value when compared with an enum field
+    fn coerce(
+        &self,
+        typed: Typed,
+        ty: ValueType,
+        target: ValueType,
+        span: Span,
+    ) -> Result<(Typed, ValueType), ExpressionError> {
+        match (&typed, target) {
+            (
+                Typed::Literal(Literal::Str(name)),
+                ValueType::MessageType | ValueType::MemoryPressure,
+            ) => Ok((self.resolve_name(name, span, target)?, target)),
+            _ => Ok((typed, ty)),
+        }
+    }
+
+    fn aggregate(
+        &mut self,
+        span: Span,
+        function: &Name,
+        source: &Name,
+        field: Option<&Name>,
+        filter: &Option<Box<Node>>,
+    ) -> Result<(Typed, ValueType), ExpressionError> {
+        if self.scope.is_some() {
+            return Err(self.error(span, "aggregates cannot be nested inside a filter"));
+        }
+        let Some(aggregate_fn) = AggregateFn::parse(&function.text) else {
+            return Err(self.error(
+                function.span,
+                &format!(
+                    "unknown function '{}'; expected count, last, min, max, avg or sum",
+                    function.text
+                ),
+            ));
+        };
+        let Some(event_source) = Source::parse(&source.text) else {
+            return Err(self.error(
+                source.span,
+                &format!(
+                    "unknown event source '{}'; expected log, metrics or disk",
+                    source.text
+                ),
+            ));
+        };
+
+        let field = match (aggregate_fn, field) {
+            (AggregateFn::Count, Some(field)) => {
+                return Err(self.error(
+                    field.span,
+                    &format!(
+                        "count takes an event source, not a field; use count({})",
+                        source.text
+                    ),
+                ))
+            }
+            (AggregateFn::Count, None) => None,
+            (_, None) => {
+                return Err(self.error(
+                    source.span,
+                    &format!(
+                        "{} needs a field, e.g. {}(metrics.cpu_usage_percent)",
+                        function.text, function.text
+                    ),
+                ))
+            }
+            (_, Some(field)) => {
+                Some(Field::lookup(event_source, &field.text).ok_or_else(|| {
+                    self.error(
+                        field.span,
+                        &format!(
+                            "unknown field '{}' on {} events",
+                            field.text,
+                            event_source.describe()
+                        ),
+                    )
+                })?)
+            }
+        };
+
+        let result_type = match (aggregate_fn, field.map(Field::field_type)) {
+            (AggregateFn::Count, _) => ValueType::Number,
+            (AggregateFn::Last, Some(ty)) => ty,
+            (AggregateFn::Min | AggregateFn::Max, Some(ty)) if ty.is_ordered() => ty,
+            (AggregateFn::Avg | AggregateFn::Sum, Some(ValueType::Number)) => ValueType::Number,
+            (_, ty) => {
+                let found = ty.map_or("nothing", ValueType::describe);
+                return Err(self.error(
+                    span,
+                    &format!("{} cannot aggregate {}", function.text, found),
+                ));
+            }
+        };
+
+        let filter = match filter {
+            Some(filter) => {
+                self.scope = Some(event_source);
+                let checked = self.expect_bool(filter, "a 'where' filter");
+                self.scope = None;
+                Some(checked?)
+            }
+            None => None,
+        };
+
+        self.max_window_seconds = self.max_window_seconds.max(self.window_seconds);
+        let index = filter.as_ref().and_then(index_hint);
+        Ok((
+            Typed::Aggregate(Box::new(Aggregate {
+                function: aggregate_fn,
+                source: event_source,
+                field,
+                filter,
+                window_seconds: self.window_seconds,
+                index,
+            })),
+            result_type,
+        ))
+    }
+}
+
+/// Find a `process == "..."` or `subsystem == "..."` conjunct in a log filter
+fn index_hint(filter: &Typed) -> Option<IndexHint> {
+    match filter {
+        Typed::And(lhs, rhs) => index_hint(lhs).or_else(|| index_hint(rhs)),
+        Typed::Compare(CompareOp::Eq, lhs, rhs) => match (lhs.as_ref(), rhs.as_ref()) {
+            (Typed::Field(field), Typed::Literal(Literal::Str(value)))
+            | (Typed::Literal(Literal::Str(value)), Typed::Field(field)) => match field {
+                Field::Log(LogField::Process) => Some(IndexHint::Process(value.clone())),
+                Field::Log(LogField::Subsystem) => Some(IndexHint::Subsystem(value.clone())),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn message_type(name: &str) -> Option<MessageType> {
+    match name.to_ascii_lowercase().as_str() {
+        "error" => Some(MessageType::Error),
+        "fault" => Some(MessageType::Fault),
+        "info" => Some(MessageType::Info),
+        "debug" => Some(MessageType::Debug),
+        _ => None,
+    }
+}
+
+fn memory_pressure(name: &str) -> Option<MemoryPressure> {
+    match name.to_ascii_lowercase().as_str() {
+        "normal" => Some(MemoryPressure::Normal),
+        "warning" => Some(MemoryPressure::Warning),
+        "critical" => Some(MemoryPressure::Critical),
+        "unknown" => Some(MemoryPressure::Unknown),
+        _ => None,
+    }
+}
@@ -0,0 +1,218 @@
+//! Evaluation of type-checked expressions over an event snapshot
+
+use super::checker::{Aggregate, AggregateFn, Field, IndexHint, Literal, LogField, Source, Typed};
+use super::parser::CompareOp;
+use crate::aggregator::EventSnapshot;
+use crate::events::{
+    DiskEvent, LogEvent, MeasurementKind, MemoryPressure, MessageType, MetricsEvent, Timestamp,
+};
+use chrono::Duration;
+use std::cmp::Ordering;
+
+/// Runtime value; missing measurements are represented by `None` instead
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Value<'a> {
+    Bool(bool),
+    Number(f64),
+    Str(&'a str),
+    MessageType(MessageType),
+    MemoryPressure(MemoryPressure),
+}
+
+impl Value<'_> {
+    fn compare(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Bool(a), Self::Bool(b)) => Some(a.cmp(b)),
+            (Self::Number(a), Self::Number(b)) => a.partial_cmp(b),
+            (Self::Str(a), Self::Str(b)) => Some(a.cmp(b)),
+            // Message types are unordered; the checker only allows == and != on them
+            (Self::MessageType(a), Self::MessageType(b)) => Some((*a as u8).cmp(&(*b as u8))),
+            (Self::MemoryPressure(a), Self::MemoryPressure(b)) => Some(a.cmp(b)),
+            _ => None,
+        }
+    }
+}
+
+/// One event from any of the snapshot's streams
+#[derive(Clone, Copy)]
+pub(super) enum Event<'a> {
+    Log(&'a LogEvent),
+    Metrics(&'a MetricsEvent),
+    Disk(&'a DiskEvent),
+}
+
+impl<'a> Event<'a> {
+    fn timestamp(&self) -> Timestamp {
+        match self {
+            Self::Log(event) => event.timestamp,
+            Self::Metrics(event) => event.timestamp,
+            Self::Disk(event) => event.timestamp,
+        }
+    }
+
+    fn field(&self, field: Field) -> Option<Value<'a>> {
+        match (*self, field) {
+            (Self::Log(event), Field::Log(field)) => Some(match field {
+                LogField::Process => Value::Str(&event.process),
+                LogField::Subsystem => Value::Str(&event.subsystem),
+                LogField::Category => Value::Str(&event.category),
+                LogField::Message => Value::Str(&event.message),
+                LogField::MessageType => Value::MessageType(event.message_type),
+                LogField::ProcessId => Value::Number(event.process_id as f64),
+            }),
+            (Self::Metrics(event), Field::Metric(field)) => field.value(event).map(Value::Number),
+            (Self::Metrics(event), Field::MemoryPressure) => (event.provenance.memory_pressure
+                == MeasurementKind::Measured)
+                .then_some(Value::MemoryPressure(event.memory_pressure)),
+            (Self::Disk(event), Field::Disk(field)) => Some(Value::Number(field.value(event))),
+            (Self::Disk(event), Field::DiskName) => Some(Value::Str(&event.disk_name)),
+            (Self::Disk(event), Field::FilesystemPath) => {
+                event.filesystem_path.as_deref().map(Value::Str)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Aggregate {
+    /// Events in this aggregate's window that pass its filter
+    pub(super) fn matches<'s, 'a: 's>(
+        &'s self,
+        events: &EventSnapshot<'a>,
+        now: Timestamp,
+    ) -> Box<dyn Iterator<Item = Event<'a>> + 's> {
+        let cutoff = now - Duration::seconds(self.window_seconds);
+        let candidates: Box<dyn Iterator<Item = Event<'a>> + 's> = match self.source {
+            Source::Log => {
+                let logs = events.logs();
+                let logs: Box<dyn Iterator<Item = &'a LogEvent> + 's> = match &self.index {
+                    Some(IndexHint::Process(process)) => logs.for_process(process),
+                    Some(IndexHint::Subsystem(subsystem)) => logs.for_subsystem(subsystem),
+                    None => Box::new(logs.iter()),
+                };
+                Box::new(logs.map(Event::Log))
+            }
+            Source::Metrics => Box::new(events.metrics().iter().map(Event::Metrics)),
+            Source::Disk => Box::new(events.disk().iter().map(Event::Disk)),
+        };
+
+        Box::new(candidates.filter(move |event| {
+            event.timestamp() >= cutoff
+                && self.filter.as_ref().is_none_or(|filter| {
+                    matches!(
+                        filter.eval(None, Some(*event), now),
+                        Some(Value::Bool(true))
+                    )
+                })
+        }))
+    }
+
+    fn eval<'a>(&'a self, events: &EventSnapshot<'a>, now: Timestamp) -> Option<Value<'a>> {
+        let matches = self.matches(events, now);
+        let Some(field) = self.field else {
+            return Some(Value::Number(matches.count() as f64));
+        };
+
+        let values = matches.filter_map(|event| event.field(field));
+        match self.function {
+            AggregateFn::Count => unreachable!("count has no field"),
+            AggregateFn::Last => values.last(),
+            AggregateFn::Min => values.reduce(|a, b| match b.compare(&a) {
+                Some(Ordering::Less) => b,
+                _ => a,
+            }),
+            AggregateFn::Max => values.reduce(|a, b| match b.compare(&a) {
+                Some(Ordering::Greater) => b,
+                _ => a,
+            }),
+            AggregateFn::Sum | AggregateFn::Avg => {
+                let (sum, count) = values.fold((0.0, 0usize), |(sum, count), value| match value {
+                    Value::Number(number) => (sum + number, count + 1),
+                    _ => (sum, count),
+                });
+                match self.function {
+                    AggregateFn::Sum => Some(Value::Number(sum)),
+                    _ => (count > 0).then(|| Value::Number(sum / count as f64)),
+                }
+            }
+        }
+    }
+}
+
+impl Typed {
+    /// Evaluate as a condition; missing values make comparisons false
+    pub(super) fn holds(&self, events: &EventSnapshot<'_>, now: Timestamp) -> bool {
+        matches!(self.eval(Some(events), None, now), Some(Value::Bool(true)))
+    }
+
+    /// Evaluate against the snapshot (outside filters) or one event (inside)
+    fn eval<'a>(
+        &'a self,
+        events: Option<&EventSnapshot<'a>>,
+        event: Option<Event<'a>>,
+        now: Timestamp,
+    ) -> Option<Value<'a>> {
+        match self {
+            Self::Literal(literal) => Some(match literal {
+                Literal::Bool(value) => Value::Bool(*value),
+                Literal::Number(value) => Value::Number(*value),
+                Literal::Str(value) => Value::Str(value),
+                Literal::MessageType(value) => Value::MessageType(*value),
+                Literal::MemoryPressure(value) => Value::MemoryPressure(*value),
+            }),
+            Self::Field(field) => event?.field(*field),
+            Self::Not(operand) => match operand.eval(events, event, now) {
+                Some(Value::Bool(value)) => Some(Value::Bool(!value)),
+                _ => Some(Value::Bool(false)),
+            },
+            Self::And(lhs, rhs) => Some(Value::Bool(
+                truthy(lhs.eval(events, event, now)) && truthy(rhs.eval(events, event, now)),
+            )),
+            Self::Or(lhs, rhs) => Some(Value::Bool(
+                truthy(lhs.eval(events, event, now)) || truthy(rhs.eval(events, event, now)),
+            )),
+            Self::Compare(op, lhs, rhs) => {
+                let ordering = match (lhs.eval(events, event, now), rhs.eval(events, event, now)) {
+                    (Some(lhs), Some(rhs)) => lhs.compare(&rhs),
+                    _ => None,
+                };
+                Some(Value::Bool(ordering.is_some_and(|ordering| match op {
+                    CompareOp::Eq => ordering == Ordering::Equal,
+                    CompareOp::Ne => ordering != Ordering::Equal,
+                    CompareOp::Lt => ordering == Ordering::Less,
+                    CompareOp::Le => ordering != Ordering::Greater,
+                    CompareOp::Gt => ordering == Ordering::Greater,
+                    CompareOp::Ge => ordering != Ordering::Less,
+                    CompareOp::Match | CompareOp::NotMatch => false,
+                })))
+            }
+            Self::Match {
+                negated,
+                value,
+                regex,
+            } => Some(Value::Bool(match value.eval(events, event, now) {
+                Some(Value::Str(text)) => regex.is_match(text) != *negated,
+                _ => false,
+            })),
+            Self::Aggregate(aggregate) => aggregate.eval(events?, now),
+        }
+    }
+
+    /// Visit every aggregate in the tree
+    pub(super) fn for_each_aggregate<'s>(&'s self, visit: &mut impl FnMut(&'s Aggregate)) {
+        match self {
+            Self::Literal(_) | Self::Field(_) => {}
+            Self::Not(operand) => operand.for_each_aggregate(visit),
+            Self::And(lhs, rhs) | Self::Or(lhs, rhs) | Self::Compare(_, lhs, rhs) => {
+                lhs.for_each_aggregate(visit);
+                rhs.for_each_aggregate(visit);
+            }
+            Self::Match { value, .. } => value.for_each_aggregate(visit),
+            Self::Aggregate(aggregate) => visit(aggregate),
+        }
+    }
+}
+
+fn truthy(value: Option<Value<'_>>) -> bool {
+    matches!(value, Some(Value::Bool(true)))
+}
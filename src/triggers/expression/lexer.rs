@@ -0,0 +1,223 @@
+//! Tokenizer for rule expressions
+
+use super::{syntax_error, Span};
+use crate::error::ExpressionError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum TokenKind {
+    Ident(String),
+    Str(String),
+    Number(f64),
+    /// A number with an `s`, `m` or `h` suffix, in seconds
+    Duration(i64),
+    LParen,
+    RParen,
+    Dot,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Match,
+    NotMatch,
+    End,
+}
+
+impl TokenKind {
+    /// How the token is named in error messages
+    pub(super) fn describe(&self) -> String {
+        match self {
+            Self::Ident(name) => format!("'{}'", name),
+            Self::Str(_) => "a string".to_string(),
+            Self::Number(_) => "a number".to_string(),
+            Self::Duration(_) => "a duration".to_string(),
+            Self::LParen => "'('".to_string(),
+            Self::RParen => "')'".to_string(),
+            Self::Dot => "'.'".to_string(),
+            Self::Eq => "'=='".to_string(),
+            Self::Ne => "'!='".to_string(),
+            Self::Lt => "'<'".to_string(),
+            Self::Le => "'<='".to_string(),
+            Self::Gt => "'>'".to_string(),
+            Self::Ge => "'>='".to_string(),
+            Self::Match => "'~'".to_string(),
+            Self::NotMatch => "'!~'".to_string(),
+            Self::End => "end of expression".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+/// Split `source` into tokens, ending with [`TokenKind::End`]
+pub(super) fn tokenize(source: &str) -> Result<Vec<Token>, ExpressionError> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let start = pos;
+        let c = bytes[pos];
+        let kind = match c {
+            b' ' | b'\t' | b'\r' | b'\n' => {
+                pos += 1;
+                continue;
+            }
+            b'(' => {
+                pos += 1;
+                TokenKind::LParen
+            }
+            b')' => {
+                pos += 1;
+                TokenKind::RParen
+            }
+            b'.' => {
+                pos += 1;
+                TokenKind::Dot
+            }
+            b'~' => {
+                pos += 1;
+                TokenKind::Match
+            }
+            b'=' | b'!' | b'<' | b'>' => {
+                let next = bytes.get(pos + 1).copied();
+                let (kind, len) = match (c, next) {
+                    (b'=', Some(b'=')) => (TokenKind::Eq, 2),
+                    (b'!', Some(b'=')) => (TokenKind::Ne, 2),
+                    (b'!', Some(b'~')) => (TokenKind::NotMatch, 2),
+                    (b'<', Some(b'=')) => (TokenKind::Le, 2),
+                    (b'>', Some(b'=')) => (TokenKind::Ge, 2),
+                    (b'<', _) => (TokenKind::Lt, 1),
+                    (b'>', _) => (TokenKind::Gt, 1),
+                    (b'=', _) => {
+                        return Err(syntax_error(
+                            source,
+                            Span::new(start, start + 1),
+                            "expected '==' for equality",
+                        ))
+                    }
+                    _ => {
+                        return Err(syntax_error(
+                            source,
+                            Span::new(start, start + 1),
+                            "expected '!=' or '!~'; use 'not' for negation",
+                        ))
+                    }
+                };
+                pos += len;
+                kind
+            }
+            b'"' => {
+                let (value, end) = string_literal(source, start)?;
+                pos = end;
+                TokenKind::Str(value)
+            }
+            b'0'..=b'9' => {
+                while pos < bytes.len() && (bytes[pos].is_ascii_digit() || bytes[pos] == b'.') {
+                    pos += 1;
+                }
+                let number = &source[start..pos];
+                let value = number.parse::<f64>().map_err(|_| {
+                    syntax_error(
+                        source,
+                        Span::new(start, pos),
+                        &format!("invalid number '{}'", number),
+                    )
+                })?;
+                let unit_start = pos;
+                while pos < bytes.len() && bytes[pos].is_ascii_alphabetic() {
+                    pos += 1;
+                }
+                match &source[unit_start..pos] {
+                    "" => TokenKind::Number(value),
+                    unit => {
+                        let scale = match unit {
+                            "s" => 1.0,
+                            "m" => 60.0,
+                            "h" => 3600.0,
+                            _ => {
+                                return Err(syntax_error(
+                                    source,
+                                    Span::new(unit_start, pos),
+                                    &format!("unknown duration unit '{}'; use s, m or h", unit),
+                                ))
+                            }
+                        };
+                        if value.fract() != 0.0 {
+                            return Err(syntax_error(
+                                source,
+                                Span::new(start, pos),
+                                "durations must be whole numbers",
+                            ));
+                        }
+                        TokenKind::Duration((value * scale) as i64)
+                    }
+                }
+            }
+            c if c.is_ascii_alphabetic() || c == b'_' => {
+                while pos < bytes.len()
+                    && (bytes[pos].is_ascii_alphanumeric() || bytes[pos] == b'_')
+                {
+                    pos += 1;
+                }
+                TokenKind::Ident(source[start..pos].to_string())
+            }
+            _ => {
+                let len = source[start..].chars().next().map_or(1, char::len_utf8);
+                return Err(syntax_error(
+                    source,
+                    Span::new(start, start + len),
+                    &format!("unexpected character '{}'", &source[start..start + len]),
+                ));
+            }
+        };
+        tokens.push(Token {
+            kind,
+            span: Span::new(start, pos),
+        });
+    }
+
+    tokens.push(Token {
+        kind: TokenKind::End,
+        span: Span::new(source.len(), source.len()),
+    });
+    Ok(tokens)
+}
+
+/// Read a double-quoted string starting at `start`, returning it and the end offset
+///
+/// Supports `\"`, `\\`, `\n` and `\t`; other escapes are kept verbatim so
+/// regex escapes such as `\d` need no doubling.
+fn string_literal(source: &str, start: usize) -> Result<(String, usize), ExpressionError> {
+    let mut value = String::new();
+    let mut chars = source[start + 1..].char_indices();
+
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '"' => return Ok((value, start + 1 + offset + 1)),
+            '\\' => match chars.next() {
+                Some((_, '"')) => value.push('"'),
+                Some((_, '\\')) => value.push('\\'),
+                Some((_, 'n')) => value.push('\n'),
+                Some((_, 't')) => value.push('\t'),
+                Some((_, other)) => {
+                    value.push('\\');
+                    value.push(other);
+                }
+                None => break,
+            },
+            c => value.push(c),
+        }
+    }
+
+    Err(syntax_error(
+        source,
+        Span::new(start, source.len()),
+        "unterminated string",
+    ))
+}
@@ -0,0 +1,360 @@
+//! Rule expression language
+//!
+//! Expressions combine aggregates over the log, metrics and disk streams into
+//! one condition, for example:
+//!
+//! ```text
+//! count(log where process == "kernel" and message ~ "I/O error") > 3 within 60s
+//!     and last(metrics.memory_pressure) >= Warning
+//! ```
+//!
+//! Source text is tokenized, parsed and then type-checked against the
+//! `LogEvent`, `MetricsEvent` and `DiskEvent` schemas, so unknown fields,
+//! mismatched types and invalid regexes are reported with their line and
+//! column before a rule is ever evaluated. See `docs/trigger-rules.md` for
+//! the full grammar.
+
+/// Type checking against the event schemas
+mod checker;
+/// Evaluation over event snapshots
+mod eval;
+/// Tokenizer
+mod lexer;
+/// Recursive-descent parser
+mod parser;
+
+use crate::aggregator::EventSnapshot;
+use crate::error::ExpressionError;
+use crate::events::{DiskEvent, LogEvent, MetricsEvent};
+use eval::Event;
+use std::collections::HashSet;
+
+/// Byte range of a token or node in the expression source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Span {
+    start: usize,
+    end: usize,
+}
+
+impl Span {
+    fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// Span covering `self` through `other`
+    fn to(self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+}
+
+/// 1-based line and character column of a byte offset
+fn position(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+fn syntax_error(source: &str, span: Span, message: &str) -> ExpressionError {
+    let (line, column) = position(source, span.start);
+    ExpressionError::Syntax {
+        message: message.to_string(),
+        offset: span.start,
+        line,
+        column,
+    }
+}
+
+fn type_error(source: &str, span: Span, message: &str) -> ExpressionError {
+    let (line, column) = position(source, span.start);
+    ExpressionError::Type {
+        message: message.to_string(),
+        offset: span.start,
+        line,
+        column,
+    }
+}
+
+/// A parsed and type-checked rule expression
+#[derive(Debug)]
+pub struct Expression {
+    source: String,
+    root: checker::Typed,
+    max_window_seconds: i64,
+}
+
+impl Expression {
+    /// Parse and type-check an expression
+    ///
+    /// Aggregates without an enclosing `within` use `default_window_seconds`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `ExpressionError` locating the first syntax or type error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use eyes::triggers::Expression;
+    ///
+    /// let expression = Expression::parse("count(log where message_type == fault) > 0", 60);
+    /// assert!(expression.is_ok());
+    ///
+    /// let error = Expression::parse("count(log where proces == \"kernel\") > 0", 60).unwrap_err();
+    /// assert_eq!((error.line(), error.column()), (1, 17));
+    /// ```
+    pub fn parse(source: &str, default_window_seconds: i64) -> Result<Self, ExpressionError> {
+        let tokens = lexer::tokenize(source)?;
+        let ast = parser::Parser::new(source, tokens).parse()?;
+        let mut checker = checker::Checker::new(source, default_window_seconds);
+        let root = checker.check_root(&ast)?;
+        Ok(Self {
+            source: source.to_string(),
+            root,
+            max_window_seconds: checker.max_window_seconds,
+        })
+    }
+
+    /// The expression's source text
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Longest window any aggregate reads, in seconds
+    pub fn max_window_seconds(&self) -> i64 {
+        self.max_window_seconds
+    }
+
+    /// Whether the condition holds for the snapshot
+    pub fn evaluate(&self, events: &EventSnapshot<'_>) -> bool {
+        self.root.holds(events, events.now())
+    }
+
+    /// Log events matched by the expression's log aggregates
+    pub fn relevant_logs<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a LogEvent> {
+        self.relevant(events, |event| match event {
+            Event::Log(event) => Some(event),
+            _ => None,
+        })
+    }
+
+    /// Metrics events matched by the expression's metrics aggregates
+    pub fn relevant_metrics<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a MetricsEvent> {
+        self.relevant(events, |event| match event {
+            Event::Metrics(event) => Some(event),
+            _ => None,
+        })
+    }
+
+    /// Disk events matched by the expression's disk aggregates
+    pub fn relevant_disk_events<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a DiskEvent> {
+        self.relevant(events, |event| match event {
+            Event::Disk(event) => Some(event),
+            _ => None,
+        })
+    }
+
+    /// Events of one kind matched by any aggregate, each listed once
+    fn relevant<'a, T>(
+        &self,
+        events: &EventSnapshot<'a>,
+        select: impl Fn(Event<'a>) -> Option<&'a T>,
+    ) -> Vec<&'a T> {
+        let now = events.now();
+        let mut seen = HashSet::new();
+        let mut relevant = Vec::new();
+        self.root.for_each_aggregate(&mut |aggregate| {
+            for event in aggregate.matches(events, now).filter_map(&select) {
+                if seen.insert(event as *const T) {
+                    relevant.push(event);
+                }
+            }
+        });
+        relevant
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{
+        MeasurementKind, MemoryPressure, MessageType, MetricsProvenance, MetricsSource,
+    };
+    use chrono::{Duration, Utc};
+
+    fn log_event(process: &str, message_type: MessageType, message: &str) -> LogEvent {
+        LogEvent {
+            timestamp: Utc::now(),
+            message_type,
+            subsystem: "com.apple.kernel".to_string(),
+            category: "default".to_string(),
+            process: process.to_string(),
+            process_id: 0,
+            message: message.to_string(),
+        }
+    }
+
+    fn metrics_event(memory_pressure: MemoryPressure, cpu_usage_percent: f64) -> MetricsEvent {
+        MetricsEvent {
+            timestamp: Utc::now(),
+            cpu_power_mw: 0.0,
+            cpu_usage_percent,
+            gpu_power_mw: None,
+            gpu_usage_percent: None,
+            memory_pressure,
+            memory_used_mb: 0.0,
+            energy_impact: 0.0,
+            provenance: MetricsProvenance {
+                source: MetricsSource::Procfs,
+                cpu_usage: MeasurementKind::Measured,
+                memory_pressure: MeasurementKind::Measured,
+                ..Default::default()
+            },
+            process_metrics: Vec::new(),
+        }
+    }
+
+    fn disk_event(disk_name: &str, read_kb_per_sec: f64) -> DiskEvent {
+        DiskEvent {
+            timestamp: Utc::now(),
+            read_kb_per_sec,
+            write_kb_per_sec: 0.0,
+            read_ops_per_sec: 0.0,
+            write_ops_per_sec: 0.0,
+            disk_name: disk_name.to_string(),
+            filesystem_path: None,
+        }
+    }
+
+    #[test]
+    fn test_composite_condition_across_logs_and_metrics() {
+        let expression = Expression::parse(
+            r#"count(log where process == "kernel" and message ~ "I/O error") > 3 within 60s
+               and last(metrics.memory_pressure) >= Warning"#,
+            60,
+        )
+        .unwrap();
+        let mut logs = (0..4)
+            .map(|i| {
+                log_event(
+                    "kernel",
+                    MessageType::Error,
+                    &format!("disk{}: I/O error", i),
+                )
+            })
+            .collect::<Vec<_>>();
+        logs.push(log_event("mds", MessageType::Error, "I/O error"));
+        let mut stale = log_event("kernel", MessageType::Error, "I/O error");
+        stale.timestamp -= Duration::seconds(120);
+        logs.insert(0, stale);
+        let normal = vec![metrics_event(MemoryPressure::Normal, 10.0)];
+        let warning = vec![
+            metrics_event(MemoryPressure::Critical, 10.0),
+            metrics_event(MemoryPressure::Warning, 10.0),
+        ];
+
+        assert!(!expression.evaluate(&EventSnapshot::from_slices(&logs, &normal, &[])));
+        let snapshot = EventSnapshot::from_slices(&logs, &warning, &[]);
+        assert!(expression.evaluate(&snapshot));
+        assert_eq!(expression.relevant_logs(&snapshot).len(), 4);
+        assert_eq!(expression.relevant_metrics(&snapshot).len(), 2);
+        assert!(!expression.evaluate(&EventSnapshot::from_slices(&logs[..4], &warning, &[])));
+    }
+
+    #[test]
+    fn test_aggregates_and_missing_values() {
+        let metrics = vec![
+            metrics_event(MemoryPressure::Normal, 20.0),
+            metrics_event(MemoryPressure::Normal, 80.0),
+        ];
+        let disk = vec![disk_event("disk0", 100.0), disk_event("disk1", 900.0)];
+        let snapshot = EventSnapshot::from_slices(&[], &metrics, &disk);
+        let holds = |source: &str| Expression::parse(source, 60).unwrap().evaluate(&snapshot);
+
+        assert!(holds("avg(metrics.cpu_usage_percent) == 50"));
+        assert!(holds(
+            "min(metrics.cpu_usage_percent) < 30 and max(metrics.cpu_usage_percent) > 70"
+        ));
+        assert!(holds(
+            "sum(disk.read_kb_per_sec where disk_name != \"disk0\") >= 900"
+        ));
+        assert!(holds("not count(log) > 0"));
+        assert!(holds(
+            "count(metrics where memory_pressure != Warning) == 2"
+        ));
+        // GPU power was never measured, so every comparison with it is false
+        assert!(!holds(
+            "last(metrics.gpu_power_mw) > 0 or last(metrics.gpu_power_mw) <= 0"
+        ));
+    }
+
+    #[test]
+    fn test_errors_report_line_and_column() {
+        let cases = [
+            ("count(log) > ", 1, 14, "expected a value"),
+            ("count(log where process = \"x\") > 1", 1, 25, "'=='"),
+            (
+                "count(log where proces == \"x\") > 1",
+                1,
+                17,
+                "unknown field 'proces' on log events",
+            ),
+            (
+                "count(log)\n  > 1 and\n  last(metrics.memory_pressure) >= Severe",
+                3,
+                36,
+                "not a memory pressure level",
+            ),
+            (
+                "count(log where message ~ \"(\") > 0",
+                1,
+                27,
+                "invalid regex",
+            ),
+            (
+                "avg(log.message) > 1",
+                1,
+                1,
+                "avg cannot aggregate a string",
+            ),
+            (
+                "count(log) > \"three\"",
+                1,
+                14,
+                "cannot compare a number with a string",
+            ),
+            (
+                "metrics.cpu_usage_percent > 90",
+                1,
+                1,
+                "must be wrapped in an aggregate",
+            ),
+            (
+                "count(log where message ~ \"x\") within 0s",
+                1,
+                39,
+                "at least 1s",
+            ),
+            ("count(log)", 1, 1, "must be a condition"),
+            ("count(kernel) > 1", 1, 7, "unknown event source"),
+            (
+                "count(log where message_type == \"panic\") > 0",
+                1,
+                33,
+                "not a message type",
+            ),
+        ];
+
+        for (source, line, column, message) in cases {
+            let error = Expression::parse(source, 60).unwrap_err();
+            assert_eq!(
+                (error.line(), error.column()),
+                (line, column),
+                "{}: {}",
+                source,
+                error
+            );
+            assert!(error.to_string().contains(message), "{}: {}", source, error);
+        }
+    }
+}
@@ -0,0 +1,345 @@
+//! Recursive-descent parser producing an untyped syntax tree
+//!
+//! ```text
+//! expr       := or
+//! or         := and ("or" and)*
+//! and        := unary ("and" unary)*
+//! unary      := "not" unary | comparison
+//! comparison := operand (cmp_op operand)? ("within" DURATION)?
+//! operand    := "(" expr ")" | NUMBER | STRING | "true" | "false"
+//!             | IDENT "(" IDENT ("." IDENT)? ("where" expr)? ")"
+//!             | IDENT ("." IDENT)?
+//! cmp_op     := "==" | "!=" | "<" | "<=" | ">" | ">=" | "~" | "!~"
+//! ```
+
+use super::lexer::{Token, TokenKind};
+use super::{syntax_error, Span};
+use crate::error::ExpressionError;
+
+const KEYWORDS: [&str; 7] = ["and", "or", "not", "where", "within", "true", "false"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Match,
+    NotMatch,
+}
+
+impl CompareOp {
+    fn from_token(kind: &TokenKind) -> Option<Self> {
+        Some(match kind {
+            TokenKind::Eq => Self::Eq,
+            TokenKind::Ne => Self::Ne,
+            TokenKind::Lt => Self::Lt,
+            TokenKind::Le => Self::Le,
+            TokenKind::Gt => Self::Gt,
+            TokenKind::Ge => Self::Ge,
+            TokenKind::Match => Self::Match,
+            TokenKind::NotMatch => Self::NotMatch,
+            _ => return None,
+        })
+    }
+
+    pub(super) fn symbol(self) -> &'static str {
+        match self {
+            Self::Eq => "==",
+            Self::Ne => "!=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Match => "~",
+            Self::NotMatch => "!~",
+        }
+    }
+}
+
+/// An identifier with its location
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Name {
+    pub text: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Ast {
+    Bool(bool),
+    Number(f64),
+    Str(String),
+    /// A bare identifier: a field inside `where`, or an enum value
+    Ident(String),
+    /// `source.field`
+    Qualified {
+        source: Name,
+        field: Name,
+    },
+    Not(Box<Node>),
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    Compare {
+        op: CompareOp,
+        lhs: Box<Node>,
+        rhs: Box<Node>,
+    },
+    Call {
+        function: Name,
+        source: Name,
+        field: Option<Name>,
+        filter: Option<Box<Node>>,
+    },
+    Within {
+        inner: Box<Node>,
+        seconds: i64,
+        duration_span: Span,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Node {
+    pub ast: Ast,
+    pub span: Span,
+}
+
+impl Node {
+    fn new(ast: Ast, span: Span) -> Self {
+        Self { ast, span }
+    }
+}
+
+pub(super) struct Parser<'s> {
+    source: &'s str,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl<'s> Parser<'s> {
+    pub(super) fn new(source: &'s str, tokens: Vec<Token>) -> Self {
+        Self {
+            source,
+            tokens,
+            pos: 0,
+        }
+    }
+
+    /// Parse the whole token stream as one expression
+    pub(super) fn parse(mut self) -> Result<Node, ExpressionError> {
+        if self.peek().kind == TokenKind::End {
+            return Err(self.error_here("expected an expression"));
+        }
+        let node = self.or()?;
+        if self.peek().kind != TokenKind::End {
+            let found = self.peek().kind.describe();
+            return Err(self.error_here(&format!(
+                "expected 'and', 'or' or end of expression, found {}",
+                found
+            )));
+        }
+        Ok(node)
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if token.kind != TokenKind::End {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn at_keyword(&self, keyword: &str) -> bool {
+        matches!(&self.peek().kind, TokenKind::Ident(name) if name == keyword)
+    }
+
+    fn error_here(&self, message: &str) -> ExpressionError {
+        syntax_error(self.source, self.peek().span, message)
+    }
+
+    fn expect(&mut self, kind: TokenKind, what: &str) -> Result<Token, ExpressionError> {
+        if self.peek().kind == kind {
+            return Ok(self.advance());
+        }
+        let found = self.peek().kind.describe();
+        Err(self.error_here(&format!("expected {}, found {}", what, found)))
+    }
+
+    fn name(&mut self, what: &str) -> Result<Name, ExpressionError> {
+        match &self.peek().kind {
+            TokenKind::Ident(text) if !KEYWORDS.contains(&text.as_str()) => {
+                let text = text.clone();
+                let span = self.advance().span;
+                Ok(Name { text, span })
+            }
+            kind => {
+                let found = kind.describe();
+                Err(self.error_here(&format!("expected {}, found {}", what, found)))
+            }
+        }
+    }
+
+    fn or(&mut self) -> Result<Node, ExpressionError> {
+        let mut lhs = self.and()?;
+        while self.at_keyword("or") {
+            self.advance();
+            let rhs = self.and()?;
+            let span = lhs.span.to(rhs.span);
+            lhs = Node::new(Ast::Or(Box::new(lhs), Box::new(rhs)), span);
+        }
+        Ok(lhs)
+    }
+
+    fn and(&mut self) -> Result<Node, ExpressionError> {
+        let mut lhs = self.unary()?;
+        while self.at_keyword("and") {
+            self.advance();
+            let rhs = self.unary()?;
+            let span = lhs.span.to(rhs.span);
+            lhs = Node::new(Ast::And(Box::new(lhs), Box::new(rhs)), span);
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Node, ExpressionError> {
+        if self.at_keyword("not") {
+            let start = self.advance().span;
+            let operand = self.unary()?;
+            let span = start.to(operand.span);
+            return Ok(Node::new(Ast::Not(Box::new(operand)), span));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Node, ExpressionError> {
+        let mut node = self.operand()?;
+
+        if let Some(op) = CompareOp::from_token(&self.peek().kind) {
+            self.advance();
+            let rhs = self.operand()?;
+            let span = node.span.to(rhs.span);
+            node = Node::new(
+                Ast::Compare {
+                    op,
+                    lhs: Box::new(node),
+                    rhs: Box::new(rhs),
+                },
+                span,
+            );
+        }
+
+        if self.at_keyword("within") {
+            self.advance();
+            let token = self.peek().clone();
+            let TokenKind::Duration(seconds) = token.kind else {
+                let found = token.kind.describe();
+                return Err(self.error_here(&format!(
+                    "expected a duration such as 60s, 5m or 1h after 'within', found {}",
+                    found
+                )));
+            };
+            self.advance();
+            if seconds <= 0 {
+                return Err(syntax_error(
+                    self.source,
+                    token.span,
+                    "window must be at least 1s",
+                ));
+            }
+            let span = node.span.to(token.span);
+            node = Node::new(
+                Ast::Within {
+                    inner: Box::new(node),
+                    seconds,
+                    duration_span: token.span,
+                },
+                span,
+            );
+        }
+
+        Ok(node)
+    }
+
+    fn operand(&mut self) -> Result<Node, ExpressionError> {
+        let token = self.peek().clone();
+        match token.kind {
+            TokenKind::LParen => {
+                self.advance();
+                let inner = self.or()?;
+                let close = self.expect(TokenKind::RParen, "')'")?;
+                Ok(Node::new(inner.ast, token.span.to(close.span)))
+            }
+            TokenKind::Number(value) => {
+                self.advance();
+                Ok(Node::new(Ast::Number(value), token.span))
+            }
+            TokenKind::Str(value) => {
+                self.advance();
+                Ok(Node::new(Ast::Str(value), token.span))
+            }
+            TokenKind::Duration(_) => {
+                Err(self.error_here("durations are only allowed after 'within'"))
+            }
+            TokenKind::Ident(ref text) if text == "true" || text == "false" => {
+                self.advance();
+                Ok(Node::new(Ast::Bool(text == "true"), token.span))
+            }
+            TokenKind::Ident(_) => {
+                let name = self.name("a field, function or value")?;
+                match self.peek().kind {
+                    TokenKind::LParen => self.call(name),
+                    TokenKind::Dot => {
+                        self.advance();
+                        let field = self.name("a field name after '.'")?;
+                        let span = name.span.to(field.span);
+                        Ok(Node::new(
+                            Ast::Qualified {
+                                source: name,
+                                field,
+                            },
+                            span,
+                        ))
+                    }
+                    _ => Ok(Node::new(Ast::Ident(name.text), name.span)),
+                }
+            }
+            ref kind => {
+                let found = kind.describe();
+                Err(self.error_here(&format!("expected a value, found {}", found)))
+            }
+        }
+    }
+
+    fn call(&mut self, function: Name) -> Result<Node, ExpressionError> {
+        self.expect(TokenKind::LParen, "'('")?;
+        let source = self.name("an event source (log, metrics or disk)")?;
+        let field = if self.peek().kind == TokenKind::Dot {
+            self.advance();
+            Some(self.name("a field name after '.'")?)
+        } else {
+            None
+        };
+        let filter = if self.at_keyword("where") {
+            self.advance();
+            Some(Box::new(self.or()?))
+        } else {
+            None
+        };
+        let close = self.expect(TokenKind::RParen, "')' or 'where'")?;
+        let span = function.span.to(close.span);
+        Ok(Node::new(
+            Ast::Call {
+                function,
+                source,
+                field,
+                filter,
+            },
+            span,
+        ))
+    }
+}
@@ -0,0 +1,301 @@
+//! Silence detection for expected log sources
+//!
+//! Some daemons log periodically, and when they hang their silence is the only
+//! symptom. A `HeartbeatRule` is compiled from each `[[triggers.heartbeats]]`
+//! entry and fires when its source logs fewer heartbeats than expected within
+//! the configured gap.
+
+use crate::aggregator::EventSnapshot;
+use crate::config::HeartbeatConfig;
+use crate::error::ConfigError;
+use crate::events::{LogEvent, Severity, Timestamp};
+use crate::triggers::{RelevantLogGroup, TriggerRule};
+use chrono::Duration;
+use regex::Regex;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+#[derive(Debug, Default)]
+struct HeartbeatState {
+    /// First evaluation; a source is not judged until a full gap after it
+    started: Option<Timestamp>,
+    /// Latest heartbeats, oldest first, at most `min_count` of them
+    recent: VecDeque<Timestamp>,
+    /// Newest log event already observed
+    logs_seen: Option<Timestamp>,
+}
+
+/// Trigger rule for a log source that has gone quiet
+///
+/// The rule remembers the latest heartbeats itself, so the gap may be far
+/// longer than the event buffer.
+pub struct HeartbeatRule {
+    name: String,
+    process: Option<String>,
+    subsystem: Option<String>,
+    message: Option<Regex>,
+    /// Longest expected gap between heartbeats
+    pub max_gap: Duration,
+    /// Heartbeats expected within each gap
+    pub min_count: usize,
+    /// Severity level to assign when this rule triggers
+    pub severity: Severity,
+    state: Mutex<HeartbeatState>,
+}
+
+impl HeartbeatRule {
+    /// Compile a `[[triggers.heartbeats]]` entry
+    ///
+    /// Returns `ConfigError::ValidationError` naming the entry if it has no
+    /// name, names neither a process nor a subsystem, has an invalid message
+    /// regex, or has a zero gap or count.
+    pub fn compile(config: &HeartbeatConfig) -> Result<Self, ConfigError> {
+        let name = config.name.trim();
+        if name.is_empty() {
+            return Err(ConfigError::ValidationError(
+                "triggers.heartbeats name cannot be empty".to_string(),
+            ));
+        }
+        let invalid = |message: &str| {
+            ConfigError::ValidationError(format!("triggers.heartbeats '{}' {}", name, message))
+        };
+        if config.process.is_none() && config.subsystem.is_none() {
+            return Err(invalid("needs a process or a subsystem"));
+        }
+        if config.max_gap_seconds == 0 {
+            return Err(invalid("max_gap_seconds must be at least 1"));
+        }
+        if config.min_count == 0 {
+            return Err(invalid("min_count must be at least 1"));
+        }
+        let message = config
+            .message
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .map_err(|e| invalid(&format!("message regex is invalid: {}", e)))?;
+
+        Ok(Self {
+            name: name.to_string(),
+            process: config.process.clone(),
+            subsystem: config.subsystem.clone(),
+            message,
+            max_gap: Duration::seconds(config.max_gap_seconds as i64),
+            min_count: config.min_count,
+            severity: config.severity,
+            state: Mutex::new(HeartbeatState::default()),
+        })
+    }
+
+    /// The source's log events in the snapshot, heartbeat or not
+    fn source_logs<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a LogEvent> {
+        let logs = events.logs();
+        let candidates: Box<dyn Iterator<Item = &'a LogEvent> + '_> =
+            match (&self.process, &self.subsystem) {
+                (Some(process), _) => logs.for_process(process),
+                (None, Some(subsystem)) => logs.for_subsystem(subsystem),
+                (None, None) => Box::new(logs.iter()),
+            };
+        candidates
+            .filter(|event| {
+                self.subsystem
+                    .as_ref()
+                    .is_none_or(|subsystem| event.subsystem == *subsystem)
+            })
+            .collect()
+    }
+
+    fn is_heartbeat(&self, event: &LogEvent) -> bool {
+        self.message
+            .as_ref()
+            .is_none_or(|regex| regex.is_match(&event.message))
+    }
+
+    /// Fold heartbeats newer than the last evaluation into the history
+    fn ingest(&self, state: &mut HeartbeatState, events: &EventSnapshot<'_>) {
+        let seen = state.logs_seen;
+        for event in self.source_logs(events) {
+            if seen.is_none_or(|seen| event.timestamp > seen) {
+                state.logs_seen = state.logs_seen.max(Some(event.timestamp));
+                if self.is_heartbeat(event) {
+                    state.recent.push_back(event.timestamp);
+                }
+            }
+        }
+        state.recent.make_contiguous().sort();
+        while state.recent.len() > self.min_count {
+            state.recent.pop_front();
+        }
+    }
+
+    /// Heartbeats within the gap ending at `now`, or `None` while the source
+    /// is not yet judged
+    fn heartbeats_in_gap(&self, state: &HeartbeatState, now: Timestamp) -> Option<usize> {
+        let cutoff = now - self.max_gap;
+        state.started.filter(|started| *started <= cutoff)?;
+        Some(state.recent.iter().filter(|at| **at >= cutoff).count())
+    }
+
+    fn evaluate_at(&self, events: &EventSnapshot<'_>, now: Timestamp) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.started.get_or_insert(now);
+        self.ingest(&mut state, events);
+        self.heartbeats_in_gap(&state, now)
+            .is_some_and(|count| count < self.min_count)
+    }
+
+    /// The process and subsystem the source is made of
+    fn source(&self) -> String {
+        match (&self.process, &self.subsystem) {
+            (Some(process), Some(subsystem)) => format!("{} ({})", process, subsystem),
+            (Some(source), None) | (None, Some(source)) => source.clone(),
+            (None, None) => String::new(),
+        }
+    }
+
+    fn describe(&self, now: Timestamp) -> String {
+        let state = self.state.lock().unwrap();
+        let count = self.heartbeats_in_gap(&state, now).unwrap_or_default();
+        let mut description = format!(
+            "{} logged {} of {} expected heartbeats",
+            self.source(),
+            count,
+            self.min_count
+        );
+        if let Some(regex) = &self.message {
+            description.push_str(&format!(" matching /{}/", regex.as_str()));
+        }
+        description.push_str(&format!(" in the last {}s", self.max_gap.num_seconds()));
+        match (state.recent.back(), state.started) {
+            (Some(last), _) => description.push_str(&format!(
+                "; last at {} ({}s ago)",
+                last.format("%Y-%m-%d %H:%M:%S UTC"),
+                (now - *last).num_seconds()
+            )),
+            (None, Some(started)) => description.push_str(&format!(
+                "; none since monitoring started at {}",
+                started.format("%Y-%m-%d %H:%M:%S UTC")
+            )),
+            (None, None) => {}
+        }
+        description
+    }
+}
+
+impl TriggerRule for HeartbeatRule {
+    fn evaluate(&self, events: &EventSnapshot<'_>) -> bool {
+        self.evaluate_at(events, events.now())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Whatever the source still logged besides heartbeats
+    fn relevant_logs<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a LogEvent> {
+        self.source_logs(events)
+    }
+
+    fn relevant_log_groups<'a>(&self, events: &EventSnapshot<'a>) -> Vec<RelevantLogGroup<'a>> {
+        vec![RelevantLogGroup {
+            source: Some(self.source()),
+            events: self.relevant_logs(events),
+        }]
+    }
+
+    fn trigger_reason(&self, events: &EventSnapshot<'_>, _source: Option<&str>) -> String {
+        format!(
+            "Rule '{}' triggered: {}",
+            self.name,
+            self.describe(events.now())
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::MessageType;
+    use chrono::Utc;
+
+    fn log_event(process: &str, message: &str, at: Timestamp) -> LogEvent {
+        LogEvent {
+            timestamp: at,
+            message_type: MessageType::Info,
+            subsystem: "com.example.backup".to_string(),
+            category: "default".to_string(),
+            process: process.to_string(),
+            process_id: 42,
+            message: message.to_string(),
+        }
+    }
+
+    fn heartbeat(message: Option<&str>, max_gap_seconds: u64, min_count: usize) -> HeartbeatRule {
+        HeartbeatRule::compile(&HeartbeatConfig {
+            name: "backupd heartbeat".to_string(),
+            process: Some("backupd".to_string()),
+            subsystem: None,
+            message: message.map(str::to_string),
+            max_gap_seconds,
+            min_count,
+            severity: Severity::Warning,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_fires_when_heartbeat_stops() {
+        let rule = heartbeat(Some("^Backup completed"), 600, 1);
+        let start = Utc::now() - Duration::hours(1);
+        let at = |seconds| start + Duration::seconds(seconds);
+        let empty = EventSnapshot::from_slices(&[], &[], &[]);
+
+        // Nothing is judged until a full gap has passed
+        assert!(!rule.evaluate_at(&empty, at(0)));
+        assert!(!rule.evaluate_at(&empty, at(599)));
+        assert!(rule.evaluate_at(&empty, at(601)));
+        assert!(rule
+            .describe(at(601))
+            .contains("none since monitoring started at"));
+
+        // Other messages from the process are not heartbeats
+        let logs = vec![
+            log_event("backupd", "Backup completed in 41s", at(610)),
+            log_event("backupd", "Starting backup", at(1200)),
+        ];
+        assert!(!rule.evaluate_at(&EventSnapshot::from_slices(&logs, &[], &[]), at(1200)));
+        assert!(rule.evaluate_at(&empty, at(1211)));
+        assert_eq!(
+            rule.describe(at(1211)),
+            format!(
+                "backupd logged 0 of 1 expected heartbeats matching /^Backup completed/ in the last 600s; last at {} (601s ago)",
+                at(610).format("%Y-%m-%d %H:%M:%S UTC")
+            )
+        );
+    }
+
+    #[test]
+    fn test_minimum_rate_over_gap() {
+        let rule = heartbeat(None, 60, 3);
+        let start = Utc::now() - Duration::hours(1);
+        let at = |seconds| start + Duration::seconds(seconds);
+        rule.evaluate_at(&EventSnapshot::from_slices(&[], &[], &[]), at(0));
+
+        let logs = (0..6)
+            .map(|tick| log_event("backupd", "tick", at(40 + tick * 20)))
+            .collect::<Vec<_>>();
+        assert!(!rule.evaluate_at(&EventSnapshot::from_slices(&logs, &[], &[]), at(140)));
+
+        // The rate halves: two events in the next minute are too few
+        let slower = vec![
+            log_event("backupd", "tick", at(180)),
+            log_event("backupd", "tick", at(210)),
+        ];
+        assert!(rule.evaluate_at(&EventSnapshot::from_slices(&slower, &[], &[]), at(230)));
+        assert_eq!(rule.state.lock().unwrap().recent.len(), 3);
+    }
+}
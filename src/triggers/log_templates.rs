@@ -0,0 +1,374 @@
+//! Alerts on novel log templates and template rate jumps
+//!
+//! `LogTemplateRule` reads the templates the aggregator mines from the log
+//! stream. It fires when a process logs an error or fault whose template has
+//! never produced one before, and when a long-known template suddenly
+//! arrives far faster than its lifetime average. Because templates are saved
+//! with the aggregator state, "never before" spans restarts; after a restore
+//! the learning period starts again, since templates mined after the last
+//! save may be missing.
+
+use crate::aggregator::{EventSnapshot, LogTemplate, LogTemplates};
+use crate::config::TemplatesConfig;
+use crate::events::{LogEvent, MessageType, Severity, Timestamp};
+use crate::triggers::{RelevantLogGroup, TriggerRule};
+use chrono::Duration;
+use std::collections::BTreeMap;
+
+/// Why a template was reported
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Finding {
+    /// First error or fault from the template
+    NewErrorTemplate,
+    /// Matches in the window against the count expected from the lifetime rate
+    RateJump { recent: u64, expected: f64 },
+}
+
+/// A reported template and its events in the window
+struct TemplateFinding<'a> {
+    template: &'a LogTemplate,
+    finding: Finding,
+    events: Vec<&'a LogEvent>,
+}
+
+impl TemplateFinding<'_> {
+    fn describe(&self) -> String {
+        match self.finding {
+            Finding::NewErrorTemplate => format!(
+                "new error template from {}: \"{}\" ({} in window)",
+                self.template.process,
+                self.template.text(),
+                self.events.len()
+            ),
+            Finding::RateJump { recent, expected } => format!(
+                "{} template \"{}\" matched {} times against {:.1} expected from its rate since {}",
+                self.template.process,
+                self.template.text(),
+                recent,
+                expected,
+                self.template.first_seen.format("%Y-%m-%d %H:%M:%S UTC")
+            ),
+        }
+    }
+}
+
+/// Trigger rule for never-before-seen error templates and template rate jumps
+///
+/// Only snapshots taken from the aggregator carry templates; on other
+/// snapshots the rule never fires.
+pub struct LogTemplateRule {
+    /// Window in which new templates and rates are checked
+    pub window: Duration,
+    /// Time after mining starts before templates count as new, and minimum
+    /// template age before its rate is compared
+    pub learning: Duration,
+    /// Multiple of the lifetime rate that counts as a jump
+    pub rate_factor: f64,
+    /// Matches in the window required before a rate jump fires
+    pub min_count: u64,
+    /// Severity level to assign when this rule triggers
+    pub severity: Severity,
+}
+
+impl LogTemplateRule {
+    /// Create a template rule from `[triggers.templates]`
+    pub fn new(config: &TemplatesConfig) -> Self {
+        Self {
+            window: Duration::seconds(config.window_seconds as i64),
+            learning: Duration::seconds(config.learning_seconds as i64),
+            rate_factor: config.rate_factor,
+            min_count: config.min_count,
+            severity: config.severity,
+        }
+    }
+
+    /// Create a template rule with the default configuration
+    pub fn with_defaults() -> Self {
+        Self::new(&TemplatesConfig::default())
+    }
+
+    fn findings<'a>(&self, events: &EventSnapshot<'a>) -> Vec<TemplateFinding<'a>> {
+        let Some(learning_since) = events.templates().and_then(LogTemplates::learning_since) else {
+            return Vec::new();
+        };
+        let cutoff = events.now() - self.window;
+
+        let mut recent = BTreeMap::<u64, (&LogTemplate, Vec<&LogEvent>)>::new();
+        for event in events
+            .logs()
+            .iter()
+            .filter(|event| event.timestamp >= cutoff)
+        {
+            if let Some(template) = events.template_of(event) {
+                recent
+                    .entry(template.id)
+                    .or_insert_with(|| (template, Vec::new()))
+                    .1
+                    .push(event);
+            }
+        }
+
+        recent
+            .into_values()
+            .filter_map(|(template, events)| {
+                let finding = self.classify(template, &events, learning_since, cutoff)?;
+                Some(TemplateFinding {
+                    template,
+                    finding,
+                    events,
+                })
+            })
+            .collect()
+    }
+
+    fn classify(
+        &self,
+        template: &LogTemplate,
+        events: &[&LogEvent],
+        learning_since: Timestamp,
+        cutoff: Timestamp,
+    ) -> Option<Finding> {
+        let is_new_error = template
+            .first_error_seen
+            .is_some_and(|first| first >= cutoff && first >= learning_since + self.learning)
+            && events
+                .iter()
+                .any(|event| matches!(event.message_type, MessageType::Error | MessageType::Fault));
+        if is_new_error {
+            return Some(Finding::NewErrorTemplate);
+        }
+
+        if template.first_seen > cutoff - self.learning {
+            return None;
+        }
+        let recent = events.len() as u64;
+        let history_seconds = (cutoff - template.first_seen).num_seconds().max(1) as f64;
+        let expected = template.count.saturating_sub(recent) as f64
+            * self.window.num_seconds() as f64
+            / history_seconds;
+        (recent >= self.min_count && recent as f64 >= self.rate_factor * expected)
+            .then_some(Finding::RateJump { recent, expected })
+    }
+}
+
+impl TriggerRule for LogTemplateRule {
+    fn evaluate(&self, events: &EventSnapshot<'_>) -> bool {
+        !self.findings(events).is_empty()
+    }
+
+    fn name(&self) -> &str {
+        "LogTemplateRule"
+    }
+
+    fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    fn relevant_logs<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a LogEvent> {
+        self.findings(events)
+            .into_iter()
+            .flat_map(|finding| finding.events)
+            .collect()
+    }
+
+    /// One context per process, so each has its own cooldown
+    fn relevant_log_groups<'a>(&self, events: &EventSnapshot<'a>) -> Vec<RelevantLogGroup<'a>> {
+        let mut groups = BTreeMap::<&str, Vec<&LogEvent>>::new();
+        for finding in self.findings(events) {
+            groups
+                .entry(finding.template.process.as_str())
+                .or_default()
+                .extend(finding.events);
+        }
+        groups
+            .into_iter()
+            .map(|(process, events)| RelevantLogGroup {
+                source: Some(process.to_string()),
+                events,
+            })
+            .collect()
+    }
+
+    fn trigger_reason(&self, events: &EventSnapshot<'_>, source: Option<&str>) -> String {
+        let evidence = self
+            .findings(events)
+            .iter()
+            .filter(|finding| source.is_none_or(|source| finding.template.process == source))
+            .map(TemplateFinding::describe)
+            .collect::<Vec<_>>()
+            .join("; ");
+        format!("Rule '{}' triggered: {}", self.name(), evidence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn log_event(
+        process: &str,
+        message_type: MessageType,
+        message: &str,
+        at: Timestamp,
+    ) -> LogEvent {
+        LogEvent {
+            timestamp: at,
+            message_type,
+            subsystem: "com.apple.test".to_string(),
+            category: "default".to_string(),
+            process: process.to_string(),
+            process_id: 42,
+            message: message.to_string(),
+        }
+    }
+
+    fn findings_at<'a>(
+        rule: &LogTemplateRule,
+        templates: &'a LogTemplates,
+        logs: &'a [LogEvent],
+        now: Timestamp,
+    ) -> Vec<TemplateFinding<'a>> {
+        let snapshot = EventSnapshot::from_slices(logs, &[], &[])
+            .with_templates(templates)
+            .at(now);
+        rule.findings(&snapshot)
+    }
+
+    fn mine(logs: &[LogEvent]) -> LogTemplates {
+        let mut templates = LogTemplates::new();
+        for event in logs {
+            templates.add(event);
+        }
+        templates
+    }
+
+    #[test]
+    fn test_new_error_template_fires_after_learning() {
+        let rule = LogTemplateRule::with_defaults();
+        let now = Utc::now();
+        let mut logs = vec![
+            log_event(
+                "backupd",
+                MessageType::Info,
+                "Backup started",
+                now - Duration::hours(2),
+            ),
+            log_event(
+                "backupd",
+                MessageType::Error,
+                "Volume 7 not mounted",
+                now - Duration::hours(2),
+            ),
+            log_event(
+                "backupd",
+                MessageType::Error,
+                "Volume 3 not mounted",
+                now - Duration::seconds(5),
+            ),
+        ];
+        assert!(
+            findings_at(&rule, &mine(&logs), &logs, now).is_empty(),
+            "known error template must not fire"
+        );
+
+        logs.push(log_event(
+            "backupd",
+            MessageType::Error,
+            "Snapshot 0x1f2e rejected by /Volumes/Backup",
+            now - Duration::seconds(2),
+        ));
+        let templates = mine(&logs);
+        let findings = findings_at(&rule, &templates, &logs, now);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].finding, Finding::NewErrorTemplate);
+        assert_eq!(
+            findings[0].describe(),
+            "new error template from backupd: \"Snapshot <HEX> rejected by <PATH>\" (1 in window)"
+        );
+
+        // During the learning period everything is new, so nothing is reported
+        let fresh = &logs[2..];
+        assert!(findings_at(&rule, &mine(fresh), fresh, now).is_empty());
+
+        // Nor during the learning period after a restore, which may have lost
+        // the templates mined since the state was saved
+        let mut restored = mine(&logs);
+        restored.resume_at(now - Duration::minutes(10));
+        assert!(findings_at(&rule, &restored, &logs, now).is_empty());
+        restored.resume_at(now - Duration::hours(1) - Duration::minutes(1));
+        assert_eq!(findings_at(&rule, &restored, &logs, now).len(), 1);
+    }
+
+    #[test]
+    fn test_rate_jump_against_lifetime_rate() {
+        let rule = LogTemplateRule::with_defaults();
+        let now = Utc::now();
+        // One retry a minute for two hours
+        let mut logs = (1..=120)
+            .map(|minutes| {
+                log_event(
+                    "syncd",
+                    MessageType::Info,
+                    &format!("retrying request {}", minutes),
+                    now - Duration::minutes(minutes) - Duration::seconds(30),
+                )
+            })
+            .collect::<Vec<_>>();
+        logs.extend((0..10).map(|second| {
+            log_event(
+                "syncd",
+                MessageType::Info,
+                &format!("retrying request {}", 500 + second),
+                now - Duration::seconds(second),
+            )
+        }));
+        assert!(findings_at(&rule, &mine(&logs), &logs, now).is_empty());
+
+        logs.push(log_event(
+            "syncd",
+            MessageType::Info,
+            "retrying request 999",
+            now,
+        ));
+        let templates = mine(&logs);
+        let findings = findings_at(&rule, &templates, &logs, now);
+        assert_eq!(findings.len(), 1);
+        let Finding::RateJump { recent, expected } = findings[0].finding else {
+            panic!("expected a rate jump");
+        };
+        assert_eq!(recent, 11);
+        assert!((expected - 1.0).abs() < 0.05, "expected {}", expected);
+    }
+
+    #[test]
+    fn test_groups_by_process_through_snapshot() {
+        let rule = LogTemplateRule::with_defaults();
+        let now = Utc::now();
+        let logs = vec![
+            log_event("a", MessageType::Info, "started", now - Duration::hours(2)),
+            log_event("a", MessageType::Fault, "assertion failed in parser", now),
+            log_event("b", MessageType::Error, "socket 12 closed early", now),
+        ];
+        let templates = mine(&logs);
+        let snapshot = EventSnapshot::from_slices(&logs, &[], &[]);
+        assert!(
+            !rule.evaluate(&snapshot),
+            "snapshots without templates never fire"
+        );
+
+        let snapshot = snapshot.with_templates(&templates);
+        assert!(rule.evaluate(&snapshot));
+        let groups = rule.relevant_log_groups(&snapshot);
+        assert_eq!(
+            groups
+                .iter()
+                .map(|group| group.source.as_deref().unwrap())
+                .collect::<Vec<_>>(),
+            vec!["a", "b"]
+        );
+        assert!(rule
+            .trigger_reason(&snapshot, Some("b"))
+            .contains("new error template from b: \"socket <NUM> closed early\""));
+    }
+}
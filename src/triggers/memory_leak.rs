@@ -0,0 +1,384 @@
+//! Per-process memory leak detection from process metrics
+//!
+//! `MemoryLeakRule` keeps a per-minute history of `resident_memory_mb` for
+//! every process instance seen in the per-process snapshots of metrics
+//! events, fits a least-squares trend over the window and fires when memory
+//! rises steadily and significantly. `MemoryPressureRule` only reacts once
+//! the whole system is short of memory; this catches the process that is
+//! getting it there.
+
+use crate::aggregator::EventSnapshot;
+use crate::config::MemoryLeakConfig;
+use crate::events::{DiskEvent, LogEvent, MetricsEvent, Severity, Timestamp};
+use crate::triggers::{RelevantLogGroup, TriggerRule};
+use chrono::Duration;
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+use std::sync::Mutex;
+
+/// Instances missing from process snapshots this long are treated as exited
+const STALE_AFTER: Duration = Duration::minutes(5);
+
+/// Resident memory of one process instance at one time
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Sample {
+    at: Timestamp,
+    resident_mb: f64,
+}
+
+#[derive(Debug, Default)]
+struct LeakState {
+    /// Per-minute samples per process name and PID, oldest first
+    instances: BTreeMap<(String, u32), VecDeque<Sample>>,
+    /// Newest metrics event already observed
+    metrics_seen: Option<Timestamp>,
+}
+
+impl LeakState {
+    /// Record a sample, keeping only the latest one per minute
+    fn observe(&mut self, process: &str, process_id: u32, sample: Sample) {
+        let samples = self
+            .instances
+            .entry((process.to_string(), process_id))
+            .or_default();
+        let minute = |at: Timestamp| at.timestamp().div_euclid(60);
+        match samples.back_mut() {
+            Some(last) if minute(last.at) == minute(sample.at) => *last = sample,
+            _ => samples.push_back(sample),
+        }
+    }
+
+    /// Drop samples before `cutoff` and instances not seen since `stale`
+    fn prune(&mut self, cutoff: Timestamp, stale: Timestamp) {
+        self.instances.retain(|_, samples| {
+            while samples.front().is_some_and(|sample| sample.at < cutoff) {
+                samples.pop_front();
+            }
+            samples.back().is_some_and(|sample| sample.at >= stale)
+        });
+    }
+}
+
+/// A process instance whose memory keeps growing
+#[derive(Debug, Clone, PartialEq)]
+struct Leak {
+    process: String,
+    process_id: u32,
+    first: Sample,
+    last: Sample,
+    /// Slope of the fitted trend in megabytes per hour
+    mb_per_hour: f64,
+    /// Fraction of sample-to-sample steps that did not decrease
+    rising_fraction: f64,
+    /// Resident memory the trend reaches after the projection horizon
+    projected_mb: f64,
+}
+
+/// Trigger rule for processes whose resident memory grows steadily
+///
+/// The per-minute history outlives the event buffer, so `window` can span
+/// hours. Sawtooth growth, such as a cache that is flushed, and instances
+/// that have exited do not fire.
+pub struct MemoryLeakRule {
+    /// Window over which the memory trend is fitted
+    pub window: Duration,
+    /// Per-minute samples required before a trend is judged
+    pub min_samples: usize,
+    /// Growth of the fitted trend across the samples that counts as a leak
+    pub min_growth_mb: f64,
+    /// Fraction of sample-to-sample steps that must not decrease
+    pub min_rising_fraction: f64,
+    /// How far ahead the reported projected size looks
+    pub projection: Duration,
+    /// Processes whose memory growth is expected
+    pub ignore_processes: HashSet<String>,
+    /// Severity level to assign when this rule triggers
+    pub severity: Severity,
+    state: Mutex<LeakState>,
+}
+
+impl MemoryLeakRule {
+    /// Create a memory leak rule from `[triggers.memory_leak]`
+    pub fn new(config: &MemoryLeakConfig) -> Self {
+        Self {
+            window: Duration::seconds(config.window_seconds as i64),
+            min_samples: config.min_samples,
+            min_growth_mb: config.min_growth_mb,
+            min_rising_fraction: config.min_rising_fraction,
+            projection: Duration::seconds(config.projection_seconds as i64),
+            ignore_processes: config.ignore_processes.iter().cloned().collect(),
+            severity: config.severity,
+            state: Mutex::new(LeakState::default()),
+        }
+    }
+
+    /// Create a memory leak rule with the default configuration
+    pub fn with_defaults() -> Self {
+        Self::new(&MemoryLeakConfig::default())
+    }
+
+    /// Fold process snapshots newer than the last evaluation into the history
+    fn ingest(&self, state: &mut LeakState, events: &EventSnapshot<'_>) {
+        let mut fresh = events
+            .metrics()
+            .iter()
+            .filter(|event| state.metrics_seen.is_none_or(|seen| event.timestamp > seen))
+            .collect::<Vec<_>>();
+        fresh.sort_by_key(|event| event.timestamp);
+        for event in fresh {
+            for process in &event.process_metrics {
+                // PID 0 means the source did not report one
+                if process.process_id != 0 && !self.ignore_processes.contains(&process.process) {
+                    let sample = Sample {
+                        at: event.timestamp,
+                        resident_mb: process.resident_memory_mb,
+                    };
+                    state.observe(&process.process, process.process_id, sample);
+                }
+            }
+            state.metrics_seen = state.metrics_seen.max(Some(event.timestamp));
+        }
+    }
+
+    /// Judge one instance's samples, oldest first
+    fn judge(&self, process: &str, process_id: u32, samples: &VecDeque<Sample>) -> Option<Leak> {
+        let (first, last) = (*samples.front()?, *samples.back()?);
+        let span = last.at - first.at;
+        if samples.len() < self.min_samples || span < self.window / 2 {
+            return None;
+        }
+
+        // Least-squares slope in megabytes per second
+        let points = samples
+            .iter()
+            .map(|sample| {
+                let seconds = (sample.at - first.at).num_milliseconds() as f64 / 1000.0;
+                (seconds, sample.resident_mb)
+            })
+            .collect::<Vec<_>>();
+        let count = points.len() as f64;
+        let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / count;
+        let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / count;
+        let covariance = points
+            .iter()
+            .map(|(x, y)| (x - mean_x) * (y - mean_y))
+            .sum::<f64>();
+        let variance = points
+            .iter()
+            .map(|(x, _)| (x - mean_x).powi(2))
+            .sum::<f64>();
+        if variance <= 0.0 {
+            return None;
+        }
+        let slope = covariance / variance;
+
+        let rising = samples
+            .iter()
+            .zip(samples.iter().skip(1))
+            .filter(|(earlier, later)| later.resident_mb >= earlier.resident_mb)
+            .count();
+        let rising_fraction = rising as f64 / (samples.len() - 1) as f64;
+
+        let growth = slope * span.num_seconds() as f64;
+        (growth >= self.min_growth_mb && rising_fraction >= self.min_rising_fraction).then(|| {
+            Leak {
+                process: process.to_string(),
+                process_id,
+                first,
+                last,
+                mb_per_hour: slope * 3600.0,
+                rising_fraction,
+                projected_mb: last.resident_mb + slope * self.projection.num_seconds() as f64,
+            }
+        })
+    }
+
+    /// Process instances whose memory is leaking
+    fn leaks(&self) -> Vec<Leak> {
+        let state = self.state.lock().unwrap();
+        state
+            .instances
+            .iter()
+            .filter_map(|((process, process_id), samples)| {
+                self.judge(process, *process_id, samples)
+            })
+            .collect()
+    }
+
+    fn evaluate_at(&self, events: &EventSnapshot<'_>, now: Timestamp) -> bool {
+        {
+            let mut state = self.state.lock().unwrap();
+            self.ingest(&mut state, events);
+            state.prune(now - self.window, now - STALE_AFTER);
+        }
+        !self.leaks().is_empty()
+    }
+
+    fn describe(&self, leak: &Leak) -> String {
+        format!(
+            "{} (PID {}) grew from {:.0} MB to {:.0} MB over {}m ({:.0} MB/h, {:.0}% of samples rising), projected {:.0} MB within {}s",
+            leak.process,
+            leak.process_id,
+            leak.first.resident_mb,
+            leak.last.resident_mb,
+            (leak.last.at - leak.first.at).num_minutes(),
+            leak.mb_per_hour,
+            leak.rising_fraction * 100.0,
+            leak.projected_mb,
+            self.projection.num_seconds()
+        )
+    }
+}
+
+impl TriggerRule for MemoryLeakRule {
+    fn evaluate(&self, events: &EventSnapshot<'_>) -> bool {
+        self.evaluate_at(events, events.now())
+    }
+
+    fn name(&self) -> &str {
+        "MemoryLeakRule"
+    }
+
+    fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    fn relevant_logs<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a LogEvent> {
+        self.relevant_log_groups(events)
+            .into_iter()
+            .flat_map(|group| group.events)
+            .collect()
+    }
+
+    /// One context per leaking process, holding its log events
+    fn relevant_log_groups<'a>(&self, events: &EventSnapshot<'a>) -> Vec<RelevantLogGroup<'a>> {
+        let logs = events.logs();
+        let processes = self
+            .leaks()
+            .into_iter()
+            .map(|leak| leak.process)
+            .collect::<BTreeSet<_>>();
+        processes
+            .into_iter()
+            .map(|process| RelevantLogGroup {
+                events: logs.for_process(&process).collect(),
+                source: Some(process),
+            })
+            .collect()
+    }
+
+    /// Metrics events whose process snapshot includes a leaking instance
+    fn relevant_metrics<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a MetricsEvent> {
+        let leaking = self
+            .leaks()
+            .into_iter()
+            .map(|leak| (leak.process, leak.process_id))
+            .collect::<HashSet<_>>();
+        events
+            .metrics()
+            .iter()
+            .filter(|event| {
+                event
+                    .process_metrics
+                    .iter()
+                    .any(|process| leaking.contains(&(process.process.clone(), process.process_id)))
+            })
+            .collect()
+    }
+
+    fn relevant_disk_events<'a>(&self, _events: &EventSnapshot<'a>) -> Vec<&'a DiskEvent> {
+        Vec::new()
+    }
+
+    fn trigger_reason(&self, _events: &EventSnapshot<'_>, source: Option<&str>) -> String {
+        let evidence = self
+            .leaks()
+            .iter()
+            .filter(|leak| source.is_none_or(|source| leak.process == source))
+            .map(|leak| self.describe(leak))
+            .collect::<Vec<_>>()
+            .join("; ");
+        format!("Rule '{}' triggered: {}", self.name(), evidence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::triggers::tests::process_metrics_event;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_steady_growth_fires_with_rate_and_projection() {
+        let rule = MemoryLeakRule::with_defaults();
+        let start = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+        // Samples every 20 seconds for 45 minutes: syncd grows 4 MB a minute
+        // but dips every ten, WindowServer stays flat around 800 MB
+        let metrics = (0..135)
+            .map(|tick| {
+                let minutes = tick as f64 / 3.0;
+                let jitter = if tick % 30 == 29 { -6.0 } else { 0.0 };
+                process_metrics_event(
+                    &[
+                        ("syncd", 412, 1.0, 200.0 + 4.0 * minutes + jitter),
+                        ("WindowServer", 150, 1.0, 800.0 + (tick % 4) as f64),
+                    ],
+                    start + Duration::seconds(tick * 20),
+                )
+            })
+            .collect::<Vec<_>>();
+        let now = start + Duration::minutes(45);
+
+        // The first half hour is not enough history
+        let early = EventSnapshot::from_slices(&[], &metrics[..60], &[]);
+        assert!(!rule.evaluate_at(&early, start + Duration::minutes(20)));
+
+        let snapshot = EventSnapshot::from_slices(&[], &metrics, &[]);
+        assert!(rule.evaluate_at(&snapshot, now));
+        let leaks = rule.leaks();
+        assert_eq!(leaks.len(), 1);
+        let leak = &leaks[0];
+        assert_eq!((leak.process.as_str(), leak.process_id), ("syncd", 412));
+        assert!(
+            (leak.mb_per_hour - 240.0).abs() < 5.0,
+            "{}",
+            leak.mb_per_hour
+        );
+        assert!(
+            (leak.projected_mb - (leak.last.resident_mb + leak.mb_per_hour)).abs() < 1e-6,
+            "{}",
+            leak.projected_mb
+        );
+        assert!(rule
+            .describe(leak)
+            .starts_with("syncd (PID 412) grew from 203 MB to 379 MB over 44m ("));
+
+        // Only one sample per minute is kept
+        let state = rule.state.lock().unwrap();
+        assert_eq!(state.instances[&("syncd".to_string(), 412)].len(), 45);
+    }
+
+    #[test]
+    fn test_sawtooth_and_exited_instances_do_not_fire() {
+        let rule = MemoryLeakRule::with_defaults();
+        let start = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+        // A cache that grows and is flushed every ten minutes, and an
+        // instance that leaked for 40 minutes but exited 10 minutes ago
+        let metrics = (0..50)
+            .map(|minute| {
+                let mut processes =
+                    vec![("mds_stores", 90, 1.0, 300.0 + 40.0 * (minute % 10) as f64)];
+                if minute <= 40 {
+                    processes.push(("photoanalysisd", 77, 1.0, 100.0 + 10.0 * minute as f64));
+                }
+                process_metrics_event(&processes, start + Duration::minutes(minute))
+            })
+            .collect::<Vec<_>>();
+
+        let snapshot = EventSnapshot::from_slices(&[], &metrics, &[]);
+        assert!(!rule.evaluate_at(&snapshot, start + Duration::minutes(50)));
+        let state = rule.state.lock().unwrap();
+        assert!(!state
+            .instances
+            .contains_key(&("photoanalysisd".to_string(), 77)));
+    }
+}
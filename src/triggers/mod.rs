@@ -1,8 +1,73 @@
+/// Anomaly detection over learned metric baselines
+pub mod anomaly;
+/// Correlations of other rules' firings
+pub mod composite;
+/// Sustained per-process CPU usage from process metrics
+pub mod cpu_hog;
+/// User-defined rules compiled from `[[triggers.rules]]`
+pub mod declarative;
+/// Rule expression language over logs, metrics and disk events
+pub mod expression;
+/// Silence detection for expected log sources from `[[triggers.heartbeats]]`
+pub mod heartbeat;
+/// Novel log template and template rate alerts
+pub mod log_templates;
+/// Per-process memory leak detection from process metrics
+pub mod memory_leak;
+/// Respawn loop detection from process identities
+pub mod respawn;
 pub mod rules;
 /// Trigger engine and rule implementations
 pub mod trigger_engine;
 
+pub use anomaly::AnomalyRule;
+pub use composite::CompositeRule;
+pub use cpu_hog::CpuHogRule;
+pub use declarative::DeclarativeRule;
+pub use expression::Expression;
+pub use heartbeat::HeartbeatRule;
+pub use log_templates::LogTemplateRule;
+pub use memory_leak::MemoryLeakRule;
+pub use respawn::RespawnLoopRule;
 pub use rules::{
     CrashDetectionRule, DiskIOSpikeRule, ErrorFrequencyRule, MemoryPressureRule, ResourceSpikeRule,
 };
-pub use trigger_engine::{RelevantLogGroup, TriggerContext, TriggerEngine, TriggerRule};
+pub use trigger_engine::{
+    RelevantLogGroup, RetainedEvents, RulePolicy, TriggerContext, TriggerCooldowns, TriggerEngine,
+    TriggerRule, BUILTIN_RULES,
+};
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::events::{MemoryPressure, MetricsEvent, ProcessMetric, Timestamp};
+
+    /// Quiet system metrics at `at` carrying per-process samples, each given
+    /// as `(process, PID, CPU %, resident MB)`
+    pub(crate) fn process_metrics_event(
+        processes: &[(&str, u32, f64, f64)],
+        at: Timestamp,
+    ) -> MetricsEvent {
+        MetricsEvent {
+            timestamp: at,
+            cpu_power_mw: 0.0,
+            cpu_usage_percent: 0.0,
+            gpu_power_mw: None,
+            gpu_usage_percent: None,
+            memory_pressure: MemoryPressure::Normal,
+            memory_used_mb: 0.0,
+            energy_impact: 0.0,
+            provenance: Default::default(),
+            process_metrics: processes
+                .iter()
+                .map(
+                    |&(process, process_id, cpu_usage_percent, resident_memory_mb)| ProcessMetric {
+                        process_id,
+                        process: process.to_string(),
+                        cpu_usage_percent,
+                        resident_memory_mb,
+                    },
+                )
+                .collect(),
+        }
+    }
+}
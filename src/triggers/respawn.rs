@@ -0,0 +1,398 @@
+//! Respawn loop detection from process identities
+//!
+//! `RespawnLoopRule` follows which PIDs each process name has been seen with,
+//! in log events and in the per-process snapshots of metrics events. A PID
+//! first seen after every earlier PID was last seen is a respawn; several
+//! respawns within the window are a loop, such as a launchd job that exits
+//! and is restarted every few seconds without ever logging a crash.
+
+use crate::aggregator::EventSnapshot;
+use crate::config::RespawnConfig;
+use crate::events::{DiskEvent, LogEvent, MetricsEvent, Severity, Timestamp};
+use crate::triggers::{RelevantLogGroup, TriggerRule};
+use chrono::Duration;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::sync::Mutex;
+
+/// How far behind the newest observed event a late one is still folded in
+///
+/// Log streams, syslog and OTLP exporters can deliver events a few seconds
+/// out of order. Observing an event twice changes nothing, so each evaluation
+/// reads this margin again rather than dropping what arrived late.
+const LATE_EVENT_MARGIN_SECONDS: i64 = 30;
+
+/// When one PID of a process was observed
+#[derive(Debug, Clone, PartialEq)]
+struct PidSpan {
+    process_id: u32,
+    first_seen: Timestamp,
+    last_seen: Timestamp,
+    /// Whether every earlier PID had stopped appearing when this one started
+    respawn: bool,
+}
+
+#[derive(Debug, Default)]
+struct RespawnState {
+    /// PIDs per process name, ordered by first sighting
+    processes: BTreeMap<String, VecDeque<PidSpan>>,
+    /// Newest log and metrics events already observed; events up to
+    /// `LATE_EVENT_MARGIN_SECONDS` older are observed again
+    logs_seen: Option<Timestamp>,
+    metrics_seen: Option<Timestamp>,
+}
+
+impl RespawnState {
+    fn observe(&mut self, process: &str, process_id: u32, at: Timestamp) {
+        if !self.processes.contains_key(process) {
+            self.processes.insert(process.to_string(), VecDeque::new());
+        }
+        let spans = self.processes.get_mut(process).unwrap();
+        if let Some(index) = spans.iter().position(|span| span.process_id == process_id) {
+            let span = &mut spans[index];
+            span.first_seen = span.first_seen.min(at);
+            span.last_seen = span.last_seen.max(at);
+            // Still alive when a newer PID appeared: concurrent instances,
+            // not a respawn
+            for later in spans.iter_mut().skip(index + 1) {
+                if later.first_seen <= at {
+                    later.respawn = false;
+                }
+            }
+            return;
+        }
+        // A late event can start a PID before ones already seen
+        let index = spans.partition_point(|span| span.first_seen <= at);
+        let respawn = index > 0 && spans.range(..index).all(|span| span.last_seen < at);
+        spans.insert(
+            index,
+            PidSpan {
+                process_id,
+                first_seen: at,
+                last_seen: at,
+                respawn,
+            },
+        );
+    }
+
+    /// Drop PIDs last seen before `cutoff`, keeping each process's newest
+    fn prune(&mut self, cutoff: Timestamp) {
+        for spans in self.processes.values_mut() {
+            while spans.len() > 1 && spans.front().is_some_and(|span| span.last_seen < cutoff) {
+                spans.pop_front();
+            }
+        }
+    }
+}
+
+/// Trigger rule for processes that keep coming back with new PIDs
+///
+/// Unlike `CrashDetectionRule`, this needs no crash message: a process that
+/// exits quietly and is relaunched shows up only as a string of PIDs, which
+/// are remembered for the whole `window` after their events leave the buffer.
+pub struct RespawnLoopRule {
+    /// Window in which respawns are counted
+    pub window: Duration,
+    /// Respawns within the window that make a loop
+    pub min_respawns: usize,
+    /// Processes that legitimately start a new PID for each job
+    pub ignore_processes: HashSet<String>,
+    /// Severity level to assign when this rule triggers
+    pub severity: Severity,
+    state: Mutex<RespawnState>,
+}
+
+impl RespawnLoopRule {
+    /// Create a respawn loop rule from `[triggers.respawn]`
+    pub fn new(config: &RespawnConfig) -> Self {
+        Self {
+            window: Duration::seconds(config.window_seconds as i64),
+            min_respawns: config.min_respawns,
+            ignore_processes: config.ignore_processes.iter().cloned().collect(),
+            severity: config.severity,
+            state: Mutex::new(RespawnState::default()),
+        }
+    }
+
+    /// Create a respawn loop rule with the default configuration
+    pub fn with_defaults() -> Self {
+        Self::new(&RespawnConfig::default())
+    }
+
+    /// Fold process identities newer than the last evaluation, less the
+    /// lateness margin, into the history
+    fn ingest(&self, state: &mut RespawnState, events: &EventSnapshot<'_>) {
+        let margin = Duration::seconds(LATE_EVENT_MARGIN_SECONDS);
+        let logs_since = state.logs_seen.map(|seen| seen - margin);
+        let metrics_since = state.metrics_seen.map(|seen| seen - margin);
+        let mut observations = Vec::new();
+        let mut logs_seen = state.logs_seen;
+        for event in events.logs() {
+            if logs_since.is_none_or(|since| event.timestamp > since) {
+                observations.push((event.timestamp, event.process.as_str(), event.process_id));
+                logs_seen = logs_seen.max(Some(event.timestamp));
+            }
+        }
+        let mut metrics_seen = state.metrics_seen;
+        for event in events.metrics() {
+            if metrics_since.is_none_or(|since| event.timestamp > since) {
+                observations.extend(event.process_metrics.iter().map(|process| {
+                    (
+                        event.timestamp,
+                        process.process.as_str(),
+                        process.process_id,
+                    )
+                }));
+                metrics_seen = metrics_seen.max(Some(event.timestamp));
+            }
+        }
+        state.logs_seen = logs_seen;
+        state.metrics_seen = metrics_seen;
+
+        observations.sort_by_key(|(timestamp, _, _)| *timestamp);
+        for (timestamp, process, process_id) in observations {
+            // PID 0 means the source did not report one
+            if process_id != 0 && !self.ignore_processes.contains(process) {
+                state.observe(process, process_id, timestamp);
+            }
+        }
+    }
+
+    /// Processes in a respawn loop, with their respawn count and PID history
+    /// in the window
+    fn loops(&self, now: Timestamp) -> Vec<(String, usize, Vec<PidSpan>)> {
+        let state = self.state.lock().unwrap();
+        let cutoff = now - self.window;
+        state
+            .processes
+            .iter()
+            .filter_map(|(process, spans)| {
+                let respawns = spans
+                    .iter()
+                    .filter(|span| span.respawn && span.first_seen >= cutoff)
+                    .count();
+                (respawns >= self.min_respawns).then(|| {
+                    let history = spans
+                        .iter()
+                        .filter(|span| span.last_seen >= cutoff)
+                        .cloned()
+                        .collect();
+                    (process.clone(), respawns, history)
+                })
+            })
+            .collect()
+    }
+
+    fn evaluate_at(&self, events: &EventSnapshot<'_>, now: Timestamp) -> bool {
+        {
+            let mut state = self.state.lock().unwrap();
+            self.ingest(&mut state, events);
+            state.prune(now - self.window);
+        }
+        !self.loops(now).is_empty()
+    }
+
+    fn describe(&self, process: &str, respawns: usize, history: &[PidSpan]) -> String {
+        let pids = history
+            .iter()
+            .map(|span| {
+                format!(
+                    "PID {} ({}-{})",
+                    span.process_id,
+                    span.first_seen.format("%H:%M:%S"),
+                    span.last_seen.format("%H:%M:%S")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            "{} respawned {} times within {}s: {}",
+            process,
+            respawns,
+            self.window.num_seconds(),
+            pids
+        )
+    }
+}
+
+impl TriggerRule for RespawnLoopRule {
+    fn evaluate(&self, events: &EventSnapshot<'_>) -> bool {
+        self.evaluate_at(events, events.now())
+    }
+
+    fn name(&self) -> &str {
+        "RespawnLoopRule"
+    }
+
+    fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    fn relevant_logs<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a LogEvent> {
+        self.relevant_log_groups(events)
+            .into_iter()
+            .flat_map(|group| group.events)
+            .collect()
+    }
+
+    /// One context per looping process, holding its log events
+    fn relevant_log_groups<'a>(&self, events: &EventSnapshot<'a>) -> Vec<RelevantLogGroup<'a>> {
+        let logs = events.logs();
+        self.loops(events.now())
+            .into_iter()
+            .map(|(process, _, _)| RelevantLogGroup {
+                events: logs.for_process(&process).collect(),
+                source: Some(process),
+            })
+            .collect()
+    }
+
+    /// Metrics events whose process snapshot includes a looping process
+    fn relevant_metrics<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a MetricsEvent> {
+        let looping = self
+            .loops(events.now())
+            .into_iter()
+            .map(|(process, _, _)| process)
+            .collect::<HashSet<_>>();
+        events
+            .metrics()
+            .iter()
+            .filter(|event| {
+                event
+                    .process_metrics
+                    .iter()
+                    .any(|process| looping.contains(&process.process))
+            })
+            .collect()
+    }
+
+    fn relevant_disk_events<'a>(&self, _events: &EventSnapshot<'a>) -> Vec<&'a DiskEvent> {
+        Vec::new()
+    }
+
+    fn trigger_reason(&self, events: &EventSnapshot<'_>, source: Option<&str>) -> String {
+        let evidence = self
+            .loops(events.now())
+            .iter()
+            .filter(|(process, _, _)| source.is_none_or(|source| process == source))
+            .map(|(process, respawns, history)| self.describe(process, *respawns, history))
+            .collect::<Vec<_>>()
+            .join("; ");
+        format!("Rule '{}' triggered: {}", self.name(), evidence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::MessageType;
+    use crate::triggers::tests::process_metrics_event;
+    use chrono::Utc;
+
+    fn log_event(process: &str, process_id: u32, at: Timestamp) -> LogEvent {
+        LogEvent {
+            timestamp: at,
+            message_type: MessageType::Info,
+            subsystem: "com.example.sync".to_string(),
+            category: "default".to_string(),
+            process: process.to_string(),
+            process_id,
+            message: "starting".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_respawn_loop_fires_with_pid_history() {
+        let rule = RespawnLoopRule::with_defaults();
+        let start = Utc::now() - Duration::seconds(300);
+        let at = |seconds| start + Duration::seconds(seconds);
+        // syncd comes back with a new PID every 30 seconds; the third
+        // sighting is from the metrics process snapshot
+        let logs = vec![
+            log_event("syncd", 100, at(0)),
+            log_event("syncd", 100, at(10)),
+            log_event("syncd", 101, at(30)),
+            log_event("syncd", 103, at(90)),
+        ];
+        let metrics = vec![process_metrics_event(&[("syncd", 102, 1.0, 10.0)], at(60))];
+
+        assert!(!rule.evaluate_at(&EventSnapshot::from_slices(&logs[..3], &[], &[]), at(30)));
+        let snapshot = EventSnapshot::from_slices(&logs, &metrics, &[]);
+        assert!(rule.evaluate_at(&snapshot, at(90)));
+
+        let loops = rule.loops(at(90));
+        assert_eq!(loops.len(), 1);
+        let (process, respawns, history) = &loops[0];
+        assert_eq!(
+            history
+                .iter()
+                .map(|span| span.process_id)
+                .collect::<Vec<_>>(),
+            vec![100, 101, 102, 103]
+        );
+        assert!(rule
+            .describe(process, *respawns, history)
+            .starts_with("syncd respawned 3 times within 600s: PID 100 ("));
+        // The loop ages out once the respawns leave the window
+        assert!(!rule.evaluate_at(&EventSnapshot::from_slices(&[], &[], &[]), at(700)));
+    }
+
+    #[test]
+    fn test_concurrent_instances_are_not_respawns() {
+        let rule = RespawnLoopRule::with_defaults();
+        let start = Utc::now() - Duration::seconds(300);
+        let at = |seconds| start + Duration::seconds(seconds);
+        // Worker pools keep several PIDs alive at once
+        let metrics = (0..6)
+            .map(|tick| {
+                process_metrics_event(
+                    &[
+                        ("worker", 200, 1.0, 10.0),
+                        ("worker", 201 + tick, 1.0, 10.0),
+                    ],
+                    at(tick as i64 * 10),
+                )
+            })
+            .collect::<Vec<_>>();
+        let logs = vec![
+            log_event("kernel", 0, at(0)),
+            log_event("kernel", 0, at(10)),
+            log_event("xpcproxy", 300, at(0)),
+            log_event("xpcproxy", 301, at(10)),
+            log_event("xpcproxy", 302, at(20)),
+            log_event("xpcproxy", 303, at(30)),
+        ];
+
+        assert!(!rule.evaluate_at(&EventSnapshot::from_slices(&logs, &metrics, &[]), at(50)));
+    }
+
+    #[test]
+    fn test_late_events_within_the_margin_are_observed() {
+        let rule = RespawnLoopRule::with_defaults();
+        let start = Utc::now() - Duration::seconds(300);
+        let at = |seconds| start + Duration::seconds(seconds);
+        let mut logs = vec![
+            log_event("syncd", 100, at(0)),
+            log_event("syncd", 101, at(30)),
+            log_event("syncd", 103, at(90)),
+        ];
+        assert!(!rule.evaluate_at(&EventSnapshot::from_slices(&logs, &[], &[]), at(90)));
+
+        // PID 102 is delivered after the evaluation that saw PID 103
+        logs.insert(2, log_event("syncd", 102, at(70)));
+        assert!(rule.evaluate_at(&EventSnapshot::from_slices(&logs, &[], &[]), at(91)));
+        let loops = rule.loops(at(91));
+        let (_, respawns, history) = &loops[0];
+        assert_eq!(*respawns, 3);
+        assert_eq!(
+            history
+                .iter()
+                .map(|span| span.process_id)
+                .collect::<Vec<_>>(),
+            vec![100, 101, 102, 103]
+        );
+
+        // Reading the margin again does not add respawns
+        assert!(rule.evaluate_at(&EventSnapshot::from_slices(&logs, &[], &[]), at(92)));
+        assert_eq!(rule.loops(at(92))[0].1, 3);
+    }
+}
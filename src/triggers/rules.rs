@@ -3,17 +3,41 @@
 //! This module provides concrete implementations of trigger rules that determine
 //! when AI analysis should be invoked based on system events and metrics.
 
+use crate::aggregator::{EventSnapshot, EventWindow};
 use crate::events::{
     DiskEvent, LogEvent, MeasurementKind, MemoryPressure, MessageType, MetricsEvent, Severity,
+    Timestamp,
 };
 use crate::triggers::{RelevantLogGroup, TriggerRule};
-use chrono::{Duration, Utc};
+use chrono::Duration;
 use std::collections::{BTreeMap, HashMap, HashSet};
 
 type ErrorSource = (String, String, Option<String>);
 
-const DEFAULT_DISK_READ_SPIKE_THRESHOLD_KB_PER_SEC: f64 = 512.0 * 1024.0;
-const DEFAULT_DISK_WRITE_SPIKE_THRESHOLD_KB_PER_SEC: f64 = 256.0 * 1024.0;
+/// Read rate rise that `DiskIOSpikeRule::with_defaults` treats as a spike
+pub const DEFAULT_DISK_READ_SPIKE_THRESHOLD_KB_PER_SEC: f64 = 512.0 * 1024.0;
+/// Write rate rise that `DiskIOSpikeRule::with_defaults` treats as a spike
+pub const DEFAULT_DISK_WRITE_SPIKE_THRESHOLD_KB_PER_SEC: f64 = 256.0 * 1024.0;
+
+/// Common crash indicators used by `CrashDetectionRule::with_defaults`
+pub const DEFAULT_CRASH_KEYWORDS: &[&str] = &[
+    "crash",
+    "crashed",
+    "segmentation fault",
+    "segfault",
+    "kernel panic",
+    "panic(cpu",
+    "abort trap",
+    "abort() called",
+    "aborted due to signal",
+    "terminated unexpectedly",
+    "signal 11",
+    "SIGSEGV",
+    "SIGABRT",
+    "EXC_BAD_ACCESS",
+    "termination reason: namespace signal",
+    "fatal error",
+];
 
 fn error_source(event: &LogEvent) -> ErrorSource {
     (
@@ -70,8 +94,11 @@ impl ErrorFrequencyRule {
         Self::new(5, 60, Severity::Warning)
     }
 
-    fn triggering_sources(&self, log_events: &[LogEvent]) -> HashSet<ErrorSource> {
-        let now = Utc::now();
+    fn triggering_sources(
+        &self,
+        log_events: EventWindow<'_, LogEvent>,
+        now: Timestamp,
+    ) -> HashSet<ErrorSource> {
         let cutoff = now - Duration::seconds(self.window_seconds);
         let baseline_cutoff = cutoff - Duration::seconds(self.window_seconds);
         let mut current_counts =
@@ -121,13 +148,9 @@ impl ErrorFrequencyRule {
 }
 
 impl TriggerRule for ErrorFrequencyRule {
-    fn evaluate(
-        &self,
-        log_events: &[LogEvent],
-        _metrics_events: &[MetricsEvent],
-        _disk_events: &[DiskEvent],
-    ) -> bool {
-        !self.triggering_sources(log_events).is_empty()
+    fn evaluate(&self, events: &EventSnapshot<'_>) -> bool {
+        let log_events = events.logs();
+        !self.triggering_sources(log_events, events.now()).is_empty()
     }
 
     fn name(&self) -> &str {
@@ -138,9 +161,10 @@ impl TriggerRule for ErrorFrequencyRule {
         self.severity
     }
 
-    fn relevant_logs<'a>(&self, log_events: &'a [LogEvent]) -> Vec<&'a LogEvent> {
-        let cutoff = Utc::now() - Duration::seconds(self.window_seconds);
-        let triggering_sources = self.triggering_sources(log_events);
+    fn relevant_logs<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a LogEvent> {
+        let log_events = events.logs();
+        let cutoff = events.now() - Duration::seconds(self.window_seconds);
+        let triggering_sources = self.triggering_sources(log_events, events.now());
         log_events
             .iter()
             .filter(|event| {
@@ -152,9 +176,10 @@ impl TriggerRule for ErrorFrequencyRule {
             .collect()
     }
 
-    fn relevant_log_groups<'a>(&self, log_events: &'a [LogEvent]) -> Vec<RelevantLogGroup<'a>> {
-        let cutoff = Utc::now() - Duration::seconds(self.window_seconds);
-        let triggering_sources = self.triggering_sources(log_events);
+    fn relevant_log_groups<'a>(&self, events: &EventSnapshot<'a>) -> Vec<RelevantLogGroup<'a>> {
+        let log_events = events.logs();
+        let cutoff = events.now() - Duration::seconds(self.window_seconds);
+        let triggering_sources = self.triggering_sources(log_events, events.now());
         let mut groups = BTreeMap::<ErrorSource, Vec<&LogEvent>>::new();
 
         for event in log_events.iter().filter(|event| {
@@ -175,11 +200,11 @@ impl TriggerRule for ErrorFrequencyRule {
             .collect()
     }
 
-    fn relevant_metrics<'a>(&self, _metrics_events: &'a [MetricsEvent]) -> Vec<&'a MetricsEvent> {
+    fn relevant_metrics<'a>(&self, _events: &EventSnapshot<'a>) -> Vec<&'a MetricsEvent> {
         Vec::new()
     }
 
-    fn relevant_disk_events<'a>(&self, _disk_events: &'a [DiskEvent]) -> Vec<&'a DiskEvent> {
+    fn relevant_disk_events<'a>(&self, _events: &EventSnapshot<'a>) -> Vec<&'a DiskEvent> {
         Vec::new()
     }
 }
@@ -221,12 +246,8 @@ impl MemoryPressureRule {
 }
 
 impl TriggerRule for MemoryPressureRule {
-    fn evaluate(
-        &self,
-        _log_events: &[LogEvent],
-        metrics_events: &[MetricsEvent],
-        _disk_events: &[DiskEvent],
-    ) -> bool {
+    fn evaluate(&self, events: &EventSnapshot<'_>) -> bool {
+        let metrics_events = events.metrics();
         // Check if any recent metrics event shows memory pressure at or above threshold
         metrics_events.iter().any(|event| {
             event.provenance.memory_pressure == MeasurementKind::Measured
@@ -242,12 +263,8 @@ impl TriggerRule for MemoryPressureRule {
         self.severity
     }
 
-    fn severity_for(
-        &self,
-        _log_events: &[LogEvent],
-        metrics_events: &[MetricsEvent],
-        _disk_events: &[DiskEvent],
-    ) -> Severity {
+    fn severity_for(&self, events: &EventSnapshot<'_>) -> Severity {
+        let metrics_events = events.metrics();
         match metrics_events
             .iter()
             .filter(|event| event.provenance.memory_pressure == MeasurementKind::Measured)
@@ -260,11 +277,12 @@ impl TriggerRule for MemoryPressureRule {
         }
     }
 
-    fn relevant_logs<'a>(&self, _log_events: &'a [LogEvent]) -> Vec<&'a LogEvent> {
+    fn relevant_logs<'a>(&self, _events: &EventSnapshot<'a>) -> Vec<&'a LogEvent> {
         Vec::new()
     }
 
-    fn relevant_metrics<'a>(&self, metrics_events: &'a [MetricsEvent]) -> Vec<&'a MetricsEvent> {
+    fn relevant_metrics<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a MetricsEvent> {
+        let metrics_events = events.metrics();
         metrics_events
             .iter()
             .filter(|event| {
@@ -274,7 +292,7 @@ impl TriggerRule for MemoryPressureRule {
             .collect()
     }
 
-    fn relevant_disk_events<'a>(&self, _disk_events: &'a [DiskEvent]) -> Vec<&'a DiskEvent> {
+    fn relevant_disk_events<'a>(&self, _events: &EventSnapshot<'a>) -> Vec<&'a DiskEvent> {
         Vec::new()
     }
 }
@@ -309,35 +327,17 @@ impl CrashDetectionRule {
 
     /// Create a default crash detection rule with common crash indicators
     pub fn with_defaults() -> Self {
-        let keywords = vec![
-            "crash".to_string(),
-            "crashed".to_string(),
-            "segmentation fault".to_string(),
-            "segfault".to_string(),
-            "kernel panic".to_string(),
-            "panic(cpu".to_string(),
-            "abort trap".to_string(),
-            "abort() called".to_string(),
-            "aborted due to signal".to_string(),
-            "terminated unexpectedly".to_string(),
-            "signal 11".to_string(),
-            "SIGSEGV".to_string(),
-            "SIGABRT".to_string(),
-            "EXC_BAD_ACCESS".to_string(),
-            "termination reason: namespace signal".to_string(),
-            "fatal error".to_string(),
-        ];
+        let keywords = DEFAULT_CRASH_KEYWORDS
+            .iter()
+            .map(|keyword| keyword.to_string())
+            .collect();
         Self::new(keywords, Severity::Critical)
     }
 }
 
 impl TriggerRule for CrashDetectionRule {
-    fn evaluate(
-        &self,
-        log_events: &[LogEvent],
-        _metrics_events: &[MetricsEvent],
-        _disk_events: &[DiskEvent],
-    ) -> bool {
+    fn evaluate(&self, events: &EventSnapshot<'_>) -> bool {
+        let log_events = events.logs();
         // Look for crash keywords in error and fault messages
         log_events.iter().any(|event| self.matches_event(event))
     }
@@ -350,14 +350,16 @@ impl TriggerRule for CrashDetectionRule {
         self.severity
     }
 
-    fn relevant_logs<'a>(&self, log_events: &'a [LogEvent]) -> Vec<&'a LogEvent> {
+    fn relevant_logs<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a LogEvent> {
+        let log_events = events.logs();
         log_events
             .iter()
             .filter(|event| self.matches_event(event))
             .collect()
     }
 
-    fn relevant_log_groups<'a>(&self, log_events: &'a [LogEvent]) -> Vec<RelevantLogGroup<'a>> {
+    fn relevant_log_groups<'a>(&self, events: &EventSnapshot<'a>) -> Vec<RelevantLogGroup<'a>> {
+        let log_events = events.logs();
         let mut groups = BTreeMap::<ErrorSource, Vec<&LogEvent>>::new();
         for event in log_events.iter().filter(|event| self.matches_event(event)) {
             groups.entry(error_source(event)).or_default().push(event);
@@ -371,11 +373,11 @@ impl TriggerRule for CrashDetectionRule {
             .collect()
     }
 
-    fn relevant_metrics<'a>(&self, _metrics_events: &'a [MetricsEvent]) -> Vec<&'a MetricsEvent> {
+    fn relevant_metrics<'a>(&self, _events: &EventSnapshot<'a>) -> Vec<&'a MetricsEvent> {
         Vec::new()
     }
 
-    fn relevant_disk_events<'a>(&self, _disk_events: &'a [DiskEvent]) -> Vec<&'a DiskEvent> {
+    fn relevant_disk_events<'a>(&self, _events: &EventSnapshot<'a>) -> Vec<&'a DiskEvent> {
         Vec::new()
     }
 }
@@ -459,17 +461,13 @@ impl ResourceSpikeRule {
 }
 
 impl TriggerRule for ResourceSpikeRule {
-    fn evaluate(
-        &self,
-        _log_events: &[LogEvent],
-        metrics_events: &[MetricsEvent],
-        _disk_events: &[DiskEvent],
-    ) -> bool {
+    fn evaluate(&self, events: &EventSnapshot<'_>) -> bool {
+        let metrics_events = events.metrics();
         if metrics_events.len() < 2 {
             return false; // Need at least 2 data points to detect a spike
         }
 
-        let now = Utc::now();
+        let now = events.now();
         let comparison_cutoff = now - Duration::seconds(self.comparison_window_seconds);
 
         // Get recent metrics (within comparison window)
@@ -478,12 +476,59 @@ impl TriggerRule for ResourceSpikeRule {
             .filter(|event| event.timestamp >= comparison_cutoff)
             .collect();
 
-        if recent_metrics.len() < 2 {
+        // Power readings are only comparable within one source; OTLP samples,
+        // for example, carry no power data at all
+        let mut sources = Vec::new();
+        for event in &recent_metrics {
+            if !sources.contains(&event.provenance.source) {
+                sources.push(event.provenance.source);
+            }
+        }
+        sources.into_iter().any(|source| {
+            self.series_has_spike(
+                recent_metrics
+                    .iter()
+                    .filter(|event| event.provenance.source == source)
+                    .copied()
+                    .collect(),
+            )
+        })
+    }
+
+    fn name(&self) -> &str {
+        "ResourceSpikeRule"
+    }
+
+    fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    fn relevant_logs<'a>(&self, _events: &EventSnapshot<'a>) -> Vec<&'a LogEvent> {
+        Vec::new()
+    }
+
+    fn relevant_metrics<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a MetricsEvent> {
+        let metrics_events = events.metrics();
+        let cutoff = events.now() - Duration::seconds(self.comparison_window_seconds);
+        metrics_events
+            .iter()
+            .filter(|event| event.timestamp >= cutoff)
+            .collect()
+    }
+
+    fn relevant_disk_events<'a>(&self, _events: &EventSnapshot<'a>) -> Vec<&'a DiskEvent> {
+        Vec::new()
+    }
+}
+
+impl ResourceSpikeRule {
+    /// Whether samples from a single source rise by more than a threshold
+    fn series_has_spike(&self, mut sorted_metrics: Vec<&MetricsEvent>) -> bool {
+        if sorted_metrics.len() < 2 {
             return false; // Need at least 2 recent data points
         }
 
         // Sort by timestamp to get chronological order
-        let mut sorted_metrics = recent_metrics;
         sorted_metrics.sort_by_key(|event| event.timestamp);
 
         // Find the maximum upward spike within the time window using running minimum approach
@@ -521,30 +566,6 @@ impl TriggerRule for ResourceSpikeRule {
         // Trigger if either CPU or GPU spike exceeds threshold
         max_cpu_spike >= self.cpu_spike_threshold_mw || max_gpu_spike >= self.gpu_spike_threshold_mw
     }
-
-    fn name(&self) -> &str {
-        "ResourceSpikeRule"
-    }
-
-    fn severity(&self) -> Severity {
-        self.severity
-    }
-
-    fn relevant_logs<'a>(&self, _log_events: &'a [LogEvent]) -> Vec<&'a LogEvent> {
-        Vec::new()
-    }
-
-    fn relevant_metrics<'a>(&self, metrics_events: &'a [MetricsEvent]) -> Vec<&'a MetricsEvent> {
-        let cutoff = Utc::now() - Duration::seconds(self.comparison_window_seconds);
-        metrics_events
-            .iter()
-            .filter(|event| event.timestamp >= cutoff)
-            .collect()
-    }
-
-    fn relevant_disk_events<'a>(&self, _disk_events: &'a [DiskEvent]) -> Vec<&'a DiskEvent> {
-        Vec::new()
-    }
 }
 
 /// Trigger rule that activates when disk I/O activity spikes suddenly
@@ -633,8 +654,12 @@ impl DiskIOSpikeRule {
         )
     }
 
-    fn triggering_spikes(&self, disk_events: &[DiskEvent]) -> Vec<DiskSpike> {
-        let cutoff = Utc::now() - Duration::seconds(self.comparison_window_seconds);
+    fn triggering_spikes(
+        &self,
+        disk_events: EventWindow<'_, DiskEvent>,
+        now: Timestamp,
+    ) -> Vec<DiskSpike> {
+        let cutoff = now - Duration::seconds(self.comparison_window_seconds);
         let mut events_by_disk = BTreeMap::<String, Vec<&DiskEvent>>::new();
 
         for event in disk_events.iter().filter(|event| event.timestamp >= cutoff) {
@@ -716,13 +741,9 @@ impl DiskIOSpikeRule {
 }
 
 impl TriggerRule for DiskIOSpikeRule {
-    fn evaluate(
-        &self,
-        _log_events: &[LogEvent],
-        _metrics_events: &[MetricsEvent],
-        disk_events: &[DiskEvent],
-    ) -> bool {
-        !self.triggering_spikes(disk_events).is_empty()
+    fn evaluate(&self, events: &EventSnapshot<'_>) -> bool {
+        let disk_events = events.disk();
+        !self.triggering_spikes(disk_events, events.now()).is_empty()
     }
 
     fn name(&self) -> &str {
@@ -733,15 +754,10 @@ impl TriggerRule for DiskIOSpikeRule {
         self.severity
     }
 
-    fn trigger_reason(
-        &self,
-        _log_events: &[LogEvent],
-        _metrics_events: &[MetricsEvent],
-        disk_events: &[DiskEvent],
-        _source: Option<&str>,
-    ) -> String {
+    fn trigger_reason(&self, events: &EventSnapshot<'_>, _source: Option<&str>) -> String {
+        let disk_events = events.disk();
         let evidence = self
-            .triggering_spikes(disk_events)
+            .triggering_spikes(disk_events, events.now())
             .into_iter()
             .map(|spike| spike.evidence())
             .collect::<Vec<_>>()
@@ -749,18 +765,19 @@ impl TriggerRule for DiskIOSpikeRule {
         format!("Rule '{}' triggered: {}", self.name(), evidence)
     }
 
-    fn relevant_logs<'a>(&self, _log_events: &'a [LogEvent]) -> Vec<&'a LogEvent> {
+    fn relevant_logs<'a>(&self, _events: &EventSnapshot<'a>) -> Vec<&'a LogEvent> {
         Vec::new()
     }
 
-    fn relevant_metrics<'a>(&self, _metrics_events: &'a [MetricsEvent]) -> Vec<&'a MetricsEvent> {
+    fn relevant_metrics<'a>(&self, _events: &EventSnapshot<'a>) -> Vec<&'a MetricsEvent> {
         Vec::new()
     }
 
-    fn relevant_disk_events<'a>(&self, disk_events: &'a [DiskEvent]) -> Vec<&'a DiskEvent> {
-        let cutoff = Utc::now() - Duration::seconds(self.comparison_window_seconds);
+    fn relevant_disk_events<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a DiskEvent> {
+        let disk_events = events.disk();
+        let cutoff = events.now() - Duration::seconds(self.comparison_window_seconds);
         let triggering_disks = self
-            .triggering_spikes(disk_events)
+            .triggering_spikes(disk_events, events.now())
             .into_iter()
             .map(|spike| spike.disk_name)
             .collect::<HashSet<_>>();
@@ -835,7 +852,11 @@ mod tests {
 
         let metrics_events = vec![];
 
-        assert!(!rule.evaluate(&log_events, &metrics_events, &[]));
+        assert!(!rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[]
+        )));
     }
 
     #[test]
@@ -852,7 +873,11 @@ mod tests {
 
         let metrics_events = vec![];
 
-        assert!(rule.evaluate(&log_events, &metrics_events, &[]));
+        assert!(rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[]
+        )));
     }
 
     #[test]
@@ -863,7 +888,7 @@ mod tests {
             .chain((0..2).map(|_| create_test_log_event(MessageType::Error, "Repeated error", 1)))
             .collect::<Vec<_>>();
 
-        assert!(!rule.evaluate(&repeated, &[], &[]));
+        assert!(!rule.evaluate(&EventSnapshot::from_slices(&repeated, &[], &[])));
 
         let mut distinct = repeated;
         distinct.push(create_test_log_event(
@@ -871,7 +896,7 @@ mod tests {
             "Different error",
             1,
         ));
-        assert!(rule.evaluate(&distinct, &[], &[]));
+        assert!(rule.evaluate(&EventSnapshot::from_slices(&distinct, &[], &[])));
     }
 
     #[test]
@@ -884,7 +909,7 @@ mod tests {
         second.process = "second-process".to_string();
         second.subsystem = "second-subsystem".to_string();
 
-        assert!(!rule.evaluate(&[first, second], &[], &[]));
+        assert!(!rule.evaluate(&EventSnapshot::from_slices(&[first, second], &[], &[])));
     }
 
     #[test]
@@ -905,7 +930,7 @@ mod tests {
         second.process = "runningboardd".to_string();
         second.subsystem = "com.apple.runningboard".to_string();
 
-        assert!(!rule.evaluate(&[first, second], &[], &[]));
+        assert!(!rule.evaluate(&EventSnapshot::from_slices(&[first, second], &[], &[])));
     }
 
     #[test]
@@ -927,8 +952,8 @@ mod tests {
         second.subsystem = "com.apple.runningboard".to_string();
 
         let events = [first, second];
-        assert!(rule.evaluate(&events, &[], &[]));
-        let groups = rule.relevant_log_groups(&events);
+        assert!(rule.evaluate(&EventSnapshot::from_slices(&events, &[], &[])));
+        let groups = rule.relevant_log_groups(&EventSnapshot::from_slices(&events, &[], &[]));
         assert_eq!(groups.len(), 1);
         assert_eq!(groups[0].events.len(), 2);
         assert_eq!(
@@ -948,7 +973,7 @@ mod tests {
         .chain((0..6).map(|_| create_test_log_event(MessageType::Error, "Repeated error", 1)))
         .collect::<Vec<_>>();
 
-        assert!(rule.evaluate(&events, &[], &[]));
+        assert!(rule.evaluate(&EventSnapshot::from_slices(&events, &[], &[])));
     }
 
     #[test]
@@ -962,7 +987,7 @@ mod tests {
         event.process = "syspolicyd".to_string();
         event.process_id = 473;
 
-        assert!(!rule.evaluate(&[event], &[], &[]));
+        assert!(!rule.evaluate(&EventSnapshot::from_slices(&[event], &[], &[])));
     }
 
     #[test]
@@ -980,7 +1005,11 @@ mod tests {
         let metrics_events = vec![];
 
         // Should not trigger because only 2 errors in the 30s window (threshold is 2, need >2)
-        assert!(!rule.evaluate(&log_events, &metrics_events, &[]));
+        assert!(!rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[]
+        )));
 
         // Add one more recent error to exceed threshold
         let mut log_events_with_extra = log_events;
@@ -991,7 +1020,11 @@ mod tests {
         ));
 
         // Should trigger because 3 errors in 30s window (> threshold of 2)
-        assert!(rule.evaluate(&log_events_with_extra, &metrics_events, &[]));
+        assert!(rule.evaluate(&EventSnapshot::from_slices(
+            &log_events_with_extra,
+            &metrics_events,
+            &[]
+        )));
     }
 
     #[test]
@@ -1004,7 +1037,11 @@ mod tests {
             create_test_metrics_event(1200.0, Some(600.0), MemoryPressure::Normal, 20),
         ];
 
-        assert!(!rule.evaluate(&log_events, &metrics_events, &[]));
+        assert!(!rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[]
+        )));
     }
 
     #[test]
@@ -1019,7 +1056,11 @@ mod tests {
         ];
 
         // Should trigger because one event has Warning level memory pressure
-        assert!(rule.evaluate(&log_events, &metrics_events, &[]));
+        assert!(rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[]
+        )));
     }
 
     #[test]
@@ -1034,7 +1075,11 @@ mod tests {
         ];
 
         // Should trigger because one event has Critical level memory pressure
-        assert!(rule.evaluate(&log_events, &metrics_events, &[]));
+        assert!(rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[]
+        )));
     }
 
     #[test]
@@ -1042,7 +1087,10 @@ mod tests {
         let rule = MemoryPressureRule::new(MemoryPressure::Warning, Severity::Warning);
         let critical = create_test_metrics_event(1000.0, None, MemoryPressure::Critical, 1);
 
-        assert_eq!(rule.severity_for(&[], &[critical], &[]), Severity::Critical);
+        assert_eq!(
+            rule.severity_for(&EventSnapshot::from_slices(&[], &[critical], &[])),
+            Severity::Critical
+        );
     }
 
     #[test]
@@ -1050,7 +1098,7 @@ mod tests {
         let rule = MemoryPressureRule::new(MemoryPressure::Warning, Severity::Warning);
         let unknown = create_test_metrics_event(1000.0, None, MemoryPressure::Unknown, 1);
 
-        assert!(!rule.evaluate(&[], &[unknown], &[]));
+        assert!(!rule.evaluate(&EventSnapshot::from_slices(&[], &[unknown], &[])));
     }
 
     #[test]
@@ -1064,7 +1112,11 @@ mod tests {
 
         let metrics_events = vec![];
 
-        assert!(!rule.evaluate(&log_events, &metrics_events, &[]));
+        assert!(!rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[]
+        )));
     }
 
     #[test]
@@ -1074,7 +1126,7 @@ mod tests {
             create_test_log_event(MessageType::Error, "Simulating crash. Reason: <private>", 1);
         event.process = "ContextStoreAgent".to_string();
 
-        assert!(!rule.evaluate(&[event], &[], &[]));
+        assert!(!rule.evaluate(&EventSnapshot::from_slices(&[event], &[], &[])));
     }
 
     #[test]
@@ -1089,7 +1141,11 @@ mod tests {
 
         let metrics_events = vec![];
 
-        assert!(rule.evaluate(&log_events, &metrics_events, &[]));
+        assert!(rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[]
+        )));
     }
 
     #[test]
@@ -1103,7 +1159,7 @@ mod tests {
         browser_crash.subsystem = "com.example.browser".to_string();
 
         let events = vec![editor_crash, browser_crash];
-        let groups = rule.relevant_log_groups(&events);
+        let groups = rule.relevant_log_groups(&EventSnapshot::from_slices(&events, &[], &[]));
 
         assert_eq!(groups.len(), 2);
         assert!(groups.iter().any(|group| {
@@ -1128,7 +1184,11 @@ mod tests {
 
         let metrics_events = vec![];
 
-        assert!(rule.evaluate(&log_events, &metrics_events, &[]));
+        assert!(rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[]
+        )));
     }
 
     #[test]
@@ -1140,7 +1200,7 @@ mod tests {
             1,
         );
 
-        assert!(!rule.evaluate(&[event], &[], &[]));
+        assert!(!rule.evaluate(&EventSnapshot::from_slices(&[event], &[], &[])));
     }
 
     #[test]
@@ -1152,7 +1212,7 @@ mod tests {
             1,
         );
 
-        assert!(!rule.evaluate(&[event], &[], &[]));
+        assert!(!rule.evaluate(&EventSnapshot::from_slices(&[event], &[], &[])));
     }
 
     #[test]
@@ -1167,7 +1227,7 @@ mod tests {
             ),
         ];
 
-        assert!(rule.evaluate(&events, &[], &[]));
+        assert!(rule.evaluate(&EventSnapshot::from_slices(&events, &[], &[])));
     }
 
     #[test]
@@ -1179,7 +1239,7 @@ mod tests {
             1,
         );
 
-        assert!(rule.evaluate(&[event], &[], &[]));
+        assert!(rule.evaluate(&EventSnapshot::from_slices(&[event], &[], &[])));
     }
 
     #[test]
@@ -1195,7 +1255,11 @@ mod tests {
 
         let metrics_events = vec![];
 
-        assert!(rule.evaluate(&log_events, &metrics_events, &[]));
+        assert!(rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[]
+        )));
         assert_eq!(rule.severity(), Severity::Warning);
     }
 
@@ -1212,7 +1276,11 @@ mod tests {
         )];
 
         // Should not trigger with only 1 data point
-        assert!(!rule.evaluate(&log_events, &metrics_events, &[]));
+        assert!(!rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[]
+        )));
     }
 
     #[test]
@@ -1227,7 +1295,11 @@ mod tests {
 
         // CPU increase: 1500 - 1000 = 500mW (below 1000mW threshold)
         // GPU increase: 800 - 500 = 300mW (below 2000mW threshold)
-        assert!(!rule.evaluate(&log_events, &metrics_events, &[]));
+        assert!(!rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[]
+        )));
     }
 
     #[test]
@@ -1241,7 +1313,27 @@ mod tests {
         ];
 
         // CPU increase: 2500 - 1000 = 1500mW (above 1000mW threshold)
-        assert!(rule.evaluate(&log_events, &metrics_events, &[]));
+        assert!(rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[]
+        )));
+    }
+
+    #[test]
+    fn test_resource_spike_rule_compares_samples_within_one_source() {
+        let rule = ResourceSpikeRule::new(1000.0, 2000.0, 30, Severity::Warning);
+
+        // An OTLP sample without power data between two steady host samples
+        let mut otlp = create_test_metrics_event(0.0, None, MemoryPressure::Normal, 20);
+        otlp.provenance.source = crate::events::MetricsSource::Otlp;
+        let metrics_events = vec![
+            create_test_metrics_event(3000.0, None, MemoryPressure::Normal, 25),
+            otlp,
+            create_test_metrics_event(3100.0, None, MemoryPressure::Normal, 10),
+        ];
+
+        assert!(!rule.evaluate(&EventSnapshot::from_slices(&[], &metrics_events, &[])));
     }
 
     #[test]
@@ -1256,7 +1348,11 @@ mod tests {
 
         // CPU increase: 1200 - 1000 = 200mW (below 1000mW threshold)
         // GPU increase: 3000 - 500 = 2500mW (above 2000mW threshold)
-        assert!(rule.evaluate(&log_events, &metrics_events, &[]));
+        assert!(rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[]
+        )));
     }
 
     #[test]
@@ -1271,7 +1367,11 @@ mod tests {
 
         // CPU increase: 2500 - 1000 = 1500mW (above 1000mW threshold)
         // GPU data not available, should still trigger on CPU
-        assert!(rule.evaluate(&log_events, &metrics_events, &[]));
+        assert!(rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[]
+        )));
     }
 
     #[test]
@@ -1286,7 +1386,11 @@ mod tests {
         ];
 
         // Should compare events within 20s window: 1200 -> 2500 = 1300mW increase (above threshold)
-        assert!(rule.evaluate(&log_events, &metrics_events, &[]));
+        assert!(rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[]
+        )));
     }
 
     #[test]
@@ -1325,7 +1429,11 @@ mod tests {
 
         // This should trigger because there was a 4000mW CPU spike from running min (1000) to peak (6000)
         // The running minimum approach correctly detects the upward spike
-        assert!(rule.evaluate(&log_events, &metrics_events, &[]));
+        assert!(rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[]
+        )));
     }
 
     #[test]
@@ -1342,7 +1450,11 @@ mod tests {
 
         // This should trigger because there was a 3000mW CPU spike from min (1000) to peak (4000)
         // Running minimum correctly tracks the lowest point and detects the subsequent spike
-        assert!(rule.evaluate(&log_events, &metrics_events, &[]));
+        assert!(rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[]
+        )));
     }
 
     #[test]
@@ -1358,7 +1470,11 @@ mod tests {
 
         // This should NOT trigger because we only have decreases (5000→3000→1000)
         // Even though max-min = 4000mW > threshold, it's a decrease not a spike
-        assert!(!rule.evaluate(&log_events, &metrics_events, &[]));
+        assert!(!rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[]
+        )));
     }
 
     #[test]
@@ -1374,7 +1490,11 @@ mod tests {
 
         // This should trigger because there was a 3000mW GPU spike from t0->t1 (above 1500mW threshold)
         // CPU spike is only 200mW (below 1000mW threshold)
-        assert!(rule.evaluate(&log_events, &metrics_events, &[]));
+        assert!(rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[]
+        )));
     }
 
     #[test]
@@ -1386,7 +1506,11 @@ mod tests {
         let disk_events = vec![create_test_disk_event(100.0, 50.0, "disk0", 10)];
 
         // Should not trigger with only 1 data point
-        assert!(!rule.evaluate(&log_events, &metrics_events, &disk_events));
+        assert!(!rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &disk_events
+        )));
     }
 
     #[test]
@@ -1402,7 +1526,11 @@ mod tests {
 
         // Read increase: 200 - 100 = 100KB/s (below 1024KB/s threshold)
         // Write increase: 100 - 50 = 50KB/s (below 512KB/s threshold)
-        assert!(!rule.evaluate(&log_events, &metrics_events, &disk_events));
+        assert!(!rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &disk_events
+        )));
     }
 
     #[test]
@@ -1417,7 +1545,11 @@ mod tests {
         ];
 
         // Read increase: 2000 - 100 = 1900KB/s (above 1024KB/s threshold)
-        assert!(rule.evaluate(&log_events, &metrics_events, &disk_events));
+        assert!(rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &disk_events
+        )));
     }
 
     #[test]
@@ -1433,7 +1565,11 @@ mod tests {
 
         // Read increase: 200 - 100 = 100KB/s (below 1024KB/s threshold)
         // Write increase: 1000 - 50 = 950KB/s (above 512KB/s threshold)
-        assert!(rule.evaluate(&log_events, &metrics_events, &disk_events));
+        assert!(rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &disk_events
+        )));
     }
 
     #[test]
@@ -1449,7 +1585,11 @@ mod tests {
         ];
 
         // Should compare events within 20s window: 200 -> 2000 = 1800KB/s increase (above threshold)
-        assert!(rule.evaluate(&log_events, &metrics_events, &disk_events));
+        assert!(rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &disk_events
+        )));
     }
 
     #[test]
@@ -1460,7 +1600,7 @@ mod tests {
             create_test_disk_event(2000.0, 1000.0, "disk1", 10),
         ];
 
-        assert!(!rule.evaluate(&[], &[], &disk_events));
+        assert!(!rule.evaluate(&EventSnapshot::from_slices(&[], &[], &disk_events)));
     }
 
     #[test]
@@ -1472,7 +1612,7 @@ mod tests {
         peak.filesystem_path = Some("/Users/example/project".to_string());
         let disk_events = vec![baseline, peak];
 
-        let reason = rule.trigger_reason(&[], &[], &disk_events, None);
+        let reason = rule.trigger_reason(&EventSnapshot::from_slices(&[], &[], &disk_events), None);
 
         assert!(reason.contains("fs_usage read spike"));
         assert!(reason.contains("baseline 100.0KB/s"));
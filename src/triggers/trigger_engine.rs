@@ -1,21 +1,101 @@
-use crate::events::{DiskEvent, LogEvent, MetricsEvent, Severity, Timestamp};
+use crate::aggregator::{EventSnapshot, LogTemplateSummary};
+use crate::events::{CrashReport, DiskEvent, LogEvent, MetricsEvent, Severity, Timestamp};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Names of the built-in rules, as returned by [`TriggerRule::name`]
+pub const BUILTIN_RULES: &[&str] = &[
+    "ErrorFrequencyRule",
+    "MemoryPressureRule",
+    "CrashDetectionRule",
+    "ResourceSpikeRule",
+    "DiskIOSpikeRule",
+    "AnomalyRule",
+    "LogTemplateRule",
+    "RespawnLoopRule",
+    "MemoryLeakRule",
+    "CpuHogRule",
+];
 
 /// Engine for evaluating trigger conditions and determining when to invoke AI analysis
 pub struct TriggerEngine {
     rules: Vec<Box<dyn TriggerRule>>,
+    /// Cooldown and re-arm settings by rule name
+    policies: HashMap<String, RulePolicy>,
+    /// Cooldown keys of re-arming rules that fired and were acted on, and
+    /// have kept firing since
+    latched: Mutex<HashSet<String>>,
+}
+
+/// How often the daemon may act on a rule's triggers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RulePolicy {
+    /// Minimum time between alerts for the same rule and source; the
+    /// caller's default when `None`
+    pub cooldown: Option<Duration>,
+    /// Suppress a source that keeps firing after it was acted on until a
+    /// pass in which it does not fire
+    pub rearm: bool,
+}
+
+/// When each trigger was last acted on, for applying cooldowns
+///
+/// Times are the snapshot times the triggers fired at, so the live analysis
+/// loop, capture replay and backtests all measure cooldowns the same way.
+#[derive(Debug, Clone, Default)]
+pub struct TriggerCooldowns {
+    /// Cooldown for rules added without their own
+    pub default: Duration,
+    last_triggered: HashMap<String, Timestamp>,
+}
+
+impl TriggerCooldowns {
+    /// No trigger has fired yet
+    pub fn new(default: Duration) -> Self {
+        Self::with_history(default, HashMap::new())
+    }
+
+    /// Continue from firing times saved by a previous run, by cooldown key
+    pub fn with_history(default: Duration, last_triggered: HashMap<String, Timestamp>) -> Self {
+        Self {
+            default,
+            last_triggered,
+        }
+    }
+
+    /// Firing times less than `longest` before `now`, for saving
+    pub fn recent(&self, now: Timestamp, longest: Duration) -> HashMap<String, Timestamp> {
+        self.last_triggered
+            .iter()
+            .filter(|(_, fired_at)| {
+                (now - **fired_at)
+                    .to_std()
+                    .map_or(true, |elapsed| elapsed < longest)
+            })
+            .map(|(key, fired_at)| (key.clone(), *fired_at))
+            .collect()
+    }
+
+    /// Whether the trigger under `key` is still in its cooldown at `now`
+    fn cooling(&self, key: &str, cooldown: Duration, now: Timestamp) -> bool {
+        self.last_triggered.get(key).is_some_and(|last| {
+            (now - *last)
+                .to_std()
+                .map_or(true, |elapsed| elapsed < cooldown)
+        })
+    }
 }
 
 /// Trait for implementing trigger rules that determine when AI analysis should be invoked
+///
+/// Rules read recent events through a borrowed [`EventSnapshot`], which
+/// references the aggregator's buffers instead of copies of them.
 pub trait TriggerRule: Send + Sync {
     /// Evaluate whether this rule is triggered by the given events
-    fn evaluate(
-        &self,
-        log_events: &[LogEvent],
-        metrics_events: &[MetricsEvent],
-        disk_events: &[DiskEvent],
-    ) -> bool;
+    fn evaluate(&self, events: &EventSnapshot<'_>) -> bool;
 
     /// Get a human-readable name for this rule
     fn name(&self) -> &str;
@@ -23,44 +103,43 @@ pub trait TriggerRule: Send + Sync {
     /// Get the severity level if this rule triggers
     fn severity(&self) -> Severity;
 
-    fn severity_for(
-        &self,
-        _log_events: &[LogEvent],
-        _metrics_events: &[MetricsEvent],
-        _disk_events: &[DiskEvent],
-    ) -> Severity {
+    fn severity_for(&self, _events: &EventSnapshot<'_>) -> Severity {
         self.severity()
     }
 
-    fn relevant_logs<'a>(&self, log_events: &'a [LogEvent]) -> Vec<&'a LogEvent> {
-        log_events
+    fn relevant_logs<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a LogEvent> {
+        events
+            .logs()
             .iter()
             .filter(|event| !event.is_known_benign_noise())
             .collect()
     }
 
-    fn relevant_log_groups<'a>(&self, log_events: &'a [LogEvent]) -> Vec<RelevantLogGroup<'a>> {
+    fn relevant_log_groups<'a>(&self, events: &EventSnapshot<'a>) -> Vec<RelevantLogGroup<'a>> {
         vec![RelevantLogGroup {
             source: None,
-            events: self.relevant_logs(log_events),
+            events: self.relevant_logs(events),
         }]
     }
 
-    fn relevant_metrics<'a>(&self, metrics_events: &'a [MetricsEvent]) -> Vec<&'a MetricsEvent> {
-        metrics_events.iter().collect()
+    fn relevant_metrics<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a MetricsEvent> {
+        events.metrics().iter().collect()
     }
 
-    fn relevant_disk_events<'a>(&self, disk_events: &'a [DiskEvent]) -> Vec<&'a DiskEvent> {
-        disk_events.iter().collect()
+    fn relevant_disk_events<'a>(&self, events: &EventSnapshot<'a>) -> Vec<&'a DiskEvent> {
+        events.disk().iter().collect()
     }
 
-    fn trigger_reason(
-        &self,
-        _log_events: &[LogEvent],
-        _metrics_events: &[MetricsEvent],
-        _disk_events: &[DiskEvent],
-        source: Option<&str>,
-    ) -> String {
+    /// Events from earlier passes that belong in the trigger context
+    ///
+    /// They are added to the relevant events of the current snapshot. Rules
+    /// that correlate firings over time keep the evidence of each firing
+    /// here, since it may have left the snapshot by the time they fire.
+    fn retained_events(&self) -> RetainedEvents {
+        RetainedEvents::default()
+    }
+
+    fn trigger_reason(&self, _events: &EventSnapshot<'_>, source: Option<&str>) -> String {
         match source {
             Some(source) => format!("Rule '{}' triggered for {}", self.name(), source),
             None => format!("Rule '{}' triggered", self.name()),
@@ -73,6 +152,14 @@ pub struct RelevantLogGroup<'a> {
     pub events: Vec<&'a LogEvent>,
 }
 
+/// Owned copies of events a rule kept from earlier snapshots
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RetainedEvents {
+    pub logs: Vec<LogEvent>,
+    pub metrics: Vec<MetricsEvent>,
+    pub disk: Vec<DiskEvent>,
+}
+
 /// Context passed to AI analyzer containing recent system events and trigger information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TriggerContext {
@@ -84,6 +171,12 @@ pub struct TriggerContext {
     pub metrics_events: Vec<MetricsEvent>,
     /// Recent disk events that contributed to the trigger
     pub disk_events: Vec<DiskEvent>,
+    /// Crash reports from the processes in `log_events`
+    #[serde(default)]
+    pub crash_reports: Vec<CrashReport>,
+    /// Mined templates of the log events, with their lifetime counts
+    #[serde(default)]
+    pub log_templates: Vec<LogTemplateSummary>,
     /// Name of the rule that triggered this analysis
     pub triggered_by: String,
     /// Process/subsystem source for rules that emit source-coherent contexts
@@ -104,7 +197,11 @@ impl Default for TriggerEngine {
 impl TriggerEngine {
     /// Create a new trigger engine with no rules
     pub fn new() -> Self {
-        Self { rules: Vec::new() }
+        Self {
+            rules: Vec::new(),
+            policies: HashMap::new(),
+            latched: Mutex::new(HashSet::new()),
+        }
     }
 
     /// Add a trigger rule to the engine
@@ -119,55 +216,139 @@ impl TriggerEngine {
         self.rules.push(rule);
     }
 
+    /// Add a trigger rule with its own cooldown and re-arm settings
+    pub fn add_rule_with_policy(&mut self, rule: Box<dyn TriggerRule>, policy: RulePolicy) {
+        self.policies.insert(rule.name().to_string(), policy);
+        self.add_rule(rule);
+    }
+
+    /// Swap in the rules and policies of `next` in one step
+    ///
+    /// Running rules that `next` has too keep their instance, and the state
+    /// they have learned, unless they are named in `rebuilt`. Re-arm holds
+    /// are kept for rules that are still present and still re-arm, so a
+    /// reload does not alert again on a condition that has not cleared.
+    pub fn replace_rules(&mut self, next: TriggerEngine, rebuilt: &[String]) {
+        use log::info;
+
+        let TriggerEngine {
+            rules, policies, ..
+        } = next;
+        let latched = self.latched.get_mut().unwrap();
+        latched.retain(|key| {
+            policies.iter().any(|(name, policy)| {
+                policy.rearm && (key == name || key.starts_with(&format!("{}:", name)))
+            })
+        });
+        let mut running = std::mem::take(&mut self.rules);
+        let mut kept = 0;
+        self.rules = rules
+            .into_iter()
+            .map(|rule| {
+                if rebuilt.iter().any(|name| name == rule.name()) {
+                    return rule;
+                }
+                match running.iter().position(|old| old.name() == rule.name()) {
+                    Some(index) => {
+                        kept += 1;
+                        running.swap_remove(index)
+                    }
+                    None => rule,
+                }
+            })
+            .collect();
+        info!(
+            "Replaced trigger rules: {} kept, {} rebuilt or added, {} removed",
+            kept,
+            self.rules.len() - kept,
+            running.len()
+        );
+        self.policies = policies;
+    }
+
+    /// The rule's own cooldown, if it was added with one
+    pub fn cooldown(&self, rule_name: &str) -> Option<Duration> {
+        self.policies
+            .get(rule_name)
+            .and_then(|policy| policy.cooldown)
+    }
+
+    /// The longest cooldown any rule was added with
+    pub fn longest_cooldown(&self) -> Option<Duration> {
+        self.policies
+            .values()
+            .filter_map(|policy| policy.cooldown)
+            .max()
+    }
+
+    /// Record that a trigger was acted on
+    ///
+    /// If its rule re-arms, later triggers for the same rule and source are
+    /// dropped by [`TriggerEngine::evaluate`] until an evaluation in which the
+    /// rule no longer fires for that source.
+    fn latch(&self, context: &TriggerContext) {
+        if self
+            .policies
+            .get(&context.triggered_by)
+            .is_some_and(|policy| policy.rearm)
+        {
+            self.latched.lock().unwrap().insert(context.cooldown_key());
+        }
+    }
+
     /// Evaluate all rules against recent events and return trigger contexts for any that fire
-    pub fn evaluate(
-        &self,
-        log_events: &[LogEvent],
-        metrics_events: &[MetricsEvent],
-        disk_events: &[DiskEvent],
-    ) -> Vec<TriggerContext> {
+    ///
+    /// Only the events that end up in a returned context are cloned.
+    pub fn evaluate(&self, events: &EventSnapshot<'_>) -> Vec<TriggerContext> {
         use log::debug;
 
         debug!(
             "Evaluating {} trigger rules against {} log events, {} metrics events, and {} disk events",
             self.rules.len(),
-            log_events.len(),
-            metrics_events.len(),
-            disk_events.len()
+            events.logs().len(),
+            events.metrics().len(),
+            events.disk().len()
         );
 
         let mut contexts = Vec::new();
 
         for rule in &self.rules {
             debug!("Evaluating rule: '{}'", rule.name());
-            if rule.evaluate(log_events, metrics_events, disk_events) {
-                let severity = rule.severity_for(log_events, metrics_events, disk_events);
+            if rule.evaluate(events) {
+                let severity = rule.severity_for(events);
                 debug!(
                     "Trigger rule '{}' activated with severity: {:?}",
                     rule.name(),
                     severity
                 );
 
-                for group in rule.relevant_log_groups(log_events) {
-                    let trigger_reason = rule.trigger_reason(
-                        log_events,
-                        metrics_events,
-                        disk_events,
-                        group.source.as_deref(),
-                    );
+                let retained = rule.retained_events();
+                for group in rule.relevant_log_groups(events) {
+                    let trigger_reason = rule.trigger_reason(events, group.source.as_deref());
+                    let log_templates = events
+                        .summarize_templates(group.events.iter().copied().chain(&retained.logs));
                     contexts.push(TriggerContext {
-                        timestamp: Utc::now(),
-                        log_events: group.events.into_iter().cloned().collect(),
+                        timestamp: events.now(),
+                        log_events: group
+                            .events
+                            .into_iter()
+                            .chain(&retained.logs)
+                            .cloned()
+                            .collect(),
                         metrics_events: rule
-                            .relevant_metrics(metrics_events)
+                            .relevant_metrics(events)
                             .into_iter()
+                            .chain(&retained.metrics)
                             .cloned()
                             .collect(),
                         disk_events: rule
-                            .relevant_disk_events(disk_events)
+                            .relevant_disk_events(events)
                             .into_iter()
+                            .chain(&retained.disk)
                             .cloned()
                             .collect(),
+                        crash_reports: Vec::new(),
+                        log_templates,
                         triggered_by: rule.name().to_string(),
                         trigger_source: group.source,
                         expected_severity: severity,
@@ -179,6 +360,26 @@ impl TriggerEngine {
             }
         }
 
+        {
+            let mut latched = self.latched.lock().unwrap();
+            let fired = contexts
+                .iter()
+                .map(TriggerContext::cooldown_key)
+                .collect::<HashSet<_>>();
+            latched.retain(|key| fired.contains(key));
+            contexts.retain(|context| {
+                let held = latched.contains(&context.cooldown_key());
+                if held {
+                    debug!(
+                        "Rule '{}' is waiting to re-arm for {}",
+                        context.triggered_by,
+                        context.cooldown_key()
+                    );
+                }
+                !held
+            });
+        }
+
         if contexts.is_empty() {
             debug!("No trigger rules activated");
         } else {
@@ -191,6 +392,36 @@ impl TriggerEngine {
         contexts
     }
 
+    /// Evaluate all rules and keep the triggers that may be acted on
+    ///
+    /// Triggers whose rule and source are still in cooldown are dropped. The
+    /// rest are latched for re-arm and recorded in `cooldowns` at the
+    /// snapshot's time, so callers act on every trigger returned.
+    pub fn evaluate_with_policy(
+        &self,
+        events: &EventSnapshot<'_>,
+        cooldowns: &mut TriggerCooldowns,
+    ) -> Vec<TriggerContext> {
+        use log::debug;
+
+        let now = events.now();
+        let mut contexts = self.evaluate(events);
+        contexts.retain(|context| {
+            let trigger_key = context.cooldown_key();
+            let cooldown = self
+                .cooldown(&context.triggered_by)
+                .unwrap_or(cooldowns.default);
+            if cooldowns.cooling(&trigger_key, cooldown, now) {
+                debug!("Trigger {} is in cooldown", trigger_key);
+                return false;
+            }
+            self.latch(context);
+            cooldowns.last_triggered.insert(trigger_key, now);
+            true
+        });
+        contexts
+    }
+
     /// Get the number of configured rules
     pub fn rule_count(&self) -> usize {
         self.rules.len()
@@ -209,6 +440,8 @@ impl TriggerContext {
             log_events: log_events.to_vec(),
             metrics_events: metrics_events.to_vec(),
             disk_events: disk_events.to_vec(),
+            crash_reports: Vec::new(),
+            log_templates: Vec::new(),
             triggered_by: "summary".to_string(),
             trigger_source: None,
             expected_severity: Severity::Info,
@@ -216,6 +449,23 @@ impl TriggerContext {
         }
     }
 
+    /// Attach the crash reports filed by processes that appear in `log_events`
+    ///
+    /// Rules only see crash reports through the log events synthesized from
+    /// them, so this restores the full report (exception, termination reason
+    /// and faulting frames) for whichever contexts those events landed in.
+    pub fn attach_crash_reports(&mut self, reports: &[&CrashReport]) {
+        self.crash_reports = reports
+            .iter()
+            .filter(|report| {
+                self.log_events
+                    .iter()
+                    .any(|event| event.process == report.process)
+            })
+            .map(|report| (*report).clone())
+            .collect();
+    }
+
     pub fn cooldown_key(&self) -> String {
         match &self.trigger_source {
             Some(source) => format!("{}:{}", self.triggered_by, source),
@@ -375,12 +625,7 @@ mod tests {
     }
 
     impl TriggerRule for MockTriggerRule {
-        fn evaluate(
-            &self,
-            _log_events: &[LogEvent],
-            _metrics_events: &[MetricsEvent],
-            _disk_events: &[DiskEvent],
-        ) -> bool {
+        fn evaluate(&self, _events: &EventSnapshot<'_>) -> bool {
             self.should_trigger
         }
 
@@ -416,6 +661,123 @@ mod tests {
         assert_eq!(engine.rule_count(), 1);
     }
 
+    #[test]
+    fn test_evaluate_with_policy_applies_cooldowns_in_snapshot_time() {
+        let rule = |name: &str| {
+            Box::new(MockTriggerRule {
+                name: name.to_string(),
+                should_trigger: true,
+                severity: Severity::Warning,
+            })
+        };
+        let mut engine = TriggerEngine::new();
+        engine.add_rule_with_policy(
+            rule("short"),
+            RulePolicy {
+                cooldown: Some(Duration::from_secs(60)),
+                rearm: false,
+            },
+        );
+        engine.add_rule(rule("default"));
+        let mut cooldowns = TriggerCooldowns::new(Duration::from_secs(300));
+        let start = Utc::now() - chrono::Duration::days(1);
+        let fired_at = |cooldowns: &mut TriggerCooldowns, seconds: i64| {
+            let snapshot = EventSnapshot::from_slices(&[], &[], &[])
+                .at(start + chrono::Duration::seconds(seconds));
+            engine
+                .evaluate_with_policy(&snapshot, cooldowns)
+                .into_iter()
+                .map(|context| context.triggered_by)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(fired_at(&mut cooldowns, 0), vec!["short", "default"]);
+        assert!(fired_at(&mut cooldowns, 30).is_empty());
+        assert_eq!(fired_at(&mut cooldowns, 61), vec!["short"]);
+        assert_eq!(fired_at(&mut cooldowns, 300), vec!["short", "default"]);
+
+        // Saved firing times survive until the longest cooldown has passed
+        let longest = Duration::from_secs(300);
+        let saved = cooldowns.recent(start + chrono::Duration::seconds(400), longest);
+        assert_eq!(saved.len(), 2);
+        let mut restored = TriggerCooldowns::with_history(Duration::from_secs(300), saved);
+        assert_eq!(fired_at(&mut restored, 400), vec!["short"]);
+        assert!(restored
+            .recent(start + chrono::Duration::seconds(700), longest)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_replace_rules_keeps_rearm_holds_of_remaining_rules() {
+        let rule = |name: &str| {
+            Box::new(MockTriggerRule {
+                name: name.to_string(),
+                should_trigger: true,
+                severity: Severity::Warning,
+            })
+        };
+        let rearm = RulePolicy {
+            cooldown: Some(Duration::from_secs(60)),
+            rearm: true,
+        };
+        let snapshot = EventSnapshot::from_slices(&[], &[], &[]);
+        let mut engine = TriggerEngine::new();
+        engine.add_rule_with_policy(rule("kept"), rearm);
+        engine.add_rule_with_policy(rule("dropped"), rearm);
+        for context in engine.evaluate(&snapshot) {
+            engine.latch(&context);
+        }
+        assert!(engine.evaluate(&snapshot).is_empty());
+
+        let mut next = TriggerEngine::new();
+        next.add_rule_with_policy(rule("kept"), rearm);
+        next.add_rule_with_policy(rule("dropped"), RulePolicy::default());
+        engine.replace_rules(next, &[]);
+
+        // "kept" is still held; "dropped" was rebuilt without re-arm
+        let fired = engine.evaluate(&snapshot);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].triggered_by, "dropped");
+        assert_eq!(engine.cooldown("dropped"), None);
+        assert_eq!(engine.cooldown("kept"), Some(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_replace_rules_keeps_unchanged_instances() {
+        let rule = |name: &str, severity: Severity| {
+            Box::new(MockTriggerRule {
+                name: name.to_string(),
+                should_trigger: true,
+                severity,
+            })
+        };
+        let snapshot = EventSnapshot::from_slices(&[], &[], &[]);
+        let mut engine = TriggerEngine::new();
+        engine.add_rule(rule("unchanged", Severity::Warning));
+        engine.add_rule(rule("changed", Severity::Warning));
+
+        // The reloaded instances differ only in severity, standing in for state
+        let mut next = TriggerEngine::new();
+        next.add_rule(rule("unchanged", Severity::Critical));
+        next.add_rule(rule("changed", Severity::Critical));
+        next.add_rule(rule("added", Severity::Critical));
+        engine.replace_rules(next, &["changed".to_string()]);
+
+        let fired: Vec<_> = engine
+            .evaluate(&snapshot)
+            .into_iter()
+            .map(|context| (context.triggered_by, context.expected_severity))
+            .collect();
+        assert_eq!(
+            fired,
+            vec![
+                ("unchanged".to_string(), Severity::Warning),
+                ("changed".to_string(), Severity::Critical),
+                ("added".to_string(), Severity::Critical),
+            ]
+        );
+    }
+
     #[test]
     fn test_trigger_engine_evaluate_no_triggers() {
         let mut engine = TriggerEngine::new();
@@ -431,7 +793,11 @@ mod tests {
         let log_events = vec![create_test_log_event(MessageType::Info, "Normal log")];
         let metrics_events = vec![create_test_metrics_event(1000.0, MemoryPressure::Normal)];
 
-        let contexts = engine.evaluate(&log_events, &metrics_events, &[]);
+        let contexts = engine.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[],
+        ));
         assert_eq!(contexts.len(), 0);
     }
 
@@ -446,7 +812,7 @@ mod tests {
         ];
         let metrics = vec![create_test_metrics_event(1000.0, MemoryPressure::Normal)];
 
-        let contexts = engine.evaluate(&logs, &metrics, &[]);
+        let contexts = engine.evaluate(&EventSnapshot::from_slices(&logs, &metrics, &[]));
 
         assert_eq!(contexts.len(), 1);
         assert_eq!(contexts[0].log_events.len(), 2);
@@ -473,7 +839,7 @@ mod tests {
             logs.extend([first, second]);
         }
 
-        let contexts = engine.evaluate(&logs, &[], &[]);
+        let contexts = engine.evaluate(&EventSnapshot::from_slices(&logs, &[], &[]));
 
         assert_eq!(contexts.len(), 2);
         assert_ne!(contexts[0].trigger_source, contexts[1].trigger_source);
@@ -487,6 +853,41 @@ mod tests {
         }));
     }
 
+    #[test]
+    fn test_evaluate_uses_snapshot_time() {
+        let mut engine = TriggerEngine::new();
+        engine.add_rule(Box::new(ErrorFrequencyRule::new(1, 60, Severity::Warning)));
+        let incident_time = Utc::now() - chrono::Duration::days(3);
+        let logs = ["First error", "Second error"]
+            .into_iter()
+            .enumerate()
+            .map(|(index, message)| {
+                let mut event = create_test_log_event(MessageType::Error, message);
+                event.timestamp = incident_time - chrono::Duration::seconds(index as i64 * 10);
+                event
+            })
+            .collect::<Vec<_>>();
+
+        assert!(engine
+            .evaluate(&EventSnapshot::from_slices(&logs, &[], &[]))
+            .is_empty());
+        let contexts =
+            engine.evaluate(&EventSnapshot::from_slices(&logs, &[], &[]).at(incident_time));
+
+        assert_eq!(contexts.len(), 1);
+        assert_eq!(contexts[0].timestamp, incident_time);
+        assert_eq!(
+            contexts[0].time_range(),
+            Some((incident_time - chrono::Duration::seconds(10), incident_time))
+        );
+        assert!(engine
+            .evaluate(
+                &EventSnapshot::from_slices(&logs, &[], &[])
+                    .at(incident_time + chrono::Duration::minutes(5))
+            )
+            .is_empty());
+    }
+
     #[test]
     fn test_trigger_engine_evaluate_with_triggers() {
         let mut engine = TriggerEngine::new();
@@ -516,7 +917,11 @@ mod tests {
         let log_events = vec![create_test_log_event(MessageType::Error, "Error log")];
         let metrics_events = vec![create_test_metrics_event(3000.0, MemoryPressure::Warning)];
 
-        let contexts = engine.evaluate(&log_events, &metrics_events, &[]);
+        let contexts = engine.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[],
+        ));
         assert_eq!(contexts.len(), 2); // Only the first two rules should trigger
 
         // Check first context
@@ -547,6 +952,49 @@ mod tests {
         assert_eq!(context.metrics_events.len(), 1);
     }
 
+    #[test]
+    fn test_crash_reports_attach_to_contexts_for_the_crashing_process() {
+        let report = |process: &str| CrashReport {
+            timestamp: Utc::now(),
+            kind: crate::events::CrashReportKind::Crash,
+            process: process.to_string(),
+            process_id: 77,
+            bundle_id: None,
+            exception_type: Some("EXC_BAD_ACCESS".to_string()),
+            exception_signal: Some("SIGSEGV".to_string()),
+            termination_reason: None,
+            frames: Vec::new(),
+            report_path: format!("/tmp/{process}.ips"),
+        };
+        let editor = report("ExampleEditor");
+        let unrelated = report("Unrelated");
+        let log_events = vec![
+            editor.to_log_event(),
+            create_test_log_event(MessageType::Fault, "testd crashed"),
+        ];
+        let mut engine = TriggerEngine::new();
+        engine.add_rule(Box::new(
+            crate::triggers::CrashDetectionRule::with_defaults(),
+        ));
+
+        let mut contexts = engine.evaluate(&EventSnapshot::from_slices(&log_events, &[], &[]));
+        for context in &mut contexts {
+            context.attach_crash_reports(&[&editor, &unrelated]);
+        }
+
+        assert_eq!(contexts.len(), 2);
+        let editor_context = contexts
+            .iter()
+            .find(|context| context.log_events[0].process == "ExampleEditor")
+            .unwrap();
+        assert_eq!(editor_context.crash_reports, vec![editor.clone()]);
+        let testd_context = contexts
+            .iter()
+            .find(|context| context.log_events[0].process == "testd")
+            .unwrap();
+        assert!(testd_context.crash_reports.is_empty());
+    }
+
     #[test]
     fn test_trigger_context_time_range() {
         let now = Utc::now();
@@ -958,7 +1406,11 @@ mod property_tests {
             })
             .count();
         let should_trigger = errors_in_window > threshold;
-        let actually_triggers = rule.evaluate(&log_events, &metrics_events, &[]);
+        let actually_triggers = rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[],
+        ));
 
         // Property: Rule should trigger if and only if error count exceeds threshold
         should_trigger == actually_triggers
@@ -976,10 +1428,18 @@ mod property_tests {
         let metrics_events = scenario.generate_metrics_events();
 
         let warning_should_trigger = scenario.has_warning_or_critical();
-        let warning_actually_triggers = warning_rule.evaluate(&log_events, &metrics_events, &[]);
+        let warning_actually_triggers = warning_rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[],
+        ));
 
         let critical_should_trigger = scenario.has_critical();
-        let critical_actually_triggers = critical_rule.evaluate(&log_events, &metrics_events, &[]);
+        let critical_actually_triggers = critical_rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[],
+        ));
 
         // Property: Warning rule triggers on Warning or Critical, Critical rule only on Critical
         (warning_should_trigger == warning_actually_triggers)
@@ -1005,7 +1465,11 @@ mod property_tests {
         let metrics_events = scenario.generate_metrics_events();
 
         let should_trigger = scenario.should_trigger_any_spike(cpu_threshold, gpu_threshold);
-        let actually_triggers = rule.evaluate(&log_events, &metrics_events, &[]);
+        let actually_triggers = rule.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[],
+        ));
 
         // Property: Rule should trigger if and only if CPU or GPU spike exceeds threshold
         should_trigger == actually_triggers
@@ -1038,7 +1502,11 @@ mod property_tests {
         let log_events = error_scenario.generate_log_events();
         let metrics_events = memory_scenario.generate_metrics_events();
 
-        let contexts = engine.evaluate(&log_events, &metrics_events, &[]);
+        let contexts = engine.evaluate(&EventSnapshot::from_slices(
+            &log_events,
+            &metrics_events,
+            &[],
+        ));
 
         // Calculate expected triggers
         let cutoff = chrono::Utc::now() - chrono::Duration::seconds(60);
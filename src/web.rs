@@ -1,11 +1,14 @@
 use crate::alerts::store::{AlertSort, AlertStore};
+use crate::collectors::EventSinks;
 use crate::error::AlertError;
+use crate::otlp::{self, ExportLogsServiceRequest, ExportMetricsServiceRequest};
 use crate::triggers::TriggerContext;
 use axum::extract::{Path, Query, State};
 use axum::http::{header, HeaderValue, StatusCode};
 use axum::response::{Html, IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::{Json, Router};
+use chrono::Utc;
 use log::{error, info};
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
@@ -24,6 +27,7 @@ const FAVICON_SVG: &str = include_str!("../statics/favicon.svg");
 struct AppState {
     database_path: PathBuf,
     manual_analysis_sender: Option<SyncSender<ManualAnalysisRequest>>,
+    telemetry: Option<EventSinks>,
 }
 
 #[derive(Debug)]
@@ -52,11 +56,16 @@ struct AnalysisQueued {
     analysis_status: &'static str,
 }
 
+/// `ExportLogsServiceResponse` / `ExportMetricsServiceResponse` with full success
+#[derive(Debug, Serialize)]
+struct OtlpExportResponse {}
+
 pub fn spawn(
     database_path: PathBuf,
     bind_address: SocketAddr,
     shutdown: Receiver<()>,
     manual_analysis_sender: SyncSender<ManualAnalysisRequest>,
+    telemetry: Option<EventSinks>,
 ) -> std::io::Result<JoinHandle<()>> {
     let listener = std::net::TcpListener::bind(bind_address)?;
     listener.set_nonblocking(true)?;
@@ -78,8 +87,14 @@ pub fn spawn(
                     return;
                 }
             };
-            let app = router(database_path, Some(manual_analysis_sender));
+            let otlp_enabled = telemetry.is_some();
+            let app = router(database_path, Some(manual_analysis_sender), telemetry);
             info!("Alert dashboard available at http://{bind_address}");
+            if otlp_enabled {
+                info!(
+                    "OTLP/HTTP receiver available at http://{bind_address}/v1/logs and /v1/metrics"
+                );
+            }
             let shutdown_signal = async move {
                 let _ = tokio::task::spawn_blocking(move || shutdown.recv()).await;
             };
@@ -97,8 +112,9 @@ pub fn spawn(
 fn router(
     database_path: PathBuf,
     manual_analysis_sender: Option<SyncSender<ManualAnalysisRequest>>,
+    telemetry: Option<EventSinks>,
 ) -> Router {
-    Router::new()
+    let mut router = Router::new()
         .route("/", get(index))
         .route("/rules", get(rules_page))
         .route("/api/alerts", get(alerts))
@@ -111,11 +127,17 @@ fn router(
         .route("/assets/styles.css", get(styles))
         .route("/assets/app.js", get(script))
         .route("/assets/rules.js", get(rules_script))
-        .route("/favicon.svg", get(favicon))
-        .with_state(AppState {
-            database_path,
-            manual_analysis_sender,
-        })
+        .route("/favicon.svg", get(favicon));
+    if telemetry.is_some() {
+        router = router
+            .route("/v1/logs", post(otlp_logs))
+            .route("/v1/metrics", post(otlp_metrics));
+    }
+    router.with_state(AppState {
+        database_path,
+        manual_analysis_sender,
+        telemetry,
+    })
 }
 
 async fn index() -> Response {
@@ -301,6 +323,44 @@ async fn analyze_candidate(
     }
 }
 
+async fn otlp_logs(
+    State(state): State<AppState>,
+    Json(request): Json<ExportLogsServiceRequest>,
+) -> Result<Json<OtlpExportResponse>, (StatusCode, Json<ApiError>)> {
+    let sinks = otlp_sinks(state)?;
+    for event in otlp::log_events(&request, Utc::now()) {
+        if sinks.logs.send(event).is_err() {
+            return Err(api_error_with_status(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "log pipeline is not running",
+            ));
+        }
+    }
+    Ok(Json(OtlpExportResponse {}))
+}
+
+async fn otlp_metrics(
+    State(state): State<AppState>,
+    Json(request): Json<ExportMetricsServiceRequest>,
+) -> Result<Json<OtlpExportResponse>, (StatusCode, Json<ApiError>)> {
+    let sinks = otlp_sinks(state)?;
+    for event in otlp::metrics_events(&request, Utc::now()) {
+        if sinks.metrics.send(event).is_err() {
+            return Err(api_error_with_status(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "metrics pipeline is not running",
+            ));
+        }
+    }
+    Ok(Json(OtlpExportResponse {}))
+}
+
+fn otlp_sinks(state: AppState) -> Result<EventSinks, (StatusCode, Json<ApiError>)> {
+    state
+        .telemetry
+        .ok_or_else(|| api_error_with_status(StatusCode::NOT_FOUND, "OTLP ingestion is disabled"))
+}
+
 fn manual_analysis_error(error: AlertError) -> (StatusCode, String) {
     let status = match error {
         AlertError::CandidateNotFound(_) => StatusCode::NOT_FOUND,
@@ -330,12 +390,11 @@ mod tests {
     use crate::events::{LogEvent, MessageType};
     use crate::triggers::TriggerContext;
     use axum::body::to_bytes;
-    use chrono::Utc;
     use tempfile::tempdir;
 
     #[test]
     fn router_builds_with_a_database_path() {
-        let _ = router(PathBuf::from("eyes.db"), None);
+        let _ = router(PathBuf::from("eyes.db"), None, None);
     }
 
     #[tokio::test]
@@ -395,6 +454,7 @@ mod tests {
             State(AppState {
                 database_path: database_path.clone(),
                 manual_analysis_sender: None,
+                telemetry: None,
             }),
             Query(AlertQuery {
                 page: Some(1),
@@ -425,6 +485,7 @@ mod tests {
             State(AppState {
                 database_path,
                 manual_analysis_sender: None,
+                telemetry: None,
             }),
         )
         .await
@@ -460,6 +521,7 @@ mod tests {
         let state = AppState {
             database_path,
             manual_analysis_sender: None,
+            telemetry: None,
         };
 
         let hidden = alerts(
@@ -520,6 +582,7 @@ mod tests {
         let response = auto_group_rules(State(AppState {
             database_path,
             manual_analysis_sender: None,
+            telemetry: None,
         }))
         .await
         .unwrap();
@@ -554,6 +617,7 @@ mod tests {
             State(AppState {
                 database_path: database_path.clone(),
                 manual_analysis_sender: Some(sender),
+                telemetry: None,
             }),
         )
         .await
@@ -569,4 +633,52 @@ mod tests {
             .unwrap();
         assert_eq!(page.alerts[0].analysis_status, "pending");
     }
+
+    #[tokio::test]
+    async fn otlp_endpoints_forward_events_to_the_sinks() {
+        let (logs, log_receiver) = std::sync::mpsc::channel();
+        let (metrics, metrics_receiver) = std::sync::mpsc::channel();
+        let state = AppState {
+            database_path: PathBuf::from("eyes.db"),
+            manual_analysis_sender: None,
+            telemetry: Some(EventSinks {
+                logs,
+                metrics,
+                disk: std::sync::mpsc::channel().0,
+                crashes: std::sync::mpsc::channel().0,
+            }),
+        };
+
+        let logs_request = serde_json::from_str(
+            r#"{"resourceLogs":[{"resource":{"attributes":[{"key":"service.name","value":{"stringValue":"api"}}]},
+                "scopeLogs":[{"logRecords":[{"severityNumber":17,"body":{"stringValue":"upstream timed out"}}]}]}]}"#,
+        )
+        .unwrap();
+        assert!(otlp_logs(State(state.clone()), Json(logs_request))
+            .await
+            .is_ok());
+        let event = log_receiver.try_recv().unwrap();
+        assert_eq!(event.process, "api");
+        assert_eq!(event.message_type, MessageType::Error);
+
+        let metrics_request = serde_json::from_str(
+            r#"{"resourceMetrics":[{"scopeMetrics":[{"metrics":[
+                {"name":"system.cpu.utilization","gauge":{"dataPoints":[{"asDouble":0.4}]}}]}]}]}"#,
+        )
+        .unwrap();
+        assert!(otlp_metrics(State(state), Json(metrics_request))
+            .await
+            .is_ok());
+        assert_eq!(metrics_receiver.try_recv().unwrap().cpu_usage_percent, 40.0);
+
+        let disabled = AppState {
+            database_path: PathBuf::from("eyes.db"),
+            manual_analysis_sender: None,
+            telemetry: None,
+        };
+        let error = otlp_logs(State(disabled), Json(ExportLogsServiceRequest::default()))
+            .await
+            .unwrap_err();
+        assert_eq!(error.0, StatusCode::NOT_FOUND);
+    }
 }
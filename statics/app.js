@@ -109,6 +109,7 @@ function triggerEvidenceMarkup(alert) {
     const logs = alert.log_events || [];
     const metrics = alert.metrics_events || [];
     const diskEvents = alert.disk_events || [];
+    const crashReports = alert.crash_reports || [];
     const processes = [...new Set(logs.map((event) => event.process).filter(Boolean))];
     const processMarkup = processes.length
         ? `<div class="affected-apps"><span>Affected ${processes.length === 1 ? "app" : "apps"}</span>${processes.map((process) => `<strong>${escapeHtml(process)}</strong>`).join("")}</div>`
@@ -142,7 +143,22 @@ function triggerEvidenceMarkup(alert) {
             <p>${Number(event.write_kb_per_sec).toFixed(1)} KB/s write · ${Number(event.read_ops_per_sec + event.write_ops_per_sec).toFixed(1)} ops/s</p>
         </article>`).join("")}</div>`
         : "";
-    if (!logs.length && !metrics.length && !diskEvents.length) {
+    const crashMarkup = crashReports.length
+        ? `<div class="raw-events">${crashReports.map((report) => {
+            const exception = [report.exception_type, report.exception_signal && `(${report.exception_signal})`].filter(Boolean).join(" ");
+            const frames = (report.frames || []).map((frame, index) => `${index} ${frame.image} ${frame.symbol || `+0x${Number(frame.image_offset).toString(16)}`}`);
+            return `<article class="raw-event raw-crash">
+                <div class="raw-event-meta">
+                    <span>${escapeHtml(formatTime(report.timestamp).exact)}</span>
+                    <span class="raw-event-level level-fault">${escapeHtml(report.kind)} report</span>
+                    <span>${escapeHtml(report.process)}${report.process_id ? ` · PID ${report.process_id}` : ""}</span>
+                    ${report.bundle_id ? `<span>${escapeHtml(report.bundle_id)}</span>` : ""}
+                </div>
+                <code>${escapeHtml([exception, report.termination_reason, ...frames].filter(Boolean).join("\n"))}</code>
+            </article>`;
+        }).join("")}</div>`
+        : "";
+    if (!logs.length && !metrics.length && !diskEvents.length && !crashReports.length) {
         return `<section class="detail-section">
             <p class="detail-label">Trigger evidence</p>
             <p class="detail-copy context-unavailable">Raw trigger evidence was not captured for this older alert.</p>
@@ -152,6 +168,7 @@ function triggerEvidenceMarkup(alert) {
         <p class="detail-label">Trigger evidence</p>
         ${processMarkup}
         ${logMarkup}
+        ${crashMarkup}
         ${metricMarkup}
         ${diskMarkup}
     </section>`;